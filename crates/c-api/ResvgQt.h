@@ -86,6 +86,14 @@ static QString errorToString(const int err)
             return QLatin1String("SVG doesn't have a valid size.");
         case RESVG_ERROR_PARSING_FAILED :
             return QLatin1String("Failed to parse an SVG data.");
+        case RESVG_ERROR_NESTING_DEPTH_LIMIT_REACHED :
+            return QLatin1String("Elements nesting is too deep.");
+        case RESVG_ERROR_IMAGE_SIZE_LIMIT_REACHED :
+            return QLatin1String("An image is too big.");
+        case RESVG_ERROR_GZIP_RATIO_LIMIT_REACHED :
+            return QLatin1String("Compressed data expands too much.");
+        case RESVG_ERROR_ENTITY_EXPANSION_LIMIT_REACHED :
+            return QLatin1String("DTD entities expand too much.");
     }
 
     Q_UNREACHABLE();
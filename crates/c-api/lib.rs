@@ -29,7 +29,7 @@ pub enum resvg_error {
     FILE_OPEN_FAILED,
     /// Compressed SVG must use the GZip algorithm.
     MALFORMED_GZIP,
-    /// We do not allow SVG with more than 1_000_000 elements for security reasons.
+    /// SVG has more elements than allowed.
     ELEMENTS_LIMIT_REACHED,
     /// SVG doesn't have a valid size.
     ///
@@ -39,6 +39,14 @@ pub enum resvg_error {
     INVALID_SIZE,
     /// Failed to parse an SVG data.
     PARSING_FAILED,
+    /// SVG elements nesting is deeper than allowed.
+    NESTING_DEPTH_LIMIT_REACHED,
+    /// A raster image is bigger than allowed.
+    IMAGE_SIZE_LIMIT_REACHED,
+    /// Compressed SVG expands more than allowed.
+    GZIP_RATIO_LIMIT_REACHED,
+    /// DTD entities expand more than allowed.
+    ENTITY_EXPANSION_LIMIT_REACHED,
 }
 
 /// @brief A rectangle representation.
@@ -838,6 +846,10 @@ fn convert_error(e: usvg::Error) -> resvg_error {
         usvg::Error::ElementsLimitReached => resvg_error::ELEMENTS_LIMIT_REACHED,
         usvg::Error::InvalidSize => resvg_error::INVALID_SIZE,
         usvg::Error::ParsingFailed(_) => resvg_error::PARSING_FAILED,
+        usvg::Error::NestingDepthLimitReached => resvg_error::NESTING_DEPTH_LIMIT_REACHED,
+        usvg::Error::ImageSizeLimitReached => resvg_error::IMAGE_SIZE_LIMIT_REACHED,
+        usvg::Error::GZipRatioLimitReached => resvg_error::GZIP_RATIO_LIMIT_REACHED,
+        usvg::Error::EntityExpansionLimitReached => resvg_error::ENTITY_EXPANSION_LIMIT_REACHED,
     }
 }
 
@@ -42,7 +42,7 @@ typedef enum {
      */
     RESVG_ERROR_MALFORMED_GZIP,
     /**
-     * We do not allow SVG with more than 1_000_000 elements for security reasons.
+     * SVG has more elements than allowed.
      */
     RESVG_ERROR_ELEMENTS_LIMIT_REACHED,
     /**
@@ -57,6 +57,22 @@ typedef enum {
      * Failed to parse an SVG data.
      */
     RESVG_ERROR_PARSING_FAILED,
+    /**
+     * SVG elements nesting is deeper than allowed.
+     */
+    RESVG_ERROR_NESTING_DEPTH_LIMIT_REACHED,
+    /**
+     * A raster image is bigger than allowed.
+     */
+    RESVG_ERROR_IMAGE_SIZE_LIMIT_REACHED,
+    /**
+     * Compressed SVG expands more than allowed.
+     */
+    RESVG_ERROR_GZIP_RATIO_LIMIT_REACHED,
+    /**
+     * DTD entities expand more than allowed.
+     */
+    RESVG_ERROR_ENTITY_EXPANSION_LIMIT_REACHED,
 } resvg_error;
 
 /**
@@ -1,8 +1,6 @@
-use std::rc::Rc;
-
 fn main() {
     let size = usvg::Size::from_wh(200.0, 200.0).unwrap();
-    let tree = usvg::Tree {
+    let mut tree = usvg::Tree {
         size,
         view_box: usvg::ViewBox {
             rect: size.to_non_zero_rect(0.0, 0.0),
@@ -37,15 +35,19 @@ fn main() {
     };
 
     let fill = Some(usvg::Fill {
-        paint: usvg::Paint::LinearGradient(Rc::new(gradient)),
+        paint: usvg::Paint::LinearGradient(usvg::Shared::new(gradient)),
         ..usvg::Fill::default()
     });
 
-    let mut path = usvg::Path::new(Rc::new(tiny_skia::PathBuilder::from_rect(
+    let mut path = usvg::Path::new(usvg::Shared::new(tiny_skia::PathBuilder::from_rect(
         tiny_skia::Rect::from_xywh(20.0, 20.0, 160.0, 160.0).unwrap(),
     )));
     path.fill = fill;
 
+    let root = tree.root.clone();
+    tree.append_node(&root, usvg::Node::new(usvg::NodeKind::Path(path)))
+        .unwrap();
+
     let rtree = resvg::Tree::from_usvg(&tree);
 
     let pixmap_size = rtree.size.to_int_size();
@@ -0,0 +1,83 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::render::{Context, RenderOptions, RenderState};
+use crate::tree::{Group, Node};
+use crate::{FillPath, Filter, Image, StrokePath};
+
+/// A drawing backend.
+///
+/// resvg traverses the render tree and calls the backend for each drawing operation.
+/// The built-in tiny-skia renderer, used by [`Tree::render`](crate::Tree::render),
+/// is one implementation of this trait.
+///
+/// Groups that only have a transform are flattened, i.e. the backend sees only groups
+/// that require a layer: groups with opacity, blend modes, clip paths, masks, filters
+/// or isolation.
+///
+/// All transforms are in the current coordinate system of the backend,
+/// which can be changed by [`Backend::push_layer`].
+pub trait Backend {
+    /// Fills a path.
+    ///
+    /// `transform` doesn't include the path's own transform.
+    fn fill_path(&mut self, path: &FillPath, transform: tiny_skia::Transform);
+
+    /// Strokes a path.
+    ///
+    /// `transform` doesn't include the path's own transform.
+    fn stroke_path(&mut self, path: &StrokePath, transform: tiny_skia::Transform);
+
+    /// Draws an image.
+    ///
+    /// `transform` doesn't include the image's own transform.
+    fn draw_image(&mut self, image: &Image, transform: tiny_skia::Transform);
+
+    /// Starts a new layer for a group.
+    ///
+    /// `transform` already includes the group's transform.
+    ///
+    /// Returns a transform that should be used for the group's content,
+    /// filters and clipping, which allows backends to use layer-local coordinates.
+    /// Returns `None` when the group should be skipped. In this case
+    /// [`Backend::pop_layer`] will not be called.
+    fn push_layer(
+        &mut self,
+        group: &Group,
+        transform: tiny_skia::Transform,
+    ) -> Option<tiny_skia::Transform>;
+
+    /// Applies a filter to the current layer.
+    ///
+    /// Called after the group's content was drawn, for each filter in order.
+    fn apply_filter(&mut self, filter: &Filter, transform: tiny_skia::Transform);
+
+    /// Finishes the current layer.
+    ///
+    /// The backend should apply the group's clip path and mask and then composite
+    /// the layer onto the parent one using the group's opacity and blend mode.
+    fn pop_layer(&mut self, group: &Group, transform: tiny_skia::Transform);
+
+    /// Checks that a group is completely outside the drawing area,
+    /// so it can be skipped.
+    ///
+    /// `transform` already includes the group's transform.
+    ///
+    /// Never skips groups by default.
+    fn is_outside(&self, group: &Group, transform: tiny_skia::Transform) -> bool {
+        let _ = (group, transform);
+        false
+    }
+}
+
+/// Renders nodes using a custom backend.
+///
+/// Can be used by backends to render clip paths, masks and patterns content.
+pub fn render_nodes(nodes: &[Node], transform: tiny_skia::Transform, backend: &mut dyn Backend) {
+    let state = RenderState::default();
+    // Only used for interruption checks.
+    let max_bbox = tiny_skia::IntRect::from_xywh(0, 0, 1, 1).unwrap();
+    let ctx = Context::new(&state, max_bbox, &RenderOptions::default());
+    crate::render::render_nodes(nodes, &ctx, transform, backend);
+}
@@ -2,19 +2,23 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use std::rc::Rc;
-
 use crate::render::Context;
 use crate::tree::{Node, OptionLog};
 
+/// A clip path.
 pub struct ClipPath {
+    /// Clip path's transform. Already includes `objectBoundingBox` units.
     pub transform: tiny_skia::Transform,
+    /// A clip path that should be applied to this clip path.
     pub clip_path: Option<Box<Self>>,
+    /// Clip path's children.
+    ///
+    /// Only fill paths and groups with clip paths affect clipping.
     pub children: Vec<Node>,
 }
 
 pub fn convert(
-    upath: Option<Rc<usvg::ClipPath>>,
+    upath: Option<usvg::Shared<usvg::ClipPath>>,
     object_bbox: tiny_skia::Rect,
 ) -> Option<ClipPath> {
     let upath = upath?;
@@ -22,9 +26,9 @@ pub fn convert(
     let mut transform = upath.transform;
 
     if upath.units == usvg::Units::ObjectBoundingBox {
-        let object_bbox = object_bbox
-            .to_non_zero_rect()
-            .log_none(|| log::warn!("Clipping of zero-sized shapes is not allowed."))?;
+        let object_bbox = object_bbox.to_non_zero_rect().log_none(|| {
+            usvg::report!(InvalidSize, "Clipping of zero-sized shapes is not allowed.")
+        })?;
 
         let ts = usvg::Transform::from_bbox(object_bbox);
         transform = transform.pre_concat(ts);
@@ -38,19 +42,28 @@ pub fn convert(
     })
 }
 
-pub fn apply(clip: &ClipPath, transform: tiny_skia::Transform, pixmap: &mut tiny_skia::Pixmap) {
-    let mut clip_pixmap = tiny_skia::Pixmap::new(pixmap.width(), pixmap.height()).unwrap();
+pub fn apply(
+    clip: &ClipPath,
+    ctx: &Context,
+    transform: tiny_skia::Transform,
+    pixmap: &mut tiny_skia::Pixmap,
+) {
+    let mut clip_pixmap = match ctx.create_pixmap(pixmap.width(), pixmap.height()) {
+        Some(v) => v,
+        None => return,
+    };
     clip_pixmap.fill(tiny_skia::Color::BLACK);
 
     draw_children(
         &clip.children,
         tiny_skia::BlendMode::Clear,
+        ctx,
         transform.pre_concat(clip.transform),
         &mut clip_pixmap.as_mut(),
     );
 
     if let Some(ref clip) = clip.clip_path {
-        apply(clip, transform, pixmap);
+        apply(clip, ctx, transform, pixmap);
     }
 
     let mut mask = tiny_skia::Mask::from_pixmap(clip_pixmap.as_ref(), tiny_skia::MaskType::Alpha);
@@ -61,29 +74,26 @@ pub fn apply(clip: &ClipPath, transform: tiny_skia::Transform, pixmap: &mut tiny
 fn draw_children(
     children: &[Node],
     mode: tiny_skia::BlendMode,
+    ctx: &Context,
     transform: tiny_skia::Transform,
     pixmap: &mut tiny_skia::PixmapMut,
 ) {
     for child in children {
         match child {
-            Node::FillPath(ref path) => {
-                // We could use any values here. They will not be used anyway.
-                let ctx = Context {
-                    max_bbox: tiny_skia::IntRect::from_xywh(0, 0, 1, 1).unwrap(),
-                };
-
-                crate::path::render_fill_path(path, mode, &ctx, transform, pixmap);
+            Node::FillPath(ref path) if path.visible => {
+                // The context is used only by vector effects.
+                crate::path::render_fill_path(path, mode, ctx, transform, pixmap);
             }
-            Node::Group(ref group) => {
+            Node::Group(ref group) if group.visible => {
                 let transform = transform.pre_concat(group.transform);
 
                 if let Some(ref clip) = group.clip_path {
                     // If a `clipPath` child also has a `clip-path`
                     // then we should render this child on a new canvas,
                     // clip it, and only then draw it to the `clipPath`.
-                    clip_group(&group.children, clip, transform, pixmap);
+                    clip_group(&group.children, clip, ctx, transform, pixmap);
                 } else {
-                    draw_children(&group.children, mode, transform, pixmap);
+                    draw_children(&group.children, mode, ctx, transform, pixmap);
                 }
             }
             _ => {}
@@ -94,18 +104,20 @@ fn draw_children(
 fn clip_group(
     children: &[Node],
     clip: &ClipPath,
+    ctx: &Context,
     transform: tiny_skia::Transform,
     pixmap: &mut tiny_skia::PixmapMut,
 ) -> Option<()> {
-    let mut clip_pixmap = tiny_skia::Pixmap::new(pixmap.width(), pixmap.height()).unwrap();
+    let mut clip_pixmap = ctx.create_pixmap(pixmap.width(), pixmap.height())?;
 
     draw_children(
         children,
         tiny_skia::BlendMode::SourceOver,
+        ctx,
         transform,
         &mut clip_pixmap.as_mut(),
     );
-    apply(clip, transform, &mut clip_pixmap);
+    apply(clip, ctx, transform, &mut clip_pixmap);
 
     let mut paint = tiny_skia::PixmapPaint::default();
     paint.blend_mode = tiny_skia::BlendMode::Xor;
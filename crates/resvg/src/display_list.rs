@@ -0,0 +1,859 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Display lists.
+//!
+//! A display list is a flat sequence of drawing commands in the device space,
+//! i.e. all transforms, paint servers, vector effects and strokes are already resolved.
+//! It can be replayed onto a pixmap, compared with another list or printed
+//! using a stable text format.
+//!
+//! Groups with filters are rasterized and recorded as images.
+//! Clip paths and masks are recorded as coverage masks of the layer size.
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::render::{Context, RenderOptions, RenderState};
+use crate::tree::{Group, Tree};
+use crate::{Backend, FillPath, Filter, Gradient, GradientKind, Image, ImageKind, StrokePath};
+
+/// A display list.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DisplayList {
+    /// Canvas size.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::int_size"))]
+    pub size: tiny_skia::IntSize,
+    /// Drawing commands, in order.
+    pub commands: Vec<Command>,
+}
+
+/// A drawing command.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Command {
+    /// Fills a path.
+    Fill(Fill),
+    /// Starts a new layer.
+    ///
+    /// All commands up to the matching [`Command::PopLayer`] are drawn into it.
+    PushLayer(Layer),
+    /// Composites the current layer onto the parent one.
+    PopLayer,
+    /// Draws an image.
+    DrawImage(DrawImage),
+}
+
+/// A path fill.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Fill {
+    /// Path in the device space.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::path"))]
+    pub path: Arc<tiny_skia::Path>,
+    /// Fill paint.
+    pub paint: Paint,
+    /// Fill rule.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::FillRuleDef"))]
+    pub rule: tiny_skia::FillRule,
+    /// Whether the path should be anti-aliased.
+    pub anti_alias: bool,
+}
+
+/// A fill paint.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Paint {
+    /// A solid color.
+    Color(
+        #[cfg_attr(feature = "serde", serde(with = "crate::serialization::color"))]
+        tiny_skia::Color,
+    ),
+    /// A gradient. Its transform maps it into the device space.
+    Gradient(
+        #[cfg_attr(feature = "serde", serde(with = "crate::serialization::gradient"))]
+        Arc<Gradient>,
+    ),
+    /// A rasterized pattern tile.
+    Pattern(Pattern),
+}
+
+/// A rasterized pattern tile.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pattern {
+    /// Tile pixels.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::pixmap"))]
+    pub pixmap: Arc<tiny_skia::Pixmap>,
+    /// Maps the tile into the device space.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::transform"))]
+    pub transform: tiny_skia::Transform,
+    /// Pattern opacity.
+    pub opacity: f32,
+}
+
+/// A layer.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Layer {
+    /// Layer region in the device space.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::int_rect"))]
+    pub bbox: tiny_skia::IntRect,
+    /// Layer opacity.
+    pub opacity: f32,
+    /// Layer blend mode.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::BlendModeDef"))]
+    pub blend_mode: tiny_skia::BlendMode,
+    /// A clip path coverage mask of the `bbox` size.
+    ///
+    /// Applied to the layer content before compositing.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::mask_option"))]
+    pub clip: Option<Arc<tiny_skia::Mask>>,
+    /// A mask of the `bbox` size.
+    ///
+    /// Applied to the layer content after `clip`.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::mask_option"))]
+    pub mask: Option<Arc<tiny_skia::Mask>>,
+}
+
+/// An image blit.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DrawImage {
+    /// Image pixels.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::pixmap"))]
+    pub pixmap: Arc<tiny_skia::Pixmap>,
+    /// Maps image pixels into the device space.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::transform"))]
+    pub transform: tiny_skia::Transform,
+    /// Resampling quality.
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serialization::FilterQualityDef")
+    )]
+    pub quality: tiny_skia::FilterQuality,
+    /// Image opacity.
+    pub opacity: f32,
+    /// Image blend mode.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::BlendModeDef"))]
+    pub blend_mode: tiny_skia::BlendMode,
+    /// A clip path in the device space.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::path_option"))]
+    pub clip: Option<Arc<tiny_skia::Path>>,
+}
+
+impl Tree {
+    /// Records the tree into a display list.
+    ///
+    /// `transform` will be used as a root transform, like in [`Tree::render`].
+    /// `size` is the canvas size. Content outside of the canvas is not recorded.
+    pub fn to_display_list(
+        &self,
+        transform: tiny_skia::Transform,
+        size: tiny_skia::IntSize,
+    ) -> DisplayList {
+        let state = RenderState::default();
+        let ctx = Context::new(
+            &state,
+            crate::render::max_bbox_for_size(size),
+            &RenderOptions::default(),
+        );
+
+        let mut recorder = Recorder {
+            ctx,
+            canvas: tiny_skia::IntRect::from_xywh(0, 0, size.width(), size.height()).unwrap(),
+            layers: Vec::new(),
+            commands: Vec::new(),
+        };
+
+        let ts =
+            usvg::utils::view_box_to_transform(self.view_box.rect, self.view_box.aspect, self.size);
+        crate::render::render_nodes(
+            &self.children,
+            &ctx,
+            transform.pre_concat(ts),
+            &mut recorder,
+        );
+
+        DisplayList {
+            size,
+            commands: recorder.commands,
+        }
+    }
+}
+
+impl DisplayList {
+    /// Draws the display list onto the pixmap.
+    ///
+    /// Produces the same image as [`Tree::render`], except for very thin strokes,
+    /// which are rendered as hairlines by tiny-skia.
+    pub fn replay(&self, pixmap: &mut tiny_skia::PixmapMut) {
+        let mut layers: Vec<(&Layer, tiny_skia::Pixmap)> = Vec::new();
+        for command in &self.commands {
+            match command {
+                Command::Fill(ref fill) => {
+                    with_target(&mut layers, pixmap, |ts, target| {
+                        replay_fill(fill, ts, target)
+                    });
+                }
+                Command::DrawImage(ref image) => {
+                    with_target(&mut layers, pixmap, |ts, target| {
+                        replay_image(image, ts, target)
+                    });
+                }
+                Command::PushLayer(ref layer) => {
+                    if let Some(layer_pixmap) =
+                        tiny_skia::Pixmap::new(layer.bbox.width(), layer.bbox.height())
+                    {
+                        layers.push((layer, layer_pixmap));
+                    }
+                }
+                Command::PopLayer => {
+                    let (layer, mut layer_pixmap) = match layers.pop() {
+                        Some(v) => v,
+                        None => continue,
+                    };
+
+                    if let Some(ref clip) = layer.clip {
+                        layer_pixmap.apply_mask(clip);
+                    }
+
+                    if let Some(ref mask) = layer.mask {
+                        layer_pixmap.apply_mask(mask);
+                    }
+
+                    let paint = tiny_skia::PixmapPaint {
+                        opacity: layer.opacity,
+                        blend_mode: layer.blend_mode,
+                        quality: tiny_skia::FilterQuality::Nearest,
+                    };
+
+                    with_target(&mut layers, pixmap, |ts, target| {
+                        target.draw_pixmap(
+                            layer.bbox.x() + ts.tx as i32,
+                            layer.bbox.y() + ts.ty as i32,
+                            layer_pixmap.as_ref(),
+                            &paint,
+                            tiny_skia::Transform::identity(),
+                            None,
+                        );
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Calls `f` with the current layer pixmap and a transform that maps
+/// the device space into it.
+fn with_target<F>(
+    layers: &mut [(&Layer, tiny_skia::Pixmap)],
+    canvas: &mut tiny_skia::PixmapMut,
+    f: F,
+) where
+    F: FnOnce(tiny_skia::Transform, &mut tiny_skia::PixmapMut),
+{
+    match layers.last_mut() {
+        Some((layer, ref mut pixmap)) => {
+            let ts = tiny_skia::Transform::from_translate(
+                -layer.bbox.x() as f32,
+                -layer.bbox.y() as f32,
+            );
+            f(ts, &mut pixmap.as_mut());
+        }
+        None => f(tiny_skia::Transform::identity(), canvas),
+    }
+}
+
+fn replay_fill(fill: &Fill, transform: tiny_skia::Transform, pixmap: &mut tiny_skia::PixmapMut) {
+    let mut paint = tiny_skia::Paint::default();
+    match fill.paint {
+        Paint::Color(color) => paint.set_color(color),
+        Paint::Gradient(ref gradient) => paint.shader = gradient.shader().clone(),
+        Paint::Pattern(ref pattern) => {
+            paint.shader = tiny_skia::Pattern::new(
+                pattern.pixmap.as_ref().as_ref(),
+                tiny_skia::SpreadMode::Repeat,
+                tiny_skia::FilterQuality::Bicubic,
+                pattern.opacity,
+                pattern.transform,
+            );
+        }
+    }
+    paint.anti_alias = fill.anti_alias;
+
+    pixmap.fill_path(&fill.path, &paint, fill.rule, transform, None);
+}
+
+fn replay_image(
+    image: &DrawImage,
+    transform: tiny_skia::Transform,
+    pixmap: &mut tiny_skia::PixmapMut,
+) {
+    let mask = image.clip.as_ref().and_then(|path| {
+        let mut mask = tiny_skia::Mask::new(pixmap.width(), pixmap.height())?;
+        mask.fill_path(path, tiny_skia::FillRule::Winding, true, transform);
+        Some(mask)
+    });
+
+    let paint = tiny_skia::PixmapPaint {
+        opacity: image.opacity,
+        blend_mode: image.blend_mode,
+        quality: image.quality,
+    };
+
+    pixmap.draw_pixmap(
+        0,
+        0,
+        image.pixmap.as_ref().as_ref(),
+        &paint,
+        transform.pre_concat(image.transform),
+        mask.as_ref(),
+    );
+}
+
+/// A backend that records drawing operations in the device space.
+struct Recorder<'a> {
+    ctx: Context<'a>,
+    canvas: tiny_skia::IntRect,
+    /// Regions of the active layers.
+    layers: Vec<tiny_skia::IntRect>,
+    commands: Vec<Command>,
+}
+
+impl Recorder<'_> {
+    fn convert_paint(
+        &self,
+        paint: &crate::Paint,
+        transform: tiny_skia::Transform,
+        path_transform: tiny_skia::Transform,
+    ) -> Option<Paint> {
+        let device_ts = transform.pre_concat(path_transform);
+        match paint {
+            crate::Paint::Color(color) => Some(Paint::Color(*color)),
+            crate::Paint::Gradient(ref gradient) => {
+                let gradient = gradient.with_transform(device_ts.pre_concat(gradient.transform))?;
+                Some(Paint::Gradient(Arc::new(gradient)))
+            }
+            crate::Paint::Pattern(ref pattern) => {
+                let (pixmap, patt_ts) =
+                    crate::paint_server::prepare_pattern_pixmap(pattern, &self.ctx, transform)?;
+                Some(Paint::Pattern(Pattern {
+                    pixmap: Arc::new(pixmap),
+                    transform: device_ts.pre_concat(patt_ts),
+                    opacity: pattern.opacity.get(),
+                }))
+            }
+        }
+    }
+
+    fn record_fill(&mut self, path: &FillPath, transform: tiny_skia::Transform) -> Option<()> {
+        let transform = crate::geom::apply_vector_effect(
+            path.vector_effect,
+            transform,
+            path.transform,
+            self.ctx.host_transform,
+        )
+        .unwrap_or(transform);
+
+        let paint = self.convert_paint(&path.paint, transform, path.transform)?;
+        let transform = transform.pre_concat(path.transform);
+        let device_path = path.path.as_ref().clone().transform(transform)?;
+
+        self.commands.push(Command::Fill(Fill {
+            path: Arc::new(device_path),
+            paint,
+            rule: path.rule,
+            anti_alias: path.anti_alias,
+        }));
+
+        Some(())
+    }
+
+    fn record_stroke(&mut self, path: &StrokePath, transform: tiny_skia::Transform) -> Option<()> {
+        let transform = crate::geom::apply_vector_effect(
+            path.vector_effect,
+            transform,
+            path.transform,
+            self.ctx.host_transform,
+        )
+        .unwrap_or(transform);
+
+        let paint = self.convert_paint(&path.paint, transform, path.transform)?;
+        let transform = transform.pre_concat(path.transform);
+
+        // Mirrors `render_stroke_path` and `PixmapMut::stroke_path`.
+        let outline = if path.vector_effect == usvg::VectorEffect::NonScalingStroke {
+            crate::path::stroke_non_scaling(&path.path, &path.stroke, transform)?
+                .transform(transform)?
+        } else {
+            let res_scale = tiny_skia::PathStroker::compute_resolution_scale(&transform);
+            let outline = match path.stroke.dash {
+                Some(ref dash) => path
+                    .path
+                    .dash(dash, res_scale)?
+                    .stroke(&path.stroke, res_scale)?,
+                None => path.path.stroke(&path.stroke, res_scale)?,
+            };
+            outline.transform(transform)?
+        };
+
+        self.commands.push(Command::Fill(Fill {
+            path: Arc::new(outline),
+            paint,
+            rule: tiny_skia::FillRule::Winding,
+            anti_alias: path.anti_alias,
+        }));
+
+        Some(())
+    }
+
+    fn record_image(&mut self, image: &Image, transform: tiny_skia::Transform) -> Option<()> {
+        let transform = crate::geom::apply_vector_effect(
+            image.vector_effect,
+            transform,
+            image.transform,
+            self.ctx.host_transform,
+        )
+        .unwrap_or(transform);
+
+        match image.kind {
+            #[cfg(feature = "raster-images")]
+            ImageKind::Raster(ref raster) => {
+                let img_size = tiny_skia::IntSize::from_wh(raster.width(), raster.height())?;
+                let rect = crate::image::raster_images::image_rect(&image.view_box, img_size);
+
+                let ts = tiny_skia::Transform::from_row(
+                    rect.width() / raster.width() as f32,
+                    0.0,
+                    0.0,
+                    rect.height() / raster.height() as f32,
+                    rect.x(),
+                    rect.y(),
+                );
+
+                let clip = if image.view_box.aspect.slice {
+                    let path = tiny_skia::PathBuilder::from_rect(image.view_box.rect.to_rect());
+                    path.transform(transform).map(Arc::new)
+                } else {
+                    None
+                };
+
+                self.commands.push(Command::DrawImage(DrawImage {
+                    pixmap: raster.clone(),
+                    transform: transform.pre_concat(image.transform).pre_concat(ts),
+                    quality: image.quality,
+                    opacity: 1.0,
+                    blend_mode: tiny_skia::BlendMode::SourceOver,
+                    clip,
+                }));
+            }
+            ImageKind::Vector(ref tree) => {
+                let img_size = tree.size.to_int_size();
+                let (ts, clip) =
+                    crate::geom::view_box_to_transform_with_clip(&image.view_box, img_size);
+
+                // Like with rendering, a nested tree is drawn onto a separate layer.
+                let bbox = self.layers.last().copied().unwrap_or(self.canvas);
+                let clip = match clip {
+                    Some(clip) => {
+                        let shift_ts = tiny_skia::Transform::from_translate(
+                            -bbox.x() as f32,
+                            -bbox.y() as f32,
+                        );
+                        let mut mask = tiny_skia::Mask::new(bbox.width(), bbox.height())?;
+                        mask.fill_path(
+                            &tiny_skia::PathBuilder::from_rect(clip.to_rect()),
+                            tiny_skia::FillRule::Winding,
+                            true,
+                            shift_ts.pre_concat(transform),
+                        );
+                        Some(Arc::new(mask))
+                    }
+                    None => None,
+                };
+
+                self.commands.push(Command::PushLayer(Layer {
+                    bbox,
+                    opacity: 1.0,
+                    blend_mode: tiny_skia::BlendMode::SourceOver,
+                    clip,
+                    mask: None,
+                }));
+                self.layers.push(bbox);
+
+                let transform = transform.pre_concat(image.transform).pre_concat(ts);
+                let tree_ts = usvg::utils::view_box_to_transform(
+                    tree.view_box.rect,
+                    tree.view_box.aspect,
+                    tree.size,
+                );
+                let ctx = self.ctx;
+                crate::render::render_nodes(
+                    &tree.children,
+                    &ctx,
+                    transform.pre_concat(tree_ts),
+                    self,
+                );
+
+                self.layers.pop();
+                self.commands.push(Command::PopLayer);
+            }
+        }
+
+        Some(())
+    }
+
+    /// Rasterizes a group with filters and records it as an image.
+    fn record_filtered_group(
+        &mut self,
+        group: &Group,
+        transform: tiny_skia::Transform,
+    ) -> Option<()> {
+        let layer = crate::render::render_layer(
+            group,
+            &self.ctx,
+            transform,
+            crate::render::render_nodes_to_pixmap,
+        )?;
+
+        self.commands.push(Command::DrawImage(DrawImage {
+            pixmap: Arc::new(layer.pixmap),
+            transform: tiny_skia::Transform::from_translate(
+                layer.ibbox.x() as f32,
+                layer.ibbox.y() as f32,
+            ),
+            quality: tiny_skia::FilterQuality::Nearest,
+            opacity: group.opacity.get(),
+            blend_mode: group.blend_mode,
+            clip: None,
+        }));
+
+        Some(())
+    }
+
+    fn push_group_layer(&mut self, group: &Group, transform: tiny_skia::Transform) -> Option<()> {
+        let bbox = crate::render::layer_bbox(group, &self.ctx, transform)?;
+        // Pixels outside the canvas are never visible.
+        let bbox = crate::geom::fit_to_rect(bbox, self.canvas)?;
+
+        let shift_ts = tiny_skia::Transform::from_translate(-bbox.x() as f32, -bbox.y() as f32);
+        let layer_ts = shift_ts.pre_concat(transform);
+
+        let ctx = Context {
+            host_transform: shift_ts.pre_concat(self.ctx.host_transform),
+            ..self.ctx
+        };
+
+        let clip = match group.clip_path {
+            Some(ref clip_path) => {
+                let mut pixmap = coverage_pixmap(&ctx, bbox)?;
+                crate::clip::apply(clip_path, &ctx, layer_ts, &mut pixmap);
+                Some(Arc::new(to_mask(&pixmap)))
+            }
+            None => None,
+        };
+
+        let mask = match group.mask {
+            Some(ref mask) => {
+                let mut pixmap = coverage_pixmap(&ctx, bbox)?;
+                crate::mask::apply(mask, &ctx, layer_ts, &mut pixmap);
+                Some(Arc::new(to_mask(&pixmap)))
+            }
+            None => None,
+        };
+
+        self.commands.push(Command::PushLayer(Layer {
+            bbox,
+            opacity: group.opacity.get(),
+            blend_mode: group.blend_mode,
+            clip,
+            mask,
+        }));
+        self.layers.push(bbox);
+
+        Some(())
+    }
+}
+
+/// Creates an opaque pixmap, which coverage is reduced by clip paths and masks.
+fn coverage_pixmap(ctx: &Context, bbox: tiny_skia::IntRect) -> Option<tiny_skia::Pixmap> {
+    let mut pixmap = ctx.create_pixmap(bbox.width(), bbox.height())?;
+    pixmap.fill(tiny_skia::Color::WHITE);
+    Some(pixmap)
+}
+
+fn to_mask(pixmap: &tiny_skia::Pixmap) -> tiny_skia::Mask {
+    tiny_skia::Mask::from_pixmap(pixmap.as_ref(), tiny_skia::MaskType::Alpha)
+}
+
+impl Backend for Recorder<'_> {
+    fn fill_path(&mut self, path: &FillPath, transform: tiny_skia::Transform) {
+        self.record_fill(path, transform);
+    }
+
+    fn stroke_path(&mut self, path: &StrokePath, transform: tiny_skia::Transform) {
+        self.record_stroke(path, transform);
+    }
+
+    fn draw_image(&mut self, image: &Image, transform: tiny_skia::Transform) {
+        self.record_image(image, transform);
+    }
+
+    fn push_layer(
+        &mut self,
+        group: &Group,
+        transform: tiny_skia::Transform,
+    ) -> Option<tiny_skia::Transform> {
+        if !group.filters.is_empty() {
+            self.record_filtered_group(group, transform);
+            return None;
+        }
+
+        self.push_group_layer(group, transform)?;
+        Some(transform)
+    }
+
+    fn apply_filter(&mut self, _: &Filter, _: tiny_skia::Transform) {
+        // Groups with filters are rasterized in `push_layer`.
+    }
+
+    fn pop_layer(&mut self, _: &Group, _: tiny_skia::Transform) {
+        self.layers.pop();
+        self.commands.push(Command::PopLayer);
+    }
+
+    fn is_outside(&self, group: &Group, transform: tiny_skia::Transform) -> bool {
+        crate::render::is_outside(group, transform, self.canvas.width(), self.canvas.height())
+    }
+}
+
+/// Prints the display list in a line-based text format.
+///
+/// Images and masks are printed as their size and a hash of their pixels.
+/// The format is stable and suitable for diffing.
+impl fmt::Display for DisplayList {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "size {}x{}", self.size.width(), self.size.height())?;
+
+        let mut depth = 0usize;
+        for command in &self.commands {
+            if let Command::PopLayer = command {
+                depth = depth.saturating_sub(1);
+            }
+
+            for _ in 0..depth {
+                f.write_str("  ")?;
+            }
+
+            match command {
+                Command::Fill(ref fill) => {
+                    let rule = match fill.rule {
+                        tiny_skia::FillRule::Winding => "nonzero",
+                        tiny_skia::FillRule::EvenOdd => "evenodd",
+                    };
+                    write!(f, "fill {}", rule)?;
+                    if !fill.anti_alias {
+                        f.write_str(" no-aa")?;
+                    }
+                    writeln!(f, " {} {}", PaintFmt(&fill.paint), PathFmt(&fill.path))?;
+                }
+                Command::PushLayer(ref layer) => {
+                    let r = layer.bbox;
+                    write!(f, "push {} {} {} {}", r.x(), r.y(), r.width(), r.height())?;
+                    if layer.opacity != 1.0 {
+                        write!(f, " opacity {}", Num(layer.opacity))?;
+                    }
+                    if layer.blend_mode != tiny_skia::BlendMode::SourceOver {
+                        write!(f, " blend {:?}", layer.blend_mode)?;
+                    }
+                    if let Some(ref clip) = layer.clip {
+                        write!(
+                            f,
+                            " clip {}",
+                            Pixels(clip.width(), clip.height(), clip.data())
+                        )?;
+                    }
+                    if let Some(ref mask) = layer.mask {
+                        write!(
+                            f,
+                            " mask {}",
+                            Pixels(mask.width(), mask.height(), mask.data())
+                        )?;
+                    }
+                    writeln!(f)?;
+                    depth += 1;
+                }
+                Command::PopLayer => {
+                    writeln!(f, "pop")?;
+                }
+                Command::DrawImage(ref image) => {
+                    let p = &image.pixmap;
+                    write!(
+                        f,
+                        "image {} {} {:?}",
+                        Pixels(p.width(), p.height(), p.data()),
+                        Matrix(image.transform),
+                        image.quality
+                    )?;
+                    if image.opacity != 1.0 {
+                        write!(f, " opacity {}", Num(image.opacity))?;
+                    }
+                    if image.blend_mode != tiny_skia::BlendMode::SourceOver {
+                        write!(f, " blend {:?}", image.blend_mode)?;
+                    }
+                    if let Some(ref clip) = image.clip {
+                        write!(f, " clip {}", PathFmt(clip))?;
+                    }
+                    writeln!(f)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A number rounded to 3 decimal places.
+struct Num(f32);
+
+impl fmt::Display for Num {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let v = (self.0 as f64 * 1000.0).round() / 1000.0;
+        // Avoid `-0`.
+        let v = if v == 0.0 { 0.0 } else { v };
+        write!(f, "{}", v)
+    }
+}
+
+struct Matrix(tiny_skia::Transform);
+
+impl fmt::Display for Matrix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let ts = self.0;
+        write!(
+            f,
+            "matrix({} {} {} {} {} {})",
+            Num(ts.sx),
+            Num(ts.ky),
+            Num(ts.kx),
+            Num(ts.sy),
+            Num(ts.tx),
+            Num(ts.ty)
+        )
+    }
+}
+
+struct ColorFmt(tiny_skia::Color);
+
+impl fmt::Display for ColorFmt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let c = self.0.to_color_u8();
+        write!(
+            f,
+            "#{:02x}{:02x}{:02x}{:02x}",
+            c.red(),
+            c.green(),
+            c.blue(),
+            c.alpha()
+        )
+    }
+}
+
+struct PathFmt<'a>(&'a tiny_skia::Path);
+
+impl fmt::Display for PathFmt<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("\"")?;
+        for (i, segment) in self.0.segments().enumerate() {
+            if i != 0 {
+                f.write_str(" ")?;
+            }
+
+            match segment {
+                tiny_skia::PathSegment::MoveTo(p) => write!(f, "M {} {}", Num(p.x), Num(p.y))?,
+                tiny_skia::PathSegment::LineTo(p) => write!(f, "L {} {}", Num(p.x), Num(p.y))?,
+                tiny_skia::PathSegment::QuadTo(p1, p) => {
+                    write!(f, "Q {} {} {} {}", Num(p1.x), Num(p1.y), Num(p.x), Num(p.y))?
+                }
+                tiny_skia::PathSegment::CubicTo(p1, p2, p) => write!(
+                    f,
+                    "C {} {} {} {} {} {}",
+                    Num(p1.x),
+                    Num(p1.y),
+                    Num(p2.x),
+                    Num(p2.y),
+                    Num(p.x),
+                    Num(p.y)
+                )?,
+                tiny_skia::PathSegment::Close => f.write_str("Z")?,
+            }
+        }
+        f.write_str("\"")
+    }
+}
+
+struct PaintFmt<'a>(&'a Paint);
+
+impl fmt::Display for PaintFmt<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Paint::Color(color) => write!(f, "{}", ColorFmt(*color)),
+            Paint::Gradient(ref gradient) => {
+                match gradient.kind {
+                    GradientKind::Linear { x1, y1, x2, y2 } => {
+                        write!(f, "linear({} {} {} {}", Num(x1), Num(y1), Num(x2), Num(y2))?
+                    }
+                    GradientKind::Radial { fx, fy, cx, cy, r } => write!(
+                        f,
+                        "radial({} {} {} {} {}",
+                        Num(fx),
+                        Num(fy),
+                        Num(cx),
+                        Num(cy),
+                        Num(r)
+                    )?,
+                }
+
+                write!(
+                    f,
+                    "; {:?}; {}",
+                    gradient.spread_mode,
+                    Matrix(gradient.transform)
+                )?;
+                for stop in &gradient.stops {
+                    write!(f, "; {} {}", Num(stop.offset), ColorFmt(stop.color))?;
+                }
+                f.write_str(")")
+            }
+            Paint::Pattern(ref pattern) => {
+                let p = &pattern.pixmap;
+                write!(
+                    f,
+                    "pattern({}; {}; {})",
+                    Pixels(p.width(), p.height(), p.data()),
+                    Matrix(pattern.transform),
+                    Num(pattern.opacity)
+                )
+            }
+        }
+    }
+}
+
+/// Pixels data printed as a size and a hash.
+struct Pixels<'a>(u32, u32, &'a [u8]);
+
+impl fmt::Display for Pixels<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // FNV-1a, which is stable unlike `std` hashers.
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for b in self.2 {
+            hash ^= u64::from(*b);
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+
+        write!(f, "{}x{}:{:016x}", self.0, self.1, hash)
+    }
+}
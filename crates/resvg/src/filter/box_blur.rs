@@ -18,10 +18,13 @@ const STEPS: usize = 5;
 ///
 /// A negative or zero `sigma_x`/`sigma_y` will disable the blur along that axis.
 ///
+/// `interrupted` is checked for each row and column. The image is left in an undefined state
+/// when it returns `true`.
+///
 /// # Allocations
 ///
 /// This method will allocate a copy of the `src` image as a back buffer.
-pub fn apply(sigma_x: f64, sigma_y: f64, mut src: ImageRefMut) {
+pub fn apply(sigma_x: f64, sigma_y: f64, mut src: ImageRefMut, interrupted: &dyn Fn() -> bool) {
     let boxes_horz = create_box_gauss(sigma_x as f32);
     let boxes_vert = create_box_gauss(sigma_y as f32);
     let mut backbuf = src.data.to_vec();
@@ -30,7 +33,13 @@ pub fn apply(sigma_x: f64, sigma_y: f64, mut src: ImageRefMut) {
     for (box_size_horz, box_size_vert) in boxes_horz.iter().zip(boxes_vert.iter()) {
         let radius_horz = ((box_size_horz - 1) / 2) as usize;
         let radius_vert = ((box_size_vert - 1) / 2) as usize;
-        box_blur_impl(radius_horz, radius_vert, &mut backbuf, &mut src);
+        box_blur_impl(
+            radius_horz,
+            radius_vert,
+            &mut backbuf,
+            &mut src,
+            interrupted,
+        );
     }
 }
 
@@ -77,13 +86,19 @@ fn box_blur_impl(
     blur_radius_vert: usize,
     backbuf: &mut ImageRefMut,
     frontbuf: &mut ImageRefMut,
+    interrupted: &dyn Fn() -> bool,
 ) {
-    box_blur_vert(blur_radius_vert, frontbuf, backbuf);
-    box_blur_horz(blur_radius_horz, backbuf, frontbuf);
+    box_blur_vert(blur_radius_vert, frontbuf, backbuf, interrupted);
+    box_blur_horz(blur_radius_horz, backbuf, frontbuf, interrupted);
 }
 
 #[inline]
-fn box_blur_vert(blur_radius: usize, backbuf: &ImageRefMut, frontbuf: &mut ImageRefMut) {
+fn box_blur_vert(
+    blur_radius: usize,
+    backbuf: &ImageRefMut,
+    frontbuf: &mut ImageRefMut,
+    interrupted: &dyn Fn() -> bool,
+) {
     if blur_radius == 0 {
         frontbuf.data.copy_from_slice(backbuf.data);
         return;
@@ -97,6 +112,10 @@ fn box_blur_vert(blur_radius: usize, backbuf: &ImageRefMut, frontbuf: &mut Image
     let blur_radius_next = blur_radius as isize + 1;
 
     for i in 0..width {
+        if interrupted() {
+            return;
+        }
+
         let col_start = i; //inclusive
         let col_end = i + width * (height - 1); //inclusive
         let mut ti = i;
@@ -208,7 +227,12 @@ fn box_blur_vert(blur_radius: usize, backbuf: &ImageRefMut, frontbuf: &mut Image
 }
 
 #[inline]
-fn box_blur_horz(blur_radius: usize, backbuf: &ImageRefMut, frontbuf: &mut ImageRefMut) {
+fn box_blur_horz(
+    blur_radius: usize,
+    backbuf: &ImageRefMut,
+    frontbuf: &mut ImageRefMut,
+    interrupted: &dyn Fn() -> bool,
+) {
     if blur_radius == 0 {
         frontbuf.data.copy_from_slice(backbuf.data);
         return;
@@ -222,6 +246,10 @@ fn box_blur_horz(blur_radius: usize, backbuf: &ImageRefMut, frontbuf: &mut Image
     let blur_radius_next = blur_radius as isize + 1;
 
     for i in 0..height {
+        if interrupted() {
+            return;
+        }
+
         let row_start = i * width; // inclusive
         let row_end = (i + 1) * width - 1; // inclusive
         let mut ti = i * width; // VERTICAL: $i;
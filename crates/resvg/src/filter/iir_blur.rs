@@ -43,10 +43,13 @@ struct BlurData {
 ///
 /// A negative or zero `sigma_x`/`sigma_y` will disable the blur along that axis.
 ///
+/// `interrupted` is checked for each row and column. The image is left in an undefined state
+/// when it returns `true`.
+///
 /// # Allocations
 ///
 /// This method will allocate a 2x `src` buffer.
-pub fn apply(sigma_x: f64, sigma_y: f64, src: ImageRefMut) {
+pub fn apply(sigma_x: f64, sigma_y: f64, src: ImageRefMut, interrupted: &dyn Fn() -> bool) {
     let buf_size = (src.width * src.height) as usize;
     let mut buf = vec![0.0; buf_size];
     let buf = &mut buf;
@@ -60,30 +63,39 @@ pub fn apply(sigma_x: f64, sigma_y: f64, src: ImageRefMut) {
     };
 
     let data = src.data.as_mut_slice();
-    gaussian_channel(data, &d, 0, buf);
-    gaussian_channel(data, &d, 1, buf);
-    gaussian_channel(data, &d, 2, buf);
-    gaussian_channel(data, &d, 3, buf);
+    for channel in 0..4 {
+        gaussian_channel(data, &d, channel, buf, interrupted);
+    }
 }
 
-fn gaussian_channel(data: &mut [u8], d: &BlurData, channel: usize, buf: &mut Vec<f64>) {
+fn gaussian_channel(
+    data: &mut [u8],
+    d: &BlurData,
+    channel: usize,
+    buf: &mut Vec<f64>,
+    interrupted: &dyn Fn() -> bool,
+) {
     for i in 0..data.len() / 4 {
         buf[i] = data[i * 4 + channel] as f64 / 255.0;
     }
 
-    gaussianiir2d(d, buf);
+    gaussianiir2d(d, buf, interrupted);
 
     for i in 0..data.len() / 4 {
         data[i * 4 + channel] = (buf[i] * 255.0) as u8;
     }
 }
 
-fn gaussianiir2d(d: &BlurData, buf: &mut Vec<f64>) {
+fn gaussianiir2d(d: &BlurData, buf: &mut Vec<f64>, interrupted: &dyn Fn() -> bool) {
     // Filter horizontally along each row.
     let (lambda_x, dnu_x) = if d.sigma_x > 0.0 {
         let (lambda, dnu) = gen_coefficients(d.sigma_x, d.steps);
 
         for y in 0..d.height {
+            if interrupted() {
+                return;
+            }
+
             for _ in 0..d.steps {
                 let idx = d.width * y;
 
@@ -111,6 +123,10 @@ fn gaussianiir2d(d: &BlurData, buf: &mut Vec<f64>) {
     let (lambda_y, dnu_y) = if d.sigma_y > 0.0 {
         let (lambda, dnu) = gen_coefficients(d.sigma_y, d.steps);
         for x in 0..d.width {
+            if interrupted() {
+                return;
+            }
+
             for _ in 0..d.steps {
                 let idx = x;
 
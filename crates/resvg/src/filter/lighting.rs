@@ -129,6 +129,8 @@ impl Normal {
 ///
 /// Does nothing when `src` is less than 3x3.
 ///
+/// `interrupted` is checked for each row.
+///
 /// # Panics
 ///
 /// - When `src` and `dest` have different sizes.
@@ -137,6 +139,7 @@ pub fn diffuse_lighting(
     light_source: LightSource,
     src: ImageRef,
     dest: ImageRefMut,
+    interrupted: &dyn Fn() -> bool,
 ) {
     assert!(src.width == dest.width && src.height == dest.height);
 
@@ -164,6 +167,7 @@ pub fn diffuse_lighting(
         calc_diffuse_alpha,
         src,
         dest,
+        interrupted,
     );
 }
 
@@ -174,6 +178,8 @@ pub fn diffuse_lighting(
 ///
 /// Does nothing when `src` is less than 3x3.
 ///
+/// `interrupted` is checked for each row.
+///
 /// # Panics
 ///
 /// - When `src` and `dest` have different sizes.
@@ -182,6 +188,7 @@ pub fn specular_lighting(
     light_source: LightSource,
     src: ImageRef,
     dest: ImageRefMut,
+    interrupted: &dyn Fn() -> bool,
 ) {
     assert!(src.width == dest.width && src.height == dest.height);
 
@@ -226,6 +233,7 @@ pub fn specular_lighting(
         calc_specular_alpha,
         src,
         dest,
+        interrupted,
     );
 }
 
@@ -237,6 +245,7 @@ fn apply(
     calc_alpha: fn(u8, u8, u8) -> u8,
     src: ImageRef,
     mut dest: ImageRefMut,
+    interrupted: &dyn Fn() -> bool,
 ) {
     if src.width < 3 || src.height < 3 {
         return;
@@ -305,6 +314,10 @@ fn apply(
     }
 
     for y in 1..height - 1 {
+        if interrupted() {
+            return;
+        }
+
         for x in 1..width - 1 {
             calc(x, y, interior_normal(src, x, y));
         }
@@ -8,6 +8,7 @@ use rgb::{FromSlice, RGBA8};
 use tiny_skia::IntRect;
 use usvg::{ApproxEqUlps, ApproxZeroUlps};
 
+use crate::render::Context;
 use crate::tree::Node;
 
 mod box_blur;
@@ -90,16 +91,65 @@ pub struct Primitive {
     pub region: tiny_skia::NonZeroRect,
     pub color_interpolation: usvg::filter::ColorInterpolation,
     pub result: String,
-    pub kind: usvg::filter::Kind,
+    pub kind: Kind,
 }
 
+/// Same as `usvg::filter::Kind`, but with `feImage` data already converted.
+pub enum Kind {
+    Blend(usvg::filter::Blend),
+    ColorMatrix(usvg::filter::ColorMatrix),
+    ComponentTransfer(usvg::filter::ComponentTransfer),
+    Composite(usvg::filter::Composite),
+    ConvolveMatrix(usvg::filter::ConvolveMatrix),
+    DiffuseLighting(usvg::filter::DiffuseLighting),
+    DisplacementMap(usvg::filter::DisplacementMap),
+    DropShadow(usvg::filter::DropShadow),
+    Flood(usvg::filter::Flood),
+    GaussianBlur(usvg::filter::GaussianBlur),
+    Image(ImagePrimitive),
+    Merge(usvg::filter::Merge),
+    Morphology(usvg::filter::Morphology),
+    Offset(usvg::filter::Offset),
+    SpecularLighting(usvg::filter::SpecularLighting),
+    Tile(usvg::filter::Tile),
+    Turbulence(usvg::filter::Turbulence),
+}
+
+pub struct ImagePrimitive {
+    pub aspect: usvg::AspectRatio,
+    pub quality: tiny_skia::FilterQuality,
+    /// `None` when an image cannot be loaded. Results in an empty image.
+    pub data: Option<ImagePrimitiveKind>,
+}
+
+pub enum ImagePrimitiveKind {
+    Image(crate::image::ImageKind),
+    Use(Box<crate::Tree>),
+}
+
+/// A filter with resolved units and primitives.
 pub struct Filter {
+    /// Filter region in the group's user space.
     pub region: tiny_skia::NonZeroRect,
-    pub primitives: Vec<Primitive>,
+    pub(crate) primitives: Vec<Primitive>,
+}
+
+impl Filter {
+    /// Applies the filter to a pixmap.
+    ///
+    /// Can be used by custom backends to reuse the built-in filters implementation.
+    /// `transform` maps the group's user space into the pixmap.
+    pub fn apply(&self, transform: tiny_skia::Transform, pixmap: &mut tiny_skia::Pixmap) {
+        let state = crate::render::RenderState::default();
+        let max_bbox = crate::render::max_bbox(&pixmap.as_mut());
+        let ctx = Context::new(&state, max_bbox, &crate::RenderOptions::default());
+
+        apply(self, &ctx, transform, pixmap);
+    }
 }
 
 pub fn convert(
-    ufilters: &[Rc<usvg::filter::Filter>],
+    ufilters: &[usvg::Shared<usvg::filter::Filter>],
     object_bbox: Option<tiny_skia::Rect>,
 ) -> (Vec<Filter>, Option<tiny_skia::Rect>) {
     let object_bbox = object_bbox.and_then(|bbox| bbox.to_non_zero_rect());
@@ -131,7 +181,7 @@ fn convert_filter(
         let subregion = match calc_subregion(ufilter, uprimitive, object_bbox, region) {
             Some(v) => v,
             None => {
-                log::warn!("Invalid filter primitive region.");
+                usvg::report!(FilterFailed, "Invalid filter primitive region.");
                 continue;
             }
         };
@@ -153,25 +203,23 @@ fn convert_primitive(
     uprimitive: &usvg::filter::Primitive,
     units: usvg::Units,
     object_bbox: Option<tiny_skia::NonZeroRect>,
-) -> Option<usvg::filter::Kind> {
+) -> Option<Kind> {
     match uprimitive.kind {
         usvg::filter::Kind::DisplacementMap(ref fe) => {
             let (sx, _) = scale_coordinates(fe.scale, fe.scale, units, object_bbox)?;
-            Some(usvg::filter::Kind::DisplacementMap(
-                usvg::filter::DisplacementMap {
-                    input1: fe.input1.clone(),
-                    input2: fe.input2.clone(),
-                    scale: sx,
-                    x_channel_selector: fe.x_channel_selector,
-                    y_channel_selector: fe.y_channel_selector,
-                },
-            ))
+            Some(Kind::DisplacementMap(usvg::filter::DisplacementMap {
+                input1: fe.input1.clone(),
+                input2: fe.input2.clone(),
+                scale: sx,
+                x_channel_selector: fe.x_channel_selector,
+                y_channel_selector: fe.y_channel_selector,
+            }))
         }
         usvg::filter::Kind::DropShadow(ref fe) => {
             let (dx, dy) = scale_coordinates(fe.dx, fe.dy, units, object_bbox)?;
             let (std_dev_x, std_dev_y) =
                 scale_coordinates(fe.std_dev_x.get(), fe.std_dev_y.get(), units, object_bbox)?;
-            Some(usvg::filter::Kind::DropShadow(usvg::filter::DropShadow {
+            Some(Kind::DropShadow(usvg::filter::DropShadow {
                 input: fe.input.clone(),
                 dx,
                 dy,
@@ -184,18 +232,16 @@ fn convert_primitive(
         usvg::filter::Kind::GaussianBlur(ref fe) => {
             let (std_dev_x, std_dev_y) =
                 scale_coordinates(fe.std_dev_x.get(), fe.std_dev_y.get(), units, object_bbox)?;
-            Some(usvg::filter::Kind::GaussianBlur(
-                usvg::filter::GaussianBlur {
-                    input: fe.input.clone(),
-                    std_dev_x: usvg::PositiveF32::new(std_dev_x).unwrap_or_default(),
-                    std_dev_y: usvg::PositiveF32::new(std_dev_y).unwrap_or_default(),
-                },
-            ))
+            Some(Kind::GaussianBlur(usvg::filter::GaussianBlur {
+                input: fe.input.clone(),
+                std_dev_x: usvg::PositiveF32::new(std_dev_x).unwrap_or_default(),
+                std_dev_y: usvg::PositiveF32::new(std_dev_y).unwrap_or_default(),
+            }))
         }
         usvg::filter::Kind::Morphology(ref fe) => {
             let (radius_x, radius_y) =
                 scale_coordinates(fe.radius_x.get(), fe.radius_y.get(), units, object_bbox)?;
-            Some(usvg::filter::Kind::Morphology(usvg::filter::Morphology {
+            Some(Kind::Morphology(usvg::filter::Morphology {
                 input: fe.input.clone(),
                 operator: fe.operator,
                 radius_x: usvg::PositiveF32::new(radius_x).unwrap_or_default(),
@@ -204,13 +250,73 @@ fn convert_primitive(
         }
         usvg::filter::Kind::Offset(ref fe) => {
             let (dx, dy) = scale_coordinates(fe.dx, fe.dy, units, object_bbox)?;
-            Some(usvg::filter::Kind::Offset(usvg::filter::Offset {
+            Some(Kind::Offset(usvg::filter::Offset {
                 input: fe.input.clone(),
                 dx,
                 dy,
             }))
         }
-        _ => Some(uprimitive.kind.clone()),
+        usvg::filter::Kind::Image(ref fe) => Some(Kind::Image(convert_image(fe))),
+        usvg::filter::Kind::Blend(ref fe) => Some(Kind::Blend(fe.clone())),
+        usvg::filter::Kind::ColorMatrix(ref fe) => Some(Kind::ColorMatrix(fe.clone())),
+        usvg::filter::Kind::ComponentTransfer(ref fe) => Some(Kind::ComponentTransfer(fe.clone())),
+        usvg::filter::Kind::Composite(ref fe) => Some(Kind::Composite(fe.clone())),
+        usvg::filter::Kind::ConvolveMatrix(ref fe) => Some(Kind::ConvolveMatrix(fe.clone())),
+        usvg::filter::Kind::DiffuseLighting(ref fe) => Some(Kind::DiffuseLighting(fe.clone())),
+        usvg::filter::Kind::Flood(fe) => Some(Kind::Flood(fe)),
+        usvg::filter::Kind::Merge(ref fe) => Some(Kind::Merge(fe.clone())),
+        usvg::filter::Kind::SpecularLighting(ref fe) => Some(Kind::SpecularLighting(fe.clone())),
+        usvg::filter::Kind::Tile(ref fe) => Some(Kind::Tile(fe.clone())),
+        usvg::filter::Kind::Turbulence(fe) => Some(Kind::Turbulence(fe)),
+    }
+}
+
+fn convert_image(fe: &usvg::filter::Image) -> ImagePrimitive {
+    let mut quality = tiny_skia::FilterQuality::Bicubic;
+    if fe.rendering_mode == usvg::ImageRendering::OptimizeSpeed {
+        quality = tiny_skia::FilterQuality::Nearest;
+    }
+
+    let data = match fe.data {
+        usvg::filter::ImageKind::Image(ref kind) => {
+            // The actual view box depends on the primitive region and will be set during rendering.
+            let uimage = usvg::Image {
+                id: String::new(),
+                classes: Vec::new(),
+                transform: usvg::Transform::default(),
+                visibility: usvg::Visibility::Visible,
+                pointer_events: usvg::PointerEvents::default(),
+                view_box: usvg::ViewBox {
+                    rect: tiny_skia::NonZeroRect::from_xywh(0.0, 0.0, 1.0, 1.0).unwrap(),
+                    aspect: fe.aspect,
+                },
+                rendering_mode: fe.rendering_mode,
+                vector_effect: usvg::VectorEffect::None,
+                kind: kind.clone(),
+                // Title shouldn't matter here because we are just rendering anyway.
+                title: None,
+                origin: None,
+            };
+
+            let mut children = Vec::new();
+            crate::image::convert(&uimage, tiny_skia::Transform::default(), &mut children);
+            match children.pop() {
+                Some(Node::Image(image)) => Some(ImagePrimitiveKind::Image(image.kind)),
+                _ => None,
+            }
+        }
+        usvg::filter::ImageKind::Use(ref node) => {
+            crate::Tree::from_usvg_node(node).map(|mut rtree| {
+                rtree.view_box.rect = rtree.view_box.rect.translate_to(0.0, 0.0).unwrap();
+                ImagePrimitiveKind::Use(Box::new(rtree))
+            })
+        }
+    };
+
+    ImagePrimitive {
+        aspect: fe.aspect,
+        quality,
+        data,
     }
 }
 
@@ -218,6 +324,8 @@ fn convert_primitive(
 pub(crate) enum Error {
     InvalidRegion,
     NoResults,
+    /// Rendering was interrupted. The reason is stored in the render context.
+    Interrupted,
 }
 
 trait PixmapExt: Sized {
@@ -463,8 +571,13 @@ struct FilterResult {
     image: Image,
 }
 
-pub fn apply(filter: &Filter, ts: tiny_skia::Transform, source: &mut tiny_skia::Pixmap) {
-    let result = apply_inner(filter, ts, source);
+pub fn apply(
+    filter: &Filter,
+    ctx: &Context,
+    ts: tiny_skia::Transform,
+    source: &mut tiny_skia::Pixmap,
+) {
+    let result = apply_inner(filter, ctx, ts, source);
     let result = result.and_then(|image| apply_to_canvas(image, source));
 
     // Clear on error.
@@ -475,14 +588,16 @@ pub fn apply(filter: &Filter, ts: tiny_skia::Transform, source: &mut tiny_skia::
     match result {
         Ok(_) => {}
         Err(Error::InvalidRegion) => {
-            log::warn!("Filter has an invalid region.");
+            usvg::report!(FilterFailed, "Filter has an invalid region.");
         }
         Err(Error::NoResults) => {}
+        Err(Error::Interrupted) => {}
     }
 }
 
 fn apply_inner(
     filter: &Filter,
+    ctx: &Context,
     ts: usvg::Transform,
     source: &mut tiny_skia::Pixmap,
 ) -> Result<Image, Error> {
@@ -495,6 +610,10 @@ fn apply_inner(
         .ok_or(Error::InvalidRegion)?;
 
     for primitive in &filter.primitives {
+        if ctx.is_interrupted() {
+            return Err(Error::Interrupted);
+        }
+
         let cs = primitive.color_interpolation;
         let mut subregion = primitive
             .region
@@ -503,7 +622,7 @@ fn apply_inner(
             .ok_or(Error::InvalidRegion)?;
 
         // `feOffset` inherits its region from the input.
-        if let usvg::filter::Kind::Offset(ref fe) = primitive.kind {
+        if let Kind::Offset(ref fe) = primitive.kind {
             if let usvg::filter::Input::Reference(ref name) = fe.input {
                 if let Some(res) = results.iter().rev().find(|v| v.name == *name) {
                     subregion = res.image.region;
@@ -512,72 +631,77 @@ fn apply_inner(
         }
 
         let mut result = match primitive.kind {
-            usvg::filter::Kind::Blend(ref fe) => {
+            Kind::Blend(ref fe) => {
                 let input1 = get_input(&fe.input1, region, source, &results)?;
                 let input2 = get_input(&fe.input2, region, source, &results)?;
                 apply_blend(fe, cs, region, input1, input2)
             }
-            usvg::filter::Kind::DropShadow(ref fe) => {
+            Kind::DropShadow(ref fe) => {
                 let input = get_input(&fe.input, region, source, &results)?;
-                apply_drop_shadow(fe, cs, ts, input)
+                apply_drop_shadow(fe, cs, ts, input, ctx)
             }
-            usvg::filter::Kind::Flood(ref fe) => apply_flood(fe, region),
-            usvg::filter::Kind::GaussianBlur(ref fe) => {
+            Kind::Flood(ref fe) => apply_flood(fe, region),
+            Kind::GaussianBlur(ref fe) => {
                 let input = get_input(&fe.input, region, source, &results)?;
-                apply_blur(fe, cs, ts, input)
+                apply_blur(fe, cs, ts, input, ctx)
             }
-            usvg::filter::Kind::Offset(ref fe) => {
+            Kind::Offset(ref fe) => {
                 let input = get_input(&fe.input, region, source, &results)?;
                 apply_offset(fe, ts, input)
             }
-            usvg::filter::Kind::Composite(ref fe) => {
+            Kind::Composite(ref fe) => {
                 let input1 = get_input(&fe.input1, region, source, &results)?;
                 let input2 = get_input(&fe.input2, region, source, &results)?;
                 apply_composite(fe, cs, region, input1, input2)
             }
-            usvg::filter::Kind::Merge(ref fe) => apply_merge(fe, cs, region, source, &results),
-            usvg::filter::Kind::Tile(ref fe) => {
+            Kind::Merge(ref fe) => apply_merge(fe, cs, region, source, &results),
+            Kind::Tile(ref fe) => {
                 let input = get_input(&fe.input, region, source, &results)?;
                 apply_tile(input, region)
             }
-            usvg::filter::Kind::Image(ref fe) => apply_image(fe, region, subregion, ts),
-            usvg::filter::Kind::ComponentTransfer(ref fe) => {
+            Kind::Image(ref fe) => apply_image(fe, ctx, region, subregion, ts),
+            Kind::ComponentTransfer(ref fe) => {
                 let input = get_input(&fe.input, region, source, &results)?;
                 apply_component_transfer(fe, cs, input)
             }
-            usvg::filter::Kind::ColorMatrix(ref fe) => {
+            Kind::ColorMatrix(ref fe) => {
                 let input = get_input(&fe.input, region, source, &results)?;
                 apply_color_matrix(fe, cs, input)
             }
-            usvg::filter::Kind::ConvolveMatrix(ref fe) => {
+            Kind::ConvolveMatrix(ref fe) => {
                 let input = get_input(&fe.input, region, source, &results)?;
                 apply_convolve_matrix(fe, cs, input)
             }
-            usvg::filter::Kind::Morphology(ref fe) => {
+            Kind::Morphology(ref fe) => {
                 let input = get_input(&fe.input, region, source, &results)?;
                 apply_morphology(fe, cs, ts, input)
             }
-            usvg::filter::Kind::DisplacementMap(ref fe) => {
+            Kind::DisplacementMap(ref fe) => {
                 let input1 = get_input(&fe.input1, region, source, &results)?;
                 let input2 = get_input(&fe.input2, region, source, &results)?;
                 apply_displacement_map(fe, region, cs, ts, input1, input2)
             }
-            usvg::filter::Kind::Turbulence(ref fe) => apply_turbulence(fe, region, cs, ts),
-            usvg::filter::Kind::DiffuseLighting(ref fe) => {
+            Kind::Turbulence(ref fe) => apply_turbulence(fe, region, cs, ts, ctx),
+            Kind::DiffuseLighting(ref fe) => {
                 let input = get_input(&fe.input, region, source, &results)?;
-                apply_diffuse_lighting(fe, region, cs, ts, input)
+                apply_diffuse_lighting(fe, region, cs, ts, input, ctx)
             }
-            usvg::filter::Kind::SpecularLighting(ref fe) => {
+            Kind::SpecularLighting(ref fe) => {
                 let input = get_input(&fe.input, region, source, &results)?;
-                apply_specular_lighting(fe, region, cs, ts, input)
+                apply_specular_lighting(fe, region, cs, ts, input, ctx)
             }
         }?;
 
+        // Primitives can be interrupted midway, leaving an incomplete result.
+        if ctx.is_interrupted() {
+            return Err(Error::Interrupted);
+        }
+
         if region != subregion {
             // Clip result.
 
             // TODO: explain
-            let subregion2 = if let usvg::filter::Kind::Offset(..) = primitive.kind {
+            let subregion2 = if let Kind::Offset(..) = primitive.kind {
                 // We do not support clipping on feOffset.
                 region.translate_to(0, 0)
             } else {
@@ -652,7 +776,7 @@ fn calc_region(
 }
 
 pub fn calc_filters_region(
-    filters: &[Rc<usvg::filter::Filter>],
+    filters: &[usvg::Shared<usvg::filter::Filter>],
     object_bbox: Option<tiny_skia::NonZeroRect>,
 ) -> Option<tiny_skia::NonZeroRect> {
     let mut global_region = usvg::BBox::default();
@@ -763,7 +887,11 @@ fn get_input(
                 Ok(v.image.clone())
             } else {
                 // Technically unreachable.
-                log::warn!("Unknown filter primitive reference '{}'.", name);
+                usvg::report!(
+                    FilterFailed,
+                    "Unknown filter primitive reference '{}'.",
+                    name
+                );
                 get_input(&usvg::filter::Input::SourceGraphic, region, source, results)
             }
         }
@@ -790,6 +918,7 @@ fn apply_drop_shadow(
     cs: usvg::filter::ColorInterpolation,
     ts: usvg::Transform,
     input: Image,
+    ctx: &Context,
 ) -> Result<Image, Error> {
     let mut pixmap = tiny_skia::Pixmap::try_create(input.width(), input.height())?;
     let input_pixmap = input.into_color_space(cs)?.take()?;
@@ -800,9 +929,13 @@ fn apply_drop_shadow(
         resolve_std_dev(fe.std_dev_x.get() * sx, fe.std_dev_y.get() * sy)
     {
         if use_box_blur {
-            box_blur::apply(std_dx, std_dy, shadow_pixmap.as_image_ref_mut());
+            box_blur::apply(std_dx, std_dy, shadow_pixmap.as_image_ref_mut(), &|| {
+                ctx.is_interrupted()
+            });
         } else {
-            iir_blur::apply(std_dx, std_dy, shadow_pixmap.as_image_ref_mut());
+            iir_blur::apply(std_dx, std_dy, shadow_pixmap.as_image_ref_mut(), &|| {
+                ctx.is_interrupted()
+            });
         }
     }
 
@@ -850,6 +983,7 @@ fn apply_blur(
     cs: usvg::filter::ColorInterpolation,
     ts: usvg::Transform,
     input: Image,
+    ctx: &Context,
 ) -> Result<Image, Error> {
     let (sx, sy) = ts.get_scale();
     let (std_dx, std_dy, use_box_blur) =
@@ -861,9 +995,13 @@ fn apply_blur(
     let mut pixmap = input.into_color_space(cs)?.take()?;
 
     if use_box_blur {
-        box_blur::apply(std_dx, std_dy, pixmap.as_image_ref_mut());
+        box_blur::apply(std_dx, std_dy, pixmap.as_image_ref_mut(), &|| {
+            ctx.is_interrupted()
+        });
     } else {
-        iir_blur::apply(std_dx, std_dy, pixmap.as_image_ref_mut());
+        iir_blur::apply(std_dx, std_dy, pixmap.as_image_ref_mut(), &|| {
+            ctx.is_interrupted()
+        });
     }
 
     Ok(Image::from_image(pixmap, cs))
@@ -1062,7 +1200,8 @@ fn apply_tile(input: Image, region: IntRect) -> Result<Image, Error> {
 }
 
 fn apply_image(
-    fe: &usvg::filter::Image,
+    fe: &ImagePrimitive,
+    ctx: &Context,
     region: IntRect,
     subregion: IntRect,
     ts: usvg::Transform,
@@ -1070,7 +1209,7 @@ fn apply_image(
     let mut pixmap = tiny_skia::Pixmap::try_create(region.width(), region.height())?;
 
     match fe.data {
-        usvg::filter::ImageKind::Image(ref kind) => {
+        Some(ImagePrimitiveKind::Image(ref kind)) => {
             let dx = (subregion.x() - region.x()) as f32;
             let dy = (subregion.y() - region.y()) as f32;
             let transform = tiny_skia::Transform::from_translate(dx, dy);
@@ -1085,32 +1224,28 @@ fn apply_image(
                 aspect: fe.aspect,
             };
 
-            let uimage = usvg::Image {
-                id: String::new(),
-                transform: usvg::Transform::default(),
-                visibility: usvg::Visibility::Visible,
+            let image = crate::image::Image {
+                transform: tiny_skia::Transform::default(),
                 view_box,
-                rendering_mode: fe.rendering_mode,
+                quality: fe.quality,
+                vector_effect: usvg::VectorEffect::None,
+                id: String::new(),
+                visible: true,
+                pointer_events: usvg::PointerEvents::default(),
                 kind: kind.clone(),
-                // Title shouldn't matter here because we are just rendering anyway.
-                title: None,
             };
 
-            let mut children = Vec::new();
-            crate::image::convert(&uimage, &mut children);
-            if let Some(Node::Image(image)) = children.first() {
-                crate::image::render_image(image, transform, &mut pixmap.as_mut());
-            }
+            // Vector effects are not used by `feImage`, so only limits matter.
+            let ctx = ctx.nested(tiny_skia::IntRect::from_xywh(0, 0, 1, 1).unwrap());
+
+            crate::image::render_image(&image, &ctx, transform, &mut pixmap.as_mut());
         }
-        usvg::filter::ImageKind::Use(ref node) => {
+        Some(ImagePrimitiveKind::Use(ref rtree)) => {
             let (sx, sy) = ts.get_scale();
             let transform = tiny_skia::Transform::from_scale(sx, sy);
-
-            if let Some(mut rtree) = crate::Tree::from_usvg_node(node) {
-                rtree.view_box.rect = rtree.view_box.rect.translate_to(0.0, 0.0).unwrap();
-                rtree.render(transform, &mut pixmap.as_mut());
-            }
+            rtree.render_nested(transform, ctx, &mut pixmap.as_mut());
         }
+        None => {}
     }
 
     Ok(Image::from_image(
@@ -1217,6 +1352,7 @@ fn apply_turbulence(
     region: IntRect,
     cs: usvg::filter::ColorInterpolation,
     ts: usvg::Transform,
+    ctx: &Context,
 ) -> Result<Image, Error> {
     let mut pixmap = tiny_skia::Pixmap::try_create(region.width(), region.height())?;
 
@@ -1237,6 +1373,7 @@ fn apply_turbulence(
         fe.stitch_tiles,
         fe.kind == usvg::filter::TurbulenceKind::FractalNoise,
         pixmap.as_image_ref_mut(),
+        &|| ctx.is_interrupted(),
     );
 
     multiply_alpha(pixmap.data_mut().as_rgba_mut());
@@ -1250,6 +1387,7 @@ fn apply_diffuse_lighting(
     cs: usvg::filter::ColorInterpolation,
     ts: usvg::Transform,
     input: Image,
+    ctx: &Context,
 ) -> Result<Image, Error> {
     let mut pixmap = tiny_skia::Pixmap::try_create(region.width(), region.height())?;
 
@@ -1260,6 +1398,7 @@ fn apply_diffuse_lighting(
         light_source,
         input.as_ref().as_image_ref(),
         pixmap.as_image_ref_mut(),
+        &|| ctx.is_interrupted(),
     );
 
     Ok(Image::from_image(pixmap, cs))
@@ -1271,6 +1410,7 @@ fn apply_specular_lighting(
     cs: usvg::filter::ColorInterpolation,
     ts: usvg::Transform,
     input: Image,
+    ctx: &Context,
 ) -> Result<Image, Error> {
     let mut pixmap = tiny_skia::Pixmap::try_create(region.width(), region.height())?;
 
@@ -1281,6 +1421,7 @@ fn apply_specular_lighting(
         light_source,
         input.as_ref().as_image_ref(),
         pixmap.as_image_ref_mut(),
+        &|| ctx.is_interrupted(),
     );
 
     Ok(Image::from_image(pixmap, cs))
@@ -31,6 +31,7 @@ struct StitchInfo {
 ///
 /// - `offset_x` and `offset_y` indicate filter region offset.
 /// - `sx` and `sy` indicate canvas scale.
+/// - `interrupted` is checked for each row.
 pub fn apply(
     offset_x: f64,
     offset_y: f64,
@@ -43,6 +44,7 @@ pub fn apply(
     stitch_tiles: bool,
     fractal_noise: bool,
     dest: ImageRefMut,
+    interrupted: &dyn Fn() -> bool,
 ) {
     let (lattice_selector, gradient) = init(seed);
     let width = dest.width;
@@ -50,6 +52,10 @@ pub fn apply(
     let mut x = 0;
     let mut y = 0;
     for pixel in dest.data.iter_mut() {
+        if x == 0 && interrupted() {
+            return;
+        }
+
         let turb = |channel| {
             let (tx, ty) = ((x as f64 + offset_x) / sx, (y as f64 + offset_y) / sy);
             let n = turbulence(
@@ -2,6 +2,8 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
+use usvg::ApproxZeroUlps;
+
 /// Fits the current rect into the specified bounds.
 pub fn fit_to_rect(
     r: tiny_skia::IntRect,
@@ -83,3 +85,48 @@ pub fn fit_view_box(size: usvg::Size, vb: &usvg::ViewBox) -> usvg::Size {
         size.scale_to(s)
     }
 }
+
+/// Applies `non-scaling-size`, `non-rotation` and `fixed-position` vector effects.
+///
+/// `transform` is the current canvas transform and `element_ts` is the element's own transform.
+/// `host` maps the host coordinate system into the current canvas.
+///
+/// Returns an updated canvas transform. Or `None` when the effect doesn't affect transforms.
+///
+/// https://www.w3.org/TR/SVG2/coords.html#VectorEffects
+pub fn apply_vector_effect(
+    effect: usvg::VectorEffect,
+    transform: tiny_skia::Transform,
+    element_ts: tiny_skia::Transform,
+    host: tiny_skia::Transform,
+) -> Option<tiny_skia::Transform> {
+    // A complete element transform in the host coordinate system.
+    let ts = host.invert()?.pre_concat(transform).pre_concat(element_ts);
+    let det = (ts.sx * ts.sy - ts.kx * ts.ky).abs().sqrt();
+
+    let ts = match effect {
+        usvg::VectorEffect::NonScalingSize => {
+            if det.approx_zero_ulps(4) {
+                return None;
+            }
+
+            tiny_skia::Transform::from_row(
+                ts.sx / det,
+                ts.ky / det,
+                ts.kx / det,
+                ts.sy / det,
+                ts.tx,
+                ts.ty,
+            )
+        }
+        usvg::VectorEffect::NonRotation => {
+            tiny_skia::Transform::from_row(det, 0.0, 0.0, det, ts.tx, ts.ty)
+        }
+        usvg::VectorEffect::FixedPosition => {
+            tiny_skia::Transform::from_row(ts.sx, ts.ky, ts.kx, ts.sy, 0.0, 0.0)
+        }
+        usvg::VectorEffect::None | usvg::VectorEffect::NonScalingStroke => return None,
+    };
+
+    Some(host.pre_concat(ts).pre_concat(element_ts.invert()?))
+}
@@ -0,0 +1,462 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Hit-testing.
+
+use std::borrow::Cow;
+
+use crate::render::{Context, RenderOptions, RenderState};
+use crate::tree::{Group, Node, Tree};
+
+/// Hit-testing options.
+#[derive(Clone, Copy, Debug)]
+pub struct HitTestOptions {
+    /// A minimal mask value, in the 0..=1 range, at which masked content can be hit.
+    ///
+    /// Default: 0.5
+    pub mask_threshold: f32,
+}
+
+impl Default for HitTestOptions {
+    fn default() -> Self {
+        HitTestOptions {
+            mask_threshold: 0.5,
+        }
+    }
+}
+
+impl Tree {
+    /// Returns the topmost node at the specified point.
+    ///
+    /// `point` is in the image coordinates, i.e. the ones used by [`Tree::render`]
+    /// with an identity transform. The returned path can be passed to [`Tree::node`].
+    ///
+    /// Transforms, fill rules, stroke geometry, clip paths, masks, visibility
+    /// and `pointer-events` are taken into account, while filters are ignored.
+    /// Since only painted areas are stored in the render tree, `pointer-events`
+    /// like `fill` or `all` cannot make an unfilled area hittable.
+    pub fn hit_test(
+        &self,
+        point: tiny_skia::Point,
+        options: &HitTestOptions,
+    ) -> Option<Vec<usize>> {
+        // Map the point to the center of a single pixel and test it without anti-aliasing,
+        // which is the same as checking whether the point is inside.
+        let transform = tiny_skia::Transform::from_translate(0.5 - point.x, 0.5 - point.y);
+        let size = tiny_skia::IntSize::from_wh(1, 1).unwrap();
+
+        let query = Query {
+            transform,
+            size,
+            anti_alias: false,
+            topmost: true,
+            pointer_events: true,
+            mask_threshold: options.mask_threshold,
+        };
+
+        match run(self, &query, Output::Hits(Vec::new())) {
+            Output::Hits(mut hits) => hits.pop(),
+            _ => None,
+        }
+    }
+
+    /// Returns all nodes that intersect the specified rectangle, in rendering order.
+    ///
+    /// Same as [`Tree::hit_test`], but for a rectangle selection.
+    /// The rectangle is tested with a one-unit precision.
+    pub fn hit_test_rect(
+        &self,
+        rect: tiny_skia::Rect,
+        options: &HitTestOptions,
+    ) -> Vec<Vec<usize>> {
+        let transform = tiny_skia::Transform::from_translate(-rect.x(), -rect.y());
+        let size = match tiny_skia::IntSize::from_wh(
+            rect.width().ceil().max(1.0) as u32,
+            rect.height().ceil().max(1.0) as u32,
+        ) {
+            Some(v) => v,
+            None => return Vec::new(),
+        };
+
+        let query = Query {
+            transform,
+            size,
+            anti_alias: true,
+            topmost: false,
+            pointer_events: true,
+            mask_threshold: options.mask_threshold,
+        };
+
+        match run(self, &query, Output::Hits(Vec::new())) {
+            Output::Hits(hits) => hits,
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Checks that a hidden node can still be hit because of its `pointer-events`.
+pub fn is_hidden_target(pointer_events: usvg::PointerEvents) -> bool {
+    is_target(pointer_events, false, Area::Fill) || is_target(pointer_events, false, Area::Stroke)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Area {
+    Fill,
+    Stroke,
+}
+
+/// Checks that a painted area can be hit.
+///
+/// https://www.w3.org/TR/SVG2/interact.html#PointerEventsProperty
+fn is_target(pointer_events: usvg::PointerEvents, visible: bool, area: Area) -> bool {
+    match pointer_events {
+        usvg::PointerEvents::VisiblePainted | usvg::PointerEvents::Visible => visible,
+        usvg::PointerEvents::VisibleFill => visible && area == Area::Fill,
+        usvg::PointerEvents::VisibleStroke => visible && area == Area::Stroke,
+        usvg::PointerEvents::Painted | usvg::PointerEvents::All => true,
+        usvg::PointerEvents::Fill => area == Area::Fill,
+        usvg::PointerEvents::Stroke => area == Area::Stroke,
+        usvg::PointerEvents::None => false,
+    }
+}
+
+/// A tree query.
+pub struct Query {
+    /// Maps the image coordinates into the query region.
+    pub transform: tiny_skia::Transform,
+    /// Query region size.
+    pub size: tiny_skia::IntSize,
+    pub anti_alias: bool,
+    /// Whether only the topmost node is needed.
+    pub topmost: bool,
+    /// Whether `pointer-events` should be used instead of visibility.
+    pub pointer_events: bool,
+    pub mask_threshold: f32,
+}
+
+/// What to do with leaf nodes that were hit.
+pub enum Output {
+    /// Collect their index paths.
+    Hits(Vec<Vec<usize>>),
+    /// Draw their indices.
+    Indices(crate::IdBuffer),
+}
+
+/// Tests tree nodes against a query region.
+pub fn run(tree: &Tree, query: &Query, output: Output) -> Output {
+    let state = RenderState::default();
+    let max_bbox = crate::render::max_bbox_for_size(query.size);
+    let ctx = Context {
+        // The image is the host coordinate system.
+        host_transform: query.transform,
+        ..Context::new(&state, max_bbox, &RenderOptions::default())
+    };
+
+    let mut tester = HitTester {
+        ctx,
+        width: query.size.width(),
+        height: query.size.height(),
+        anti_alias: query.anti_alias,
+        mask_threshold: (query.mask_threshold.clamp(0.0, 1.0) * 255.0).round() as u8,
+        topmost: query.topmost,
+        pointer_events: query.pointer_events,
+        path: Vec::new(),
+        output,
+    };
+
+    let ts =
+        usvg::utils::view_box_to_transform(tree.view_box.rect, tree.view_box.aspect, tree.size);
+    tester.test_nodes(&tree.children, query.transform.pre_concat(ts), None);
+    tester.output
+}
+
+struct HitTester<'a> {
+    ctx: Context<'a>,
+    width: u32,
+    height: u32,
+    anti_alias: bool,
+    mask_threshold: u8,
+    topmost: bool,
+    pointer_events: bool,
+    /// The path to the current node.
+    path: Vec<usize>,
+    output: Output,
+}
+
+impl HitTester<'_> {
+    /// Tests nodes, from top to bottom when only the topmost node is needed.
+    ///
+    /// `area` is the region where nodes can be hit, i.e. not clipped or masked out
+    /// by their ancestors.
+    ///
+    /// Returns `true` when testing is done.
+    fn test_nodes(
+        &mut self,
+        nodes: &[Node],
+        transform: tiny_skia::Transform,
+        area: Option<&tiny_skia::Mask>,
+    ) -> bool {
+        for i in 0..nodes.len() {
+            let index = if self.topmost { nodes.len() - 1 - i } else { i };
+
+            self.path.push(index);
+
+            let done = match nodes[index] {
+                Node::Group(ref group) => self.test_group(group, transform, area),
+                ref node => self.test_leaf(node, transform, area),
+            };
+
+            self.path.pop();
+
+            if done {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn test_group(
+        &mut self,
+        group: &Group,
+        transform: tiny_skia::Transform,
+        area: Option<&tiny_skia::Mask>,
+    ) -> bool {
+        if !group.visible {
+            return false;
+        }
+
+        let transform = transform.pre_concat(group.transform);
+        if crate::render::is_outside(group, transform, self.width, self.height) {
+            return false;
+        }
+
+        if group.clip_path.is_none() && group.mask.is_none() {
+            return self.test_nodes(&group.children, transform, area);
+        }
+
+        match self.group_area(group, transform, area) {
+            Some(ref area) => self.test_nodes(&group.children, transform, Some(area)),
+            None => false,
+        }
+    }
+
+    /// Calculates the region where group's children can be hit.
+    ///
+    /// A pixel is inside a clip path when at least half of it is covered.
+    fn group_area(
+        &self,
+        group: &Group,
+        transform: tiny_skia::Transform,
+        parent_area: Option<&tiny_skia::Mask>,
+    ) -> Option<tiny_skia::Mask> {
+        let mut area = match parent_area {
+            Some(area) => area.clone(),
+            None => {
+                let mut area = tiny_skia::Mask::new(self.width, self.height)?;
+                area.data_mut().fill(255);
+                area
+            }
+        };
+
+        if let Some(ref clip_path) = group.clip_path {
+            let mut pixmap = self.opaque_pixmap()?;
+            crate::clip::apply(clip_path, &self.ctx, transform, &mut pixmap);
+            intersect_area(&mut area, &pixmap, 128);
+        }
+
+        if let Some(ref mask) = group.mask {
+            let mut pixmap = self.opaque_pixmap()?;
+            crate::mask::apply(mask, &self.ctx, transform, &mut pixmap);
+            intersect_area(&mut area, &pixmap, self.mask_threshold.max(1));
+        }
+
+        Some(area)
+    }
+
+    fn region(&self) -> Option<tiny_skia::IntRect> {
+        tiny_skia::IntRect::from_xywh(0, 0, self.width, self.height)
+    }
+
+    fn opaque_pixmap(&self) -> Option<tiny_skia::Pixmap> {
+        let mut pixmap = tiny_skia::Pixmap::new(self.width, self.height)?;
+        pixmap.fill(tiny_skia::Color::BLACK);
+        Some(pixmap)
+    }
+
+    /// Returns `true` when testing is done.
+    fn test_leaf(
+        &mut self,
+        node: &Node,
+        transform: tiny_skia::Transform,
+        area: Option<&tiny_skia::Mask>,
+    ) -> bool {
+        let coverage = match self.leaf_coverage(node, transform) {
+            Some(v) => v,
+            None => return false,
+        };
+
+        let mut pixels = coverage.pixels(self.width).filter(|&i| match area {
+            Some(area) => area.data()[i] != 0,
+            None => true,
+        });
+
+        match self.output {
+            Output::Hits(ref mut hits) => {
+                if pixels.next().is_none() {
+                    return false;
+                }
+
+                hits.push(self.path.clone());
+                self.topmost
+            }
+            Output::Indices(ref mut buffer) => {
+                let index = buffer.paths.len() as u32;
+                let mut is_drawn = false;
+                for i in pixels {
+                    buffer.data[i] = index;
+                    is_drawn = true;
+                }
+
+                if is_drawn {
+                    buffer.paths.push(self.path.clone());
+                }
+
+                false
+            }
+        }
+    }
+
+    fn is_leaf_target(
+        &self,
+        pointer_events: usvg::PointerEvents,
+        visible: bool,
+        area: Area,
+    ) -> bool {
+        if self.pointer_events {
+            is_target(pointer_events, visible, area)
+        } else {
+            visible
+        }
+    }
+
+    /// Rasterizes an area of a leaf node that can be hit.
+    fn leaf_coverage(&self, node: &Node, transform: tiny_skia::Transform) -> Option<Coverage> {
+        let (shape, rule, transform) = match node {
+            Node::FillPath(ref path) => {
+                if !self.is_leaf_target(path.pointer_events, path.visible, Area::Fill) {
+                    return None;
+                }
+
+                let transform = crate::geom::apply_vector_effect(
+                    path.vector_effect,
+                    transform,
+                    path.transform,
+                    self.ctx.host_transform,
+                )
+                .unwrap_or(transform)
+                .pre_concat(path.transform);
+
+                (Cow::Borrowed(&*path.path), path.rule, transform)
+            }
+            Node::StrokePath(ref path) => {
+                if !self.is_leaf_target(path.pointer_events, path.visible, Area::Stroke) {
+                    return None;
+                }
+
+                let transform = crate::geom::apply_vector_effect(
+                    path.vector_effect,
+                    transform,
+                    path.transform,
+                    self.ctx.host_transform,
+                )
+                .unwrap_or(transform)
+                .pre_concat(path.transform);
+
+                // Like during rendering.
+                let outline = if path.vector_effect == usvg::VectorEffect::NonScalingStroke {
+                    crate::path::stroke_non_scaling(&path.path, &path.stroke, transform)?
+                } else {
+                    let res_scale = tiny_skia::PathStroker::compute_resolution_scale(&transform);
+                    match path.stroke.dash {
+                        Some(ref dash) => path
+                            .path
+                            .dash(dash, res_scale)?
+                            .stroke(&path.stroke, res_scale)?,
+                        None => path.path.stroke(&path.stroke, res_scale)?,
+                    }
+                };
+
+                (Cow::Owned(outline), tiny_skia::FillRule::Winding, transform)
+            }
+            Node::Image(ref image) => {
+                if !self.is_leaf_target(image.pointer_events, image.visible, Area::Fill) {
+                    return None;
+                }
+
+                let transform = crate::geom::apply_vector_effect(
+                    image.vector_effect,
+                    transform,
+                    image.transform,
+                    self.ctx.host_transform,
+                )
+                .unwrap_or(transform)
+                .pre_concat(image.transform);
+
+                let rect = tiny_skia::PathBuilder::from_rect(image.view_box.rect.to_rect());
+                (Cow::Owned(rect), tiny_skia::FillRule::Winding, transform)
+            }
+            Node::Group(_) => return None,
+        };
+
+        // Rasterize only the part of the query region covered by the node.
+        let bbox = shape.bounds().transform(transform)?;
+        let region = tiny_skia::IntRect::from_ltrb(
+            bbox.left().floor() as i32 - 1,
+            bbox.top().floor() as i32 - 1,
+            bbox.right().ceil() as i32 + 1,
+            bbox.bottom().ceil() as i32 + 1,
+        )?
+        .intersect(&self.region()?)?;
+
+        let mut mask = tiny_skia::Mask::new(region.width(), region.height())?;
+        let transform = transform.post_translate(-region.x() as f32, -region.y() as f32);
+        mask.fill_path(&shape, rule, self.anti_alias, transform);
+
+        Some(Coverage { mask, region })
+    }
+}
+
+/// A rasterized part of a leaf node.
+struct Coverage {
+    mask: tiny_skia::Mask,
+    /// Mask's region in the query region.
+    region: tiny_skia::IntRect,
+}
+
+impl Coverage {
+    /// Returns indices of covered pixels in a query region of the specified width.
+    fn pixels(&self, width: u32) -> impl Iterator<Item = usize> + '_ {
+        let x = self.region.x() as usize;
+        let y = self.region.y() as usize;
+        let region_width = self.region.width() as usize;
+        let width = width as usize;
+
+        self.mask
+            .data()
+            .iter()
+            .enumerate()
+            .filter(|(_, &c)| c != 0)
+            .map(move |(i, _)| (y + i / region_width) * width + x + i % region_width)
+    }
+}
+
+/// Removes pixels with an alpha below `threshold` from the area.
+fn intersect_area(area: &mut tiny_skia::Mask, pixmap: &tiny_skia::Pixmap, threshold: u8) {
+    for (a, pixel) in area.data_mut().iter_mut().zip(pixmap.pixels()) {
+        if pixel.alpha() < threshold {
+            *a = 0;
+        }
+    }
+}
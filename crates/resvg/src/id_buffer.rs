@@ -0,0 +1,107 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Object-ID buffer rendering.
+
+use crate::hit_test::{Output, Query};
+use crate::tree::{Node, Tree};
+
+/// A buffer of per-pixel node indices.
+///
+/// Produced by [`Tree::render_id_buffer`].
+#[derive(Clone, Debug)]
+pub struct IdBuffer {
+    /// Buffer size.
+    pub size: tiny_skia::IntSize,
+    /// Node indices, row by row.
+    ///
+    /// `0` indicates that no node covers the pixel.
+    pub data: Vec<u32>,
+    /// Node paths, indexed by node indices. Can be passed to [`Tree::node`].
+    ///
+    /// The first path is empty.
+    pub paths: Vec<Vec<usize>>,
+    /// Node IDs, indexed by node indices.
+    ///
+    /// When a node doesn't have an ID, the ID of its closest ancestor is used.
+    /// The first ID is empty.
+    pub ids: Vec<String>,
+}
+
+impl IdBuffer {
+    /// Returns a node index at the specified pixel.
+    ///
+    /// Returns `None` when no node covers the pixel or the pixel is outside the buffer.
+    pub fn index(&self, x: u32, y: u32) -> Option<u32> {
+        if x >= self.size.width() || y >= self.size.height() {
+            return None;
+        }
+
+        match self.data[(y * self.size.width() + x) as usize] {
+            0 => None,
+            index => Some(index),
+        }
+    }
+
+    /// Returns a node ID at the specified pixel.
+    ///
+    /// Returns `None` when no node covers the pixel or the node has no ID.
+    pub fn id(&self, x: u32, y: u32) -> Option<&str> {
+        let id = &self.ids[self.index(x, y)? as usize];
+        if id.is_empty() {
+            None
+        } else {
+            Some(id)
+        }
+    }
+}
+
+impl Tree {
+    /// Renders an object-ID buffer.
+    ///
+    /// Each pixel stores an index of the topmost node that covers the pixel's center.
+    /// Nodes are rasterized without anti-aliasing, blending and filters.
+    /// A pixel is inside a clip path when at least half of it is covered
+    /// and inside a mask when the mask value is at least 0.5.
+    pub fn render_id_buffer(
+        &self,
+        transform: tiny_skia::Transform,
+        size: tiny_skia::IntSize,
+    ) -> IdBuffer {
+        let buffer = IdBuffer {
+            size,
+            data: vec![0; size.width() as usize * size.height() as usize],
+            paths: vec![Vec::new()],
+            ids: Vec::new(),
+        };
+
+        let query = Query {
+            transform,
+            size,
+            anti_alias: false,
+            topmost: false,
+            pointer_events: false,
+            mask_threshold: 0.5,
+        };
+
+        let mut buffer = match crate::hit_test::run(self, &query, Output::Indices(buffer)) {
+            Output::Indices(buffer) => buffer,
+            Output::Hits(_) => unreachable!(),
+        };
+
+        buffer.ids = buffer.paths.iter().map(|path| self.node_id(path)).collect();
+        buffer
+    }
+
+    /// Returns an ID of a node or of its closest ancestor with an ID.
+    fn node_id(&self, path: &[usize]) -> String {
+        (1..=path.len())
+            .rev()
+            .filter_map(|len| self.node(&path[..len]))
+            .map(Node::id)
+            .find(|id| !id.is_empty())
+            .unwrap_or_default()
+            .to_string()
+    }
+}
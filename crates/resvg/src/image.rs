@@ -2,31 +2,75 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use crate::render::TinySkiaPixmapMutExt;
+use std::sync::Arc;
+
+use crate::render::{Context, TinySkiaPixmapMutExt};
 use crate::tree::{BBoxes, Node, Tree};
 
+/// An image data.
+#[derive(Clone)]
 pub enum ImageKind {
+    /// A decoded raster image.
     #[cfg(feature = "raster-images")]
-    Raster(tiny_skia::Pixmap),
-    Vector(Tree),
+    Raster(Arc<tiny_skia::Pixmap>),
+    /// An SVG image.
+    Vector(Arc<Tree>),
 }
 
+/// An image.
 pub struct Image {
+    /// Image's transform.
     pub transform: tiny_skia::Transform,
+    /// Image's view box. The image is fitted into it.
     pub view_box: usvg::ViewBox,
+    /// Resampling quality.
     pub quality: tiny_skia::FilterQuality,
+    /// Image's vector effect.
+    pub vector_effect: usvg::VectorEffect,
+    /// Element's ID. Can be empty.
+    pub id: String,
+    /// Whether the image should be rendered.
+    pub visible: bool,
+    /// Image's pointer events. Used by hit-testing.
+    pub pointer_events: usvg::PointerEvents,
+    /// Image's data.
     pub kind: ImageKind,
 }
 
-pub fn convert(image: &usvg::Image, children: &mut Vec<Node>) -> Option<BBoxes> {
-    let object_bbox = image.view_box.rect.to_rect();
+pub fn convert(
+    image: &usvg::Image,
+    parent_transform: tiny_skia::Transform,
+    children: &mut Vec<Node>,
+) -> Option<BBoxes> {
+    let mut object_bbox = image.view_box.rect.to_rect();
+
+    // The canvas transform is used as a host coordinate system, like with paths.
+    if let Some(ts) = crate::geom::apply_vector_effect(
+        image.vector_effect,
+        parent_transform,
+        image.transform,
+        tiny_skia::Transform::default(),
+    ) {
+        let ts = image
+            .transform
+            .invert()?
+            .pre_concat(parent_transform.invert()?)
+            .pre_concat(ts)
+            .pre_concat(image.transform);
+        object_bbox = object_bbox.transform(ts)?;
+    }
+
     let bboxes = BBoxes {
         object: usvg::BBox::from(object_bbox),
         transformed_object: usvg::BBox::from(object_bbox.transform(image.transform)?),
         layer: usvg::BBox::from(object_bbox),
+        host_dependent: image.vector_effect != usvg::VectorEffect::None,
     };
 
-    if image.visibility != usvg::Visibility::Visible {
+    // Hidden images that can still be hit are kept, but will not be rendered.
+    if image.visibility != usvg::Visibility::Visible
+        && !crate::hit_test::is_hidden_target(image.pointer_events)
+    {
         return Some(bboxes);
     }
 
@@ -36,12 +80,15 @@ pub fn convert(image: &usvg::Image, children: &mut Vec<Node>) -> Option<BBoxes>
     }
 
     let kind = match image.kind {
-        usvg::ImageKind::SVG(ref utree) => ImageKind::Vector(Tree::from_usvg(utree)),
+        usvg::ImageKind::SVG(ref utree) => ImageKind::Vector(Arc::new(Tree::from_usvg(utree))),
         #[cfg(feature = "raster-images")]
-        _ => ImageKind::Raster(raster_images::decode_raster(image)?),
+        _ => ImageKind::Raster(Arc::new(raster_images::decode_raster(image)?)),
         #[cfg(not(feature = "raster-images"))]
         _ => {
-            log::warn!("Images decoding was disabled by a build feature.");
+            usvg::report!(
+                Unsupported,
+                "Images decoding was disabled by a build feature."
+            );
             return None;
         }
     };
@@ -50,6 +97,10 @@ pub fn convert(image: &usvg::Image, children: &mut Vec<Node>) -> Option<BBoxes>
         transform: image.transform,
         view_box: image.view_box,
         quality,
+        vector_effect: image.vector_effect,
+        id: image.id.clone(),
+        visible: image.visibility == usvg::Visibility::Visible,
+        pointer_events: image.pointer_events,
         kind,
     }));
 
@@ -58,16 +109,25 @@ pub fn convert(image: &usvg::Image, children: &mut Vec<Node>) -> Option<BBoxes>
 
 pub fn render_image(
     image: &Image,
+    ctx: &Context,
     transform: tiny_skia::Transform,
     pixmap: &mut tiny_skia::PixmapMut,
 ) {
+    let transform = crate::geom::apply_vector_effect(
+        image.vector_effect,
+        transform,
+        image.transform,
+        ctx.host_transform,
+    )
+    .unwrap_or(transform);
+
     match image.kind {
         #[cfg(feature = "raster-images")]
         ImageKind::Raster(ref raster) => {
             raster_images::render_raster(image, raster, transform, pixmap);
         }
         ImageKind::Vector(ref rtree) => {
-            render_vector(image, rtree, transform, pixmap);
+            render_vector(image, rtree, ctx, transform, pixmap);
         }
     }
 }
@@ -75,18 +135,19 @@ pub fn render_image(
 fn render_vector(
     image: &Image,
     tree: &Tree,
+    ctx: &Context,
     transform: tiny_skia::Transform,
     pixmap: &mut tiny_skia::PixmapMut,
 ) -> Option<()> {
     let img_size = tree.size.to_int_size();
     let (ts, clip) = crate::geom::view_box_to_transform_with_clip(&image.view_box, img_size);
 
-    let mut sub_pixmap = tiny_skia::Pixmap::new(pixmap.width(), pixmap.height()).unwrap();
+    let mut sub_pixmap = ctx.create_pixmap(pixmap.width(), pixmap.height())?;
 
     let source_transform = transform;
     let transform = transform.pre_concat(image.transform).pre_concat(ts);
 
-    tree.render(transform, &mut sub_pixmap.as_mut());
+    tree.render_nested(transform, ctx, &mut sub_pixmap.as_mut());
 
     let mask = if let Some(clip) = clip {
         pixmap.create_rect_mask(source_transform, clip.to_rect())
@@ -107,7 +168,7 @@ fn render_vector(
 }
 
 #[cfg(feature = "raster-images")]
-mod raster_images {
+pub mod raster_images {
     use super::Image;
     use crate::render::TinySkiaPixmapMutExt;
     use crate::tree::OptionLog;
@@ -115,15 +176,12 @@ mod raster_images {
     pub fn decode_raster(image: &usvg::Image) -> Option<tiny_skia::Pixmap> {
         match image.kind {
             usvg::ImageKind::SVG(_) => None,
-            usvg::ImageKind::JPEG(ref data) => {
-                decode_jpeg(data).log_none(|| log::warn!("Failed to decode a JPEG image."))
-            }
-            usvg::ImageKind::PNG(ref data) => {
-                decode_png(data).log_none(|| log::warn!("Failed to decode a PNG image."))
-            }
-            usvg::ImageKind::GIF(ref data) => {
-                decode_gif(data).log_none(|| log::warn!("Failed to decode a GIF image."))
-            }
+            usvg::ImageKind::JPEG(ref data) => decode_jpeg(data)
+                .log_none(|| usvg::report!(ImageLoadingFailed, "Failed to decode a JPEG image.")),
+            usvg::ImageKind::PNG(ref data) => decode_png(data)
+                .log_none(|| usvg::report!(ImageLoadingFailed, "Failed to decode a PNG image.")),
+            usvg::ImageKind::GIF(ref data) => decode_gif(data)
+                .log_none(|| usvg::report!(ImageLoadingFailed, "Failed to decode a GIF image.")),
         }
     }
 
@@ -248,7 +306,7 @@ mod raster_images {
     }
 
     /// Calculates an image rect depending on the provided view box.
-    fn image_rect(
+    pub fn image_rect(
         view_box: &usvg::ViewBox,
         img_size: tiny_skia::IntSize,
     ) -> tiny_skia::NonZeroRect {
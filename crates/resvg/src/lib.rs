@@ -18,14 +18,36 @@
 pub use tiny_skia;
 pub use usvg;
 
+mod backend;
 mod clip;
+pub mod display_list;
 mod filter;
 mod geom;
+mod hit_test;
+mod id_buffer;
 mod image;
 mod mask;
 mod paint_server;
+#[cfg(feature = "parallel")]
+mod parallel;
 mod path;
+#[cfg(feature = "pdf")]
+pub mod pdf;
 mod render;
+mod retained;
+#[cfg(feature = "serde")]
+mod serialization;
 mod tree;
 
-pub use crate::tree::Tree;
+pub use crate::backend::{render_nodes, Backend};
+pub use crate::clip::ClipPath;
+pub use crate::filter::Filter;
+pub use crate::hit_test::HitTestOptions;
+pub use crate::id_buffer::IdBuffer;
+pub use crate::image::{Image, ImageKind};
+pub use crate::mask::Mask;
+pub use crate::paint_server::{Gradient, GradientKind, GradientStop, Paint, Pattern};
+pub use crate::path::{FillPath, StrokePath};
+pub use crate::render::{CancellationToken, Error, NodeOverrides, RenderOptions};
+pub use crate::retained::{NodeUpdate, RetainedRenderer};
+pub use crate::tree::{Group, Node, Tree};
@@ -34,7 +34,7 @@ where
 }
 
 fn process() -> Result<(), String> {
-    let mut args = match parse_args() {
+    let args = match parse_args() {
         Ok(args) => args,
         Err(e) => {
             println!("{}", HELP);
@@ -45,12 +45,26 @@ fn process() -> Result<(), String> {
     // Do not print warning during the ID querying.
     //
     // Some crates still can print to stdout/stderr, but we can't do anything about it.
-    if !(args.query_all || args.quiet) {
+    if !(args.query_all || args.quiet || args.usvg.diagnostics.is_some()) {
         if let Ok(()) = log::set_logger(&LOGGER) {
             log::set_max_level(log::LevelFilter::Warn);
         }
     }
 
+    let diagnostics = args.usvg.diagnostics.clone();
+    let result = match diagnostics {
+        Some(ref diagnostics) => diagnostics.scope(|| process_svg(args)),
+        None => process_svg(args),
+    };
+
+    if let Some(diagnostics) = diagnostics {
+        eprintln!("{}", usvg::diagnostics::to_json(&diagnostics.take()));
+    }
+
+    result
+}
+
+fn process_svg(mut args: Args) -> Result<(), String> {
     let mut svg_data = timed(args.perf, "Reading", || -> Result<Vec<u8>, &str> {
         if let InputFrom::File(ref file) = args.in_svg {
             std::fs::read(file).map_err(|_| "failed to open the provided file")
@@ -68,26 +82,27 @@ fn process() -> Result<(), String> {
 
     if svg_data.starts_with(&[0x1f, 0x8b]) {
         svg_data = timed(args.perf, "SVGZ Decoding", || {
-            usvg::decompress_svgz(&svg_data).map_err(|e| e.to_string())
+            usvg::decompress_svgz_with_limits(&svg_data, &args.usvg.limits)
+                .map_err(|e| e.to_string())
         })?;
     };
 
     let svg_string = std::str::from_utf8(&svg_data)
         .map(|svg_string| Cow::Borrowed(svg_string))
         .or_else(|_| Ok(Cow::Owned(usvg::string_from_utf16_bytes(&svg_data)?)))
-        .map_err(|_: std::string::FromUtf16Error| "provided data has unrecognized encoding".to_string())?;
+        .map_err(|_: std::string::FromUtf16Error| {
+            "provided data has unrecognized encoding".to_string()
+        })?;
     let svg_string = usvg::preprocess_text(&svg_string, &args.usvg);
 
     let xml_tree = timed(args.perf, "XML Parsing", || {
-        let xml_opt = usvg::roxmltree::ParsingOptions {
-            allow_dtd: true,
-            forgiving: args.forgiving,
-            ..Default::default()
-        };
-        usvg::roxmltree::Document::parse_with_options(&svg_string, xml_opt)
-            .map_err(|e| e.to_string())
+        usvg::parse_xml(&svg_string, &args.usvg).map_err(|e| e.to_string())
     })?;
 
+    if let Some(frames) = args.frames {
+        return render_frames(&mut args, &xml_tree, frames);
+    }
+
     let mut tree = timed(args.perf, "SVG Parsing", || {
         usvg::Tree::from_xmltree(&xml_tree, &args.usvg).map_err(|e| e.to_string())
     })?;
@@ -124,6 +139,25 @@ fn process() -> Result<(), String> {
         return query_all(&tree);
     }
 
+    if args.format != OutputFormat::Png {
+        let data = if args.format == OutputFormat::Pdf {
+            timed(args.perf, "PDF Conversion", || render_pdf(&args, &tree))?
+        } else {
+            render_icon(&mut args, &tree)?
+        };
+
+        return match args.out_png.unwrap() {
+            OutputTo::Stdout => {
+                use std::io::Write;
+                std::io::stdout().write_all(&data).unwrap();
+                Ok(())
+            }
+            OutputTo::File(ref file) => timed(args.perf, "Saving", || {
+                std::fs::write(file, &data).map_err(|e| e.to_string())
+            }),
+        };
+    }
+
     // Render.
     let img = render_svg(&args, &tree)?;
 
@@ -154,6 +188,9 @@ USAGE:
 
   resvg in.svg out.png
   resvg -z 4 in.svg out.png
+  resvg --format pdf in.svg out.pdf
+  resvg --format ico --icon-sizes 16:small,32,48,256 in.svg favicon.ico
+  resvg --frames 0:2 --fps 24 in.svg out.png  # out-0000.png, out-0001.png, ...
   resvg --query-all in.svg
 
 OPTIONS:
@@ -168,6 +205,17 @@ OPTIONS:
                                 [default: 96] [possible values: 10..4000 (inclusive)]
   --background COLOR            Sets the background color
                                 Examples: red, #fff, #fff000
+  --format FORMAT               Sets the output format
+                                [default: png] [possible values: png, pdf, ico, icns]
+  --raster-dpi DPI              Sets the resolution of elements that have to be
+                                rasterized in PDF, like filters
+                                [default: 150] [possible values: 10..4000 (inclusive)]
+  --icon-sizes SIZES            Sets a comma-separated list of square image sizes
+                                in ICO and ICNS output. Each size can be followed
+                                by an ID to render instead of the whole image
+                                Examples: '16,32,48', '16:small,32:small,256'
+                                [default: 16,24,32,48,64,128,256 for ICO and
+                                16,32,64,128,256,512,1024 for ICNS]
 
   --languages LANG              Sets a comma-separated list of languages that
                                 will be used during the 'systemLanguage'
@@ -190,6 +238,8 @@ OPTIONS:
                                 Expected to be the same as the directory that
                                 contains the SVG file, but can be set to any.
                                 [default: input file directory]
+  --file-access MODE            Sets which files can be loaded by images
+                                [default: all] [possible values: all, resources-dir, none]
 
   --font-family FAMILY          Sets the default font family that will be
                                 used when no 'font-family' is present
@@ -228,14 +278,30 @@ OPTIONS:
 
   --export-area-drawing         Use drawing's tight bounding box instead of image size.
                                 Used during normal rendering and not during --export-id
+  --hide-id ID                  Doesn't render an object with a specified ID.
+                                This option can be set multiple times
+  --only-id ID                  Renders only objects with specified IDs
+                                along with their ancestors.
+                                This option can be set multiple times
+
+  --time SECONDS                Renders animations at the specified time
+  --frames START:END            Renders animation frames from START to END seconds,
+                                excluding END, into numbered PNG files
+  --fps FPS                     Sets the frame rate of --frames [default: 30]
 
   --perf                        Prints performance stats
   --quiet                       Disables warnings
+  --json-diagnostics            Prints warnings to stderr as a JSON array
   --unforgiving                 Don't try to proceed in the face of parse errors
+  --no-dtd                      Rejects documents with a DTD
+  --max-entity-expansion BYTES  Sets the maximum size of the text produced by
+                                DTD entities expansion [default: 10485760]
+  --max-entity-depth NUM        Sets the maximum DTD entities nesting depth
+                                [default: 10]
 
 ARGS:
   <in-svg>                      Input file
-  <out-png>                     Output file
+  <out-png>                     Output file, PNG, PDF, ICO or ICNS depending on --format
 ";
 
 #[derive(Debug)]
@@ -245,11 +311,15 @@ struct CliArgs {
     zoom: Option<f32>,
     dpi: u32,
     background: Option<svgtypes::Color>,
+    format: OutputFormat,
+    raster_dpi: u32,
+    icon_sizes: Option<Vec<IconSize>>,
 
     languages: Vec<String>,
     shape_rendering: usvg::ShapeRendering,
     text_rendering: usvg::TextRendering,
     image_rendering: usvg::ImageRendering,
+    file_access: usvg::FileAccess,
     resources_dir: Option<path::PathBuf>,
 
     font_family: Option<String>,
@@ -269,10 +339,20 @@ struct CliArgs {
     export_area_page: bool,
 
     export_area_drawing: bool,
+    hide_ids: Vec<String>,
+    only_ids: Vec<String>,
+
+    time: Option<f32>,
+    frames: Option<Frames>,
+    fps: Option<f32>,
 
     perf: bool,
     quiet: bool,
+    json_diagnostics: bool,
     forgiving: bool,
+    no_dtd: bool,
+    max_entity_expansion: Option<u64>,
+    max_entity_depth: Option<u32>,
 
     input: String,
     output: Option<String>,
@@ -297,6 +377,13 @@ fn collect_args() -> Result<CliArgs, pico_args::Error> {
         zoom: input.opt_value_from_fn(["-z", "--zoom"], parse_zoom)?,
         dpi: input.opt_value_from_fn("--dpi", parse_dpi)?.unwrap_or(96),
         background: input.opt_value_from_str("--background")?,
+        format: input
+            .opt_value_from_fn("--format", parse_format)?
+            .unwrap_or(OutputFormat::Png),
+        raster_dpi: input
+            .opt_value_from_fn("--raster-dpi", parse_dpi)?
+            .unwrap_or(150),
+        icon_sizes: input.opt_value_from_fn("--icon-sizes", parse_icon_sizes)?,
 
         languages: input
             .opt_value_from_fn("--languages", parse_languages)?
@@ -313,6 +400,9 @@ fn collect_args() -> Result<CliArgs, pico_args::Error> {
         resources_dir: input
             .opt_value_from_str("--resources-dir")
             .unwrap_or_default(),
+        file_access: input
+            .opt_value_from_fn("--file-access", parse_file_access)?
+            .unwrap_or(usvg::FileAccess::Unrestricted),
 
         font_family: input.opt_value_from_str("--font-family")?,
         font_size: input
@@ -333,10 +423,20 @@ fn collect_args() -> Result<CliArgs, pico_args::Error> {
         export_area_page: input.contains("--export-area-page"),
 
         export_area_drawing: input.contains("--export-area-drawing"),
+        hide_ids: input.values_from_str("--hide-id")?,
+        only_ids: input.values_from_str("--only-id")?,
+
+        time: input.opt_value_from_fn("--time", parse_time)?,
+        frames: input.opt_value_from_fn("--frames", parse_frames)?,
+        fps: input.opt_value_from_fn("--fps", parse_fps)?,
 
         perf: input.contains("--perf"),
         quiet: input.contains("--quiet"),
+        json_diagnostics: input.contains("--json-diagnostics"),
         forgiving: !input.contains("--unforgiving"),
+        no_dtd: input.contains("--no-dtd"),
+        max_entity_expansion: input.opt_value_from_str("--max-entity-expansion")?,
+        max_entity_depth: input.opt_value_from_str("--max-entity-depth")?,
 
         input: input.free_from_str()?,
         output: input.opt_free_from_str()?,
@@ -383,6 +483,89 @@ fn parse_font_size(s: &str) -> Result<u32, String> {
     }
 }
 
+fn parse_time(s: &str) -> Result<f32, String> {
+    let n: f32 = s.parse().map_err(|_| "invalid time")?;
+
+    if n.is_finite() && n >= 0.0 {
+        Ok(n)
+    } else {
+        Err("time cannot be negative".to_string())
+    }
+}
+
+fn parse_frames(s: &str) -> Result<Frames, String> {
+    let (start, end) = s.split_once(':').ok_or("invalid frames range")?;
+    let start = parse_time(start)?;
+    let end = parse_time(end)?;
+
+    if start < end {
+        Ok(Frames {
+            start,
+            end,
+            fps: 30.0,
+        })
+    } else {
+        Err("frames range is empty".to_string())
+    }
+}
+
+fn parse_fps(s: &str) -> Result<f32, String> {
+    let n: f32 = s.parse().map_err(|_| "invalid frame rate")?;
+
+    if n.is_finite() && n > 0.0 {
+        Ok(n)
+    } else {
+        Err("FPS should be positive".to_string())
+    }
+}
+
+fn parse_file_access(s: &str) -> Result<usvg::FileAccess, String> {
+    match s {
+        "all" => Ok(usvg::FileAccess::Unrestricted),
+        "resources-dir" => Ok(usvg::FileAccess::ResourcesDir),
+        "none" => Ok(usvg::FileAccess::Denied),
+        _ => Err("invalid file access mode".to_string()),
+    }
+}
+
+fn parse_format(s: &str) -> Result<OutputFormat, String> {
+    match s {
+        "png" => Ok(OutputFormat::Png),
+        "pdf" if cfg!(feature = "pdf") => Ok(OutputFormat::Pdf),
+        "pdf" => Err("resvg was built without PDF support".to_string()),
+        "ico" => Ok(OutputFormat::Ico),
+        "icns" => Ok(OutputFormat::Icns),
+        _ => Err("invalid output format".to_string()),
+    }
+}
+
+fn parse_icon_sizes(s: &str) -> Result<Vec<IconSize>, String> {
+    let mut sizes = Vec::new();
+    for item in s.split(',') {
+        let (size, export_id) = match item.split_once(':') {
+            Some((size, id)) => (size, Some(id.trim().to_string())),
+            None => (item, None),
+        };
+
+        let size: u32 = size.trim().parse().map_err(|_| "invalid icon size")?;
+        if !(1..=1024).contains(&size) {
+            return Err("icon size out of bounds".to_string());
+        }
+
+        if sizes.iter().any(|icon: &IconSize| icon.size == size) {
+            return Err(format!("icon size {} is set multiple times", size));
+        }
+
+        if export_id.as_deref() == Some("") {
+            return Err("icon ID cannot be empty".to_string());
+        }
+
+        sizes.push(IconSize { size, export_id });
+    }
+
+    Ok(sizes)
+}
+
 fn parse_languages(s: &str) -> Result<Vec<String>, String> {
     let mut langs = Vec::new();
     for lang in s.split(',') {
@@ -408,6 +591,59 @@ enum OutputTo {
     File(path::PathBuf),
 }
 
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum OutputFormat {
+    Png,
+    Pdf,
+    Ico,
+    Icns,
+}
+
+/// An icon image size.
+#[derive(Clone, PartialEq, Debug)]
+struct IconSize {
+    size: u32,
+    /// An ID to render instead of the whole image.
+    export_id: Option<String>,
+}
+
+/// ICNS types that store PNG data and their sizes in pixels.
+///
+/// Retina types reuse images of the doubled size.
+const ICNS_TYPES: &[(&[u8; 4], u32)] = &[
+    (b"icp4", 16),
+    (b"icp5", 32),
+    (b"ic11", 32),
+    (b"icp6", 64),
+    (b"ic12", 64),
+    (b"ic07", 128),
+    (b"ic08", 256),
+    (b"ic13", 256),
+    (b"ic09", 512),
+    (b"ic14", 512),
+    (b"ic10", 1024),
+];
+
+/// An animation frames range.
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct Frames {
+    start: f32,
+    end: f32,
+    fps: f32,
+}
+
+impl Frames {
+    fn count(&self) -> u32 {
+        // The end is excluded, so a one second range at 10 FPS produces 10 frames.
+        let count = (self.end - self.start) * self.fps - 0.001;
+        count.ceil().max(1.0) as u32
+    }
+
+    fn time(&self, index: u32) -> f32 {
+        self.start + index as f32 / self.fps
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Debug)]
 enum FitTo {
     /// Keep original size.
@@ -453,12 +689,17 @@ struct Args {
     export_id: Option<String>,
     export_area_page: bool,
     export_area_drawing: bool,
+    overrides: resvg::NodeOverrides,
+    frames: Option<Frames>,
     perf: bool,
     quiet: bool,
-    forgiving: bool,
     usvg: usvg::Options,
     fit_to: FitTo,
     background: Option<svgtypes::Color>,
+    format: OutputFormat,
+    #[cfg_attr(not(feature = "pdf"), allow(dead_code))]
+    raster_dpi: u32,
+    icon_sizes: Vec<IconSize>,
 
     serif_family: Option<String>,
     sans_serif_family: Option<String>,
@@ -514,8 +755,102 @@ fn parse_args() -> Result<Args, String> {
         println!("Warning: --export-area-drawing has no effect when --export-id is set.");
     }
 
+    if args.format == OutputFormat::Pdf {
+        if args.export_id.is_some() {
+            return Err("--export-id is not supported by PDF output".to_string());
+        }
+
+        if args.export_area_drawing {
+            return Err("--export-area-drawing is not supported by PDF output".to_string());
+        }
+
+        if !args.hide_ids.is_empty() || !args.only_ids.is_empty() {
+            return Err("--hide-id and --only-id are not supported by PDF output".to_string());
+        }
+
+        if args.background.is_some() {
+            eprintln!("Warning: --background has no effect on PDF output.");
+        }
+    }
+
+    if args.frames.is_some() {
+        if args.time.is_some() {
+            return Err("--time and --frames cannot be used together".to_string());
+        }
+
+        if args.format != OutputFormat::Png {
+            return Err("--frames is supported only by PNG output".to_string());
+        }
+
+        if args.query_all || !matches!(out_png, Some(OutputTo::File(_))) {
+            return Err("--frames requires an output file".to_string());
+        }
+    } else if args.fps.is_some() {
+        eprintln!("Warning: --fps has no effect without --frames.");
+    }
+
+    let icon_sizes = match args.format {
+        OutputFormat::Ico | OutputFormat::Icns => {
+            if args.width.is_some() || args.height.is_some() || args.zoom.is_some() {
+                eprintln!("Warning: --width, --height and --zoom have no effect on icon output.");
+            }
+
+            let default_sizes: &[u32] = if args.format == OutputFormat::Ico {
+                &[16, 24, 32, 48, 64, 128, 256]
+            } else {
+                &[16, 32, 64, 128, 256, 512, 1024]
+            };
+
+            let icon_sizes = args.icon_sizes.take().unwrap_or_else(|| {
+                default_sizes
+                    .iter()
+                    .map(|&size| IconSize {
+                        size,
+                        export_id: None,
+                    })
+                    .collect()
+            });
+
+            for icon in &icon_sizes {
+                if args.format == OutputFormat::Ico && icon.size > 256 {
+                    return Err("ICO images cannot be larger than 256 pixels".to_string());
+                }
+
+                if args.format == OutputFormat::Icns
+                    && !ICNS_TYPES.iter().any(|(_, size)| *size == icon.size)
+                {
+                    return Err(format!(
+                        "{} is not a valid ICNS image size, \
+                         expected 16, 32, 64, 128, 256, 512 or 1024",
+                        icon.size
+                    ));
+                }
+            }
+
+            icon_sizes
+        }
+        _ => {
+            if args.icon_sizes.is_some() {
+                eprintln!("Warning: --icon-sizes has no effect without ICO or ICNS output.");
+            }
+
+            Vec::new()
+        }
+    };
+
+    let frames = args.frames.map(|frames| Frames {
+        fps: args.fps.unwrap_or(frames.fps),
+        ..frames
+    });
+
     let export_id = args.export_id.as_ref().map(|v| v.to_string());
 
+    let overrides = resvg::NodeOverrides {
+        hide: args.hide_ids.into_iter().collect(),
+        only: args.only_ids.into_iter().collect(),
+        ..resvg::NodeOverrides::default()
+    };
+
     let mut fit_to = FitTo::Original;
     let mut default_size = usvg::Size::from_wh(100.0, 100.0).unwrap();
     if let (Some(w), Some(h)) = (args.width, args.height) {
@@ -542,6 +877,14 @@ fn parse_args() -> Result<Args, String> {
         None => None,
     };
 
+    let mut limits = usvg::Limits::default();
+    if let Some(n) = args.max_entity_expansion {
+        limits.max_entity_expansion = n;
+    }
+    if let Some(n) = args.max_entity_depth {
+        limits.max_entity_depth = n;
+    }
+
     let usvg = usvg::Options {
         resources_dir,
         dpi: args.dpi as f32,
@@ -556,7 +899,20 @@ fn parse_args() -> Result<Args, String> {
         image_rendering: args.image_rendering,
         default_size,
         image_href_resolver: usvg::ImageHrefResolver::default(),
+        file_access: usvg::FileAccessPolicy {
+            access: args.file_access,
+            ..usvg::FileAccessPolicy::default()
+        },
         forgiving: args.forgiving,
+        allow_dtd: !args.no_dtd,
+        diagnostics: if args.json_diagnostics && !args.quiet {
+            Some(usvg::Diagnostics::new())
+        } else {
+            None
+        },
+        keep_origins: false,
+        limits,
+        animation_time: args.time,
     };
 
     Ok(Args {
@@ -566,12 +922,16 @@ fn parse_args() -> Result<Args, String> {
         export_id,
         export_area_page: args.export_area_page,
         export_area_drawing: args.export_area_drawing,
+        overrides,
+        frames,
         perf: args.perf,
         quiet: args.quiet,
-        forgiving: args.forgiving,
         usvg,
         fit_to,
         background: args.background,
+        format: args.format,
+        raster_dpi: args.raster_dpi,
+        icon_sizes,
         serif_family: args.serif_family,
         sans_serif_family: args.sans_serif_family,
         cursive_family: args.cursive_family,
@@ -672,12 +1032,17 @@ fn render_svg(args: &Args, tree: &usvg::Tree) -> Result<tiny_skia::Pixmap, Strin
             }
         }
 
-        let ts = args.fit_to.fit_to_transform(tree.size.to_int_size());
+        // The element is fitted into the requested size, unless it's placed on a page.
+        let ts = if args.export_area_page {
+            args.fit_to.fit_to_transform(tree.size.to_int_size())
+        } else {
+            args.fit_to.fit_to_transform(bbox.size().to_int_size())
+        };
 
         let rtree = resvg::Tree::from_usvg_node(&node)
             .ok_or_else(|| "zero-size node detected".to_string())?;
 
-        rtree.render(ts, &mut pixmap.as_mut());
+        render_tree(args, &rtree, ts, &mut pixmap);
 
         if args.export_area_page {
             // TODO: add offset support to render_node() so we would not need an additional pixmap
@@ -722,7 +1087,7 @@ fn render_svg(args: &Args, tree: &usvg::Tree) -> Result<tiny_skia::Pixmap, Strin
         let ts = args.fit_to.fit_to_transform(tree.size.to_int_size());
 
         let rtree = resvg::Tree::from_usvg(tree);
-        rtree.render(ts, &mut pixmap.as_mut());
+        render_tree(args, &rtree, ts, &mut pixmap);
 
         if args.export_area_drawing {
             trim_pixmap(&rtree, ts, &pixmap).unwrap_or(pixmap)
@@ -741,6 +1106,188 @@ fn render_svg(args: &Args, tree: &usvg::Tree) -> Result<tiny_skia::Pixmap, Strin
     Ok(img)
 }
 
+/// Renders animation frames into numbered PNG files.
+fn render_frames(
+    args: &mut Args,
+    xml_tree: &usvg::roxmltree::Document,
+    frames: Frames,
+) -> Result<(), String> {
+    let file = match args.out_png {
+        Some(OutputTo::File(ref file)) => file.clone(),
+        _ => return Err("--frames requires an output file".to_string()),
+    };
+
+    let count = frames.count();
+    let width = (count - 1).to_string().len().max(4);
+    let mut fontdb = None;
+    for index in 0..count {
+        args.usvg.animation_time = Some(frames.time(index));
+
+        let mut tree = timed(args.perf, "SVG Parsing", || {
+            usvg::Tree::from_xmltree(xml_tree, &args.usvg).map_err(|e| e.to_string())
+        })?;
+
+        // fontdb initialization is pretty expensive, so perform it only once.
+        if tree.has_text_nodes() {
+            let fontdb = fontdb.get_or_insert_with(|| load_fonts(args));
+            timed(args.perf, "Text Conversion", || tree.convert_text(fontdb));
+        }
+
+        let img = render_svg(args, &tree)?;
+
+        let path = frame_path(&file, index, width);
+        timed(args.perf, "Saving", || {
+            img.save_png(&path).map_err(|e| e.to_string())
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Appends a frame number to the file name, like `out-0001.png`.
+fn frame_path(path: &path::Path, index: u32, width: usize) -> path::PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(ext) => format!(
+            "{}-{:0width$}.{}",
+            stem,
+            index,
+            ext.to_string_lossy(),
+            width = width
+        ),
+        None => format!("{}-{:0width$}", stem, index, width = width),
+    };
+
+    path.with_file_name(name)
+}
+
+fn render_tree(
+    args: &Args,
+    rtree: &resvg::Tree,
+    transform: tiny_skia::Transform,
+    pixmap: &mut tiny_skia::Pixmap,
+) {
+    let options = resvg::RenderOptions {
+        overrides: Some(&args.overrides),
+        ..resvg::RenderOptions::default()
+    };
+
+    // Cannot fail without limits.
+    let _ = rtree.render_with_options(transform, &options, &mut pixmap.as_mut());
+}
+
+#[cfg(feature = "pdf")]
+fn render_pdf(args: &Args, tree: &usvg::Tree) -> Result<Vec<u8>, String> {
+    let size = args
+        .fit_to
+        .fit_to_size(tree.size.to_int_size())
+        .ok_or_else(|| "target size is zero".to_string())?;
+
+    let options = resvg::pdf::Options {
+        raster_dpi: args.raster_dpi as f32,
+        page_size: Some(size.to_size()),
+    };
+
+    Ok(resvg::pdf::convert(tree, &options))
+}
+
+#[cfg(not(feature = "pdf"))]
+fn render_pdf(_: &Args, _: &usvg::Tree) -> Result<Vec<u8>, String> {
+    Err("resvg was built without PDF support".into())
+}
+
+/// Renders all icon sizes and packs them into an ICO or ICNS file.
+fn render_icon(args: &mut Args, tree: &usvg::Tree) -> Result<Vec<u8>, String> {
+    let export_id = args.export_id.take();
+    let mut images = Vec::new();
+    for icon in std::mem::take(&mut args.icon_sizes) {
+        args.fit_to = FitTo::Size(icon.size, icon.size);
+        args.export_id = icon.export_id.or_else(|| export_id.clone());
+
+        let img = render_svg(args, tree)?;
+        let img = center_pixmap(args, img, icon.size);
+        let data = img.encode_png().map_err(|e| e.to_string())?;
+        images.push((icon.size, data));
+    }
+
+    if args.format == OutputFormat::Ico {
+        Ok(encode_ico(&images))
+    } else {
+        Ok(encode_icns(&images))
+    }
+}
+
+/// Places a pixmap at the center of a square one, since icon images must be square.
+fn center_pixmap(args: &Args, pixmap: tiny_skia::Pixmap, size: u32) -> tiny_skia::Pixmap {
+    if pixmap.width() == size && pixmap.height() == size {
+        return pixmap;
+    }
+
+    // Unwrap is safe, because `size` is already valid.
+    let mut square = tiny_skia::Pixmap::new(size, size).unwrap();
+
+    if let Some(background) = args.background {
+        square.fill(svg_to_skia_color(background));
+    }
+
+    square.draw_pixmap(
+        (size as i32 - pixmap.width() as i32) / 2,
+        (size as i32 - pixmap.height() as i32) / 2,
+        pixmap.as_ref(),
+        &tiny_skia::PixmapPaint::default(),
+        tiny_skia::Transform::default(),
+        None,
+    );
+    square
+}
+
+/// Packs PNG images into an ICO file.
+fn encode_ico(images: &[(u32, Vec<u8>)]) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend_from_slice(&0u16.to_le_bytes()); // reserved
+    data.extend_from_slice(&1u16.to_le_bytes()); // image type: icon
+    data.extend_from_slice(&(images.len() as u16).to_le_bytes());
+
+    let mut offset = 6 + 16 * images.len() as u32;
+    for (size, png) in images {
+        // 256 is stored as 0.
+        let size = if *size >= 256 { 0 } else { *size as u8 };
+        data.push(size); // width
+        data.push(size); // height
+        data.push(0); // no palette
+        data.push(0); // reserved
+        data.extend_from_slice(&1u16.to_le_bytes()); // color planes
+        data.extend_from_slice(&32u16.to_le_bytes()); // bits per pixel
+        data.extend_from_slice(&(png.len() as u32).to_le_bytes());
+        data.extend_from_slice(&offset.to_le_bytes());
+        offset += png.len() as u32;
+    }
+
+    for (_, png) in images {
+        data.extend_from_slice(png);
+    }
+
+    data
+}
+
+/// Packs PNG images into an ICNS file.
+fn encode_icns(images: &[(u32, Vec<u8>)]) -> Vec<u8> {
+    let mut body = Vec::new();
+    for (kind, size) in ICNS_TYPES {
+        if let Some((_, png)) = images.iter().find(|(s, _)| s == size) {
+            body.extend_from_slice(*kind);
+            body.extend_from_slice(&(png.len() as u32 + 8).to_be_bytes());
+            body.extend_from_slice(png);
+        }
+    }
+
+    let mut data = Vec::with_capacity(body.len() + 8);
+    data.extend_from_slice(b"icns");
+    data.extend_from_slice(&(body.len() as u32 + 8).to_be_bytes());
+    data.extend_from_slice(&body);
+    data
+}
+
 fn trim_pixmap(
     rtree: &resvg::Tree,
     transform: tiny_skia::Transform,
@@ -2,28 +2,38 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use std::rc::Rc;
-
 use crate::render::Context;
 use crate::tree::{Node, OptionLog};
 
+/// A mask.
 pub struct Mask {
+    /// Whether everything is masked out.
+    ///
+    /// Set for `objectBoundingBox` units and a zero-sized element.
     pub mask_all: bool,
+    /// Mask region in the group's user space.
     pub region: tiny_skia::Rect,
+    /// Mask content transform. Already includes `objectBoundingBox` units.
     pub content_transform: tiny_skia::Transform,
+    /// Mask type.
     pub kind: usvg::MaskType,
+    /// A mask that should be applied to this mask.
     pub mask: Option<Box<Self>>,
+    /// Mask's children.
     pub children: Vec<Node>,
 }
 
-pub fn convert(umask: Option<Rc<usvg::Mask>>, object_bbox: tiny_skia::Rect) -> Option<Mask> {
+pub fn convert(
+    umask: Option<usvg::Shared<usvg::Mask>>,
+    object_bbox: tiny_skia::Rect,
+) -> Option<Mask> {
     let umask = umask?;
 
     let mut content_transform = tiny_skia::Transform::default();
     if umask.content_units == usvg::Units::ObjectBoundingBox {
-        let object_bbox = object_bbox
-            .to_non_zero_rect()
-            .log_none(|| log::warn!("Masking of zero-sized shapes is not allowed."))?;
+        let object_bbox = object_bbox.to_non_zero_rect().log_none(|| {
+            usvg::report!(InvalidSize, "Masking of zero-sized shapes is not allowed.")
+        })?;
 
         let ts = usvg::Transform::from_bbox(object_bbox);
         content_transform = ts;
@@ -69,7 +79,10 @@ pub fn apply(
         return;
     }
 
-    let mut mask_pixmap = tiny_skia::Pixmap::new(pixmap.width(), pixmap.height()).unwrap();
+    let mut mask_pixmap = match ctx.create_pixmap(pixmap.width(), pixmap.height()) {
+        Some(v) => v,
+        None => return,
+    };
 
     {
         // TODO: only when needed
@@ -82,10 +95,16 @@ pub fn apply(
             transform,
         );
 
+        // ID overrides are not applied to the mask content.
+        let content_ctx = Context {
+            overrides: None,
+            ..*ctx
+        };
+
         let content_transform = transform.pre_concat(mask.content_transform);
-        crate::render::render_nodes(
+        crate::render::render_nodes_to_pixmap(
             &mask.children,
-            ctx,
+            &content_ctx,
             content_transform,
             &mut mask_pixmap.as_mut(),
         );
@@ -2,24 +2,134 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use std::rc::Rc;
+use std::sync::Arc;
 
 use crate::render::Context;
 use crate::tree::{Node, OptionLog};
 
+/// A pattern.
 pub struct Pattern {
+    /// Pattern tile rect. Already includes `objectBoundingBox` units.
     pub rect: tiny_skia::NonZeroRect,
+    /// Pattern's view box.
     pub view_box: Option<usvg::ViewBox>,
+    /// Fill or stroke opacity.
     pub opacity: usvg::Opacity,
+    /// Pattern's transform.
     pub transform: tiny_skia::Transform,
+    /// Pattern content transform. Already includes `objectBoundingBox` units.
     pub content_transform: tiny_skia::Transform,
+    /// Pattern's children.
     pub children: Vec<Node>,
 }
 
+/// A gradient stop.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GradientStop {
+    /// Stop offset in the 0..=1 range.
+    pub offset: f32,
+    /// Stop color. Already includes the stop and paint opacity.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::color"))]
+    pub color: tiny_skia::Color,
+}
+
+/// A gradient kind.
+#[allow(missing_docs)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GradientKind {
+    Linear {
+        x1: f32,
+        y1: f32,
+        x2: f32,
+        y2: f32,
+    },
+    Radial {
+        fx: f32,
+        fy: f32,
+        cx: f32,
+        cy: f32,
+        r: f32,
+    },
+}
+
+/// A linear or a radial gradient.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Gradient {
+    /// Gradient kind and coordinates.
+    pub kind: GradientKind,
+    /// Gradient stops.
+    pub stops: Vec<GradientStop>,
+    /// Spread mode.
+    pub spread_mode: tiny_skia::SpreadMode,
+    /// Gradient transform. Already includes `objectBoundingBox` units.
+    pub transform: tiny_skia::Transform,
+    shader: tiny_skia::Shader<'static>,
+}
+
+impl Gradient {
+    /// Creates a new gradient.
+    ///
+    /// Returns `None` when the gradient cannot be rendered,
+    /// like when it has less than two stops.
+    pub fn new(
+        kind: GradientKind,
+        stops: Vec<GradientStop>,
+        spread_mode: tiny_skia::SpreadMode,
+        transform: tiny_skia::Transform,
+    ) -> Option<Self> {
+        let points = stops
+            .iter()
+            .map(|stop| tiny_skia::GradientStop::new(stop.offset, stop.color))
+            .collect();
+
+        let shader = match kind {
+            GradientKind::Linear { x1, y1, x2, y2 } => tiny_skia::LinearGradient::new(
+                (x1, y1).into(),
+                (x2, y2).into(),
+                points,
+                spread_mode,
+                transform,
+            )?,
+            GradientKind::Radial { fx, fy, cx, cy, r } => tiny_skia::RadialGradient::new(
+                (fx, fy).into(),
+                (cx, cy).into(),
+                r,
+                points,
+                spread_mode,
+                transform,
+            )?,
+        };
+
+        Some(Gradient {
+            kind,
+            stops,
+            spread_mode,
+            transform,
+            shader,
+        })
+    }
+
+    /// Returns the same gradient with a different transform.
+    pub fn with_transform(&self, transform: tiny_skia::Transform) -> Option<Self> {
+        Gradient::new(self.kind, self.stops.clone(), self.spread_mode, transform)
+    }
+
+    pub(crate) fn shader(&self) -> &tiny_skia::Shader<'static> {
+        &self.shader
+    }
+}
+
+/// A paint.
 #[derive(Clone)]
 pub enum Paint {
-    Shader(tiny_skia::Shader<'static>),
-    Pattern(Rc<Pattern>),
+    /// A solid color. Already includes the paint opacity.
+    Color(tiny_skia::Color),
+    /// A linear or a radial gradient.
+    Gradient(Arc<Gradient>),
+    /// A pattern.
+    Pattern(Arc<Pattern>),
 }
 
 pub fn convert(
@@ -30,7 +140,7 @@ pub fn convert(
     match paint {
         usvg::Paint::Color(c) => {
             let c = tiny_skia::Color::from_rgba8(c.red, c.green, c.blue, opacity.to_u8());
-            Some(Paint::Shader(tiny_skia::Shader::SolidColor(c)))
+            Some(Paint::Color(c))
         }
         usvg::Paint::LinearGradient(ref lg) => convert_linear_gradient(lg, opacity, object_bbox),
         usvg::Paint::RadialGradient(ref rg) => convert_radial_gradient(rg, opacity, object_bbox),
@@ -43,17 +153,17 @@ fn convert_linear_gradient(
     opacity: usvg::Opacity,
     object_bbox: Option<tiny_skia::NonZeroRect>,
 ) -> Option<Paint> {
-    let (mode, transform, points) = convert_base_gradient(gradient, opacity, object_bbox)?;
-
-    let shader = tiny_skia::LinearGradient::new(
-        (gradient.x1, gradient.y1).into(),
-        (gradient.x2, gradient.y2).into(),
-        points,
-        mode,
-        transform,
-    )?;
+    let (mode, transform, stops) = convert_base_gradient(gradient, opacity, object_bbox)?;
 
-    Some(Paint::Shader(shader))
+    let kind = GradientKind::Linear {
+        x1: gradient.x1,
+        y1: gradient.y1,
+        x2: gradient.x2,
+        y2: gradient.y2,
+    };
+    let gradient = Gradient::new(kind, stops, mode, transform)?;
+
+    Some(Paint::Gradient(Arc::new(gradient)))
 }
 
 fn convert_radial_gradient(
@@ -61,18 +171,18 @@ fn convert_radial_gradient(
     opacity: usvg::Opacity,
     object_bbox: Option<tiny_skia::NonZeroRect>,
 ) -> Option<Paint> {
-    let (mode, transform, points) = convert_base_gradient(gradient, opacity, object_bbox)?;
-
-    let shader = tiny_skia::RadialGradient::new(
-        (gradient.fx, gradient.fy).into(),
-        (gradient.cx, gradient.cy).into(),
-        gradient.r.get(),
-        points,
-        mode,
-        transform,
-    )?;
+    let (mode, transform, stops) = convert_base_gradient(gradient, opacity, object_bbox)?;
 
-    Some(Paint::Shader(shader))
+    let kind = GradientKind::Radial {
+        fx: gradient.fx,
+        fy: gradient.fy,
+        cx: gradient.cx,
+        cy: gradient.cy,
+        r: gradient.r.get(),
+    };
+    let gradient = Gradient::new(kind, stops, mode, transform)?;
+
+    Some(Paint::Gradient(Arc::new(gradient)))
 }
 
 fn convert_base_gradient(
@@ -82,7 +192,7 @@ fn convert_base_gradient(
 ) -> Option<(
     tiny_skia::SpreadMode,
     tiny_skia::Transform,
-    Vec<tiny_skia::GradientStop>,
+    Vec<GradientStop>,
 )> {
     let mode = match gradient.spread_method {
         usvg::SpreadMethod::Pad => tiny_skia::SpreadMode::Pad,
@@ -91,15 +201,16 @@ fn convert_base_gradient(
     };
 
     let transform = if gradient.units == usvg::Units::ObjectBoundingBox {
-        let bbox =
-            object_bbox.log_none(|| log::warn!("Gradient on zero-sized shapes is not allowed."))?;
+        let bbox = object_bbox.log_none(|| {
+            usvg::report!(InvalidSize, "Gradient on zero-sized shapes is not allowed.")
+        })?;
         let ts = tiny_skia::Transform::from_bbox(bbox);
         ts.pre_concat(gradient.transform)
     } else {
         gradient.transform
     };
 
-    let mut points = Vec::with_capacity(gradient.stops.len());
+    let mut stops = Vec::with_capacity(gradient.stops.len());
     for stop in &gradient.stops {
         let alpha = stop.opacity * opacity;
         let color = tiny_skia::Color::from_rgba8(
@@ -108,10 +219,13 @@ fn convert_base_gradient(
             stop.color.blue,
             alpha.to_u8(),
         );
-        points.push(tiny_skia::GradientStop::new(stop.offset.get(), color))
+        stops.push(GradientStop {
+            offset: stop.offset.get(),
+            color,
+        });
     }
 
-    Some((mode, transform, points))
+    Some((mode, transform, stops))
 }
 
 fn convert_pattern(
@@ -121,8 +235,9 @@ fn convert_pattern(
 ) -> Option<Paint> {
     let content_transform =
         if pattern.content_units == usvg::Units::ObjectBoundingBox && pattern.view_box.is_none() {
-            let bbox = object_bbox
-                .log_none(|| log::warn!("Pattern on zero-sized shapes is not allowed."))?;
+            let bbox = object_bbox.log_none(|| {
+                usvg::report!(InvalidSize, "Pattern on zero-sized shapes is not allowed.")
+            })?;
 
             // No need to shift patterns.
             tiny_skia::Transform::from_scale(bbox.width(), bbox.height())
@@ -136,15 +251,16 @@ fn convert_pattern(
     }
 
     let rect = if pattern.units == usvg::Units::ObjectBoundingBox {
-        let bbox =
-            object_bbox.log_none(|| log::warn!("Pattern on zero-sized shapes is not allowed."))?;
+        let bbox = object_bbox.log_none(|| {
+            usvg::report!(InvalidSize, "Pattern on zero-sized shapes is not allowed.")
+        })?;
 
         pattern.rect.bbox_transform(bbox)
     } else {
         pattern.rect
     };
 
-    Some(Paint::Pattern(Rc::new(Pattern {
+    Some(Paint::Pattern(Arc::new(Pattern {
         rect,
         view_box: pattern.view_box,
         opacity,
@@ -168,7 +284,7 @@ pub fn prepare_pattern_pixmap(
         (pattern.rect.width() * sx).round() as u32,
         (pattern.rect.height() * sy).round() as u32,
     )?;
-    let mut pixmap = tiny_skia::Pixmap::new(img_size.width(), img_size.height())?;
+    let mut pixmap = ctx.create_pixmap(img_size.width(), img_size.height())?;
 
     let mut transform = tiny_skia::Transform::from_scale(sx, sy);
     if let Some(vbox) = pattern.view_box {
@@ -178,7 +294,13 @@ pub fn prepare_pattern_pixmap(
 
     transform = transform.pre_concat(pattern.content_transform);
 
-    crate::render::render_nodes(&pattern.children, ctx, transform, &mut pixmap.as_mut());
+    // ID overrides are not applied to the pattern content, which has its own canvas.
+    let ctx = Context {
+        layer_region: None,
+        overrides: None,
+        ..*ctx
+    };
+    crate::render::render_nodes_to_pixmap(&pattern.children, &ctx, transform, &mut pixmap.as_mut());
 
     let mut ts = tiny_skia::Transform::default();
     ts = ts.pre_concat(pattern.transform);
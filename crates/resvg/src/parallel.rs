@@ -0,0 +1,260 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Multi-threaded rendering.
+//!
+//! Group layers do not depend on the canvas content, because resvg doesn't support
+//! `BackgroundImage`. Therefore sibling layers can be rendered concurrently
+//! and then drawn onto the canvas in the original order.
+
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::render::{self, Context, Layer, Progress, TinySkiaBackend};
+use crate::tree::{Group, Node};
+
+/// Renders nodes, rendering sibling layers concurrently.
+pub fn render_nodes(
+    children: &[Node],
+    ctx: &Context,
+    transform: tiny_skia::Transform,
+    pixmap: &mut tiny_skia::PixmapMut,
+) {
+    render_with_progress(children, ctx, transform, None, pixmap);
+}
+
+/// Same as `render_nodes`, but reports progress.
+///
+/// Progress is reported only from the current thread, one step per drawn layer.
+pub fn render_with_progress(
+    children: &[Node],
+    ctx: &Context,
+    transform: tiny_skia::Transform,
+    progress: Option<&Progress>,
+    pixmap: &mut tiny_skia::PixmapMut,
+) {
+    let threads = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1);
+
+    let mut start = 0;
+    while start < children.len() {
+        if ctx.is_interrupted() {
+            return;
+        }
+
+        // Nodes with an opacity override share a layer, so they are rendered as is.
+        if let Some((count, opacity)) = render::opacity_override(&children[start..], ctx) {
+            let nodes = &children[start..start + count];
+            let mut backend = TinySkiaBackend::new(*ctx, pixmap);
+            render::render_with_opacity(nodes, opacity, ctx, transform, &mut backend);
+            if let Some(progress) = progress {
+                progress.skip(nodes);
+            }
+
+            start += count;
+            continue;
+        }
+
+        // Collect enough layers to occupy all threads.
+        let mut end = start;
+        let mut layers_count = 0;
+        while end < children.len() && layers_count < threads {
+            if end != start && render::opacity_override(&children[end..], ctx).is_some() {
+                break;
+            }
+
+            if as_layer(&children[end], ctx, transform, pixmap).is_some() {
+                layers_count += 1;
+            }
+            end += 1;
+        }
+
+        let batch = &children[start..end];
+        if layers_count < 2 {
+            for node in batch {
+                render_node(node, ctx, transform, progress, pixmap);
+            }
+        } else {
+            let layers = render_layers(batch, ctx, transform, threads, pixmap);
+            for (node, layer) in batch.iter().zip(layers) {
+                match (node, layer) {
+                    (Node::Group(ref group), Some(layer)) => {
+                        if let Some(ref layer) = layer {
+                            render::draw_layer(group, layer, pixmap);
+                        }
+
+                        if let Some(progress) = progress {
+                            progress.advance(1);
+                        }
+                    }
+                    _ => render_node(node, ctx, transform, progress, pixmap),
+                }
+            }
+        }
+
+        start = end;
+    }
+}
+
+/// Renders a node, looking for sibling layers inside.
+fn render_node(
+    node: &Node,
+    ctx: &Context,
+    transform: tiny_skia::Transform,
+    progress: Option<&Progress>,
+    pixmap: &mut tiny_skia::PixmapMut,
+) {
+    let ctx = match render::node_context(node, ctx) {
+        Some(v) => v,
+        None => {
+            if let Some(progress) = progress {
+                progress.skip(std::slice::from_ref(node));
+            }
+            return;
+        }
+    };
+
+    if let Node::Group(ref group) = node {
+        let transform = transform.pre_concat(group.transform);
+        if render::is_outside(group, transform, pixmap.width(), pixmap.height()) {
+            if let Some(progress) = progress {
+                progress.skip(std::slice::from_ref(node));
+            }
+            return;
+        }
+
+        if group.is_transform_only() {
+            render_with_progress(&group.children, &ctx, transform, progress, pixmap);
+            return;
+        }
+
+        if let Some(layer) = render::render_layer(group, &ctx, transform, render_nodes) {
+            render::draw_layer(group, &layer, pixmap);
+        }
+    } else {
+        let mut backend = TinySkiaBackend::new(ctx, pixmap);
+        render::render_node(node, &ctx, transform, &mut backend);
+    }
+
+    if let Some(progress) = progress {
+        progress.advance(1);
+    }
+}
+
+/// Returns a group that requires a layer and is visible, with its rendering context.
+fn as_layer<'a, 'b>(
+    node: &'a Node,
+    ctx: &Context<'b>,
+    transform: tiny_skia::Transform,
+    pixmap: &tiny_skia::PixmapMut,
+) -> Option<(&'a Group, Context<'b>)> {
+    match node {
+        Node::Group(ref group) if !group.is_transform_only() => {
+            let ctx = render::node_context(node, ctx)?;
+            let transform = transform.pre_concat(group.transform);
+            if render::is_outside(group, transform, pixmap.width(), pixmap.height()) {
+                None
+            } else {
+                Some((group, ctx))
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Renders all layers in a list concurrently, using at most `threads` worker threads.
+///
+/// Returns `None` for nodes that are not layers.
+fn render_layers(
+    nodes: &[Node],
+    ctx: &Context,
+    transform: tiny_skia::Transform,
+    threads: usize,
+    pixmap: &tiny_skia::PixmapMut,
+) -> Vec<Option<Option<Layer>>> {
+    // `Context` is not `Send`, so only the node overrides are passed to workers.
+    let groups: Vec<_> = nodes
+        .iter()
+        .map(|node| {
+            as_layer(node, ctx, transform, pixmap)
+                .map(|(group, ctx)| (group, ctx.overrides, ctx.only_matched))
+        })
+        .collect();
+    let workers = threads.min(groups.iter().flatten().count());
+
+    let max_bbox = ctx.max_bbox;
+    let layer_region = ctx.layer_region;
+    let host_transform = ctx.host_transform;
+    let max_surface_pixels = ctx.max_surface_pixels;
+    let interrupt = ctx.interrupt;
+    let diagnostics = usvg::diagnostics::current();
+    let next = AtomicUsize::new(0);
+
+    let mut layers: Vec<_> = groups.iter().map(|_| None).collect();
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                let groups = &groups;
+                let next = &next;
+                let diagnostics = diagnostics.clone();
+                scope.spawn(move || {
+                    let render = || {
+                        // Each worker takes the next unrendered layer until none are left.
+                        let mut results = Vec::new();
+                        loop {
+                            let idx = next.fetch_add(1, Ordering::Relaxed);
+                            let (group, overrides, only_matched) = match groups.get(idx) {
+                                Some(Some(v)) => *v,
+                                Some(None) => continue,
+                                None => break,
+                            };
+
+                            let error = Cell::new(None);
+                            let ctx = Context {
+                                max_bbox,
+                                layer_region,
+                                host_transform,
+                                max_surface_pixels,
+                                error: &error,
+                                interrupt,
+                                overrides,
+                                only_matched,
+                            };
+
+                            let transform = transform.pre_concat(group.transform);
+                            let layer = render::render_layer(
+                                group,
+                                &ctx,
+                                transform,
+                                render::render_nodes_to_pixmap,
+                            );
+                            results.push((idx, layer, error.get()));
+                        }
+
+                        results
+                    };
+
+                    match diagnostics {
+                        Some(diagnostics) => diagnostics.scope(render),
+                        None => render(),
+                    }
+                })
+            })
+            .collect();
+
+        // Layers of a panicked worker will be rendered on the current thread.
+        for handle in handles {
+            for (idx, layer, error) in handle.join().unwrap_or_default() {
+                if let Some(error) = error {
+                    ctx.set_error(error);
+                }
+
+                layers[idx] = Some(layer);
+            }
+        }
+    });
+
+    layers
+}
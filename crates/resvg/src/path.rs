@@ -2,37 +2,81 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use std::rc::Rc;
+use std::sync::Arc;
 
 use crate::paint_server::Paint;
 use crate::render::Context;
 use crate::tree::{BBoxes, Node};
 
+/// A filled path.
 pub struct FillPath {
+    /// Path's transform.
     pub transform: tiny_skia::Transform,
+    /// Fill paint. Already includes the fill opacity.
     pub paint: Paint,
+    /// Fill rule.
     pub rule: tiny_skia::FillRule,
+    /// Whether the path should be anti-aliased.
     pub anti_alias: bool,
-    pub path: Rc<tiny_skia::Path>,
+    /// Path's vector effect.
+    pub vector_effect: usvg::VectorEffect,
+    /// Element's ID. Can be empty.
+    ///
+    /// Fill and stroke paths of the same element share it.
+    pub id: String,
+    /// Whether the path should be rendered.
+    pub visible: bool,
+    /// Path's pointer events. Used by hit-testing.
+    pub pointer_events: usvg::PointerEvents,
+    /// Path's segments.
+    pub path: Arc<tiny_skia::Path>,
 }
 
+/// A stroked path.
 pub struct StrokePath {
+    /// Path's transform.
     pub transform: tiny_skia::Transform,
+    /// Stroke paint. Already includes the stroke opacity.
     pub paint: Paint,
+    /// Stroke properties.
     pub stroke: tiny_skia::Stroke,
+    /// Whether the path should be anti-aliased.
     pub anti_alias: bool,
-    pub path: Rc<tiny_skia::Path>,
+    /// Path's vector effect.
+    ///
+    /// With `non-scaling-stroke`, stroke width and dashes are in the host coordinate system
+    /// and not in the user one.
+    pub vector_effect: usvg::VectorEffect,
+    /// Element's ID. Can be empty.
+    ///
+    /// Fill and stroke paths of the same element share it.
+    pub id: String,
+    /// Whether the path should be rendered.
+    pub visible: bool,
+    /// Path's pointer events. Used by hit-testing.
+    pub pointer_events: usvg::PointerEvents,
+    /// Path's segments.
+    pub path: Arc<tiny_skia::Path>,
 }
 
-pub fn convert(upath: &usvg::Path, children: &mut Vec<Node>) -> Option<BBoxes> {
+pub fn convert(
+    upath: &usvg::Path,
+    parent_transform: tiny_skia::Transform,
+    children: &mut Vec<Node>,
+) -> Option<BBoxes> {
     let transform = upath.transform;
     let anti_alias = upath.rendering_mode.use_shape_antialiasing();
 
+    // `usvg` paths are not thread-safe, while the render tree is.
+    let data = Arc::new(tiny_skia::Path::clone(&upath.data));
+
     let fill_path = upath.fill.as_ref().and_then(|ufill| {
         convert_fill_path(
             ufill,
-            upath.data.clone(),
+            upath,
+            data.clone(),
             transform,
+            upath.vector_effect,
             upath.text_bbox,
             anti_alias,
         )
@@ -41,8 +85,11 @@ pub fn convert(upath: &usvg::Path, children: &mut Vec<Node>) -> Option<BBoxes> {
     let stroke_path = upath.stroke.as_ref().and_then(|ustroke| {
         convert_stroke_path(
             ustroke,
-            upath.data.clone(),
+            upath,
+            data.clone(),
             transform,
+            parent_transform,
+            upath.vector_effect,
             upath.text_bbox,
             anti_alias,
         )
@@ -52,7 +99,10 @@ pub fn convert(upath: &usvg::Path, children: &mut Vec<Node>) -> Option<BBoxes> {
         return None;
     }
 
-    let mut bboxes = BBoxes::default();
+    let mut bboxes = BBoxes {
+        host_dependent: upath.vector_effect != usvg::VectorEffect::None,
+        ..BBoxes::default()
+    };
 
     if let Some((_, l_bbox, o_bbox)) = fill_path {
         bboxes.layer = bboxes.layer.expand(l_bbox);
@@ -63,11 +113,32 @@ pub fn convert(upath: &usvg::Path, children: &mut Vec<Node>) -> Option<BBoxes> {
         bboxes.object = bboxes.object.expand(o_bbox);
     }
 
+    // Like with `non-scaling-stroke`, the canvas transform is used as a host coordinate system.
+    if let Some(ts) = crate::geom::apply_vector_effect(
+        upath.vector_effect,
+        parent_transform,
+        transform,
+        tiny_skia::Transform::default(),
+    ) {
+        // Bounding boxes are in the element's coordinates, so we have to map them
+        // using the difference between the original and the effective transforms.
+        let ts = transform
+            .invert()?
+            .pre_concat(parent_transform.invert()?)
+            .pre_concat(ts)
+            .pre_concat(transform);
+        bboxes.object = bboxes.object.transform(ts)?;
+        bboxes.layer = bboxes.layer.transform(ts)?;
+    }
+
     bboxes.transformed_object = bboxes.object.transform(upath.transform)?;
 
     // Do not add hidden paths, but preserve the bbox.
     // visibility=hidden still affects the bbox calculation.
-    if upath.visibility != usvg::Visibility::Visible {
+    // Hidden paths that can still be hit are kept, but will not be rendered.
+    if upath.visibility != usvg::Visibility::Visible
+        && !crate::hit_test::is_hidden_target(upath.pointer_events)
+    {
         return Some(bboxes);
     }
 
@@ -94,8 +165,10 @@ pub fn convert(upath: &usvg::Path, children: &mut Vec<Node>) -> Option<BBoxes> {
 
 fn convert_fill_path(
     ufill: &usvg::Fill,
-    path: Rc<tiny_skia::Path>,
+    upath: &usvg::Path,
+    path: Arc<tiny_skia::Path>,
     transform: tiny_skia::Transform,
+    vector_effect: usvg::VectorEffect,
     text_bbox: Option<tiny_skia::NonZeroRect>,
     anti_alias: bool,
 ) -> Option<(FillPath, usvg::BBox, usvg::BBox)> {
@@ -122,6 +195,10 @@ fn convert_fill_path(
         paint,
         rule,
         anti_alias,
+        vector_effect,
+        id: upath.id.clone(),
+        visible: upath.visibility == usvg::Visibility::Visible,
+        pointer_events: upath.pointer_events,
         path,
     };
 
@@ -130,8 +207,11 @@ fn convert_fill_path(
 
 fn convert_stroke_path(
     ustroke: &usvg::Stroke,
-    path: Rc<tiny_skia::Path>,
+    upath: &usvg::Path,
+    path: Arc<tiny_skia::Path>,
     transform: tiny_skia::Transform,
+    parent_transform: tiny_skia::Transform,
+    vector_effect: usvg::VectorEffect,
     text_bbox: Option<tiny_skia::NonZeroRect>,
     anti_alias: bool,
 ) -> Option<(StrokePath, usvg::BBox, usvg::BBox)> {
@@ -170,11 +250,17 @@ fn convert_stroke_path(
         stroke.dash = tiny_skia::StrokeDash::new(list.clone(), ustroke.dashoffset);
     }
 
-    // TODO: explain
-    // TODO: expand by stroke width for round/bevel joins
-    let resolution_scale = tiny_skia::PathStroker::compute_resolution_scale(&transform);
-    let resolution_scale = resolution_scale.max(10.0);
-    let stroked_path = path.stroke(&stroke, resolution_scale)?;
+    let stroked_path = if vector_effect == usvg::VectorEffect::NonScalingStroke {
+        // The actual render transform is unknown at this point,
+        // so the canvas transform is used as a host coordinate system.
+        stroke_non_scaling(&path, &stroke, parent_transform.pre_concat(transform))?
+    } else {
+        // TODO: explain
+        // TODO: expand by stroke width for round/bevel joins
+        let resolution_scale = tiny_skia::PathStroker::compute_resolution_scale(&transform);
+        let resolution_scale = resolution_scale.max(10.0);
+        path.stroke(&stroke, resolution_scale)?
+    };
 
     let mut layer_bbox = usvg::BBox::from(stroked_path.bounds());
     if let Some(text_bbox) = text_bbox {
@@ -189,6 +275,10 @@ fn convert_stroke_path(
         paint,
         stroke,
         anti_alias,
+        vector_effect,
+        id: upath.id.clone(),
+        visible: upath.visibility == usvg::Visibility::Visible,
+        pointer_events: upath.pointer_events,
         path,
     };
 
@@ -202,11 +292,22 @@ pub fn render_fill_path(
     transform: tiny_skia::Transform,
     pixmap: &mut tiny_skia::PixmapMut,
 ) -> Option<()> {
+    let transform = crate::geom::apply_vector_effect(
+        path.vector_effect,
+        transform,
+        path.transform,
+        ctx.host_transform,
+    )
+    .unwrap_or(transform);
+
     let pattern_pixmap;
     let mut paint = tiny_skia::Paint::default();
     match path.paint {
-        Paint::Shader(ref shader) => {
-            paint.shader = shader.clone(); // TODO: avoid clone
+        Paint::Color(color) => {
+            paint.set_color(color);
+        }
+        Paint::Gradient(ref gradient) => {
+            paint.shader = gradient.shader().clone(); // TODO: avoid clone
         }
         Paint::Pattern(ref pattern) => {
             let (patt_pix, patt_ts) =
@@ -239,11 +340,22 @@ pub fn render_stroke_path(
     transform: tiny_skia::Transform,
     pixmap: &mut tiny_skia::PixmapMut,
 ) -> Option<()> {
+    let transform = crate::geom::apply_vector_effect(
+        path.vector_effect,
+        transform,
+        path.transform,
+        ctx.host_transform,
+    )
+    .unwrap_or(transform);
+
     let pattern_pixmap;
     let mut paint = tiny_skia::Paint::default();
     match path.paint {
-        Paint::Shader(ref shader) => {
-            paint.shader = shader.clone(); // TODO: avoid clone
+        Paint::Color(color) => {
+            paint.set_color(color);
+        }
+        Paint::Gradient(ref gradient) => {
+            paint.shader = gradient.shader().clone(); // TODO: avoid clone
         }
         Paint::Pattern(ref pattern) => {
             let (patt_pix, patt_ts) =
@@ -266,7 +378,39 @@ pub fn render_stroke_path(
     // TODO: fallback to a stroked path when possible
 
     let transform = transform.pre_concat(path.transform);
-    pixmap.stroke_path(&path.path, &paint, &path.stroke, transform, None);
+    if path.vector_effect == usvg::VectorEffect::NonScalingStroke {
+        let outline = stroke_non_scaling(&path.path, &path.stroke, transform)?;
+        pixmap.fill_path(
+            &outline,
+            &paint,
+            tiny_skia::FillRule::Winding,
+            transform,
+            None,
+        );
+    } else {
+        pixmap.stroke_path(&path.path, &paint, &path.stroke, transform, None);
+    }
 
     Some(())
 }
+
+/// Strokes a path in the host coordinate system defined by `transform`.
+///
+/// The resulting outline is mapped back into the path's user space,
+/// so it can be filled using the same transform and paint as a regular path.
+/// This way paint servers are still resolved in the user space.
+/// Dashes are applied in the host coordinate system as well.
+pub fn stroke_non_scaling(
+    path: &tiny_skia::Path,
+    stroke: &tiny_skia::Stroke,
+    transform: tiny_skia::Transform,
+) -> Option<tiny_skia::Path> {
+    let inv_transform = transform.invert()?;
+    let mut path = path.clone().transform(transform)?;
+    if let Some(ref dash) = stroke.dash {
+        path = path.dash(dash, 1.0)?;
+    }
+
+    let outline = path.stroke(stroke, 1.0)?;
+    outline.transform(inv_transform)
+}
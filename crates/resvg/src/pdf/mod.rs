@@ -0,0 +1,1182 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! PDF output.
+//!
+//! Converts a [`usvg::Tree`] into a single page PDF document.
+//!
+//! Paths, gradients and patterns are converted into native PDF objects.
+//! Groups with opacity, clipping paths and masks become transparency groups
+//! with soft masks. Filters are not supported by PDF, therefore groups
+//! with filters are rasterized.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use usvg::NodeExt;
+
+use crate::tree::OptionLog;
+
+mod writer;
+
+use writer::{Matrix, Num, RectArray, Ref, ResourceKind, Resources, Writer};
+
+/// A PDF points to pixels ratio.
+const PT_PER_PX: f32 = 72.0 / 96.0;
+
+/// PDF conversion options.
+#[derive(Clone, Debug)]
+pub struct Options {
+    /// Resolution of rasterized elements, like groups with filters, in dots per inch.
+    ///
+    /// Default: 150
+    pub raster_dpi: f32,
+
+    /// Page size in pixels.
+    ///
+    /// The image will be stretched to fill the page.
+    ///
+    /// Default: None, i.e. `usvg::Tree::size`
+    pub page_size: Option<usvg::Size>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            raster_dpi: 150.0,
+            page_size: None,
+        }
+    }
+}
+
+/// Converts a tree into a PDF document.
+///
+/// Text nodes should be already converted into paths using
+/// [`usvg::TreeTextToPath::convert_text`].
+pub fn convert(tree: &usvg::Tree, options: &Options) -> Vec<u8> {
+    if tree.has_text_nodes() {
+        usvg::report!(
+            Unsupported,
+            "Text nodes should be already converted into paths."
+        );
+    }
+
+    let page_size = options.page_size.unwrap_or(tree.size);
+
+    let mut ctx = Context {
+        writer: Writer::new(),
+        raster_scale: options.raster_dpi / 96.0,
+        bbox: page_size.to_non_zero_rect(0.0, 0.0).to_rect(),
+        ext_g_states: HashMap::new(),
+        next_name: 1,
+    };
+
+    let catalog_id = ctx.writer.alloc();
+    let pages_id = ctx.writer.alloc();
+    let page_id = ctx.writer.alloc();
+    let content_id = ctx.writer.alloc();
+
+    let ts = tiny_skia::Transform::from_scale(
+        page_size.width() / tree.size.width(),
+        page_size.height() / tree.size.height(),
+    );
+    let ts = ts.pre_concat(usvg::utils::view_box_to_transform(
+        tree.view_box.rect,
+        tree.view_box.aspect,
+        tree.size,
+    ));
+
+    // The whole image is stored in a form, so all content streams would use the same
+    // default coordinate system: pixels with the Y axis pointing down.
+    // This is important for patterns, which are positioned relative to it.
+    let mut canvas = Canvas::default();
+    convert_children(&tree.root, ts, &mut ctx, &mut canvas);
+    let form_id = ctx.write_form(canvas, false);
+
+    let mut page = Canvas::default();
+    let height = page_size.height() * PT_PER_PX;
+    let flip_ts = tiny_skia::Transform::from_row(PT_PER_PX, 0.0, 0.0, -PT_PER_PX, 0.0, height);
+    let name = ctx.add_resource(&mut page, ResourceKind::XObject, form_id);
+    writeln!(page.content, "{} cm /{} Do", Matrix(flip_ts), name).unwrap();
+
+    ctx.writer
+        .write_stream(content_id, "", page.content.as_bytes());
+
+    let media_box =
+        tiny_skia::Rect::from_xywh(0.0, 0.0, page_size.width() * PT_PER_PX, height).unwrap();
+    ctx.writer.write_object(
+        page_id,
+        &format!(
+            "<< /Type /Page /Parent {} /MediaBox {} /Resources {} /Contents {} >>",
+            pages_id,
+            RectArray(media_box),
+            page.resources.to_dict(),
+            content_id
+        ),
+    );
+    ctx.writer.write_object(
+        pages_id,
+        &format!("<< /Type /Pages /Kids [{}] /Count 1 >>", page_id),
+    );
+    ctx.writer.write_object(
+        catalog_id,
+        &format!("<< /Type /Catalog /Pages {} >>", pages_id),
+    );
+
+    ctx.writer.finish(catalog_id)
+}
+
+struct Context {
+    writer: Writer,
+    /// Rasterized elements resolution relative to the current coordinate system.
+    raster_scale: f32,
+    /// Current coordinate system bounds.
+    ///
+    /// Used as a bbox for form XObjects.
+    bbox: tiny_skia::Rect,
+    /// Graphics states cache.
+    ext_g_states: HashMap<String, Ref>,
+    next_name: u32,
+}
+
+impl Context {
+    fn add_resource(&mut self, canvas: &mut Canvas, kind: ResourceKind, id: Ref) -> String {
+        let prefix = match kind {
+            ResourceKind::ExtGState => "GS",
+            ResourceKind::Pattern => "P",
+            ResourceKind::Shading => "Sh",
+            ResourceKind::XObject => "X",
+        };
+
+        let name = format!("{}{}", prefix, self.next_name);
+        self.next_name += 1;
+        canvas.resources.push(kind, name.clone(), id);
+        name
+    }
+
+    /// Adds a graphics state to the canvas and returns its name.
+    fn add_ext_g_state(&mut self, canvas: &mut Canvas, entries: &str) -> String {
+        let id = match self.ext_g_states.get(entries) {
+            Some(id) => *id,
+            None => {
+                let id = self.writer.alloc();
+                self.writer
+                    .write_object(id, &format!("<< /Type /ExtGState {} >>", entries));
+                self.ext_g_states.insert(entries.to_string(), id);
+                id
+            }
+        };
+
+        self.add_resource(canvas, ResourceKind::ExtGState, id)
+    }
+
+    /// Writes a canvas as a transparency group form XObject.
+    fn write_form(&mut self, canvas: Canvas, isolated: bool) -> Ref {
+        let id = self.writer.alloc();
+        let entries = format!(
+            "/Type /XObject /Subtype /Form /BBox {} /Resources {} \
+             /Group << /S /Transparency /CS /DeviceRGB /I {} /K false >>",
+            RectArray(self.bbox),
+            canvas.resources.to_dict(),
+            isolated
+        );
+        self.writer
+            .write_stream(id, &entries, canvas.content.as_bytes());
+        id
+    }
+
+    /// Draws a canvas onto another one as a transparency group.
+    ///
+    /// `gs` contains graphics state entries, like opacity and soft mask.
+    fn draw_form(&mut self, canvas: &mut Canvas, form: Canvas, isolated: bool, gs: &str) {
+        let form_id = self.write_form(form, isolated);
+        let form_name = self.add_resource(canvas, ResourceKind::XObject, form_id);
+
+        if gs.is_empty() {
+            writeln!(canvas.content, "/{} Do", form_name).unwrap();
+        } else {
+            let gs_name = self.add_ext_g_state(canvas, gs);
+            writeln!(canvas.content, "q /{} gs /{} Do Q", gs_name, form_name).unwrap();
+        }
+    }
+}
+
+/// A content stream with its resources.
+#[derive(Default)]
+struct Canvas {
+    content: String,
+    resources: Resources,
+}
+
+// All content is drawn using absolute transforms, i.e. the current transformation matrix
+// is always equal to the default one, except inside `q`/`Q` blocks around individual objects.
+// This way we do not have to track the current transformation matrix and form XObjects
+// and soft masks can be drawn anywhere.
+
+fn convert_children(
+    parent: &usvg::Node,
+    transform: tiny_skia::Transform,
+    ctx: &mut Context,
+    canvas: &mut Canvas,
+) {
+    for node in parent.children() {
+        convert_node(&node, transform, ctx, canvas);
+    }
+}
+
+fn convert_node(
+    node: &usvg::Node,
+    transform: tiny_skia::Transform,
+    ctx: &mut Context,
+    canvas: &mut Canvas,
+) {
+    match *node.borrow() {
+        usvg::NodeKind::Group(ref group) => convert_group(node, group, transform, ctx, canvas),
+        usvg::NodeKind::Path(ref path) => convert_path(path, transform, ctx, canvas),
+        usvg::NodeKind::Image(ref image) => {
+            convert_image(image, transform, ctx, canvas);
+        }
+        usvg::NodeKind::Text(_) => {} // should be already converted into paths
+    }
+}
+
+fn convert_group(
+    node: &usvg::Node,
+    group: &usvg::Group,
+    transform: tiny_skia::Transform,
+    ctx: &mut Context,
+    canvas: &mut Canvas,
+) {
+    if !group.filters.is_empty() {
+        rasterize_group(node, group, transform, ctx, canvas);
+        return;
+    }
+
+    let group_ts = transform.pre_concat(group.transform);
+
+    let is_transform_only = group.opacity == usvg::Opacity::ONE
+        && group.blend_mode == usvg::BlendMode::Normal
+        && group.clip_path.is_none()
+        && group.mask.is_none()
+        && !group.isolate;
+
+    if is_transform_only {
+        convert_children(node, group_ts, ctx, canvas);
+        return;
+    }
+
+    let object_bbox = calc_object_bbox(node);
+
+    let mut soft_masks = Vec::new();
+    if let Some(ref clip_path) = group.clip_path {
+        if let Some(smask) = convert_clip_path(clip_path, object_bbox, group_ts, ctx) {
+            soft_masks.push(smask);
+        }
+    }
+
+    if let Some(ref mask) = group.mask {
+        // `objectBoundingBox` units and zero-sized bbox? Nothing will be rendered.
+        // Technically a UB, but this is what Chrome and Firefox do.
+        if mask.units == usvg::Units::ObjectBoundingBox && object_bbox.is_none() {
+            return;
+        }
+
+        if let Some(smask) = convert_mask(mask, object_bbox, group_ts, ctx) {
+            soft_masks.push(smask);
+        }
+    }
+
+    let mut content = Canvas::default();
+    convert_children(node, group_ts, ctx, &mut content);
+
+    // A graphics state can have only one soft mask, so the rest are applied by nesting.
+    let last_mask = soft_masks.pop();
+    for smask in soft_masks {
+        let mut outer = Canvas::default();
+        ctx.draw_form(&mut outer, content, false, &smask);
+        content = outer;
+    }
+
+    let mut gs = String::new();
+    if group.opacity != usvg::Opacity::ONE {
+        let opacity = Num(group.opacity.get());
+        write!(gs, "/ca {} /CA {} ", opacity, opacity).unwrap();
+    }
+    if group.blend_mode != usvg::BlendMode::Normal {
+        write!(gs, "/BM /{} ", blend_mode_name(group.blend_mode)).unwrap();
+    }
+    if let Some(smask) = last_mask {
+        gs.push_str(&smask);
+    }
+
+    ctx.draw_form(canvas, content, group.isolate, gs.trim_end());
+}
+
+/// Renders a group with filters into an image.
+fn rasterize_group(
+    node: &usvg::Node,
+    group: &usvg::Group,
+    transform: tiny_skia::Transform,
+    ctx: &mut Context,
+    canvas: &mut Canvas,
+) -> Option<()> {
+    let (children, bbox) = crate::tree::convert_node_with_transform(node.clone(), transform);
+    let bbox = bbox?.transform(transform)?;
+
+    // Anything outside the current form will be clipped anyway.
+    let bbox = tiny_skia::Rect::from_ltrb(
+        bbox.left().max(ctx.bbox.left()),
+        bbox.top().max(ctx.bbox.top()),
+        bbox.right().min(ctx.bbox.right()),
+        bbox.bottom().min(ctx.bbox.bottom()),
+    )?;
+
+    let scale = ctx.raster_scale;
+    let ibbox = tiny_skia::IntRect::from_ltrb(
+        (bbox.left() * scale).floor() as i32,
+        (bbox.top() * scale).floor() as i32,
+        (bbox.right() * scale).ceil() as i32,
+        (bbox.bottom() * scale).ceil() as i32,
+    )?;
+
+    // The current coordinate system is the host one.
+    let host_transform = tiny_skia::Transform::from_translate(-ibbox.x() as f32, -ibbox.y() as f32)
+        .pre_scale(scale, scale);
+
+    let state = crate::render::RenderState::default();
+    let max_bbox = crate::render::max_bbox_for_size(ibbox.size());
+    let render_ctx = crate::render::Context {
+        host_transform,
+        ..crate::render::Context::new(&state, max_bbox, &crate::RenderOptions::default())
+    };
+
+    let mut pixmap = render_ctx.create_pixmap(ibbox.width(), ibbox.height())?;
+
+    // The blending is done by PDF.
+    let mut children = children;
+    if let Some(crate::tree::Node::Group(ref mut g)) = children.first_mut() {
+        g.blend_mode = tiny_skia::BlendMode::SourceOver;
+    }
+
+    crate::render::render_nodes_to_pixmap(
+        &children,
+        &render_ctx,
+        host_transform.pre_concat(transform),
+        &mut pixmap.as_mut(),
+    );
+
+    let image_id = write_image(&pixmap, true, ctx);
+    let image_name = ctx.add_resource(canvas, ResourceKind::XObject, image_id);
+
+    canvas.content.push_str("q ");
+    if group.blend_mode != usvg::BlendMode::Normal {
+        let gs = format!("/BM /{}", blend_mode_name(group.blend_mode));
+        let gs_name = ctx.add_ext_g_state(canvas, &gs);
+        write!(canvas.content, "/{} gs ", gs_name).unwrap();
+    }
+
+    let rect = tiny_skia::NonZeroRect::from_xywh(
+        ibbox.x() as f32 / scale,
+        ibbox.y() as f32 / scale,
+        ibbox.width() as f32 / scale,
+        ibbox.height() as f32 / scale,
+    )?;
+    writeln!(
+        canvas.content,
+        "{} cm /{} Do Q",
+        Matrix(image_matrix(rect)),
+        image_name
+    )
+    .unwrap();
+
+    Some(())
+}
+
+fn convert_clip_path(
+    clip_path: &usvg::ClipPath,
+    object_bbox: Option<tiny_skia::NonZeroRect>,
+    transform: tiny_skia::Transform,
+    ctx: &mut Context,
+) -> Option<String> {
+    let mut ts = transform.pre_concat(clip_path.transform);
+    if clip_path.units == usvg::Units::ObjectBoundingBox {
+        let object_bbox = object_bbox.log_none(|| {
+            usvg::report!(InvalidSize, "Clipping of zero-sized shapes is not allowed.")
+        })?;
+
+        ts = ts.pre_concat(tiny_skia::Transform::from_bbox(object_bbox));
+    }
+
+    let mut content = Canvas::default();
+    convert_clip_children(&clip_path.root, ts, ctx, &mut content);
+
+    if let Some(ref clip_path) = clip_path.clip_path {
+        if let Some(smask) = convert_clip_path(clip_path, object_bbox, transform, ctx) {
+            let mut outer = Canvas::default();
+            ctx.draw_form(&mut outer, content, false, &smask);
+            content = outer;
+        }
+    }
+
+    let form_id = ctx.write_form(content, false);
+    Some(format!("/SMask << /Type /Mask /S /Alpha /G {} >>", form_id))
+}
+
+/// Draws clipping path children as opaque shapes.
+fn convert_clip_children(
+    parent: &usvg::Node,
+    transform: tiny_skia::Transform,
+    ctx: &mut Context,
+    canvas: &mut Canvas,
+) {
+    for node in parent.children() {
+        match *node.borrow() {
+            usvg::NodeKind::Path(ref path) => {
+                if path.visibility != usvg::Visibility::Visible {
+                    continue;
+                }
+
+                if let Some(ref fill) = path.fill {
+                    let ts = transform.pre_concat(path.transform);
+                    write!(canvas.content, "q {} cm ", Matrix(ts)).unwrap();
+                    write_path(&path.data, &mut canvas.content);
+                    writeln!(canvas.content, "{} Q", fill_operator(fill.rule)).unwrap();
+                }
+            }
+            usvg::NodeKind::Group(ref group) => {
+                let ts = transform.pre_concat(group.transform);
+                match group.clip_path {
+                    Some(ref clip_path) => {
+                        let object_bbox = calc_object_bbox(&node);
+                        let mut content = Canvas::default();
+                        convert_clip_children(&node, ts, ctx, &mut content);
+                        match convert_clip_path(clip_path, object_bbox, ts, ctx) {
+                            Some(smask) => ctx.draw_form(canvas, content, false, &smask),
+                            None => ctx.draw_form(canvas, content, false, ""),
+                        }
+                    }
+                    None => convert_clip_children(&node, ts, ctx, canvas),
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn convert_mask(
+    mask: &usvg::Mask,
+    object_bbox: Option<tiny_skia::NonZeroRect>,
+    transform: tiny_skia::Transform,
+    ctx: &mut Context,
+) -> Option<String> {
+    let region = if mask.units == usvg::Units::ObjectBoundingBox {
+        mask.rect.bbox_transform(object_bbox?)
+    } else {
+        mask.rect
+    };
+
+    let mut content_ts = transform;
+    if mask.content_units == usvg::Units::ObjectBoundingBox {
+        let object_bbox = object_bbox.log_none(|| {
+            usvg::report!(InvalidSize, "Masking of zero-sized shapes is not allowed.")
+        })?;
+
+        content_ts = content_ts.pre_concat(tiny_skia::Transform::from_bbox(object_bbox));
+    }
+
+    let mut content = Canvas::default();
+    content.content.push_str("q ");
+    let region_path = tiny_skia::PathBuilder::from_rect(region.to_rect()).transform(transform)?;
+    write_path(&region_path, &mut content.content);
+    content.content.push_str("W n\n");
+    convert_children(&mask.root, content_ts, ctx, &mut content);
+    content.content.push_str("Q\n");
+
+    if let Some(ref mask) = mask.mask {
+        if let Some(smask) = convert_mask(mask, object_bbox, transform, ctx) {
+            let mut outer = Canvas::default();
+            ctx.draw_form(&mut outer, content, false, &smask);
+            content = outer;
+        }
+    }
+
+    let kind = match mask.kind {
+        usvg::MaskType::Luminance => "Luminosity",
+        usvg::MaskType::Alpha => "Alpha",
+    };
+
+    let form_id = ctx.write_form(content, false);
+    Some(format!(
+        "/SMask << /Type /Mask /S /{} /G {} >>",
+        kind, form_id
+    ))
+}
+
+fn convert_path(
+    path: &usvg::Path,
+    transform: tiny_skia::Transform,
+    ctx: &mut Context,
+    canvas: &mut Canvas,
+) {
+    if path.visibility != usvg::Visibility::Visible {
+        return;
+    }
+
+    let transform = crate::geom::apply_vector_effect(
+        path.vector_effect,
+        transform,
+        path.transform,
+        tiny_skia::Transform::default(),
+    )
+    .unwrap_or(transform)
+    .pre_concat(path.transform);
+
+    let object_bbox = path
+        .text_bbox
+        .or_else(|| path.data.bounds().to_non_zero_rect());
+
+    match path.paint_order {
+        usvg::PaintOrder::FillAndStroke => {
+            fill_path(path, object_bbox, transform, ctx, canvas);
+            stroke_path(path, object_bbox, transform, ctx, canvas);
+        }
+        usvg::PaintOrder::StrokeAndFill => {
+            stroke_path(path, object_bbox, transform, ctx, canvas);
+            fill_path(path, object_bbox, transform, ctx, canvas);
+        }
+    }
+}
+
+fn fill_path(
+    path: &usvg::Path,
+    object_bbox: Option<tiny_skia::NonZeroRect>,
+    transform: tiny_skia::Transform,
+    ctx: &mut Context,
+    canvas: &mut Canvas,
+) -> Option<()> {
+    let fill = path.fill.as_ref()?;
+
+    let paint = convert_paint(
+        &fill.paint,
+        fill.opacity,
+        object_bbox,
+        transform,
+        false,
+        ctx,
+        canvas,
+    )?;
+
+    write!(canvas.content, "q {}{} cm ", paint, Matrix(transform)).unwrap();
+    write_path(&path.data, &mut canvas.content);
+    writeln!(canvas.content, "{} Q", fill_operator(fill.rule)).unwrap();
+
+    Some(())
+}
+
+fn stroke_path(
+    path: &usvg::Path,
+    object_bbox: Option<tiny_skia::NonZeroRect>,
+    transform: tiny_skia::Transform,
+    ctx: &mut Context,
+    canvas: &mut Canvas,
+) -> Option<()> {
+    let stroke = path.stroke.as_ref()?;
+
+    let paint = convert_paint(
+        &stroke.paint,
+        stroke.opacity,
+        object_bbox,
+        transform,
+        true,
+        ctx,
+        canvas,
+    )?;
+
+    let mut s = String::new();
+    write!(s, "q {}", paint).unwrap();
+
+    // With `non-scaling-stroke`, stroke properties are in the host coordinate system,
+    // so the path itself has to be transformed instead.
+    let data = if path.vector_effect == usvg::VectorEffect::NonScalingStroke {
+        tiny_skia::Path::clone(&path.data).transform(transform)?
+    } else {
+        write!(s, "{} cm ", Matrix(transform)).unwrap();
+        tiny_skia::Path::clone(&path.data)
+    };
+
+    let linecap = match stroke.linecap {
+        usvg::LineCap::Butt => 0,
+        usvg::LineCap::Round => 1,
+        usvg::LineCap::Square => 2,
+    };
+    let linejoin = match stroke.linejoin {
+        usvg::LineJoin::Miter => 0,
+        usvg::LineJoin::Round => 1,
+        usvg::LineJoin::Bevel => 2,
+    };
+    write!(
+        s,
+        "{} w {} J {} j {} M ",
+        Num(stroke.width.get()),
+        linecap,
+        linejoin,
+        Num(stroke.miterlimit.get())
+    )
+    .unwrap();
+
+    if let Some(ref list) = stroke.dasharray {
+        s.push('[');
+        for (i, n) in list.iter().enumerate() {
+            if i != 0 {
+                s.push(' ');
+            }
+            write!(s, "{}", Num(*n)).unwrap();
+        }
+        write!(s, "] {} d ", Num(stroke.dashoffset)).unwrap();
+    }
+
+    write_path(&data, &mut s);
+    s.push_str("S Q\n");
+    canvas.content.push_str(&s);
+
+    Some(())
+}
+
+/// Returns operators that set the current fill or stroke paint.
+fn convert_paint(
+    paint: &usvg::Paint,
+    opacity: usvg::Opacity,
+    object_bbox: Option<tiny_skia::NonZeroRect>,
+    transform: tiny_skia::Transform,
+    is_stroke: bool,
+    ctx: &mut Context,
+    canvas: &mut Canvas,
+) -> Option<String> {
+    let mut gs = String::new();
+    if opacity != usvg::Opacity::ONE {
+        let key = if is_stroke { "CA" } else { "ca" };
+        write!(gs, "/{} {} ", key, Num(opacity.get())).unwrap();
+    }
+
+    let mut s = String::new();
+    match paint {
+        usvg::Paint::Color(c) => {
+            let op = if is_stroke { "RG" } else { "rg" };
+            write!(
+                s,
+                "{} {} {} {} ",
+                Num(c.red as f32 / 255.0),
+                Num(c.green as f32 / 255.0),
+                Num(c.blue as f32 / 255.0),
+                op
+            )
+            .unwrap();
+        }
+        usvg::Paint::LinearGradient(ref lg) => {
+            let shading = Shading::Axial(lg.x1, lg.y1, lg.x2, lg.y2);
+            let (pattern_id, smask) =
+                convert_gradient(&lg.base, shading, object_bbox, transform, ctx)?;
+            if let Some(smask) = smask {
+                gs.push_str(&smask);
+            }
+            s.push_str(&set_pattern(pattern_id, is_stroke, ctx, canvas));
+        }
+        usvg::Paint::RadialGradient(ref rg) => {
+            let shading = Shading::Radial(rg.fx, rg.fy, rg.cx, rg.cy, rg.r.get());
+            let (pattern_id, smask) =
+                convert_gradient(&rg.base, shading, object_bbox, transform, ctx)?;
+            if let Some(smask) = smask {
+                gs.push_str(&smask);
+            }
+            s.push_str(&set_pattern(pattern_id, is_stroke, ctx, canvas));
+        }
+        usvg::Paint::Pattern(ref pattern) => {
+            let pattern_id = convert_pattern(pattern, object_bbox, transform, ctx)?;
+            s.push_str(&set_pattern(pattern_id, is_stroke, ctx, canvas));
+        }
+    }
+
+    if !gs.is_empty() {
+        let name = ctx.add_ext_g_state(canvas, gs.trim_end());
+        s.insert_str(0, &format!("/{} gs ", name));
+    }
+
+    Some(s)
+}
+
+fn set_pattern(pattern_id: Ref, is_stroke: bool, ctx: &mut Context, canvas: &mut Canvas) -> String {
+    let name = ctx.add_resource(canvas, ResourceKind::Pattern, pattern_id);
+    if is_stroke {
+        format!("/Pattern CS /{} SCN ", name)
+    } else {
+        format!("/Pattern cs /{} scn ", name)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Shading {
+    Axial(f32, f32, f32, f32),
+    Radial(f32, f32, f32, f32, f32),
+}
+
+/// Converts a gradient into a shading pattern.
+///
+/// PDF shadings cannot be transparent, therefore stops opacity is returned
+/// as a soft mask graphics state entry.
+fn convert_gradient(
+    gradient: &usvg::BaseGradient,
+    shading: Shading,
+    object_bbox: Option<tiny_skia::NonZeroRect>,
+    transform: tiny_skia::Transform,
+    ctx: &mut Context,
+) -> Option<(Ref, Option<String>)> {
+    let mut ts = transform;
+    if gradient.units == usvg::Units::ObjectBoundingBox {
+        let bbox = object_bbox.log_none(|| {
+            usvg::report!(InvalidSize, "Gradient on zero-sized shapes is not allowed.")
+        })?;
+        ts = ts.pre_concat(tiny_skia::Transform::from_bbox(bbox));
+    }
+    ts = ts.pre_concat(gradient.transform);
+
+    if gradient.spread_method != usvg::SpreadMethod::Pad {
+        usvg::report!(
+            Unsupported,
+            "Only the 'pad' gradient spread method is supported in PDF."
+        );
+    }
+
+    let colors: Vec<_> = gradient
+        .stops
+        .iter()
+        .map(|stop| {
+            let c = stop.color;
+            vec![
+                c.red as f32 / 255.0,
+                c.green as f32 / 255.0,
+                c.blue as f32 / 255.0,
+            ]
+        })
+        .collect();
+
+    let shading_id = write_shading(gradient, shading, "DeviceRGB", &colors, ctx)?;
+    let pattern_id = ctx.writer.alloc();
+    ctx.writer.write_object(
+        pattern_id,
+        &format!(
+            "<< /Type /Pattern /PatternType 2 /Shading {} /Matrix [{}] >>",
+            shading_id,
+            Matrix(ts)
+        ),
+    );
+
+    let is_opaque = gradient
+        .stops
+        .iter()
+        .all(|stop| stop.opacity == usvg::Opacity::ONE);
+    if is_opaque {
+        return Some((pattern_id, None));
+    }
+
+    let alphas: Vec<_> = gradient
+        .stops
+        .iter()
+        .map(|stop| vec![stop.opacity.get()])
+        .collect();
+    let alpha_shading_id = write_shading(gradient, shading, "DeviceGray", &alphas, ctx)?;
+
+    let mut content = Canvas::default();
+    let name = ctx.add_resource(&mut content, ResourceKind::Shading, alpha_shading_id);
+    writeln!(content.content, "q {} cm /{} sh Q", Matrix(ts), name).unwrap();
+
+    let form_id = ctx.write_form(content, false);
+    let smask = format!("/SMask << /Type /Mask /S /Luminosity /G {} >>", form_id);
+
+    Some((pattern_id, Some(smask)))
+}
+
+fn write_shading(
+    gradient: &usvg::BaseGradient,
+    shading: Shading,
+    color_space: &str,
+    colors: &[Vec<f32>],
+    ctx: &mut Context,
+) -> Option<Ref> {
+    let mut offsets: Vec<f32> = gradient.stops.iter().map(|s| s.offset.get()).collect();
+    let mut colors = colors.to_vec();
+    if offsets.is_empty() {
+        return None;
+    }
+
+    // Stops must cover the whole 0..1 range.
+    if offsets[0] > 0.0 {
+        offsets.insert(0, 0.0);
+        colors.insert(0, colors[0].clone());
+    }
+    if offsets[offsets.len() - 1] < 1.0 {
+        offsets.push(1.0);
+        colors.push(colors[colors.len() - 1].clone());
+    }
+
+    let array = |values: &[f32]| {
+        let values: Vec<_> = values.iter().map(|v| Num(*v).to_string()).collect();
+        format!("[{}]", values.join(" "))
+    };
+
+    let mut functions = String::new();
+    let mut bounds = Vec::new();
+    let mut encode = Vec::new();
+    for i in 0..offsets.len() - 1 {
+        write!(
+            functions,
+            "<< /FunctionType 2 /Domain [0 1] /C0 {} /C1 {} /N 1 >> ",
+            array(&colors[i]),
+            array(&colors[i + 1])
+        )
+        .unwrap();
+
+        if i != 0 {
+            bounds.push(offsets[i]);
+        }
+        encode.extend_from_slice(&[0.0, 1.0]);
+    }
+
+    let function = format!(
+        "<< /FunctionType 3 /Domain [0 1] /Functions [{}] /Bounds {} /Encode {} >>",
+        functions.trim_end(),
+        array(&bounds),
+        array(&encode)
+    );
+
+    let (shading_type, coords) = match shading {
+        Shading::Axial(x1, y1, x2, y2) => (2, array(&[x1, y1, x2, y2])),
+        Shading::Radial(fx, fy, cx, cy, r) => (3, array(&[fx, fy, 0.0, cx, cy, r])),
+    };
+
+    let id = ctx.writer.alloc();
+    ctx.writer.write_object(
+        id,
+        &format!(
+            "<< /ShadingType {} /ColorSpace /{} /Coords {} /Function {} /Extend [true true] >>",
+            shading_type, color_space, coords, function
+        ),
+    );
+
+    Some(id)
+}
+
+fn convert_pattern(
+    pattern: &usvg::Pattern,
+    object_bbox: Option<tiny_skia::NonZeroRect>,
+    transform: tiny_skia::Transform,
+    ctx: &mut Context,
+) -> Option<Ref> {
+    let mut content_ts = tiny_skia::Transform::default();
+    if pattern.content_units == usvg::Units::ObjectBoundingBox && pattern.view_box.is_none() {
+        let bbox = object_bbox.log_none(|| {
+            usvg::report!(InvalidSize, "Pattern on zero-sized shapes is not allowed.")
+        })?;
+
+        // No need to shift patterns.
+        content_ts = tiny_skia::Transform::from_scale(bbox.width(), bbox.height());
+    }
+
+    let rect = if pattern.units == usvg::Units::ObjectBoundingBox {
+        let bbox = object_bbox.log_none(|| {
+            usvg::report!(InvalidSize, "Pattern on zero-sized shapes is not allowed.")
+        })?;
+
+        pattern.rect.bbox_transform(bbox)
+    } else {
+        pattern.rect
+    };
+
+    if let Some(vbox) = pattern.view_box {
+        let ts = usvg::utils::view_box_to_transform(vbox.rect, vbox.aspect, rect.size());
+        content_ts = ts.pre_concat(content_ts);
+    }
+
+    let matrix = transform
+        .pre_concat(pattern.transform)
+        .pre_translate(rect.x(), rect.y());
+
+    // Pattern content is drawn in its own coordinate system.
+    let tile = rect.size().to_non_zero_rect(0.0, 0.0).to_rect();
+    let (sx, sy) = matrix.get_scale();
+    let prev_bbox = std::mem::replace(&mut ctx.bbox, tile);
+    let prev_scale = ctx.raster_scale;
+    ctx.raster_scale *= sx.max(sy);
+
+    let mut content = Canvas::default();
+    convert_children(&pattern.root, content_ts, ctx, &mut content);
+
+    ctx.bbox = prev_bbox;
+    ctx.raster_scale = prev_scale;
+
+    let id = ctx.writer.alloc();
+    let entries = format!(
+        "/Type /Pattern /PatternType 1 /PaintType 1 /TilingType 1 /BBox {} \
+         /XStep {} /YStep {} /Matrix [{}] /Resources {}",
+        RectArray(tile),
+        Num(rect.width()),
+        Num(rect.height()),
+        Matrix(matrix),
+        content.resources.to_dict()
+    );
+    ctx.writer
+        .write_stream(id, &entries, content.content.as_bytes());
+
+    Some(id)
+}
+
+fn convert_image(
+    image: &usvg::Image,
+    transform: tiny_skia::Transform,
+    ctx: &mut Context,
+    canvas: &mut Canvas,
+) -> Option<()> {
+    if image.visibility != usvg::Visibility::Visible {
+        return None;
+    }
+
+    let transform = crate::geom::apply_vector_effect(
+        image.vector_effect,
+        transform,
+        image.transform,
+        tiny_skia::Transform::default(),
+    )
+    .unwrap_or(transform);
+
+    // Unlike the image itself, the clip rect is in the parent coordinates.
+    let image_ts = transform.pre_concat(image.transform);
+
+    match image.kind {
+        usvg::ImageKind::SVG(ref tree) => {
+            let (ts, clip) = crate::geom::view_box_to_transform_with_clip(
+                &image.view_box,
+                tree.size.to_int_size(),
+            );
+
+            canvas.content.push_str("q ");
+            if let Some(clip) = clip {
+                let clip =
+                    tiny_skia::PathBuilder::from_rect(clip.to_rect()).transform(transform)?;
+                write_path(&clip, &mut canvas.content);
+                canvas.content.push_str("W n");
+            }
+            canvas.content.push('\n');
+
+            let tree_ts = usvg::utils::view_box_to_transform(
+                tree.view_box.rect,
+                tree.view_box.aspect,
+                tree.size,
+            );
+            convert_children(
+                &tree.root,
+                image_ts.pre_concat(ts).pre_concat(tree_ts),
+                ctx,
+                canvas,
+            );
+
+            canvas.content.push_str("Q\n");
+        }
+        #[cfg(feature = "raster-images")]
+        _ => {
+            let pixmap = crate::image::raster_images::decode_raster(image)?;
+            let size = tiny_skia::IntSize::from_wh(pixmap.width(), pixmap.height())?;
+            let rect = crate::image::raster_images::image_rect(&image.view_box, size);
+
+            let interpolate = image.rendering_mode != usvg::ImageRendering::OptimizeSpeed;
+            let image_id = write_image(&pixmap, interpolate, ctx);
+            let name = ctx.add_resource(canvas, ResourceKind::XObject, image_id);
+
+            canvas.content.push_str("q ");
+            if image.view_box.aspect.slice {
+                let clip = tiny_skia::PathBuilder::from_rect(image.view_box.rect.to_rect())
+                    .transform(transform)?;
+                write_path(&clip, &mut canvas.content);
+                canvas.content.push_str("W n ");
+            }
+
+            writeln!(
+                canvas.content,
+                "{} cm /{} Do Q",
+                Matrix(image_ts.pre_concat(image_matrix(rect))),
+                name
+            )
+            .unwrap();
+        }
+        #[cfg(not(feature = "raster-images"))]
+        _ => {
+            usvg::report!(
+                Unsupported,
+                "Images decoding was disabled by a build feature."
+            );
+        }
+    }
+
+    Some(())
+}
+
+/// Writes a pixmap as an image XObject. Alpha is stored as a soft mask.
+fn write_image(pixmap: &tiny_skia::Pixmap, interpolate: bool, ctx: &mut Context) -> Ref {
+    let mut rgb = Vec::with_capacity(pixmap.data().len() / 4 * 3);
+    let mut alpha = Vec::with_capacity(pixmap.data().len() / 4);
+    for p in pixmap.pixels() {
+        let c = p.demultiply();
+        rgb.extend_from_slice(&[c.red(), c.green(), c.blue()]);
+        alpha.push(c.alpha());
+    }
+
+    let entries = |color_space: &str| {
+        format!(
+            "/Type /XObject /Subtype /Image /Width {} /Height {} \
+             /ColorSpace /{} /BitsPerComponent 8 /Interpolate {}",
+            pixmap.width(),
+            pixmap.height(),
+            color_space,
+            interpolate
+        )
+    };
+
+    let mut rgb_entries = entries("DeviceRGB");
+    if alpha.iter().any(|a| *a != 255) {
+        let smask_id = ctx.writer.alloc();
+        ctx.writer
+            .write_stream(smask_id, &entries("DeviceGray"), &alpha);
+        write!(rgb_entries, " /SMask {}", smask_id).unwrap();
+    }
+
+    let id = ctx.writer.alloc();
+    ctx.writer.write_stream(id, &rgb_entries, &rgb);
+    id
+}
+
+/// Maps an image unit square onto a rect.
+///
+/// The first image row is drawn at the top of the rect.
+fn image_matrix(rect: tiny_skia::NonZeroRect) -> tiny_skia::Transform {
+    tiny_skia::Transform::from_row(
+        rect.width(),
+        0.0,
+        0.0,
+        -rect.height(),
+        rect.x(),
+        rect.y() + rect.height(),
+    )
+}
+
+fn write_path(path: &tiny_skia::Path, s: &mut String) {
+    let mut start = tiny_skia::Point::zero();
+    let mut last = tiny_skia::Point::zero();
+    for segment in path.segments() {
+        match segment {
+            tiny_skia::PathSegment::MoveTo(p) => {
+                write!(s, "{} {} m ", Num(p.x), Num(p.y)).unwrap();
+                start = p;
+                last = p;
+            }
+            tiny_skia::PathSegment::LineTo(p) => {
+                write!(s, "{} {} l ", Num(p.x), Num(p.y)).unwrap();
+                last = p;
+            }
+            tiny_skia::PathSegment::QuadTo(p1, p) => {
+                // PDF doesn't support quadratic curves.
+                let c1x = last.x + 2.0 / 3.0 * (p1.x - last.x);
+                let c1y = last.y + 2.0 / 3.0 * (p1.y - last.y);
+                let c2x = p.x + 2.0 / 3.0 * (p1.x - p.x);
+                let c2y = p.y + 2.0 / 3.0 * (p1.y - p.y);
+                write!(
+                    s,
+                    "{} {} {} {} {} {} c ",
+                    Num(c1x),
+                    Num(c1y),
+                    Num(c2x),
+                    Num(c2y),
+                    Num(p.x),
+                    Num(p.y)
+                )
+                .unwrap();
+                last = p;
+            }
+            tiny_skia::PathSegment::CubicTo(p1, p2, p) => {
+                write!(
+                    s,
+                    "{} {} {} {} {} {} c ",
+                    Num(p1.x),
+                    Num(p1.y),
+                    Num(p2.x),
+                    Num(p2.y),
+                    Num(p.x),
+                    Num(p.y)
+                )
+                .unwrap();
+                last = p;
+            }
+            tiny_skia::PathSegment::Close => {
+                s.push_str("h ");
+                last = start;
+            }
+        }
+    }
+}
+
+fn fill_operator(rule: usvg::FillRule) -> &'static str {
+    match rule {
+        usvg::FillRule::NonZero => "f",
+        usvg::FillRule::EvenOdd => "f*",
+    }
+}
+
+fn blend_mode_name(mode: usvg::BlendMode) -> &'static str {
+    match mode {
+        usvg::BlendMode::Normal => "Normal",
+        usvg::BlendMode::Multiply => "Multiply",
+        usvg::BlendMode::Screen => "Screen",
+        usvg::BlendMode::Overlay => "Overlay",
+        usvg::BlendMode::Darken => "Darken",
+        usvg::BlendMode::Lighten => "Lighten",
+        usvg::BlendMode::ColorDodge => "ColorDodge",
+        usvg::BlendMode::ColorBurn => "ColorBurn",
+        usvg::BlendMode::HardLight => "HardLight",
+        usvg::BlendMode::SoftLight => "SoftLight",
+        usvg::BlendMode::Difference => "Difference",
+        usvg::BlendMode::Exclusion => "Exclusion",
+        usvg::BlendMode::Hue => "Hue",
+        usvg::BlendMode::Saturation => "Saturation",
+        usvg::BlendMode::Color => "Color",
+        usvg::BlendMode::Luminosity => "Luminosity",
+    }
+}
+
+/// Calculates a group's object bounding box in its own coordinate system.
+fn calc_object_bbox(node: &usvg::Node) -> Option<tiny_skia::NonZeroRect> {
+    fn calc(node: &usvg::Node, ts: tiny_skia::Transform) -> Option<usvg::BBox> {
+        match *node.borrow() {
+            usvg::NodeKind::Path(ref path) => {
+                path.data.bounds().transform(ts).map(usvg::BBox::from)
+            }
+            usvg::NodeKind::Image(ref image) => {
+                image.view_box.rect.transform(ts).map(usvg::BBox::from)
+            }
+            usvg::NodeKind::Group(_) => {
+                let mut bbox = usvg::BBox::default();
+                for child in node.children() {
+                    if let Some(child_bbox) = calc(&child, ts.pre_concat(child.transform())) {
+                        bbox = bbox.expand(child_bbox);
+                    }
+                }
+
+                if bbox.is_default() {
+                    None
+                } else {
+                    Some(bbox)
+                }
+            }
+            usvg::NodeKind::Text(_) => None,
+        }
+    }
+
+    calc(node, tiny_skia::Transform::default())?.to_non_zero_rect()
+}
@@ -0,0 +1,239 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! A minimal PDF objects writer.
+
+use std::fmt::{self, Write};
+
+/// An indirect object reference.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Ref(u32);
+
+impl fmt::Display for Ref {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} 0 R", self.0)
+    }
+}
+
+/// A PDF number.
+///
+/// PDF doesn't support exponents, therefore numbers are written with a fixed precision.
+#[derive(Clone, Copy, Debug)]
+pub struct Num(pub f32);
+
+impl fmt::Display for Num {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.0.is_finite() {
+            return f.write_str("0");
+        }
+
+        let v = (self.0 as f64 * 10_000.0).round() / 10_000.0;
+        if v.fract() == 0.0 {
+            write!(f, "{}", v as i64)
+        } else {
+            let s = format!("{:.4}", v);
+            f.write_str(s.trim_end_matches('0'))
+        }
+    }
+}
+
+/// A transform as a PDF matrix, i.e. `a b c d e f`.
+#[derive(Clone, Copy, Debug)]
+pub struct Matrix(pub tiny_skia::Transform);
+
+impl fmt::Display for Matrix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let ts = self.0;
+        write!(
+            f,
+            "{} {} {} {} {} {}",
+            Num(ts.sx),
+            Num(ts.ky),
+            Num(ts.kx),
+            Num(ts.sy),
+            Num(ts.tx),
+            Num(ts.ty)
+        )
+    }
+}
+
+/// A rectangle as a PDF array, i.e. `[left bottom right top]`.
+#[derive(Clone, Copy, Debug)]
+pub struct RectArray(pub tiny_skia::Rect);
+
+impl fmt::Display for RectArray {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let r = self.0;
+        write!(
+            f,
+            "[{} {} {} {}]",
+            Num(r.left()),
+            Num(r.top()),
+            Num(r.right()),
+            Num(r.bottom())
+        )
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ResourceKind {
+    ExtGState,
+    Pattern,
+    Shading,
+    XObject,
+}
+
+impl ResourceKind {
+    fn name(self) -> &'static str {
+        match self {
+            ResourceKind::ExtGState => "ExtGState",
+            ResourceKind::Pattern => "Pattern",
+            ResourceKind::Shading => "Shading",
+            ResourceKind::XObject => "XObject",
+        }
+    }
+}
+
+/// A content stream resources dictionary.
+#[derive(Default, Debug)]
+pub struct Resources {
+    entries: Vec<(ResourceKind, String, Ref)>,
+}
+
+impl Resources {
+    pub fn push(&mut self, kind: ResourceKind, name: String, id: Ref) {
+        self.entries.push((kind, name, id));
+    }
+
+    pub fn to_dict(&self) -> String {
+        let mut s = String::from("<<");
+        for kind in [
+            ResourceKind::ExtGState,
+            ResourceKind::Pattern,
+            ResourceKind::Shading,
+            ResourceKind::XObject,
+        ] {
+            let mut entries = self.entries.iter().filter(|e| e.0 == kind).peekable();
+            if entries.peek().is_none() {
+                continue;
+            }
+
+            write!(s, " /{} <<", kind.name()).unwrap();
+            for (_, name, id) in entries {
+                write!(s, " /{} {}", name, id).unwrap();
+            }
+            s.push_str(" >>");
+        }
+        s.push_str(" >>");
+        s
+    }
+}
+
+/// Writes indirect objects and a cross-reference table.
+pub struct Writer {
+    buf: Vec<u8>,
+    offsets: Vec<Option<usize>>,
+}
+
+impl Writer {
+    pub fn new() -> Self {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(b"%PDF-1.7\n");
+        // Binary comment, so the file would not be treated as a text one.
+        buf.extend_from_slice(b"%\xE2\xE3\xCF\xD3\n");
+
+        Writer {
+            buf,
+            offsets: Vec::new(),
+        }
+    }
+
+    /// Reserves an object number.
+    pub fn alloc(&mut self) -> Ref {
+        self.offsets.push(None);
+        Ref(self.offsets.len() as u32)
+    }
+
+    /// Writes a dictionary or any other object.
+    pub fn write_object(&mut self, id: Ref, object: &str) {
+        self.start_object(id);
+        self.buf.extend_from_slice(object.as_bytes());
+        self.buf.extend_from_slice(b"\nendobj\n");
+    }
+
+    /// Writes a stream object.
+    ///
+    /// `entries` must not contain `/Length` and `/Filter`.
+    pub fn write_stream(&mut self, id: Ref, entries: &str, data: &[u8]) {
+        let data = compress(data);
+
+        self.start_object(id);
+        self.buf.extend_from_slice(b"<< ");
+        if !entries.is_empty() {
+            self.buf.extend_from_slice(entries.as_bytes());
+            self.buf.push(b' ');
+        }
+        write!(
+            self.buf_writer(),
+            "/Filter /FlateDecode /Length {} >>\nstream\n",
+            data.len()
+        )
+        .unwrap();
+        self.buf.extend_from_slice(&data);
+        self.buf.extend_from_slice(b"\nendstream\nendobj\n");
+    }
+
+    /// Writes a cross-reference table and a trailer.
+    pub fn finish(mut self, catalog: Ref) -> Vec<u8> {
+        let xref_offset = self.buf.len();
+        let count = self.offsets.len() + 1;
+
+        let mut s = String::new();
+        writeln!(s, "xref\n0 {}", count).unwrap();
+        s.push_str("0000000000 65535 f \n");
+        for offset in &self.offsets {
+            // Unused objects are marked as free.
+            match offset {
+                Some(n) => writeln!(s, "{:010} 00000 n ", n).unwrap(),
+                None => s.push_str("0000000000 65535 f \n"),
+            }
+        }
+        write!(
+            s,
+            "trailer\n<< /Size {} /Root {} >>\nstartxref\n{}\n%%EOF\n",
+            count, catalog, xref_offset
+        )
+        .unwrap();
+
+        self.buf.extend_from_slice(s.as_bytes());
+        self.buf
+    }
+
+    fn start_object(&mut self, id: Ref) {
+        self.offsets[id.0 as usize - 1] = Some(self.buf.len());
+        writeln!(self.buf_writer(), "{} 0 obj", id.0).unwrap();
+    }
+
+    fn buf_writer(&mut self) -> BufWriter<'_> {
+        BufWriter(&mut self.buf)
+    }
+}
+
+struct BufWriter<'a>(&'a mut Vec<u8>);
+
+impl Write for BufWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0.extend_from_slice(s.as_bytes());
+        Ok(())
+    }
+}
+
+fn compress(data: &[u8]) -> Vec<u8> {
+    use std::io::Write;
+
+    let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+    // Writing into a `Vec` cannot fail.
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
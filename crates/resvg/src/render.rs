@@ -2,10 +2,339 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use crate::tree::{Group, Node, OptionLog, Tree};
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
 
-pub struct Context {
+use crate::backend::Backend;
+use crate::tree::{host_children_bbox, node_bbox, union_rects, Group, Node, OptionLog, Tree};
+
+/// A rendering error.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Error {
+    /// A layer or a filter region is bigger than `usvg::Limits::max_surface_pixels`.
+    ///
+    /// Such elements are skipped, while the rest of the tree is still rendered.
+    SurfaceLimitReached,
+
+    /// Rendering was cancelled via [`CancellationToken`].
+    ///
+    /// The pixmap content is undefined in this case.
+    Cancelled,
+
+    /// Rendering took longer than [`RenderOptions::deadline`].
+    ///
+    /// The pixmap content is undefined in this case.
+    DeadlineExceeded,
+}
+
+impl Error {
+    fn is_interruption(self) -> bool {
+        matches!(self, Error::Cancelled | Error::DeadlineExceeded)
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            Error::SurfaceLimitReached => {
+                write!(f, "the maximum layer size has been reached")
+            }
+            Error::Cancelled => {
+                write!(f, "rendering was cancelled")
+            }
+            Error::DeadlineExceeded => {
+                write!(f, "rendering deadline has been exceeded")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// A token that can be used to cancel rendering from another thread.
+///
+/// Clones share the same state.
+#[derive(Clone, Default, Debug)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Creates a new, not cancelled token.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests rendering cancellation.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Checks that cancellation was requested.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Rendering overrides for nodes with specific IDs.
+///
+/// IDs are matched against [`Node::id`]. Nodes inside masks, patterns
+/// and nested SVG images are not affected.
+#[derive(Clone, Default, Debug)]
+pub struct NodeOverrides {
+    /// Nodes that should not be rendered, including their children.
+    pub hide: HashSet<String>,
+    /// When not empty, only nodes with these IDs, their children and ancestors are rendered.
+    ///
+    /// Ancestors are still rendered with their transform, opacity, clipping and filters.
+    pub only: HashSet<String>,
+    /// An additional opacity for nodes.
+    ///
+    /// Applied as if the node was wrapped into a group with the specified opacity.
+    /// Consecutive nodes with the same ID, like fill and stroke of the same path,
+    /// are wrapped together.
+    pub opacity: HashMap<String, usvg::Opacity>,
+}
+
+impl NodeOverrides {
+    fn is_hidden(&self, id: &str) -> bool {
+        !id.is_empty() && self.hide.contains(id)
+    }
+
+    fn is_only(&self, id: &str) -> bool {
+        !id.is_empty() && self.only.contains(id)
+    }
+
+    fn contains_only(&self, nodes: &[Node]) -> bool {
+        nodes.iter().any(|node| match node {
+            Node::Group(ref group) => {
+                self.is_only(&group.id) || self.contains_only(&group.children)
+            }
+            _ => self.is_only(node.id()),
+        })
+    }
+}
+
+/// Rendering options.
+pub struct RenderOptions<'a> {
+    /// See [`usvg::Limits::max_surface_pixels`].
+    ///
+    /// Default: unlimited
+    pub max_surface_pixels: u64,
+
+    /// A token to cancel rendering with.
+    ///
+    /// Default: None
+    pub cancellation: Option<CancellationToken>,
+
+    /// A point in time after which rendering will be aborted.
+    ///
+    /// Default: None
+    pub deadline: Option<Instant>,
+
+    /// A progress callback.
+    ///
+    /// Called with a value in the 0..=1 range after each path, image
+    /// or group that requires a layer was rendered.
+    ///
+    /// Default: None
+    pub progress: Option<&'a dyn Fn(f32)>,
+
+    /// Per-ID visibility and opacity overrides.
+    ///
+    /// Default: None
+    pub overrides: Option<&'a NodeOverrides>,
+}
+
+impl Default for RenderOptions<'_> {
+    fn default() -> Self {
+        RenderOptions {
+            max_surface_pixels: u64::MAX,
+            cancellation: None,
+            deadline: None,
+            progress: None,
+            overrides: None,
+        }
+    }
+}
+
+impl std::fmt::Debug for RenderOptions<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("RenderOptions")
+            .field("max_surface_pixels", &self.max_surface_pixels)
+            .field("cancellation", &self.cancellation)
+            .field("deadline", &self.deadline)
+            .field("progress", &self.progress.is_some())
+            .field("overrides", &self.overrides)
+            .finish()
+    }
+}
+
+/// Conditions under which rendering must be aborted.
+#[derive(Default)]
+pub struct Interrupt {
+    pub cancellation: Option<CancellationToken>,
+    pub deadline: Option<Instant>,
+}
+
+impl Interrupt {
+    fn check(&self) -> Option<Error> {
+        if let Some(ref token) = self.cancellation {
+            if token.is_cancelled() {
+                return Some(Error::Cancelled);
+            }
+        }
+
+        if let Some(deadline) = self.deadline {
+            if Instant::now() >= deadline {
+                return Some(Error::DeadlineExceeded);
+            }
+        }
+
+        None
+    }
+}
+
+/// A state shared by all contexts of a single rendering.
+#[derive(Default)]
+pub struct RenderState {
+    /// The first error that occurred during rendering.
+    pub error: Cell<Option<Error>>,
+    pub interrupt: Interrupt,
+}
+
+impl RenderState {
+    pub fn new(options: &RenderOptions) -> Self {
+        RenderState {
+            error: Cell::new(None),
+            interrupt: Interrupt {
+                cancellation: options.cancellation.clone(),
+                deadline: options.deadline,
+            },
+        }
+    }
+
+    /// Returns the first error that occurred during rendering.
+    pub fn result(&self) -> Result<(), Error> {
+        match self.error.get() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Context<'a> {
     pub max_bbox: tiny_skia::IntRect,
+    /// Limits layers without filters to a part of the canvas.
+    ///
+    /// Used by region rendering, where only the region and the anti-aliasing margin are visible.
+    pub layer_region: Option<tiny_skia::IntRect>,
+    /// Maps the host coordinate system (the target canvas) into the current one.
+    ///
+    /// Used by vector effects.
+    pub host_transform: tiny_skia::Transform,
+    pub max_surface_pixels: u64,
+    /// The first error that occurred during rendering.
+    pub error: &'a Cell<Option<Error>>,
+    pub interrupt: &'a Interrupt,
+    /// Per-ID rendering overrides.
+    pub overrides: Option<&'a NodeOverrides>,
+    /// Whether an ancestor is listed in `NodeOverrides::only`.
+    pub only_matched: bool,
+}
+
+impl<'a> Context<'a> {
+    /// Creates a context for a canvas that is also the host coordinate system.
+    pub fn new(
+        state: &'a RenderState,
+        max_bbox: tiny_skia::IntRect,
+        options: &RenderOptions<'a>,
+    ) -> Self {
+        Context {
+            max_bbox,
+            layer_region: None,
+            host_transform: tiny_skia::Transform::default(),
+            max_surface_pixels: options.max_surface_pixels,
+            error: &state.error,
+            interrupt: &state.interrupt,
+            overrides: options.overrides,
+            only_matched: false,
+        }
+    }
+
+    /// Creates a context for a separate canvas, like a nested SVG image.
+    ///
+    /// Limits and errors are shared with the current context, while overrides are not.
+    pub fn nested(&self, max_bbox: tiny_skia::IntRect) -> Self {
+        Context {
+            max_bbox,
+            layer_region: None,
+            host_transform: tiny_skia::Transform::default(),
+            overrides: None,
+            only_matched: false,
+            ..*self
+        }
+    }
+
+    /// Checks that rendering must be aborted.
+    ///
+    /// Interruption errors take precedence over other errors.
+    pub fn is_interrupted(&self) -> bool {
+        if let Some(e) = self.error.get() {
+            if e.is_interruption() {
+                return true;
+            }
+        }
+
+        match self.interrupt.check() {
+            Some(e) => {
+                self.error.set(Some(e));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Allocates an intermediate pixmap, like a layer or a mask.
+    ///
+    /// Records [`Error::SurfaceLimitReached`] when the pixmap is bigger than
+    /// `max_surface_pixels`.
+    pub fn create_pixmap(&self, width: u32, height: u32) -> Option<tiny_skia::Pixmap> {
+        if width as u64 * height as u64 > self.max_surface_pixels {
+            usvg::report!(
+                RenderingFailed,
+                "A {}x{} surface exceeds the size limit. Skipped.",
+                width,
+                height
+            );
+            self.set_error(Error::SurfaceLimitReached);
+            return None;
+        }
+
+        tiny_skia::Pixmap::new(width, height).log_none(|| {
+            usvg::report!(
+                RenderingFailed,
+                "Failed to allocate a {}x{} surface.",
+                width,
+                height
+            )
+        })
+    }
+
+    /// Records an error, unless an error is already set.
+    ///
+    /// Interruption errors always override other errors.
+    pub fn set_error(&self, error: Error) {
+        match self.error.get() {
+            None => self.error.set(Some(error)),
+            Some(e) if !e.is_interruption() && error.is_interruption() => {
+                self.error.set(Some(error))
+            }
+            Some(_) => {}
+        }
+    }
 }
 
 impl Tree {
@@ -16,143 +345,691 @@ impl Tree {
     ///
     /// The produced content is in the sRGB color space.
     pub fn render(&self, transform: tiny_skia::Transform, pixmap: &mut tiny_skia::PixmapMut) {
-        let target_size = tiny_skia::IntSize::from_wh(pixmap.width(), pixmap.height()).unwrap();
-        let max_bbox = tiny_skia::IntRect::from_xywh(
-            -(target_size.width() as i32) * 2,
-            -(target_size.height() as i32) * 2,
-            target_size.width() * 4,
-            target_size.height() * 4,
-        )
-        .unwrap();
+        let limits = usvg::Limits {
+            max_surface_pixels: u64::MAX,
+            ..usvg::Limits::default()
+        };
+
+        // Cannot fail without limits.
+        let _ = self.render_with_limits(transform, &limits, pixmap);
+    }
+
+    /// Renders the tree onto the pixmap, checking `limits.max_surface_pixels`.
+    ///
+    /// Same as [`Tree::render`], but layers and filter regions that are bigger
+    /// than the limit will be skipped and an error will be returned.
+    pub fn render_with_limits(
+        &self,
+        transform: tiny_skia::Transform,
+        limits: &usvg::Limits,
+        pixmap: &mut tiny_skia::PixmapMut,
+    ) -> Result<(), Error> {
+        let options = RenderOptions {
+            max_surface_pixels: limits.max_surface_pixels,
+            ..RenderOptions::default()
+        };
+
+        self.render_with_options(transform, &options, pixmap)
+    }
+
+    /// Renders the tree onto the pixmap using the provided options.
+    ///
+    /// Cancellation and the deadline are checked between nodes, between filter primitives
+    /// and inside expensive filters. When rendering was interrupted,
+    /// [`Error::Cancelled`] or [`Error::DeadlineExceeded`] is returned
+    /// and the pixmap content should be discarded.
+    pub fn render_with_options(
+        &self,
+        transform: tiny_skia::Transform,
+        options: &RenderOptions,
+        pixmap: &mut tiny_skia::PixmapMut,
+    ) -> Result<(), Error> {
+        let state = RenderState::new(options);
+        let ctx = Context::new(&state, max_bbox(pixmap), options);
 
         let ts =
             usvg::utils::view_box_to_transform(self.view_box.rect, self.view_box.aspect, self.size);
+        let root_transform = transform.pre_concat(ts);
+
+        let mut backend = TinySkiaBackend::new(ctx, pixmap);
+        match options.progress {
+            Some(callback) => {
+                let progress = Progress::new(callback, &self.children);
+
+                render_with_progress(
+                    &self.children,
+                    &ctx,
+                    root_transform,
+                    &progress,
+                    &mut backend,
+                );
+            }
+            None => render_nodes(&self.children, &ctx, root_transform, &mut backend),
+        }
+
+        state.result()
+    }
+
+    /// Renders a part of the canvas.
+    ///
+    /// `region` is a rectangle in the device space, i.e. in the coordinates of the whole image
+    /// scaled by `scale`. Its top-left corner is mapped to the pixmap's origin,
+    /// therefore the pixmap is expected to have the same size as the region.
+    ///
+    /// Stitching rendered regions together produces the same image as rendering
+    /// the whole tree at once. Filter regions are computed relative
+    /// to the whole image, so filters are seamless across region edges.
+    /// Other layers are limited to the region, so the scale can be arbitrary large.
+    /// Only anti-aliasing of curves that cross region edges can differ slightly,
+    /// because such curves are clipped before rasterization.
+    ///
+    /// Groups outside the region are skipped.
+    pub fn render_region(
+        &self,
+        region: tiny_skia::IntRect,
+        scale: f32,
+        pixmap: &mut tiny_skia::PixmapMut,
+    ) {
+        let offset = tiny_skia::Transform::from_translate(-region.x() as f32, -region.y() as f32);
+        let transform = offset.pre_scale(scale, scale);
 
+        // Limit layers to 4x the whole image size, exactly like `render` does.
+        // Coordinates can overflow at a large scale, therefore they are clamped.
+        let width = (self.size.width() * scale).ceil() as i64;
+        let height = (self.size.height() * scale).ceil() as i64;
+        let x = -(region.x() as i64) - width.saturating_mul(2);
+        let y = -(region.y() as i64) - height.saturating_mul(2);
+        let clamp = |v: i64| v.clamp(i32::MIN as i64 / 2, i32::MAX as i64 / 2) as i32;
+        let max_bbox = match tiny_skia::IntRect::from_ltrb(
+            clamp(x),
+            clamp(y),
+            clamp(x.saturating_add(width.saturating_mul(4))),
+            clamp(y.saturating_add(height.saturating_mul(4))),
+        ) {
+            Some(v) => v,
+            None => return,
+        };
+
+        // Layers without filters are needed only inside the region,
+        // expanded by 2px to account for anti-aliasing.
+        let layer_region = tiny_skia::IntRect::from_xywh(
+            -2,
+            -2,
+            region.width().saturating_add(4),
+            region.height().saturating_add(4),
+        );
+
+        let state = RenderState::default();
+        let ctx = Context {
+            layer_region,
+            // The whole image is the host coordinate system.
+            host_transform: offset,
+            ..Context::new(&state, max_bbox, &RenderOptions::default())
+        };
+
+        self.render_with_context(transform, &ctx, pixmap);
+    }
+
+    /// Renders the tree onto the pixmap using multiple threads.
+    ///
+    /// Same as [`Tree::render`], but independent group layers, like groups
+    /// with filters, opacity or masks, are rendered concurrently.
+    /// The result is identical to the single-threaded one.
+    #[cfg(feature = "parallel")]
+    pub fn render_parallel(
+        &self,
+        transform: tiny_skia::Transform,
+        pixmap: &mut tiny_skia::PixmapMut,
+    ) {
+        // Cannot fail without limits.
+        let _ = self.render_parallel_with_options(transform, &RenderOptions::default(), pixmap);
+    }
+
+    /// Renders the tree onto the pixmap using multiple threads and the provided options.
+    ///
+    /// Same as [`Tree::render_with_options`], but group layers are rendered
+    /// concurrently, like in [`Tree::render_parallel`].
+    /// Progress is reported once per top-level layer, so it advances in larger steps.
+    #[cfg(feature = "parallel")]
+    pub fn render_parallel_with_options(
+        &self,
+        transform: tiny_skia::Transform,
+        options: &RenderOptions,
+        pixmap: &mut tiny_skia::PixmapMut,
+    ) -> Result<(), Error> {
+        let state = RenderState::new(options);
+        let ctx = Context::new(&state, max_bbox(pixmap), options);
+
+        let ts =
+            usvg::utils::view_box_to_transform(self.view_box.rect, self.view_box.aspect, self.size);
         let root_transform = transform.pre_concat(ts);
 
-        let ctx = Context { max_bbox };
-        render_nodes(&self.children, &ctx, root_transform, pixmap);
+        let progress = options
+            .progress
+            .map(|callback| Progress::new(callback, &self.children));
+        crate::parallel::render_with_progress(
+            &self.children,
+            &ctx,
+            root_transform,
+            progress.as_ref(),
+            pixmap,
+        );
+
+        state.result()
+    }
+
+    /// Renders the tree using a custom drawing backend.
+    ///
+    /// `transform` will be used as a root transform.
+    pub fn render_with_backend(&self, transform: tiny_skia::Transform, backend: &mut dyn Backend) {
+        let ts =
+            usvg::utils::view_box_to_transform(self.view_box.rect, self.view_box.aspect, self.size);
+
+        crate::backend::render_nodes(&self.children, transform.pre_concat(ts), backend);
+    }
+
+    /// Renders the tree onto a separate canvas, using parent's limits.
+    ///
+    /// Used by SVG images.
+    pub(crate) fn render_nested(
+        &self,
+        transform: tiny_skia::Transform,
+        parent_ctx: &Context,
+        pixmap: &mut tiny_skia::PixmapMut,
+    ) {
+        let ctx = parent_ctx.nested(max_bbox(pixmap));
+        self.render_with_context(transform, &ctx, pixmap);
+    }
+
+    fn render_with_context(
+        &self,
+        transform: tiny_skia::Transform,
+        ctx: &Context,
+        pixmap: &mut tiny_skia::PixmapMut,
+    ) {
+        let ts =
+            usvg::utils::view_box_to_transform(self.view_box.rect, self.view_box.aspect, self.size);
+
+        let root_transform = transform.pre_concat(ts);
+
+        render_nodes_to_pixmap(&self.children, ctx, root_transform, pixmap);
+    }
+}
+
+/// Returns a region that limits layers to 4x the canvas size.
+pub fn max_bbox(pixmap: &tiny_skia::PixmapMut) -> tiny_skia::IntRect {
+    let target_size = tiny_skia::IntSize::from_wh(pixmap.width(), pixmap.height()).unwrap();
+    max_bbox_for_size(target_size)
+}
+
+/// Same as [`max_bbox`], but for a canvas of the given size.
+pub fn max_bbox_for_size(target_size: tiny_skia::IntSize) -> tiny_skia::IntRect {
+    tiny_skia::IntRect::from_xywh(
+        -(target_size.width() as i32) * 2,
+        -(target_size.height() as i32) * 2,
+        target_size.width() * 4,
+        target_size.height() * 4,
+    )
+    .unwrap()
+}
+
+pub struct Progress<'a> {
+    callback: &'a dyn Fn(f32),
+    total: usize,
+    done: Cell<usize>,
+}
+
+impl<'a> Progress<'a> {
+    fn new(callback: &'a dyn Fn(f32), children: &[Node]) -> Self {
+        Progress {
+            callback,
+            total: count_progress_steps(children),
+            done: Cell::new(0),
+        }
+    }
+
+    pub fn advance(&self, steps: usize) {
+        self.done.set(self.done.get() + steps);
+        (self.callback)(self.done.get() as f32 / self.total as f32);
+    }
+
+    /// Advances by the steps of nodes that will not be rendered.
+    pub fn skip(&self, children: &[Node]) {
+        let steps = count_progress_steps(children);
+        if steps != 0 {
+            self.advance(steps);
+        }
+    }
+}
+
+/// Counts nodes that are rendered as a whole, i.e. all except transform-only groups.
+fn count_progress_steps(children: &[Node]) -> usize {
+    children
+        .iter()
+        .map(|node| match node {
+            Node::Group(ref group) if group.is_transform_only() => {
+                count_progress_steps(&group.children)
+            }
+            _ => 1,
+        })
+        .sum()
+}
+
+/// Same as `render_nodes`, but reports progress.
+fn render_with_progress(
+    children: &[Node],
+    ctx: &Context,
+    transform: tiny_skia::Transform,
+    progress: &Progress,
+    backend: &mut dyn Backend,
+) {
+    let mut nodes = children;
+    while let Some(node) = nodes.first() {
+        if ctx.is_interrupted() {
+            return;
+        }
+
+        if let Some((count, opacity)) = opacity_override(nodes, ctx) {
+            render_with_opacity(&nodes[..count], opacity, ctx, transform, backend);
+            progress.skip(&nodes[..count]);
+            nodes = &nodes[count..];
+            continue;
+        }
+
+        match node {
+            Node::Group(ref group) if group.is_transform_only() => {
+                let transform = transform.pre_concat(group.transform);
+                match node_context(node, ctx) {
+                    Some(ref ctx) if !backend.is_outside(group, transform) => {
+                        render_with_progress(&group.children, ctx, transform, progress, backend);
+                    }
+                    _ => progress.skip(&group.children),
+                }
+            }
+            _ => {
+                render_node(node, ctx, transform, backend);
+                progress.advance(1);
+            }
+        }
+
+        nodes = &nodes[1..];
     }
 }
 
+/// Renders nodes using a drawing backend.
 pub fn render_nodes(
     children: &[Node],
     ctx: &Context,
     transform: tiny_skia::Transform,
-    pixmap: &mut tiny_skia::PixmapMut,
+    backend: &mut dyn Backend,
 ) {
-    for node in children {
-        render_node(node, ctx, transform, pixmap);
+    let mut nodes = children;
+    while let Some(node) = nodes.first() {
+        if ctx.is_interrupted() {
+            return;
+        }
+
+        let count = match opacity_override(nodes, ctx) {
+            Some((count, opacity)) => {
+                render_with_opacity(&nodes[..count], opacity, ctx, transform, backend);
+                count
+            }
+            None => {
+                render_node(node, ctx, transform, backend);
+                1
+            }
+        };
+
+        nodes = &nodes[count..];
     }
 }
 
-fn render_node(
-    node: &Node,
+/// Renders nodes onto a pixmap using the tiny-skia backend.
+pub fn render_nodes_to_pixmap(
+    children: &[Node],
     ctx: &Context,
     transform: tiny_skia::Transform,
     pixmap: &mut tiny_skia::PixmapMut,
 ) {
+    let mut backend = TinySkiaBackend::new(*ctx, pixmap);
+    render_nodes(children, ctx, transform, &mut backend);
+}
+
+pub fn render_node(
+    node: &Node,
+    ctx: &Context,
+    transform: tiny_skia::Transform,
+    backend: &mut dyn Backend,
+) {
+    let ctx = match node_context(node, ctx) {
+        Some(v) => v,
+        None => return,
+    };
+
     match node {
         Node::Group(ref group) => {
-            render_group(group, ctx, transform, pixmap);
+            render_group(group, &ctx, transform, backend);
         }
         Node::FillPath(ref path) => {
-            crate::path::render_fill_path(
-                path,
-                tiny_skia::BlendMode::SourceOver,
-                ctx,
-                transform,
-                pixmap,
-            );
+            backend.fill_path(path, transform);
         }
         Node::StrokePath(ref path) => {
-            crate::path::render_stroke_path(
-                path,
-                tiny_skia::BlendMode::SourceOver,
-                ctx,
-                transform,
-                pixmap,
-            );
+            backend.stroke_path(path, transform);
         }
         Node::Image(ref image) => {
-            crate::image::render_image(image, transform, pixmap);
+            backend.draw_image(image, transform);
+        }
+    }
+}
+
+/// Returns a context that should be used to render a node and its children.
+///
+/// Returns `None` when the node is hidden.
+pub fn node_context<'a>(node: &Node, ctx: &Context<'a>) -> Option<Context<'a>> {
+    if !node.is_visible() {
+        return None;
+    }
+
+    let overrides = match ctx.overrides {
+        Some(v) => v,
+        None => return Some(*ctx),
+    };
+
+    if overrides.is_hidden(node.id()) {
+        return None;
+    }
+
+    if overrides.only.is_empty() || ctx.only_matched {
+        return Some(*ctx);
+    }
+
+    if overrides.is_only(node.id()) {
+        return Some(Context {
+            only_matched: true,
+            ..*ctx
+        });
+    }
+
+    // Ancestors of listed nodes are rendered as well.
+    match node {
+        Node::Group(ref group) if overrides.contains_only(&group.children) => Some(*ctx),
+        _ => None,
+    }
+}
+
+/// Returns the number of leading nodes that share an opacity override and the opacity.
+pub fn opacity_override(nodes: &[Node], ctx: &Context) -> Option<(usize, usvg::Opacity)> {
+    let id = nodes.first()?.id();
+    if id.is_empty() {
+        return None;
+    }
+
+    let opacity = *ctx.overrides?.opacity.get(id)?;
+    let count = nodes.iter().take_while(|node| node.id() == id).count();
+    Some((count, opacity))
+}
+
+/// Renders nodes into a layer with the specified opacity.
+pub fn render_with_opacity(
+    nodes: &[Node],
+    opacity: usvg::Opacity,
+    ctx: &Context,
+    transform: tiny_skia::Transform,
+    backend: &mut dyn Backend,
+) -> Option<()> {
+    if nodes.iter().all(|node| node_context(node, ctx).is_none()) {
+        return Some(());
+    }
+
+    let mut bbox = None;
+    for node in nodes {
+        let node_bbox = match node_bbox(node) {
+            Some(v) => v,
+            None => {
+                bbox = None;
+                break;
+            }
+        };
+
+        bbox = Some(bbox.map_or(node_bbox, |bbox| union_rects(bbox, node_bbox)));
+    }
+
+    // Nodes with vector effects have an unknown bbox, so use the whole layer instead.
+    let (bbox, host_dependent) = match bbox {
+        Some(v) => (v, false),
+        None => (ctx.max_bbox.to_rect().transform(transform.invert()?)?, true),
+    };
+
+    let group = Group {
+        transform: tiny_skia::Transform::default(),
+        opacity,
+        blend_mode: tiny_skia::BlendMode::SourceOver,
+        clip_path: None,
+        mask: None,
+        filters: Vec::new(),
+        isolate: false,
+        id: String::new(),
+        visible: true,
+        bbox,
+        host_dependent,
+        children: Vec::new(),
+    };
+
+    if backend.is_outside(&group, transform) {
+        return Some(());
+    }
+
+    let transform = backend.push_layer(&group, transform)?;
+
+    for node in nodes {
+        if ctx.is_interrupted() {
+            break;
         }
+
+        render_node(node, ctx, transform, backend);
     }
+
+    backend.pop_layer(&group, transform);
+
+    Some(())
 }
 
 fn render_group(
     group: &Group,
     ctx: &Context,
     transform: tiny_skia::Transform,
-    pixmap: &mut tiny_skia::PixmapMut,
+    backend: &mut dyn Backend,
 ) -> Option<()> {
     let transform = transform.pre_concat(group.transform);
 
+    if backend.is_outside(group, transform) {
+        return Some(());
+    }
+
     if group.is_transform_only() {
-        render_nodes(&group.children, ctx, transform, pixmap);
+        render_nodes(&group.children, ctx, transform, backend);
         return Some(());
     }
 
-    let bbox = group.bbox.transform(transform)?;
+    let transform = backend.push_layer(group, transform)?;
 
-    let mut ibbox = if group.filters.is_empty() {
-        // Convert group bbox into an integer one, expanding each side outwards by 2px
-        // to make sure that anti-aliased pixels would not be clipped.
-        tiny_skia::IntRect::from_xywh(
-            bbox.x().floor() as i32 - 2,
-            bbox.y().floor() as i32 - 2,
-            bbox.width().ceil() as u32 + 4,
-            bbox.height().ceil() as u32 + 4,
-        )?
-    } else {
-        // The bounding box for groups with filters is special and should not be expanded by 2px,
-        // because it's already acting as a clipping region.
-        let bbox = bbox.to_non_zero_rect()?.to_int_rect();
-        // Make sure our filter region is not bigger than 4x the canvas size.
-        // This is required mainly to prevent huge filter regions that would tank the performance.
-        // It should not affect the final result in any way.
-        crate::geom::fit_to_rect(bbox, ctx.max_bbox)?
-    };
+    render_nodes(&group.children, ctx, transform, backend);
 
-    // Make sure our layer is not bigger than 4x the canvas size.
-    // This is required to prevent huge layers.
-    if group.filters.is_empty() {
-        ibbox = crate::geom::fit_to_rect(ibbox, ctx.max_bbox)?;
+    for filter in &group.filters {
+        if ctx.is_interrupted() {
+            break;
+        }
+
+        backend.apply_filter(filter, transform);
     }
 
-    let shift_ts = {
-        // Original shift.
-        let mut dx = bbox.x();
-        let mut dy = bbox.y();
+    backend.pop_layer(group, transform);
+
+    Some(())
+}
 
-        // Account for subpixel positioned layers.
-        dx -= bbox.x() - ibbox.x() as f32;
-        dy -= bbox.y() - ibbox.y() as f32;
+/// Checks that a group is completely outside the canvas.
+///
+/// `transform` must already include the group's transform.
+pub fn is_outside(group: &Group, transform: tiny_skia::Transform, width: u32, height: u32) -> bool {
+    if group.host_dependent {
+        return false;
+    }
 
-        tiny_skia::Transform::from_translate(-dx, -dy)
-    };
+    // Expand the canvas by 2px to account for anti-aliasing, like with layers.
+    match group.bbox.transform(transform) {
+        Some(bbox) => {
+            bbox.right() < -2.0
+                || bbox.bottom() < -2.0
+                || bbox.left() > width as f32 + 2.0
+                || bbox.top() > height as f32 + 2.0
+        }
+        None => false,
+    }
+}
+
+/// A rendered group layer.
+pub struct Layer {
+    /// Layer's region on the canvas.
+    pub ibbox: tiny_skia::IntRect,
+    pub pixmap: tiny_skia::Pixmap,
+}
+
+/// Renders a group into a separate layer, with filters, clipping and masking applied.
+///
+/// `transform` must already include the group's transform.
+/// `render_children` is used to render group's children.
+///
+/// Doesn't depend on the canvas content, therefore layers can be rendered independently.
+pub fn render_layer(
+    group: &Group,
+    ctx: &Context,
+    transform: tiny_skia::Transform,
+    render_children: fn(&[Node], &Context, tiny_skia::Transform, &mut tiny_skia::PixmapMut),
+) -> Option<Layer> {
+    let (mut layer, ctx, transform) = create_layer(group, ctx, transform)?;
+
+    render_children(&group.children, &ctx, transform, &mut layer.pixmap.as_mut());
+
+    for filter in &group.filters {
+        crate::filter::apply(filter, &ctx, transform, &mut layer.pixmap);
+    }
+
+    finish_layer(group, &ctx, transform, &mut layer)?;
+    Some(layer)
+}
+
+/// Allocates an empty layer for a group.
+///
+/// Returns the layer, a context and a transform that should be used
+/// to render into it.
+fn create_layer<'a>(
+    group: &Group,
+    ctx: &Context<'a>,
+    transform: tiny_skia::Transform,
+) -> Option<(Layer, Context<'a>, tiny_skia::Transform)> {
+    let ibbox = layer_bbox(group, ctx, transform)?;
+
+    // Layers are always aligned to the pixel grid.
+    let shift_ts = tiny_skia::Transform::from_translate(-ibbox.x() as f32, -ibbox.y() as f32);
 
     let transform = shift_ts.pre_concat(transform);
 
-    let mut sub_pixmap = tiny_skia::Pixmap::new(ibbox.width(), ibbox.height())
-        .log_none(|| log::warn!("Failed to allocate a group layer for: {:?}.", ibbox))?;
+    let sub_pixmap = ctx.create_pixmap(ibbox.width(), ibbox.height())?;
+
+    let ctx = Context {
+        // Filters need the whole filter region, so their content cannot be limited.
+        layer_region: match ctx.layer_region {
+            Some(region) if group.filters.is_empty() => region.translate(-ibbox.x(), -ibbox.y()),
+            _ => None,
+        },
+        host_transform: shift_ts.pre_concat(ctx.host_transform),
+        ..*ctx
+    };
 
-    render_nodes(&group.children, ctx, transform, &mut sub_pixmap.as_mut());
+    let layer = Layer {
+        ibbox,
+        pixmap: sub_pixmap,
+    };
 
-    if !group.filters.is_empty() {
-        for filter in &group.filters {
-            crate::filter::apply(filter, transform, &mut sub_pixmap);
-        }
+    Some((layer, ctx, transform))
+}
+
+/// Calculates a group layer region on the canvas.
+///
+/// `transform` must already include the group's transform.
+pub fn layer_bbox(
+    group: &Group,
+    ctx: &Context,
+    transform: tiny_skia::Transform,
+) -> Option<tiny_skia::IntRect> {
+    let bbox = if group.host_dependent && group.filters.is_empty() {
+        // Bounding boxes with vector effects were calculated for the default host transform.
+        // Recalculate them using the actual one, so layers would not be clipped
+        // when rendering with a different scale or region.
+        host_children_bbox(&group.children, transform, ctx.host_transform)?
+    } else {
+        group.bbox.transform(transform)?
+    };
+
+    let ibbox = if group.filters.is_empty() {
+        // Make sure our layer is not bigger than 4x the canvas size
+        // and than the visible region. This is required to prevent huge layers.
+        let bounds = match ctx.layer_region {
+            Some(region) => region.intersect(&ctx.max_bbox)?,
+            None => ctx.max_bbox,
+        };
+
+        // Expand each side of the group bbox outwards by 2px
+        // to make sure that anti-aliased pixels would not be clipped.
+        // It's clipped before the conversion into an integer one, which can overflow otherwise.
+        let bbox = tiny_skia::Rect::from_ltrb(
+            bbox.left() - 2.0,
+            bbox.top() - 2.0,
+            bbox.right() + 2.0,
+            bbox.bottom() + 2.0,
+        )?;
+        bbox.intersect(&bounds.to_rect())?.round_out()?
+    } else {
+        // The bounding box for groups with filters is special and should not be expanded by 2px,
+        // because it's already acting as a clipping region.
+        // Make sure our filter region is not bigger than 4x the canvas size.
+        // This is required mainly to prevent huge filter regions that would tank the performance.
+        // It should not affect the final result in any way.
+        let bbox = bbox.intersect(&ctx.max_bbox.to_rect())?;
+        bbox.to_non_zero_rect()?.to_int_rect()
+    };
+
+    Some(ibbox)
+}
+
+/// Applies clipping and masking to a rendered layer.
+///
+/// Returns `None` when rendering was interrupted and the layer must not be drawn.
+fn finish_layer(
+    group: &Group,
+    ctx: &Context,
+    transform: tiny_skia::Transform,
+    layer: &mut Layer,
+) -> Option<()> {
+    // Do not draw partially rendered layers.
+    if ctx.is_interrupted() {
+        return None;
     }
 
     if let Some(ref clip_path) = group.clip_path {
-        crate::clip::apply(clip_path, transform, &mut sub_pixmap);
+        crate::clip::apply(clip_path, ctx, transform, &mut layer.pixmap);
     }
 
     if let Some(ref mask) = group.mask {
-        crate::mask::apply(mask, ctx, transform, &mut sub_pixmap);
+        crate::mask::apply(mask, ctx, transform, &mut layer.pixmap);
     }
 
+    Some(())
+}
+
+/// Draws a group layer onto the canvas.
+pub fn draw_layer(group: &Group, layer: &Layer, pixmap: &mut tiny_skia::PixmapMut) {
     let paint = tiny_skia::PixmapPaint {
         opacity: group.opacity.get(),
         blend_mode: group.blend_mode,
@@ -160,15 +1037,13 @@ fn render_group(
     };
 
     pixmap.draw_pixmap(
-        ibbox.x(),
-        ibbox.y(),
-        sub_pixmap.as_ref(),
+        layer.ibbox.x(),
+        layer.ibbox.y(),
+        layer.pixmap.as_ref(),
         &paint,
         tiny_skia::Transform::identity(),
         None,
     );
-
-    Some(())
 }
 
 pub trait TinySkiaPixmapMutExt {
@@ -193,3 +1068,95 @@ impl TinySkiaPixmapMutExt for tiny_skia::PixmapMut<'_> {
         Some(mask)
     }
 }
+
+/// The tiny-skia drawing backend.
+pub struct TinySkiaBackend<'a, 'b, 'c> {
+    /// The context of the current layer.
+    ctx: Context<'a>,
+    canvas: &'b mut tiny_skia::PixmapMut<'c>,
+    layers: Vec<(Layer, Context<'a>)>,
+}
+
+impl<'a, 'b, 'c> TinySkiaBackend<'a, 'b, 'c> {
+    pub fn new(ctx: Context<'a>, canvas: &'b mut tiny_skia::PixmapMut<'c>) -> Self {
+        TinySkiaBackend {
+            ctx,
+            canvas,
+            layers: Vec::new(),
+        }
+    }
+
+    /// Calls `f` with the current layer context and pixmap.
+    fn with_target<F>(&mut self, f: F)
+    where
+        F: FnOnce(&Context, &mut tiny_skia::PixmapMut),
+    {
+        match self.layers.last_mut() {
+            Some((ref mut layer, _)) => f(&self.ctx, &mut layer.pixmap.as_mut()),
+            None => f(&self.ctx, self.canvas),
+        }
+    }
+}
+
+impl Backend for TinySkiaBackend<'_, '_, '_> {
+    fn fill_path(&mut self, path: &crate::FillPath, transform: tiny_skia::Transform) {
+        self.with_target(|ctx, pixmap| {
+            let mode = tiny_skia::BlendMode::SourceOver;
+            crate::path::render_fill_path(path, mode, ctx, transform, pixmap);
+        });
+    }
+
+    fn stroke_path(&mut self, path: &crate::StrokePath, transform: tiny_skia::Transform) {
+        self.with_target(|ctx, pixmap| {
+            let mode = tiny_skia::BlendMode::SourceOver;
+            crate::path::render_stroke_path(path, mode, ctx, transform, pixmap);
+        });
+    }
+
+    fn draw_image(&mut self, image: &crate::Image, transform: tiny_skia::Transform) {
+        self.with_target(|ctx, pixmap| {
+            crate::image::render_image(image, ctx, transform, pixmap);
+        });
+    }
+
+    fn push_layer(
+        &mut self,
+        group: &Group,
+        transform: tiny_skia::Transform,
+    ) -> Option<tiny_skia::Transform> {
+        let (layer, ctx, transform) = create_layer(group, &self.ctx, transform)?;
+        let parent_ctx = std::mem::replace(&mut self.ctx, ctx);
+        self.layers.push((layer, parent_ctx));
+        Some(transform)
+    }
+
+    fn apply_filter(&mut self, filter: &crate::Filter, transform: tiny_skia::Transform) {
+        if let Some((ref mut layer, _)) = self.layers.last_mut() {
+            crate::filter::apply(filter, &self.ctx, transform, &mut layer.pixmap);
+        }
+    }
+
+    fn pop_layer(&mut self, group: &Group, transform: tiny_skia::Transform) {
+        let (mut layer, parent_ctx) = match self.layers.pop() {
+            Some(v) => v,
+            None => return,
+        };
+
+        let ctx = std::mem::replace(&mut self.ctx, parent_ctx);
+        if finish_layer(group, &ctx, transform, &mut layer).is_some() {
+            self.with_target(|_, pixmap| draw_layer(group, &layer, pixmap));
+        }
+    }
+
+    fn is_outside(&self, group: &Group, transform: tiny_skia::Transform) -> bool {
+        match self.layers.last() {
+            Some((ref layer, _)) => is_outside(
+                group,
+                transform,
+                layer.pixmap.width(),
+                layer.pixmap.height(),
+            ),
+            None => is_outside(group, transform, self.canvas.width(), self.canvas.height()),
+        }
+    }
+}
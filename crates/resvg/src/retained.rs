@@ -0,0 +1,423 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Retained-mode rendering.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::paint_server::Paint;
+use crate::render::{Context, Layer, RenderOptions, RenderState, TinySkiaBackend};
+use crate::tree::{node_bbox, union_rects, Node, Tree};
+
+/// A node update.
+#[derive(Clone)]
+pub enum NodeUpdate {
+    /// Sets node's transform.
+    Transform(tiny_skia::Transform),
+    /// Sets path's paint.
+    ///
+    /// Can be applied only to fill and stroke paths.
+    Paint(Paint),
+    /// Shows or hides a node.
+    Visibility(bool),
+    /// Sets path's segments.
+    ///
+    /// Can be applied only to fill and stroke paths.
+    Path(Arc<tiny_skia::Path>),
+}
+
+impl Tree {
+    /// Returns root nodes.
+    pub fn children(&self) -> &[Node] {
+        &self.children
+    }
+
+    /// Returns a node by its index path.
+    ///
+    /// An index path contains indices of the node and its ancestors
+    /// in their parents' children, starting from the root.
+    pub fn node(&self, path: &[usize]) -> Option<&Node> {
+        let (&index, rest) = path.split_first()?;
+        let mut node = self.children.get(index)?;
+        for &index in rest {
+            match node {
+                Node::Group(ref group) => node = group.children.get(index)?,
+                _ => return None,
+            }
+        }
+
+        Some(node)
+    }
+
+    /// Updates a node in place.
+    ///
+    /// Bounding boxes of the node's ancestors are expanded to fit the updated node,
+    /// but never shrink. Filter regions are not recalculated.
+    ///
+    /// Returns `false` when the node doesn't exist or the update cannot be applied to it.
+    pub fn update_node(&mut self, path: &[usize], update: NodeUpdate) -> bool {
+        let bbox = match update_node_in(&mut self.children, path, update) {
+            Some(v) => v,
+            None => return false,
+        };
+
+        if let Some(bbox) = bbox {
+            self.content_area = Some(match self.content_area {
+                Some(area) => union_rects(area, bbox),
+                None => bbox,
+            });
+        }
+
+        true
+    }
+}
+
+/// Applies an update and returns the updated node's bounding box
+/// in the coordinates of `nodes`' parent.
+///
+/// Outer `None` indicates that the update was not applied.
+fn update_node_in(
+    nodes: &mut [Node],
+    path: &[usize],
+    update: NodeUpdate,
+) -> Option<Option<tiny_skia::Rect>> {
+    let (&index, rest) = path.split_first()?;
+    let node = nodes.get_mut(index)?;
+
+    if rest.is_empty() {
+        apply_update(node, update)?;
+        return Some(node_bbox(node));
+    }
+
+    let group = match node {
+        Node::Group(ref mut group) => group,
+        _ => return None,
+    };
+
+    let bbox = update_node_in(&mut group.children, rest, update)?;
+    // The layer of a group with filters is defined by the filter region.
+    if let (Some(bbox), true) = (bbox, group.filters.is_empty()) {
+        group.bbox = union_rects(group.bbox, bbox);
+    }
+
+    Some(node_bbox(node))
+}
+
+fn apply_update(node: &mut Node, update: NodeUpdate) -> Option<()> {
+    match (node, update) {
+        (Node::Group(ref mut group), NodeUpdate::Transform(ts)) => group.transform = ts,
+        (Node::FillPath(ref mut path), NodeUpdate::Transform(ts)) => path.transform = ts,
+        (Node::StrokePath(ref mut path), NodeUpdate::Transform(ts)) => path.transform = ts,
+        (Node::Image(ref mut image), NodeUpdate::Transform(ts)) => image.transform = ts,
+        (Node::FillPath(ref mut path), NodeUpdate::Paint(paint)) => path.paint = paint,
+        (Node::StrokePath(ref mut path), NodeUpdate::Paint(paint)) => path.paint = paint,
+        (Node::Group(ref mut group), NodeUpdate::Visibility(v)) => group.visible = v,
+        (Node::FillPath(ref mut path), NodeUpdate::Visibility(v)) => path.visible = v,
+        (Node::StrokePath(ref mut path), NodeUpdate::Visibility(v)) => path.visible = v,
+        (Node::Image(ref mut image), NodeUpdate::Visibility(v)) => image.visible = v,
+        (Node::FillPath(ref mut path), NodeUpdate::Path(data)) => path.path = data,
+        (Node::StrokePath(ref mut path), NodeUpdate::Path(data)) => path.path = data,
+        _ => return None,
+    }
+
+    Some(())
+}
+
+/// Calculates a region affected by a node in the root coordinates.
+///
+/// A node inside a group with filters affects the whole filter region.
+/// Returns `None` when the region is unknown.
+fn node_damage(tree: &Tree, path: &[usize]) -> Option<tiny_skia::Rect> {
+    let (&last, ancestors) = path.split_last()?;
+
+    let mut nodes = &tree.children;
+    let mut ts = tiny_skia::Transform::default();
+    for &index in ancestors {
+        let group = match nodes.get(index)? {
+            Node::Group(ref group) => group,
+            _ => return None,
+        };
+
+        if group.host_dependent {
+            return None;
+        }
+
+        ts = ts.pre_concat(group.transform);
+        if !group.filters.is_empty() {
+            return group.bbox.transform(ts);
+        }
+
+        nodes = &group.children;
+    }
+
+    node_bbox(nodes.get(last)?)?.transform(ts)
+}
+
+/// A retained-mode renderer.
+///
+/// Owns a render tree and tracks canvas regions that were changed by node updates.
+/// Only such regions are re-rendered, while layers of unchanged groups are reused.
+pub struct RetainedRenderer {
+    tree: Tree,
+    transform: tiny_skia::Transform,
+    size: tiny_skia::IntSize,
+    damage: Option<tiny_skia::IntRect>,
+    /// Rendered layers of top-level groups, by index path.
+    layers: HashMap<Vec<usize>, Option<Layer>>,
+}
+
+impl RetainedRenderer {
+    /// Creates a new renderer for a canvas of the specified size.
+    ///
+    /// `transform` will be used as a root transform, like in [`Tree::render`].
+    /// The whole canvas is damaged initially.
+    pub fn new(tree: Tree, transform: tiny_skia::Transform, size: tiny_skia::IntSize) -> Self {
+        RetainedRenderer {
+            tree,
+            transform,
+            size,
+            damage: Some(canvas_rect(size)),
+            layers: HashMap::new(),
+        }
+    }
+
+    /// Returns the render tree.
+    pub fn tree(&self) -> &Tree {
+        &self.tree
+    }
+
+    /// Returns the render tree, consuming the renderer.
+    pub fn into_tree(self) -> Tree {
+        self.tree
+    }
+
+    /// Returns a canvas region that has to be re-rendered.
+    pub fn damage(&self) -> Option<tiny_skia::IntRect> {
+        self.damage
+    }
+
+    /// Marks the whole canvas as damaged and drops all cached layers.
+    pub fn invalidate(&mut self) {
+        self.damage = Some(canvas_rect(self.size));
+        self.layers.clear();
+    }
+
+    /// Updates a node in place and marks its old and new regions as damaged.
+    ///
+    /// See [`Tree::update_node`] for details.
+    pub fn update_node(&mut self, path: &[usize], update: NodeUpdate) -> bool {
+        let old_rect = node_damage(&self.tree, path);
+        if !self.tree.update_node(path, update) {
+            return false;
+        }
+        let new_rect = node_damage(&self.tree, path);
+
+        // Layers of the node, its ancestors and descendants are outdated.
+        self.layers
+            .retain(|key, _| !path.starts_with(key) && !key.starts_with(path));
+
+        match (old_rect, new_rect) {
+            (Some(r1), Some(r2)) => self.add_damage(union_rects(r1, r2)),
+            _ => self.damage = Some(canvas_rect(self.size)),
+        }
+
+        true
+    }
+
+    fn root_transform(&self) -> tiny_skia::Transform {
+        let view_box = &self.tree.view_box;
+        let ts = usvg::utils::view_box_to_transform(view_box.rect, view_box.aspect, self.tree.size);
+        self.transform.pre_concat(ts)
+    }
+
+    fn add_damage(&mut self, rect: tiny_skia::Rect) {
+        let rect = match rect.transform(self.root_transform()) {
+            Some(v) => v,
+            None => {
+                self.damage = Some(canvas_rect(self.size));
+                return;
+            }
+        };
+
+        // Expand by 2px to account for anti-aliasing, like with layers.
+        let rect = match tiny_skia::IntRect::from_ltrb(
+            rect.left().floor() as i32 - 2,
+            rect.top().floor() as i32 - 2,
+            rect.right().ceil() as i32 + 2,
+            rect.bottom().ceil() as i32 + 2,
+        ) {
+            Some(v) => v,
+            None => return,
+        };
+
+        let rect = match crate::geom::fit_to_rect(rect, canvas_rect(self.size)) {
+            Some(v) => v,
+            None => return,
+        };
+
+        self.damage = Some(match self.damage {
+            Some(damage) => tiny_skia::IntRect::from_ltrb(
+                damage.left().min(rect.left()),
+                damage.top().min(rect.top()),
+                damage.right().max(rect.right()),
+                damage.bottom().max(rect.bottom()),
+            )
+            .unwrap_or(rect),
+            None => rect,
+        });
+    }
+
+    /// Re-renders the damaged region onto the pixmap.
+    ///
+    /// `pixmap` must have the renderer's size and contain the result
+    /// of the previous rendering. Pixels outside the damaged region are preserved.
+    ///
+    /// Returns the re-rendered region or `None` when nothing was rendered.
+    pub fn render(&mut self, pixmap: &mut tiny_skia::PixmapMut) -> Option<tiny_skia::IntRect> {
+        if pixmap.width() != self.size.width() || pixmap.height() != self.size.height() {
+            return None;
+        }
+
+        let region = self.damage.take()?;
+        let mut sub_pixmap = tiny_skia::Pixmap::new(region.width(), region.height())?;
+
+        let offset = tiny_skia::Transform::from_translate(-region.x() as f32, -region.y() as f32);
+        let max_bbox = crate::render::max_bbox_for_size(self.size);
+
+        let state = RenderState::default();
+        // Layers are rendered for the whole canvas, so they can be reused by other regions.
+        let canvas_ctx = Context::new(&state, max_bbox, &RenderOptions::default());
+        // Like in `Tree::render_region`.
+        let region_ctx = Context {
+            max_bbox: tiny_skia::IntRect::from_xywh(
+                max_bbox.x() - region.x(),
+                max_bbox.y() - region.y(),
+                max_bbox.width(),
+                max_bbox.height(),
+            )?,
+            host_transform: offset,
+            ..canvas_ctx
+        };
+
+        let root_transform = self.root_transform();
+        let mut state = RegionState {
+            region,
+            offset,
+            canvas_ctx,
+            region_ctx,
+            layers: &mut self.layers,
+            path: Vec::new(),
+        };
+
+        render_nodes(
+            &self.tree.children,
+            root_transform,
+            &mut state,
+            &mut sub_pixmap.as_mut(),
+        );
+
+        let paint = tiny_skia::PixmapPaint {
+            blend_mode: tiny_skia::BlendMode::Source,
+            ..tiny_skia::PixmapPaint::default()
+        };
+        pixmap.draw_pixmap(
+            region.x(),
+            region.y(),
+            sub_pixmap.as_ref(),
+            &paint,
+            tiny_skia::Transform::identity(),
+            None,
+        );
+
+        Some(region)
+    }
+}
+
+fn canvas_rect(size: tiny_skia::IntSize) -> tiny_skia::IntRect {
+    tiny_skia::IntRect::from_xywh(0, 0, size.width(), size.height()).unwrap()
+}
+
+struct RegionState<'a, 'b> {
+    region: tiny_skia::IntRect,
+    /// Maps the canvas into the region.
+    offset: tiny_skia::Transform,
+    canvas_ctx: Context<'a>,
+    region_ctx: Context<'a>,
+    layers: &'b mut HashMap<Vec<usize>, Option<Layer>>,
+    /// Index path of the current node.
+    path: Vec<usize>,
+}
+
+/// Renders nodes onto a region pixmap, using cached layers when possible.
+///
+/// `transform` maps nodes into the canvas and not into the region.
+fn render_nodes(
+    nodes: &[Node],
+    transform: tiny_skia::Transform,
+    state: &mut RegionState,
+    pixmap: &mut tiny_skia::PixmapMut,
+) {
+    for (index, node) in nodes.iter().enumerate() {
+        state.path.push(index);
+        render_node(node, transform, state, pixmap);
+        state.path.pop();
+    }
+}
+
+fn render_node(
+    node: &Node,
+    transform: tiny_skia::Transform,
+    state: &mut RegionState,
+    pixmap: &mut tiny_skia::PixmapMut,
+) {
+    let group = match node {
+        Node::Group(ref group) if group.visible => group,
+        _ => {
+            let ctx = state.region_ctx;
+            let mut backend = TinySkiaBackend::new(ctx, pixmap);
+            let transform = state.offset.pre_concat(transform);
+            crate::render::render_node(node, &ctx, transform, &mut backend);
+            return;
+        }
+    };
+
+    let transform = transform.pre_concat(group.transform);
+    let region_transform = state.offset.pre_concat(transform);
+    if crate::render::is_outside(group, region_transform, pixmap.width(), pixmap.height()) {
+        return;
+    }
+
+    if group.is_transform_only() {
+        render_nodes(&group.children, transform, state, pixmap);
+        return;
+    }
+
+    let canvas_ctx = state.canvas_ctx;
+    let layer = state.layers.entry(state.path.clone()).or_insert_with(|| {
+        crate::render::render_layer(
+            group,
+            &canvas_ctx,
+            transform,
+            crate::render::render_nodes_to_pixmap,
+        )
+    });
+
+    if let Some(ref layer) = layer {
+        let paint = tiny_skia::PixmapPaint {
+            opacity: group.opacity.get(),
+            blend_mode: group.blend_mode,
+            quality: tiny_skia::FilterQuality::Nearest,
+        };
+
+        pixmap.draw_pixmap(
+            layer.ibbox.x() - state.region.x(),
+            layer.ibbox.y() - state.region.y(),
+            layer.pixmap.as_ref(),
+            &paint,
+            tiny_skia::Transform::identity(),
+            None,
+        );
+    }
+}
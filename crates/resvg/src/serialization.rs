@@ -0,0 +1,385 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! `serde` support for display lists.
+//!
+//! tiny-skia types are serialized using stable representations:
+//! transforms as `[sx, ky, kx, sy, tx, ty]`, rects as `[x, y, width, height]`,
+//! colors as unpremultiplied `[r, g, b, a]` and paths as a list of segments.
+//! Pixmaps and masks are serialized as their size and raw data,
+//! i.e. premultiplied RGBA8 and A8 bytes respectively.
+//! Enums are serialized by their variant names.
+
+use std::fmt;
+use std::sync::Arc;
+
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::paint_server::{Gradient, GradientKind, GradientStop};
+
+/// `IntSize` as `[width, height]`.
+pub(crate) mod int_size {
+    use super::*;
+    use tiny_skia::IntSize;
+
+    pub fn serialize<S: Serializer>(size: &IntSize, serializer: S) -> Result<S::Ok, S::Error> {
+        [size.width(), size.height()].serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<IntSize, D::Error> {
+        let [w, h] = <[u32; 2]>::deserialize(deserializer)?;
+        IntSize::from_wh(w, h).ok_or_else(|| de::Error::custom("invalid size"))
+    }
+}
+
+/// `IntRect` as `[x, y, width, height]`.
+pub(crate) mod int_rect {
+    use super::*;
+    use tiny_skia::IntRect;
+
+    #[derive(Serialize, Deserialize)]
+    struct Rect(i32, i32, u32, u32);
+
+    pub fn serialize<S: Serializer>(rect: &IntRect, serializer: S) -> Result<S::Ok, S::Error> {
+        Rect(rect.x(), rect.y(), rect.width(), rect.height()).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<IntRect, D::Error> {
+        let Rect(x, y, w, h) = Rect::deserialize(deserializer)?;
+        IntRect::from_xywh(x, y, w, h).ok_or_else(|| de::Error::custom("invalid rect"))
+    }
+}
+
+/// `Transform` as `[sx, ky, kx, sy, tx, ty]`.
+pub(crate) mod transform {
+    use super::*;
+    use tiny_skia::Transform;
+
+    pub fn serialize<S: Serializer>(ts: &Transform, serializer: S) -> Result<S::Ok, S::Error> {
+        [ts.sx, ts.ky, ts.kx, ts.sy, ts.tx, ts.ty].serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Transform, D::Error> {
+        let [sx, ky, kx, sy, tx, ty] = <[f32; 6]>::deserialize(deserializer)?;
+        Ok(Transform::from_row(sx, ky, kx, sy, tx, ty))
+    }
+}
+
+/// `Color` as unpremultiplied `[r, g, b, a]`.
+pub(crate) mod color {
+    use super::*;
+    use tiny_skia::Color;
+
+    pub fn serialize<S: Serializer>(c: &Color, serializer: S) -> Result<S::Ok, S::Error> {
+        [c.red(), c.green(), c.blue(), c.alpha()].serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+        let [r, g, b, a] = <[f32; 4]>::deserialize(deserializer)?;
+        Color::from_rgba(r, g, b, a).ok_or_else(|| de::Error::custom("invalid color"))
+    }
+}
+
+/// `Arc<Path>` as a list of segments.
+pub(crate) mod path {
+    use super::*;
+    use tiny_skia::{Path, PathBuilder, PathSegment};
+
+    #[derive(Serialize, Deserialize)]
+    enum Segment {
+        M(f32, f32),
+        L(f32, f32),
+        Q(f32, f32, f32, f32),
+        C(f32, f32, f32, f32, f32, f32),
+        Z,
+    }
+
+    pub(crate) struct PathRef<'a>(pub &'a Path);
+
+    impl Serialize for PathRef<'_> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_seq(self.0.segments().map(|seg| match seg {
+                PathSegment::MoveTo(p) => Segment::M(p.x, p.y),
+                PathSegment::LineTo(p) => Segment::L(p.x, p.y),
+                PathSegment::QuadTo(p1, p) => Segment::Q(p1.x, p1.y, p.x, p.y),
+                PathSegment::CubicTo(p1, p2, p) => Segment::C(p1.x, p1.y, p2.x, p2.y, p.x, p.y),
+                PathSegment::Close => Segment::Z,
+            }))
+        }
+    }
+
+    pub(crate) struct OwnedPath(pub Arc<Path>);
+
+    impl<'de> Deserialize<'de> for OwnedPath {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let mut builder = PathBuilder::new();
+            for seg in Vec::<Segment>::deserialize(deserializer)? {
+                match seg {
+                    Segment::M(x, y) => builder.move_to(x, y),
+                    Segment::L(x, y) => builder.line_to(x, y),
+                    Segment::Q(x1, y1, x, y) => builder.quad_to(x1, y1, x, y),
+                    Segment::C(x1, y1, x2, y2, x, y) => builder.cubic_to(x1, y1, x2, y2, x, y),
+                    Segment::Z => builder.close(),
+                }
+            }
+
+            builder
+                .finish()
+                .map(|path| OwnedPath(Arc::new(path)))
+                .ok_or_else(|| de::Error::custom("invalid path"))
+        }
+    }
+
+    pub fn serialize<S: Serializer>(path: &Arc<Path>, serializer: S) -> Result<S::Ok, S::Error> {
+        PathRef(path).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Arc<Path>, D::Error> {
+        Ok(OwnedPath::deserialize(deserializer)?.0)
+    }
+}
+
+/// `Option<Arc<Path>>` as an optional list of segments.
+pub(crate) mod path_option {
+    use super::path::{OwnedPath, PathRef};
+    use super::*;
+    use tiny_skia::Path;
+
+    pub fn serialize<S: Serializer>(
+        path: &Option<Arc<Path>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        path.as_deref().map(PathRef).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Arc<Path>>, D::Error> {
+        Ok(Option::<OwnedPath>::deserialize(deserializer)?.map(|p| p.0))
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename = "Raster")]
+struct RasterRef<'a> {
+    width: u32,
+    height: u32,
+    #[serde(serialize_with = "serialize_bytes")]
+    data: &'a [u8],
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Raster")]
+struct Raster {
+    width: u32,
+    height: u32,
+    #[serde(deserialize_with = "deserialize_bytes")]
+    data: Vec<u8>,
+}
+
+fn serialize_bytes<S: Serializer>(data: &&[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_bytes(data)
+}
+
+fn deserialize_bytes<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    struct BytesVisitor;
+
+    impl<'de> Visitor<'de> for BytesVisitor {
+        type Value = Vec<u8>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a byte array")
+        }
+
+        fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+            Ok(v.to_vec())
+        }
+
+        fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+            Ok(v)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut data = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+            while let Some(b) = seq.next_element()? {
+                data.push(b);
+            }
+            Ok(data)
+        }
+    }
+
+    deserializer.deserialize_byte_buf(BytesVisitor)
+}
+
+/// `Arc<Pixmap>` as its size and premultiplied RGBA8 data.
+pub(crate) mod pixmap {
+    use super::*;
+    use tiny_skia::{IntSize, Pixmap};
+
+    pub fn serialize<S: Serializer>(
+        pixmap: &Arc<Pixmap>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        RasterRef {
+            width: pixmap.width(),
+            height: pixmap.height(),
+            data: pixmap.data(),
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Arc<Pixmap>, D::Error> {
+        let raster = Raster::deserialize(deserializer)?;
+        IntSize::from_wh(raster.width, raster.height)
+            .and_then(|size| Pixmap::from_vec(raster.data, size))
+            .map(Arc::new)
+            .ok_or_else(|| de::Error::custom("invalid pixmap"))
+    }
+}
+
+/// `Option<Arc<Mask>>` as its optional size and A8 data.
+pub(crate) mod mask_option {
+    use super::*;
+    use tiny_skia::Mask;
+
+    pub fn serialize<S: Serializer>(
+        mask: &Option<Arc<Mask>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        mask.as_ref()
+            .map(|mask| RasterRef {
+                width: mask.width(),
+                height: mask.height(),
+                data: mask.data(),
+            })
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Arc<Mask>>, D::Error> {
+        let raster = match Option::<Raster>::deserialize(deserializer)? {
+            Some(v) => v,
+            None => return Ok(None),
+        };
+
+        let mut mask = Mask::new(raster.width, raster.height)
+            .ok_or_else(|| de::Error::custom("invalid mask"))?;
+        if mask.data().len() != raster.data.len() {
+            return Err(de::Error::custom("invalid mask"));
+        }
+
+        mask.data_mut().copy_from_slice(&raster.data);
+        Ok(Some(Arc::new(mask)))
+    }
+}
+
+/// `Arc<Gradient>` as its kind, stops, spread mode and transform.
+pub(crate) mod gradient {
+    use super::*;
+
+    #[derive(Serialize)]
+    #[serde(rename = "Gradient")]
+    struct GradientRef<'a> {
+        kind: GradientKind,
+        stops: &'a [GradientStop],
+        #[serde(with = "SpreadModeDef")]
+        spread_mode: tiny_skia::SpreadMode,
+        #[serde(with = "transform")]
+        transform: tiny_skia::Transform,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename = "Gradient")]
+    struct OwnedGradient {
+        kind: GradientKind,
+        stops: Vec<GradientStop>,
+        #[serde(with = "SpreadModeDef")]
+        spread_mode: tiny_skia::SpreadMode,
+        #[serde(with = "transform")]
+        transform: tiny_skia::Transform,
+    }
+
+    pub fn serialize<S: Serializer>(
+        gradient: &Arc<Gradient>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        GradientRef {
+            kind: gradient.kind,
+            stops: &gradient.stops,
+            spread_mode: gradient.spread_mode,
+            transform: gradient.transform,
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Arc<Gradient>, D::Error> {
+        let g = OwnedGradient::deserialize(deserializer)?;
+        Gradient::new(g.kind, g.stops, g.spread_mode, g.transform)
+            .map(Arc::new)
+            .ok_or_else(|| de::Error::custom("invalid gradient"))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "tiny_skia::FillRule")]
+pub(crate) enum FillRuleDef {
+    Winding,
+    EvenOdd,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "tiny_skia::SpreadMode")]
+pub(crate) enum SpreadModeDef {
+    Pad,
+    Reflect,
+    Repeat,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "tiny_skia::FilterQuality")]
+pub(crate) enum FilterQualityDef {
+    Nearest,
+    Bilinear,
+    Bicubic,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "tiny_skia::BlendMode")]
+pub(crate) enum BlendModeDef {
+    Clear,
+    Source,
+    Destination,
+    SourceOver,
+    DestinationOver,
+    SourceIn,
+    DestinationIn,
+    SourceOut,
+    DestinationOut,
+    SourceAtop,
+    DestinationAtop,
+    Xor,
+    Plus,
+    Modulate,
+    Screen,
+    Overlay,
+    Darken,
+    Lighten,
+    ColorDodge,
+    ColorBurn,
+    HardLight,
+    SoftLight,
+    Difference,
+    Exclusion,
+    Multiply,
+    Hue,
+    Saturation,
+    Color,
+    Luminosity,
+}
@@ -9,21 +9,39 @@ use crate::image::Image;
 use crate::mask::Mask;
 use crate::path::{FillPath, StrokePath};
 
+/// A group.
 pub struct Group {
+    /// Group's transform.
     pub transform: tiny_skia::Transform,
+    /// Group's opacity.
     pub opacity: usvg::Opacity,
+    /// Group's blend mode.
     pub blend_mode: tiny_skia::BlendMode,
+    /// Group's clip path.
     pub clip_path: Option<ClipPath>,
+    /// Group's mask.
     pub mask: Option<Mask>,
+    /// A list of filters to apply, in order.
     pub filters: Vec<crate::filter::Filter>,
+    /// Whether the group has to be rendered in isolation.
     pub isolate: bool,
+    /// Element's ID. Can be empty.
+    pub id: String,
+    /// Whether the group should be rendered.
+    pub visible: bool,
     /// Group's layer bounding box in canvas coordinates.
     pub bbox: tiny_skia::Rect,
+    /// Whether `bbox` depends on the host transform because of a `vector-effect`.
+    ///
+    /// Such groups cannot be culled and their layers are calculated during rendering.
+    pub host_dependent: bool,
 
+    /// Group's children.
     pub children: Vec<Node>,
 }
 
 impl Group {
+    /// Checks that the group has only a transform and doesn't require a layer.
     pub fn is_transform_only(&self) -> bool {
         self.opacity == usvg::Opacity::ONE
             && self.blend_mode == tiny_skia::BlendMode::SourceOver
@@ -34,14 +52,51 @@ impl Group {
     }
 }
 
+/// A render tree node.
 pub enum Node {
+    /// A group.
     Group(Group), // TODO: box
+    /// A filled path.
     FillPath(FillPath),
+    /// A stroked path.
     StrokePath(StrokePath),
+    /// An image.
     Image(Image),
 }
 
-// - No hidden nodes.
+impl Node {
+    /// Returns node's transform.
+    pub fn transform(&self) -> tiny_skia::Transform {
+        match self {
+            Node::Group(ref group) => group.transform,
+            Node::FillPath(ref path) => path.transform,
+            Node::StrokePath(ref path) => path.transform,
+            Node::Image(ref image) => image.transform,
+        }
+    }
+
+    /// Returns node's ID.
+    pub fn id(&self) -> &str {
+        match self {
+            Node::Group(ref group) => &group.id,
+            Node::FillPath(ref path) => &path.id,
+            Node::StrokePath(ref path) => &path.id,
+            Node::Image(ref image) => &image.id,
+        }
+    }
+
+    /// Checks that the node should be rendered.
+    pub fn is_visible(&self) -> bool {
+        match self {
+            Node::Group(ref group) => group.visible,
+            Node::FillPath(ref path) => path.visible,
+            Node::StrokePath(ref path) => path.visible,
+            Node::Image(ref image) => image.visible,
+        }
+    }
+}
+
+// - No hidden nodes. Nodes can be hidden only after conversion.
 // - No text.
 // - Uses mostly tiny-skia types.
 // - No paint-order. Already resolved.
@@ -82,10 +137,15 @@ impl Tree {
     /// [`usvg::TreeTextToPath::convert_text`].
     pub fn from_usvg(tree: &usvg::Tree) -> Self {
         if tree.has_text_nodes() {
-            log::warn!("Text nodes should be already converted into paths.");
+            usvg::report!(
+                Unsupported,
+                "Text nodes should be already converted into paths."
+            );
         }
 
-        let (children, layer_bbox) = convert_node(tree.root.clone());
+        let ts =
+            usvg::utils::view_box_to_transform(tree.view_box.rect, tree.view_box.aspect, tree.size);
+        let (children, layer_bbox) = convert_node_with_transform(tree.root.clone(), ts);
 
         Self {
             size: tree.size,
@@ -106,7 +166,7 @@ impl Tree {
         {
             bbox
         } else {
-            log::warn!("Node '{}' has zero size.", node.id());
+            usvg::report!(InvalidSize, "Node '{}' has zero size.", node.id());
             return None;
         };
 
@@ -127,8 +187,19 @@ impl Tree {
 }
 
 pub fn convert_node(node: usvg::Node) -> (Vec<Node>, Option<tiny_skia::Rect>) {
+    convert_node_with_transform(node, tiny_skia::Transform::default())
+}
+
+/// Converts a node using the provided canvas transform of its parent.
+///
+/// The transform is used only by elements with a `vector-effect`,
+/// since their bounding boxes depend on the host coordinate system.
+pub fn convert_node_with_transform(
+    node: usvg::Node,
+    transform: tiny_skia::Transform,
+) -> (Vec<Node>, Option<tiny_skia::Rect>) {
     let mut children = Vec::new();
-    let bboxes = convert_node_inner(node, &mut children);
+    let bboxes = convert_node_inner(node, transform, &mut children);
     (children, bboxes.and_then(|b| b.layer.to_rect()))
 }
 
@@ -144,13 +215,23 @@ pub struct BBoxes {
 
     /// Similar to `object`, but expanded to fit the stroke as well.
     pub layer: usvg::BBox,
+
+    /// Bounding boxes were calculated for the default host transform
+    /// and are not precise otherwise.
+    pub host_dependent: bool,
 }
 
-fn convert_node_inner(node: usvg::Node, children: &mut Vec<Node>) -> Option<BBoxes> {
+fn convert_node_inner(
+    node: usvg::Node,
+    transform: tiny_skia::Transform,
+    children: &mut Vec<Node>,
+) -> Option<BBoxes> {
     match &*node.borrow() {
-        usvg::NodeKind::Group(ref ugroup) => convert_group(node.clone(), ugroup, children),
-        usvg::NodeKind::Path(ref upath) => crate::path::convert(upath, children),
-        usvg::NodeKind::Image(ref uimage) => crate::image::convert(uimage, children),
+        usvg::NodeKind::Group(ref ugroup) => {
+            convert_group(node.clone(), ugroup, transform, children)
+        }
+        usvg::NodeKind::Path(ref upath) => crate::path::convert(upath, transform, children),
+        usvg::NodeKind::Image(ref uimage) => crate::image::convert(uimage, transform, children),
         usvg::NodeKind::Text(_) => None, // should be already converted into paths
     }
 }
@@ -158,10 +239,12 @@ fn convert_node_inner(node: usvg::Node, children: &mut Vec<Node>) -> Option<BBox
 fn convert_group(
     node: usvg::Node,
     ugroup: &usvg::Group,
+    transform: tiny_skia::Transform,
     children: &mut Vec<Node>,
 ) -> Option<BBoxes> {
     let mut group_children = Vec::new();
-    let mut bboxes = match convert_children(node, &mut group_children) {
+    let transform = transform.pre_concat(ugroup.transform);
+    let mut bboxes = match convert_children(node, transform, &mut group_children) {
         Some(v) => v,
         None => return convert_empty_group(ugroup, children),
     };
@@ -186,8 +269,11 @@ fn convert_group(
         clip_path: crate::clip::convert(ugroup.clip_path.clone(), bboxes.object.to_rect()?),
         mask: crate::mask::convert(ugroup.mask.clone(), bboxes.object.to_rect()?),
         isolate: ugroup.isolate,
+        id: ugroup.id.clone(),
+        visible: true,
         filters,
         bbox: bboxes.layer.to_rect()?,
+        host_dependent: bboxes.host_dependent,
         children: group_children,
     };
 
@@ -214,8 +300,11 @@ fn convert_empty_group(ugroup: &usvg::Group, children: &mut Vec<Node>) -> Option
         clip_path: None,
         mask: None,
         isolate: ugroup.isolate,
+        id: ugroup.id.clone(),
+        visible: true,
         filters,
         bbox: layer_bbox,
+        host_dependent: false,
         children: Vec::new(),
     };
 
@@ -224,21 +313,27 @@ fn convert_empty_group(ugroup: &usvg::Group, children: &mut Vec<Node>) -> Option
         object: usvg::BBox::default(),
         transformed_object: usvg::BBox::default(),
         layer: usvg::BBox::from(layer_bbox),
+        host_dependent: false,
     };
 
     children.push(Node::Group(group));
     Some(bboxes)
 }
 
-fn convert_children(parent: usvg::Node, children: &mut Vec<Node>) -> Option<BBoxes> {
+fn convert_children(
+    parent: usvg::Node,
+    transform: tiny_skia::Transform,
+    children: &mut Vec<Node>,
+) -> Option<BBoxes> {
     let mut bboxes = BBoxes::default();
 
     for node in parent.children() {
-        if let Some(bboxes2) = convert_node_inner(node, children) {
+        if let Some(bboxes2) = convert_node_inner(node, transform, children) {
             bboxes.object = bboxes.object.expand(bboxes2.object);
             bboxes.transformed_object =
                 bboxes.transformed_object.expand(bboxes2.transformed_object);
             bboxes.layer = bboxes.layer.expand(bboxes2.layer);
+            bboxes.host_dependent |= bboxes2.host_dependent;
         }
     }
 
@@ -249,6 +344,115 @@ fn convert_children(parent: usvg::Node, children: &mut Vec<Node>) -> Option<BBox
     }
 }
 
+/// Calculates a node's layer bounding box in its parent's coordinates.
+///
+/// Returns `None` for nodes with vector effects, since their bounding boxes
+/// depend on the host transform.
+pub fn node_bbox(node: &Node) -> Option<tiny_skia::Rect> {
+    match node {
+        Node::Group(ref group) => {
+            if group.host_dependent {
+                return None;
+            }
+
+            group.bbox.transform(group.transform)
+        }
+        Node::FillPath(ref path) => {
+            if path.vector_effect != usvg::VectorEffect::None {
+                return None;
+            }
+
+            path.path.bounds().transform(path.transform)
+        }
+        Node::StrokePath(ref path) => {
+            if path.vector_effect != usvg::VectorEffect::None {
+                return None;
+            }
+
+            // Like during conversion.
+            let res_scale = tiny_skia::PathStroker::compute_resolution_scale(&path.transform);
+            let outline = path.path.stroke(&path.stroke, res_scale.max(10.0))?;
+            outline.bounds().transform(path.transform)
+        }
+        Node::Image(ref image) => {
+            if image.vector_effect != usvg::VectorEffect::None {
+                return None;
+            }
+
+            image.view_box.rect.to_rect().transform(image.transform)
+        }
+    }
+}
+
+/// Calculates a node's layer bounding box on the canvas, including vector effects.
+///
+/// Unlike [`node_bbox`], uses the actual host transform, therefore can be used
+/// only during rendering.
+pub fn host_node_bbox(
+    node: &Node,
+    transform: tiny_skia::Transform,
+    host: tiny_skia::Transform,
+) -> Option<tiny_skia::Rect> {
+    let effective_transform = |effect, element_ts: tiny_skia::Transform| {
+        crate::geom::apply_vector_effect(effect, transform, element_ts, host)
+            .unwrap_or(transform)
+            .pre_concat(element_ts)
+    };
+
+    match node {
+        Node::Group(ref group) => {
+            let ts = transform.pre_concat(group.transform);
+            if !group.host_dependent || !group.filters.is_empty() {
+                return group.bbox.transform(ts);
+            }
+
+            host_children_bbox(&group.children, ts, host)
+        }
+        Node::FillPath(ref path) => {
+            let ts = effective_transform(path.vector_effect, path.transform);
+            path.path.bounds().transform(ts)
+        }
+        Node::StrokePath(ref path) => {
+            let ts = effective_transform(path.vector_effect, path.transform);
+            if path.vector_effect == usvg::VectorEffect::NonScalingStroke {
+                let outline = crate::path::stroke_non_scaling(&path.path, &path.stroke, ts)?;
+                return outline.bounds().transform(ts);
+            }
+
+            let res_scale = tiny_skia::PathStroker::compute_resolution_scale(&ts);
+            let outline = path.path.stroke(&path.stroke, res_scale.max(10.0))?;
+            outline.bounds().transform(ts)
+        }
+        Node::Image(ref image) => {
+            let ts = effective_transform(image.vector_effect, image.transform);
+            image.view_box.rect.to_rect().transform(ts)
+        }
+    }
+}
+
+/// Calculates a layer bounding box of nodes on the canvas, including vector effects.
+pub fn host_children_bbox(
+    children: &[Node],
+    transform: tiny_skia::Transform,
+    host: tiny_skia::Transform,
+) -> Option<tiny_skia::Rect> {
+    let mut bbox: Option<tiny_skia::Rect> = None;
+    for child in children {
+        if let Some(child_bbox) = host_node_bbox(child, transform, host) {
+            bbox = Some(bbox.map_or(child_bbox, |bbox| union_rects(bbox, child_bbox)));
+        }
+    }
+
+    bbox
+}
+
+pub fn union_rects(r1: tiny_skia::Rect, r2: tiny_skia::Rect) -> tiny_skia::Rect {
+    usvg::BBox::from(r1)
+        .expand(usvg::BBox::from(r2))
+        .to_rect()
+        .unwrap_or(r1)
+}
+
 pub fn convert_blend_mode(mode: usvg::BlendMode) -> tiny_skia::BlendMode {
     match mode {
         usvg::BlendMode::Normal => tiny_skia::BlendMode::SourceOver,
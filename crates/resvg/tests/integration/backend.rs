@@ -0,0 +1,62 @@
+use crate::load_extra;
+
+#[derive(Default)]
+struct Recorder {
+    ops: Vec<String>,
+}
+
+impl resvg::Backend for Recorder {
+    fn fill_path(&mut self, _: &resvg::FillPath, ts: tiny_skia::Transform) {
+        self.ops.push(format!("fill {} {}", ts.tx, ts.ty));
+    }
+
+    fn stroke_path(&mut self, path: &resvg::StrokePath, _: tiny_skia::Transform) {
+        self.ops.push(format!("stroke {}", path.stroke.width));
+    }
+
+    fn draw_image(&mut self, _: &resvg::Image, _: tiny_skia::Transform) {
+        self.ops.push("image".to_string());
+    }
+
+    fn push_layer(
+        &mut self,
+        group: &resvg::Group,
+        ts: tiny_skia::Transform,
+    ) -> Option<tiny_skia::Transform> {
+        self.ops.push(format!("push {}", group.opacity.get()));
+        Some(ts)
+    }
+
+    fn apply_filter(&mut self, _: &resvg::Filter, _: tiny_skia::Transform) {
+        self.ops.push("filter".to_string());
+    }
+
+    fn pop_layer(&mut self, group: &resvg::Group, _: tiny_skia::Transform) {
+        let mut op = "pop".to_string();
+        if group.clip_path.is_some() {
+            op.push_str(" clip");
+        }
+        self.ops.push(op);
+    }
+}
+
+#[test]
+fn custom_backend() {
+    let rtree = resvg::Tree::from_usvg(&load_extra("extra/custom-backend"));
+
+    let mut recorder = Recorder::default();
+    rtree.render_with_backend(tiny_skia::Transform::default(), &mut recorder);
+    assert_eq!(
+        recorder.ops,
+        vec![
+            "fill 10 20",
+            "stroke 3",
+            "push 0.5",
+            "push 1",
+            "fill 0 0",
+            "filter",
+            "pop",
+            "pop clip",
+        ]
+    );
+}
@@ -0,0 +1,80 @@
+use std::cell::RefCell;
+
+use crate::{compare_extra, load_extra};
+
+fn render_with_options(
+    rtree: &resvg::Tree,
+    options: &resvg::RenderOptions,
+) -> (Result<(), resvg::Error>, tiny_skia::Pixmap) {
+    let mut pixmap = tiny_skia::Pixmap::new(200, 200).unwrap();
+    let result = rtree.render_with_options(
+        tiny_skia::Transform::default(),
+        options,
+        &mut pixmap.as_mut(),
+    );
+    (result, pixmap)
+}
+
+#[test]
+fn render_progress() {
+    let rtree = resvg::Tree::from_usvg(&load_extra("extra/render-cancellation"));
+
+    // Progress is reported after each top-level node.
+    let values = RefCell::new(Vec::new());
+    let progress = |v| values.borrow_mut().push(v);
+    let options = resvg::RenderOptions {
+        progress: Some(&progress),
+        ..resvg::RenderOptions::default()
+    };
+    let (result, pixmap) = render_with_options(&rtree, &options);
+    assert_eq!(result, Ok(()));
+    assert_eq!(*values.borrow(), vec![1.0 / 3.0, 2.0 / 3.0, 1.0]);
+    assert_eq!(compare_extra("extra/render-cancellation", pixmap), 0);
+}
+
+#[test]
+fn cancelled_before_rendering() {
+    let rtree = resvg::Tree::from_usvg(&load_extra("extra/render-cancellation"));
+
+    let token = resvg::CancellationToken::new();
+    token.cancel();
+    let options = resvg::RenderOptions {
+        cancellation: Some(token),
+        ..resvg::RenderOptions::default()
+    };
+    let (result, pixmap) = render_with_options(&rtree, &options);
+    assert_eq!(result, Err(resvg::Error::Cancelled));
+    assert!(pixmap.data().iter().all(|c| *c == 0));
+}
+
+#[test]
+fn cancelled_during_rendering() {
+    let rtree = resvg::Tree::from_usvg(&load_extra("extra/render-cancellation"));
+
+    // Cancelled after the first node. The filtered layer must not be drawn.
+    let token = resvg::CancellationToken::new();
+    let cancel = |_| token.cancel();
+    let options = resvg::RenderOptions {
+        cancellation: Some(token.clone()),
+        progress: Some(&cancel),
+        ..resvg::RenderOptions::default()
+    };
+    let (result, pixmap) = render_with_options(&rtree, &options);
+    assert_eq!(result, Err(resvg::Error::Cancelled));
+    assert_eq!(
+        compare_extra("extra/render-cancellation-partial", pixmap),
+        0
+    );
+}
+
+#[test]
+fn deadline_exceeded() {
+    let rtree = resvg::Tree::from_usvg(&load_extra("extra/render-cancellation"));
+
+    let options = resvg::RenderOptions {
+        deadline: Some(std::time::Instant::now()),
+        ..resvg::RenderOptions::default()
+    };
+    let (result, _) = render_with_options(&rtree, &options);
+    assert_eq!(result, Err(resvg::Error::DeadlineExceeded));
+}
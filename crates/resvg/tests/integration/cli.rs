@@ -0,0 +1,200 @@
+use std::process::{Command, Output};
+
+// A red square with a small green circle in the top-left corner.
+const ICON_SVG: &str = "
+<svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 100 100'>
+    <rect id='background' width='100' height='100' fill='red'/>
+    <circle id='dot' cx='10' cy='10' r='10' fill='green'/>
+</svg>
+";
+
+fn run_resvg(name: &str, args: &[&str]) -> Output {
+    let svg_path = std::env::temp_dir().join(format!("resvg-cli-{}.svg", name));
+    std::fs::write(&svg_path, ICON_SVG).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_resvg"))
+        .args(args)
+        .arg(&svg_path)
+        .arg("-c")
+        .output()
+        .unwrap();
+
+    std::fs::remove_file(&svg_path).unwrap();
+    output
+}
+
+fn render(name: &str, args: &[&str]) -> Vec<u8> {
+    let output = run_resvg(name, args);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output.stdout
+}
+
+fn render_error(name: &str, args: &[&str]) -> String {
+    let output = run_resvg(name, args);
+    assert!(!output.status.success());
+    String::from_utf8(output.stderr).unwrap()
+}
+
+fn u16_le(data: &[u8], pos: usize) -> u16 {
+    u16::from_le_bytes([data[pos], data[pos + 1]])
+}
+
+fn u32_le(data: &[u8], pos: usize) -> u32 {
+    u32::from_le_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]])
+}
+
+fn u32_be(data: &[u8], pos: usize) -> u32 {
+    u32::from_be_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]])
+}
+
+fn decode_png(data: &[u8]) -> tiny_skia::Pixmap {
+    assert!(data.starts_with(b"\x89PNG\r\n\x1a\n"));
+    tiny_skia::Pixmap::decode_png(data).unwrap()
+}
+
+// Returns PNG images from an ICO file, after checking its directory.
+fn parse_ico(data: &[u8]) -> Vec<(u8, tiny_skia::Pixmap)> {
+    assert_eq!(u16_le(data, 0), 0); // reserved
+    assert_eq!(u16_le(data, 2), 1); // image type: icon
+    let count = u16_le(data, 4) as usize;
+
+    let mut images = Vec::new();
+    let mut expected_offset = 6 + 16 * count;
+    for i in 0..count {
+        let entry = &data[6 + 16 * i..6 + 16 * (i + 1)];
+        assert_eq!(entry[0], entry[1]); // width and height
+        assert_eq!(entry[2], 0); // no palette
+        assert_eq!(entry[3], 0); // reserved
+        assert_eq!(u16_le(entry, 4), 1); // color planes
+        assert_eq!(u16_le(entry, 6), 32); // bits per pixel
+
+        let len = u32_le(entry, 8) as usize;
+        let offset = u32_le(entry, 12) as usize;
+        assert_eq!(offset, expected_offset);
+        expected_offset += len;
+
+        images.push((entry[0], decode_png(&data[offset..offset + len])));
+    }
+
+    assert_eq!(expected_offset, data.len());
+    images
+}
+
+// Returns PNG entries from an ICNS file, after checking its header.
+fn parse_icns(data: &[u8]) -> Vec<([u8; 4], &[u8])> {
+    assert_eq!(&data[0..4], b"icns");
+    assert_eq!(u32_be(data, 4) as usize, data.len());
+
+    let mut entries = Vec::new();
+    let mut pos = 8;
+    while pos < data.len() {
+        let kind = [data[pos], data[pos + 1], data[pos + 2], data[pos + 3]];
+        let len = u32_be(data, pos + 4) as usize;
+        assert!(len > 8);
+        entries.push((kind, &data[pos + 8..pos + len]));
+        pos += len;
+    }
+
+    assert_eq!(pos, data.len());
+    entries
+}
+
+#[test]
+fn ico_output() {
+    let data = render("ico", &["--format", "ico", "--icon-sizes", "16,48,256"]);
+    let images = parse_ico(&data);
+
+    // 256 is stored as 0.
+    let sizes: Vec<_> = images.iter().map(|(size, _)| *size).collect();
+    assert_eq!(sizes, [16, 48, 0]);
+
+    for ((_, pixmap), size) in images.iter().zip([16, 48, 256]) {
+        assert_eq!(pixmap.width(), size);
+        assert_eq!(pixmap.height(), size);
+    }
+}
+
+#[test]
+fn ico_default_sizes() {
+    let images = parse_ico(&render("ico-default", &["--format", "ico"]));
+    let sizes: Vec<_> = images.iter().map(|(_, pixmap)| pixmap.width()).collect();
+    assert_eq!(sizes, [16, 24, 32, 48, 64, 128, 256]);
+}
+
+#[test]
+fn icns_output() {
+    let data = render("icns", &["--format", "icns", "--icon-sizes", "16,32,64"]);
+    let entries = parse_icns(&data);
+
+    let kinds: Vec<_> = entries.iter().map(|(kind, _)| kind).collect();
+    assert_eq!(kinds, [b"icp4", b"icp5", b"ic11", b"icp6", b"ic12"]);
+
+    for ((_, png), size) in entries.iter().zip([16, 32, 32, 64, 64]) {
+        assert_eq!(decode_png(png).width(), size);
+    }
+
+    // Retina types reuse images of the doubled size.
+    assert_eq!(entries[1].1, entries[2].1);
+    assert_eq!(entries[3].1, entries[4].1);
+}
+
+#[test]
+fn icon_export_id_per_size() {
+    let data = render(
+        "ico-export-id",
+        &["--format", "ico", "--icon-sizes", "16:dot,32"],
+    );
+    let images = parse_ico(&data);
+
+    // The 16px image contains only the circle and the 32px one the whole image.
+    let center = |pixmap: &tiny_skia::Pixmap| {
+        let c = pixmap
+            .pixel(pixmap.width() / 2, pixmap.height() / 2)
+            .unwrap();
+        (c.red(), c.green(), c.blue(), c.alpha())
+    };
+    assert_eq!(center(&images[0].1), (0, 128, 0, 255));
+    assert_eq!(center(&images[1].1), (255, 0, 0, 255));
+}
+
+#[test]
+fn export_id_with_width() {
+    let pixmap = decode_png(&render(
+        "export-id",
+        &["--export-id", "dot", "--width", "100"],
+    ));
+    assert_eq!((pixmap.width(), pixmap.height()), (100, 100));
+
+    // The circle fills the whole image and not only its top-left corner.
+    let c = pixmap.pixel(50, 50).unwrap();
+    assert_eq!((c.red(), c.green(), c.blue(), c.alpha()), (0, 128, 0, 255));
+}
+
+#[test]
+fn invalid_icon_sizes() {
+    let check = |sizes: &str, error: &str| {
+        let stderr = render_error("invalid-sizes", &["--format", "ico", "--icon-sizes", sizes]);
+        assert!(stderr.contains(error), "{}", stderr);
+    };
+
+    check("16,abc", "invalid icon size");
+    check("0", "icon size out of bounds");
+    check("1025", "icon size out of bounds");
+    check("16,32,16", "icon size 16 is set multiple times");
+    check("16:", "icon ID cannot be empty");
+    check("512", "ICO images cannot be larger than 256 pixels");
+
+    let stderr = render_error(
+        "invalid-icns-size",
+        &["--format", "icns", "--icon-sizes", "48"],
+    );
+    assert!(
+        stderr.contains("48 is not a valid ICNS image size"),
+        "{}",
+        stderr
+    );
+}
@@ -0,0 +1,94 @@
+use resvg::display_list::{Command, DisplayList, Paint};
+
+use crate::{compare_extra, load_extra, render_extra_with_scale};
+
+fn record(name: &str) -> DisplayList {
+    let rtree = resvg::Tree::from_usvg(&load_extra(name));
+    let ts = tiny_skia::Transform::from_scale(2.0, 2.0);
+    let size = tiny_skia::IntSize::from_wh(400, 400).unwrap();
+    rtree.to_display_list(ts, size)
+}
+
+fn replay(list: &DisplayList) -> tiny_skia::Pixmap {
+    let mut pixmap = tiny_skia::Pixmap::new(400, 400).unwrap();
+    list.replay(&mut pixmap.as_mut());
+    pixmap
+}
+
+#[test]
+fn display_list() {
+    let list = record("extra/display-list");
+    let kinds: Vec<_> = list
+        .commands
+        .iter()
+        .map(|c| match c {
+            Command::Fill(ref fill) => match fill.paint {
+                Paint::Color(_) => "fill color",
+                Paint::Gradient(_) => "fill gradient",
+                Paint::Pattern(_) => "fill pattern",
+            },
+            Command::PushLayer(_) => "push",
+            Command::PopLayer => "pop",
+            Command::DrawImage(_) => "image",
+        })
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            "fill gradient",
+            "fill color",
+            "push",
+            "fill pattern",
+            "pop",
+            "push",
+            "fill color",
+            "pop",
+            "image",
+        ]
+    );
+
+    // Paths are in the device space.
+    let text = list.to_string();
+    assert!(text.starts_with("size 400x400\nfill nonzero linear(0 0 1 0; Pad; matrix(160 0 0 160 20 20); 0 #ff0000ff; 1 #0000ffff) \"M 20 20 L 180 20 L 180 180 L 20 180 Z\"\n"), "{}", text);
+
+    assert_eq!(render_extra_with_scale("extra/display-list", 2.0), 0);
+    assert_eq!(compare_extra("extra/display-list", replay(&list)), 0);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn display_list_serde_round_trip() {
+    let list = record("extra/display-list-serde");
+    let json = serde_json::to_string(&list).unwrap();
+    let list2: DisplayList = serde_json::from_str(&json).unwrap();
+    assert!(list == list2);
+    assert_eq!(serde_json::to_string(&list2).unwrap(), json);
+
+    // tiny-skia types have stable encodings.
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(value["size"], serde_json::json!([400, 400]));
+    let fill = &value["commands"][0]["Fill"];
+    assert_eq!(fill["rule"], "EvenOdd");
+    assert_eq!(fill["path"][0], serde_json::json!({ "M": [20.0, 20.0] }));
+    assert_eq!(fill["paint"]["Gradient"]["spread_mode"], "Reflect");
+    let layer = &value["commands"][1]["PushLayer"];
+    assert_eq!(layer["blend_mode"], "Multiply");
+    assert_eq!(layer["clip"]["width"], layer["bbox"][2]);
+
+    assert_eq!(render_extra_with_scale("extra/display-list-serde", 2.0), 0);
+    assert_eq!(compare_extra("extra/display-list-serde", replay(&list2)), 0);
+}
+
+#[test]
+fn display_list_non_scaling_stroke() {
+    // Dashes are applied in the host coordinate system, like when rendering.
+    let list = record("extra/display-list-non-scaling-stroke");
+    assert_eq!(
+        render_extra_with_scale("extra/display-list-non-scaling-stroke", 2.0),
+        0
+    );
+    assert_eq!(
+        compare_extra("extra/display-list-non-scaling-stroke", replay(&list)),
+        0
+    );
+}
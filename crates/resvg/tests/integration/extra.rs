@@ -63,6 +63,22 @@ fn filter_region_precision() {
     );
 }
 
+#[test]
+fn non_scaling_stroke_in_layer_with_zoom() {
+    assert_eq!(
+        render_extra_with_scale("extra/non-scaling-stroke-in-layer-with-zoom", 0.25),
+        0
+    );
+}
+
+#[test]
+fn fixed_position_in_clip_path() {
+    assert_eq!(
+        render_extra_with_scale("extra/fixed-position-in-clip-path", 0.5),
+        0
+    );
+}
+
 #[test]
 fn translate_outside_viewbox() {
     assert_eq!(render_extra("extra/translate-outside-viewbox"), 0);
@@ -0,0 +1,44 @@
+use crate::load_extra;
+
+#[test]
+fn hit_test() {
+    let rtree = resvg::Tree::from_usvg(&load_extra("extra/hit-test"));
+    let options = resvg::HitTestOptions::default();
+
+    let hit = |x: f32, y: f32| -> Option<String> {
+        let path = rtree.hit_test(tiny_skia::Point::from_xy(x, y), &options)?;
+        Some(rtree.node(&path).unwrap().id().to_string())
+    };
+
+    assert_eq!(hit(50.0, 50.0).as_deref(), Some("circle"));
+    // Inside the bbox, but outside the circle.
+    assert_eq!(hit(15.0, 85.0), None);
+    // Dashes and the transform.
+    assert_eq!(hit(10.0, 103.0).as_deref(), Some("line"));
+    assert_eq!(hit(30.0, 103.0), None);
+    assert_eq!(hit(10.0, 98.0), None);
+    // Stroke only, partially clipped.
+    assert_eq!(hit(111.0, 50.0).as_deref(), Some("ring"));
+    assert_eq!(hit(150.0, 50.0), None);
+    assert_eq!(hit(189.0, 50.0), None);
+    // The mask value is below the threshold on the right.
+    assert_eq!(hit(50.0, 175.0).as_deref(), Some("masked"));
+    assert_eq!(hit(120.0, 175.0), None);
+    // Hidden, but with `pointer-events=all`. Hit before the circle, because it's on top.
+    assert_eq!(hit(15.0, 15.0).as_deref(), Some("hidden"));
+    assert_eq!(hit(180.0, 180.0), None);
+
+    let options = resvg::HitTestOptions {
+        mask_threshold: 0.2,
+    };
+    let path = rtree.hit_test(tiny_skia::Point::from_xy(120.0, 175.0), &options);
+    assert_eq!(rtree.node(&path.unwrap()).unwrap().id(), "masked");
+
+    let rect = tiny_skia::Rect::from_xywh(75.0, 75.0, 50.0, 50.0).unwrap();
+    let ids: Vec<_> = rtree
+        .hit_test_rect(rect, &resvg::HitTestOptions::default())
+        .iter()
+        .map(|path| rtree.node(path).unwrap().id().to_string())
+        .collect();
+    assert_eq!(ids, ["circle", "line", "ring"]);
+}
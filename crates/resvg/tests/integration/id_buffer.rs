@@ -0,0 +1,32 @@
+use crate::load_extra;
+
+#[test]
+fn id_buffer() {
+    let rtree = resvg::Tree::from_usvg(&load_extra("extra/id-buffer"));
+    let size = tiny_skia::IntSize::from_wh(200, 200).unwrap();
+    let buffer = rtree.render_id_buffer(tiny_skia::Transform::from_scale(2.0, 2.0), size);
+
+    assert_eq!(buffer.data.len(), 200 * 200);
+    assert_eq!(buffer.paths.len(), buffer.ids.len());
+    assert_eq!(buffer.ids[0], "");
+
+    // A child without an ID uses the group's one.
+    assert_eq!(buffer.id(100, 80), Some("group"));
+    // Clipped out.
+    assert_eq!(buffer.id(100, 120), Some("back"));
+    // Hidden nodes are not drawn.
+    assert_eq!(buffer.id(5, 5), Some("back"));
+    // Fill and stroke of a node without an ID.
+    assert!(buffer.index(190, 190).is_some());
+    assert_eq!(buffer.id(190, 190), None);
+    assert_eq!(buffer.index(200, 0), None);
+
+    let index = buffer.index(100, 80).unwrap() as usize;
+    let node = rtree.node(&buffer.paths[index]).unwrap();
+    assert!(matches!(node, resvg::Node::FillPath(_)));
+
+    // The background covers the whole buffer and every drawn node is in the table.
+    let indices: std::collections::HashSet<_> = buffer.data.iter().copied().collect();
+    assert_eq!(indices.len(), buffer.paths.len() - 1);
+    assert!(!indices.contains(&0));
+}
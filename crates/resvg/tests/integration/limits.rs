@@ -0,0 +1,39 @@
+use crate::{compare_extra, load_extra};
+
+fn render_with_limits(
+    name: &str,
+    limits: &usvg::Limits,
+) -> (Result<(), resvg::Error>, tiny_skia::Pixmap) {
+    let rtree = resvg::Tree::from_usvg(&load_extra(name));
+    let mut pixmap = tiny_skia::Pixmap::new(200, 200).unwrap();
+    let result = rtree.render_with_limits(
+        tiny_skia::Transform::default(),
+        limits,
+        &mut pixmap.as_mut(),
+    );
+    (result, pixmap)
+}
+
+fn small_surface_limits() -> usvg::Limits {
+    usvg::Limits {
+        max_surface_pixels: 100 * 100,
+        ..usvg::Limits::default()
+    }
+}
+
+#[test]
+fn surface_limit() {
+    let (result, pixmap) = render_with_limits("extra/surface-limit", &small_surface_limits());
+    assert_eq!(result, Err(resvg::Error::SurfaceLimitReached));
+    assert!(pixmap.data().iter().all(|c| *c == 0));
+
+    let (result, pixmap) = render_with_limits("extra/surface-limit", &usvg::Limits::default());
+    assert_eq!(result, Ok(()));
+    assert_eq!(compare_extra("extra/surface-limit", pixmap), 0);
+}
+
+#[test]
+fn pattern_surface_limit() {
+    let (result, _) = render_with_limits("extra/pattern-surface-limit", &small_surface_limits());
+    assert_eq!(result, Err(resvg::Error::SurfaceLimitReached));
+}
@@ -5,7 +5,22 @@ use usvg::{fontdb, TreeParsing, TreeTextToPath};
 #[rustfmt::skip]
 mod render;
 
+mod backend;
+mod cancellation;
+mod cli;
+mod display_list;
 mod extra;
+mod hit_test;
+mod id_buffer;
+mod limits;
+mod overrides;
+#[cfg(feature = "parallel")]
+mod parallel;
+#[cfg(feature = "pdf")]
+mod pdf;
+mod region;
+mod retained;
+mod sync;
 
 const IMAGE_SIZE: u32 = 300;
 
@@ -77,25 +92,16 @@ pub fn render(name: &str) -> usize {
     pixels_d
 }
 
-pub fn render_extra_with_scale(name: &str, scale: f32) -> usize {
-    let svg_path = format!("tests/{}.svg", name);
-    let png_path = format!("tests/{}.png", name);
-
-    let opt = usvg::Options::default();
-
-    let tree = {
-        let svg_data = std::fs::read(&svg_path).unwrap();
-        usvg::Tree::from_data(&svg_data, &opt).unwrap()
-    };
-    let rtree = resvg::Tree::from_usvg(&tree);
-
-    let size = rtree.size.to_int_size().scale_by(scale).unwrap();
-    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height()).unwrap();
+pub fn load_extra(name: &str) -> usvg::Tree {
+    let svg_data = std::fs::read(format!("tests/{}.svg", name)).unwrap();
+    usvg::Tree::from_data(&svg_data, &usvg::Options::default()).unwrap()
+}
 
-    let render_ts = tiny_skia::Transform::from_scale(scale, scale);
-    rtree.render(render_ts, &mut pixmap.as_mut());
+// Returns the number of pixels that differ from the `tests/{name}.png` reference image.
+pub fn compare_extra(name: &str, pixmap: tiny_skia::Pixmap) -> usize {
+    let png_path = format!("tests/{}.png", name);
 
-    // pixmap.save_png(&format!("tests/{}.png", name)).unwrap();
+    // pixmap.save_png(&png_path).unwrap();
 
     let mut rgba = pixmap.take();
     demultiply_alpha(rgba.as_mut_slice().as_rgba_mut());
@@ -123,6 +129,19 @@ pub fn render_extra_with_scale(name: &str, scale: f32) -> usize {
     pixels_d
 }
 
+pub fn render_extra_with_scale(name: &str, scale: f32) -> usize {
+    let tree = load_extra(name);
+    let rtree = resvg::Tree::from_usvg(&tree);
+
+    let size = rtree.size.to_int_size().scale_by(scale).unwrap();
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height()).unwrap();
+
+    let render_ts = tiny_skia::Transform::from_scale(scale, scale);
+    rtree.render(render_ts, &mut pixmap.as_mut());
+
+    compare_extra(name, pixmap)
+}
+
 pub fn render_extra(name: &str) -> usize {
     render_extra_with_scale(name, 1.0)
 }
@@ -0,0 +1,80 @@
+use crate::{compare_extra, load_extra};
+
+fn render_with_options(rtree: &resvg::Tree, options: &resvg::RenderOptions) -> tiny_skia::Pixmap {
+    let mut pixmap = tiny_skia::Pixmap::new(200, 200).unwrap();
+    rtree
+        .render_with_options(
+            tiny_skia::Transform::default(),
+            options,
+            &mut pixmap.as_mut(),
+        )
+        .unwrap();
+    pixmap
+}
+
+fn render_with_overrides(
+    rtree: &resvg::Tree,
+    overrides: &resvg::NodeOverrides,
+) -> tiny_skia::Pixmap {
+    let options = resvg::RenderOptions {
+        overrides: Some(overrides),
+        ..resvg::RenderOptions::default()
+    };
+    render_with_options(rtree, &options)
+}
+
+#[test]
+fn node_ids() {
+    let rtree = resvg::Tree::from_usvg(&load_extra("extra/node-overrides"));
+    let ids: Vec<&str> = match rtree.children() {
+        [resvg::Node::Group(ref root)] => root.children.iter().map(|node| node.id()).collect(),
+        _ => unreachable!(),
+    };
+    assert_eq!(ids, ["a", "g"]);
+}
+
+#[test]
+fn hide_and_only() {
+    let rtree = resvg::Tree::from_usvg(&load_extra("extra/node-overrides"));
+
+    let mut overrides = resvg::NodeOverrides::default();
+    overrides.hide.insert("a".to_string());
+    overrides.hide.insert("c".to_string());
+    let pixmap = render_with_overrides(&rtree, &overrides);
+    assert_eq!(compare_extra("extra/node-overrides-hide", pixmap), 0);
+
+    // Ancestors of listed nodes keep their opacity.
+    let mut overrides = resvg::NodeOverrides::default();
+    overrides.only.insert("b".to_string());
+    let pixmap = render_with_overrides(&rtree, &overrides);
+    assert_eq!(compare_extra("extra/node-overrides-hide", pixmap), 0);
+
+    let mut overrides = resvg::NodeOverrides::default();
+    overrides.only.insert("g".to_string());
+    overrides.hide.insert("c".to_string());
+    let pixmap = render_with_overrides(&rtree, &overrides);
+    assert_eq!(compare_extra("extra/node-overrides-hide", pixmap), 0);
+}
+
+#[test]
+fn opacity() {
+    let rtree = resvg::Tree::from_usvg(&load_extra("extra/node-overrides"));
+
+    // Fill and stroke are blended together.
+    let mut overrides = resvg::NodeOverrides::default();
+    overrides
+        .opacity
+        .insert("c".to_string(), usvg::Opacity::new_clamped(0.5));
+    let pixmap = render_with_overrides(&rtree, &overrides);
+    assert_eq!(compare_extra("extra/node-overrides-opacity", pixmap), 0);
+
+    // Rendering with progress reporting takes a different path.
+    let progress = |_| {};
+    let options = resvg::RenderOptions {
+        overrides: Some(&overrides),
+        progress: Some(&progress),
+        ..resvg::RenderOptions::default()
+    };
+    let pixmap = render_with_options(&rtree, &options);
+    assert_eq!(compare_extra("extra/node-overrides-opacity", pixmap), 0);
+}
@@ -0,0 +1,67 @@
+use crate::{compare_extra, load_extra, render_extra_with_scale};
+
+#[test]
+fn render_parallel() {
+    assert_eq!(render_extra_with_scale("extra/render-parallel", 2.0), 0);
+
+    let rtree = resvg::Tree::from_usvg(&load_extra("extra/render-parallel"));
+    let mut pixmap = tiny_skia::Pixmap::new(400, 400).unwrap();
+    rtree.render_parallel(
+        tiny_skia::Transform::from_scale(2.0, 2.0),
+        &mut pixmap.as_mut(),
+    );
+    assert_eq!(compare_extra("extra/render-parallel", pixmap), 0);
+}
+
+#[test]
+fn render_parallel_with_options() {
+    let rtree = resvg::Tree::from_usvg(&load_extra("extra/render-parallel-with-options"));
+    let ts = tiny_skia::Transform::from_scale(2.0, 2.0);
+
+    let mut overrides = resvg::NodeOverrides::default();
+    overrides.hide.insert("blue".to_string());
+    overrides
+        .opacity
+        .insert("orange".to_string(), usvg::Opacity::new_clamped(0.5));
+
+    let last_progress = std::cell::Cell::new(0.0);
+    let on_progress = |v: f32| last_progress.set(v);
+    let options = resvg::RenderOptions {
+        overrides: Some(&overrides),
+        progress: Some(&on_progress),
+        ..resvg::RenderOptions::default()
+    };
+
+    let mut pixmap = tiny_skia::Pixmap::new(400, 400).unwrap();
+    rtree
+        .render_with_options(ts, &options, &mut pixmap.as_mut())
+        .unwrap();
+    assert_eq!(
+        compare_extra("extra/render-parallel-with-options", pixmap),
+        0
+    );
+
+    let mut pixmap = tiny_skia::Pixmap::new(400, 400).unwrap();
+    rtree
+        .render_parallel_with_options(ts, &options, &mut pixmap.as_mut())
+        .unwrap();
+    assert_eq!(
+        compare_extra("extra/render-parallel-with-options", pixmap),
+        0
+    );
+    assert_eq!(last_progress.get(), 1.0);
+
+    let cancellation = resvg::CancellationToken::new();
+    cancellation.cancel();
+    let options = resvg::RenderOptions {
+        cancellation: Some(cancellation),
+        ..resvg::RenderOptions::default()
+    };
+
+    let mut pixmap = tiny_skia::Pixmap::new(400, 400).unwrap();
+    assert_eq!(
+        rtree.render_parallel_with_options(ts, &options, &mut pixmap.as_mut()),
+        Err(resvg::Error::Cancelled)
+    );
+    assert!(pixmap.data().iter().all(|&v| v == 0));
+}
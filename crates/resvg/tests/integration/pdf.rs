@@ -0,0 +1,38 @@
+use crate::load_extra;
+
+#[test]
+fn pdf_output() {
+    let tree = load_extra("extra/pdf-output");
+    let data = resvg::pdf::convert(&tree, &resvg::pdf::Options::default());
+    let text = String::from_utf8_lossy(&data);
+
+    assert!(text.starts_with("%PDF-1.7"));
+    assert!(text.ends_with("%%EOF\n"));
+    // 200px are 150pt.
+    assert!(text.contains("/MediaBox [0 0 150 150]"));
+    assert!(text.contains("/ShadingType 2"));
+    assert!(text.contains("/PatternType 1"));
+    assert!(text.contains("/SMask"));
+    // The filter is rasterized.
+    assert!(text.contains("/Subtype /Image"));
+
+    // Check that the cross-reference table points to objects.
+    let tail = std::str::from_utf8(&data[data.len() - 32..]).unwrap();
+    let start = tail.rfind("startxref\n").unwrap() + "startxref\n".len();
+    let xref_offset: usize = tail[start..].lines().next().unwrap().parse().unwrap();
+    let xref = std::str::from_utf8(&data[xref_offset..]).unwrap();
+    assert!(xref.starts_with("xref\n"));
+    for (i, line) in xref.lines().skip(3).enumerate() {
+        if line == "trailer" {
+            break;
+        }
+
+        if line.ends_with(" f ") {
+            continue;
+        }
+
+        let offset: usize = line[..10].parse().unwrap();
+        let object = format!("{} 0 obj\n", i + 1);
+        assert!(data[offset..].starts_with(object.as_bytes()));
+    }
+}
@@ -0,0 +1,57 @@
+use crate::{compare_extra, load_extra, render_extra_with_scale};
+
+#[test]
+fn render_region() {
+    let scale = 1.5;
+    assert_eq!(render_extra_with_scale("extra/render-region", scale), 0);
+
+    // Tiles must match the whole image, including filters and layers crossing their edges.
+    let rtree = resvg::Tree::from_usvg(&load_extra("extra/render-region"));
+    let mut tiles = tiny_skia::Pixmap::new(300, 300).unwrap();
+    let tile_size = 100;
+    for y in 0..3 {
+        for x in 0..3 {
+            let region = tiny_skia::IntRect::from_xywh(
+                x * tile_size as i32,
+                y * tile_size as i32,
+                tile_size,
+                tile_size,
+            )
+            .unwrap();
+
+            let mut tile = tiny_skia::Pixmap::new(tile_size, tile_size).unwrap();
+            rtree.render_region(region, scale, &mut tile.as_mut());
+
+            tiles.draw_pixmap(
+                region.x(),
+                region.y(),
+                tile.as_ref(),
+                &tiny_skia::PixmapPaint::default(),
+                tiny_skia::Transform::default(),
+                None,
+            );
+        }
+    }
+
+    assert_eq!(compare_extra("extra/render-region", tiles), 0);
+}
+
+#[test]
+fn render_region_with_large_scale() {
+    let rtree = resvg::Tree::from_usvg(&load_extra("extra/render-region-zoom"));
+
+    let mut pixmap = tiny_skia::Pixmap::new(200, 200).unwrap();
+    rtree.render(tiny_skia::Transform::default(), &mut pixmap.as_mut());
+    let expected = pixmap.pixel(100, 100).unwrap();
+    assert_eq!(expected.alpha(), 128);
+
+    // The group layer must be limited to the region, otherwise it cannot be allocated.
+    // And at the largest scale, the doubled image width doesn't fit into `i32`.
+    for scale in [1000.0, 6_000_000.0] {
+        let center = (100.0 * scale) as i32;
+        let region = tiny_skia::IntRect::from_xywh(center, center, 64, 64).unwrap();
+        let mut tile = tiny_skia::Pixmap::new(64, 64).unwrap();
+        rtree.render_region(region, scale, &mut tile.as_mut());
+        assert!(tile.pixels().iter().all(|p| *p == expected), "{}", scale);
+    }
+}
@@ -912,6 +912,16 @@ use crate::render;
 #[test] fn painting_stroke_width_negative() { assert_eq!(render("tests/painting/stroke-width/negative"), 0); }
 #[test] fn painting_stroke_width_percentage() { assert_eq!(render("tests/painting/stroke-width/percentage"), 0); }
 #[test] fn painting_stroke_width_zero() { assert_eq!(render("tests/painting/stroke-width/zero"), 0); }
+#[test] fn painting_vector_effect_fixed_position_in_group_with_opacity() { assert_eq!(render("tests/painting/vector-effect/fixed-position-in-group-with-opacity"), 0); }
+#[test] fn painting_vector_effect_fixed_position() { assert_eq!(render("tests/painting/vector-effect/fixed-position"), 0); }
+#[test] fn painting_vector_effect_non_rotation() { assert_eq!(render("tests/painting/vector-effect/non-rotation"), 0); }
+#[test] fn painting_vector_effect_non_scaling_size_on_image() { assert_eq!(render("tests/painting/vector-effect/non-scaling-size-on-image"), 0); }
+#[test] fn painting_vector_effect_non_scaling_size() { assert_eq!(render("tests/painting/vector-effect/non-scaling-size"), 0); }
+#[test] fn painting_vector_effect_non_scaling_stroke_on_group() { assert_eq!(render("tests/painting/vector-effect/non-scaling-stroke-on-group"), 0); }
+#[test] fn painting_vector_effect_non_scaling_stroke_with_dasharray() { assert_eq!(render("tests/painting/vector-effect/non-scaling-stroke-with-dasharray"), 0); }
+#[test] fn painting_vector_effect_non_scaling_stroke_with_gradient() { assert_eq!(render("tests/painting/vector-effect/non-scaling-stroke-with-gradient"), 0); }
+#[test] fn painting_vector_effect_non_scaling_stroke_with_non_uniform_scale() { assert_eq!(render("tests/painting/vector-effect/non-scaling-stroke-with-non-uniform-scale"), 0); }
+#[test] fn painting_vector_effect_non_scaling_stroke() { assert_eq!(render("tests/painting/vector-effect/non-scaling-stroke"), 0); }
 #[test] fn painting_visibility_bbox_impact_1() { assert_eq!(render("tests/painting/visibility/bbox-impact-1"), 0); }
 #[test] fn painting_visibility_bbox_impact_2() { assert_eq!(render("tests/painting/visibility/bbox-impact-2"), 0); }
 #[test] fn painting_visibility_bbox_impact_3() { assert_eq!(render("tests/painting/visibility/bbox-impact-3"), 0); }
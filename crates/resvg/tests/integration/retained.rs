@@ -0,0 +1,51 @@
+use crate::{compare_extra, load_extra, render_extra_with_scale};
+
+#[test]
+fn retained_rendering() {
+    let rtree = resvg::Tree::from_usvg(&load_extra("extra/retained-rendering"));
+    let mut renderer = resvg::RetainedRenderer::new(
+        rtree,
+        tiny_skia::Transform::from_scale(2.0, 2.0),
+        tiny_skia::IntSize::from_wh(400, 400).unwrap(),
+    );
+
+    let mut pixmap = tiny_skia::Pixmap::new(400, 400).unwrap();
+    let full = tiny_skia::IntRect::from_xywh(0, 0, 400, 400).unwrap();
+    assert_eq!(renderer.render(&mut pixmap.as_mut()), Some(full));
+    assert_eq!(renderer.render(&mut pixmap.as_mut()), None);
+    assert_eq!(render_extra_with_scale("extra/retained-rendering", 2.0), 0);
+    assert_eq!(compare_extra("extra/retained-rendering", pixmap.clone()), 0);
+
+    // Move the red rect by 100 and make it orange.
+    let rect = [0, 0];
+    assert!(matches!(
+        renderer.tree().node(&rect),
+        Some(resvg::Node::FillPath(_))
+    ));
+    let color = tiny_skia::Color::from_rgba8(255, 165, 0, 255);
+    assert!(renderer.update_node(
+        &rect,
+        resvg::NodeUpdate::Transform(tiny_skia::Transform::from_translate(100.0, 0.0))
+    ));
+    assert!(renderer.update_node(&rect, resvg::NodeUpdate::Paint(resvg::Paint::Color(color))));
+    // Old and new positions: 10..160 x 10..60, scaled by 2 and expanded by 2px.
+    let damage = tiny_skia::IntRect::from_ltrb(18, 18, 322, 122).unwrap();
+    assert_eq!(renderer.damage(), Some(damage));
+    assert_eq!(renderer.render(&mut pixmap.as_mut()), Some(damage));
+    assert_eq!(
+        compare_extra("extra/retained-rendering-update", pixmap.clone()),
+        0
+    );
+
+    // Hide the group with opacity.
+    assert!(renderer.update_node(&[0, 1], resvg::NodeUpdate::Visibility(false)));
+    renderer.render(&mut pixmap.as_mut());
+    assert_eq!(compare_extra("extra/retained-rendering-hide", pixmap), 0);
+
+    // Paths cannot be applied to groups.
+    let path = std::sync::Arc::new(tiny_skia::PathBuilder::from_rect(
+        tiny_skia::Rect::from_xywh(0.0, 0.0, 1.0, 1.0).unwrap(),
+    ));
+    assert!(!renderer.update_node(&[0, 1], resvg::NodeUpdate::Path(path)));
+    assert!(!renderer.update_node(&[0, 5], resvg::NodeUpdate::Visibility(false)));
+}
@@ -0,0 +1,75 @@
+use usvg::NodeExt;
+
+use crate::{compare_extra, load_extra, render_extra};
+
+#[test]
+fn tree_is_send_and_sync() {
+    fn check<T: Send + Sync>() {}
+    check::<resvg::Tree>();
+    check::<usvg::Tree>();
+}
+
+#[test]
+fn shared_usvg_tree() {
+    assert_eq!(render_extra("extra/shared-usvg-tree"), 0);
+
+    // Parse once, render on multiple threads.
+    let tree = std::sync::Arc::new(load_extra("extra/shared-usvg-tree"));
+    let threads: Vec<_> = (0..4)
+        .map(|_| {
+            let tree = tree.clone();
+            std::thread::spawn(move || {
+                let rtree = resvg::Tree::from_usvg(&tree);
+                let mut pixmap = tiny_skia::Pixmap::new(200, 200).unwrap();
+                rtree.render(tiny_skia::Transform::default(), &mut pixmap.as_mut());
+                pixmap
+            })
+        })
+        .collect();
+
+    for thread in threads {
+        let pixmap = thread.join().unwrap();
+        assert_eq!(compare_extra("extra/shared-usvg-tree", pixmap), 0);
+    }
+}
+
+#[test]
+#[should_panic(expected = "the node is already mutably borrowed")]
+fn conflicting_node_borrows_panic() {
+    let node = usvg::Node::new(usvg::NodeKind::Group(usvg::Group::default()));
+    let _data = node.borrow_mut();
+    // Must panic like `RefCell` instead of waiting for the lock forever.
+    let _kind = node.borrow();
+}
+
+#[test]
+fn contended_node_borrows_block() {
+    let node = usvg::Node::new(usvg::NodeKind::Group(usvg::Group::default()));
+    let data = node.borrow_mut();
+    let threads: Vec<_> = (0..4)
+        .map(|i| {
+            let node = node.clone();
+            std::thread::spawn(move || {
+                if i == 0 {
+                    if let usvg::NodeKind::Group(ref mut group) = *node.borrow_mut() {
+                        group.id = "updated".to_string();
+                    }
+                }
+
+                // Nested borrows from one thread are allowed, even with a waiting writer.
+                let id = node.id();
+                let _kind = node.borrow();
+                id.to_string()
+            })
+        })
+        .collect();
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+    drop(data);
+
+    for thread in threads {
+        let id = thread.join().unwrap();
+        assert!(id.is_empty() || id == "updated");
+    }
+    assert_eq!(&*node.id(), "updated");
+}
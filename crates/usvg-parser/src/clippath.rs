@@ -2,10 +2,9 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use std::rc::Rc;
 use std::str::FromStr;
 
-use usvg_tree::{ClipPath, Group, Node, NodeKind, Transform, Units};
+use usvg_tree::{ClipPath, Group, Node, NodeKind, Shared, Transform, Units};
 
 use crate::converter;
 use crate::svgtree::{AId, EId, SvgNode};
@@ -14,7 +13,7 @@ pub(crate) fn convert(
     node: SvgNode,
     state: &converter::State,
     cache: &mut converter::Cache,
-) -> Option<Rc<ClipPath>> {
+) -> Option<Shared<ClipPath>> {
     // A `clip-path` attribute must reference a `clipPath` element.
     if node.tag_name() != Some(EId::ClipPath) {
         return None;
@@ -56,7 +55,7 @@ pub(crate) fn convert(
     converter::convert_clip_path_elements(node, &clip_state, cache, &mut clip.root);
 
     if clip.root.has_children() {
-        let clip = Rc::new(clip);
+        let clip = Shared::new(clip);
         cache
             .clip_paths
             .insert(node.element_id().to_string(), clip.clone());
@@ -79,7 +78,13 @@ fn resolve_transform(node: SvgNode) -> Option<Transform> {
     let ts = match svgtypes::Transform::from_str(value) {
         Ok(v) => v,
         Err(_) => {
-            log::warn!("Failed to parse {} value: '{}'.", AId::Transform, value);
+            report!(
+                node,
+                InvalidAttribute,
+                "Failed to parse {} value: '{}'.",
+                AId::Transform,
+                value
+            );
             return None;
         }
     };
@@ -4,7 +4,6 @@
 
 use std::collections::{HashMap, HashSet};
 use std::hash::{Hash, Hasher};
-use std::rc::Rc;
 use std::str::FromStr;
 
 use svgtypes::{Length, LengthUnit as Unit};
@@ -18,6 +17,9 @@ use crate::{Error, Options};
 pub struct State<'a> {
     pub(crate) parent_clip_path: Option<SvgNode<'a, 'a>>,
     pub(crate) parent_markers: Vec<SvgNode<'a, 'a>>,
+    /// Shapes that instantiated the current marker content, outermost first.
+    /// Includes their own instances.
+    pub(crate) instances: Vec<SvgNode<'a, 'a>>,
     pub(crate) fe_image_link: bool,
     /// A viewBox of the parent SVG element.
     pub(crate) view_box: NonZeroRect,
@@ -30,15 +32,21 @@ pub struct State<'a> {
 
 #[derive(Default)]
 pub struct Cache {
-    pub clip_paths: HashMap<String, Rc<ClipPath>>,
-    pub masks: HashMap<String, Rc<Mask>>,
-    pub filters: HashMap<String, Rc<usvg_tree::filter::Filter>>,
+    pub clip_paths: HashMap<String, Shared<ClipPath>>,
+    pub masks: HashMap<String, Shared<Mask>>,
+    pub filters: HashMap<String, Shared<usvg_tree::filter::Filter>>,
     pub paint: HashMap<String, Paint>,
 
     // used for ID generation
     pub all_ids: HashSet<u64>,
     pub clip_path_index: usize,
     pub filter_index: usize,
+
+    // used for source locations resolving
+    pub line_starts: Vec<usize>,
+
+    // an exceeded limit that should abort the conversion
+    pub error: Option<Error>,
 }
 
 impl Cache {
@@ -65,6 +73,43 @@ impl Cache {
     }
 }
 
+impl Cache {
+    fn source_location(&mut self, node: SvgNode) -> Option<SourceLocation> {
+        let text = node.document().input_text();
+        let span = node.range()?;
+
+        if self.line_starts.is_empty() {
+            self.line_starts.push(0);
+            for (i, c) in text.bytes().enumerate() {
+                if c == b'\n' {
+                    self.line_starts.push(i + 1);
+                }
+            }
+        }
+
+        let line_idx = match self.line_starts.binary_search(&span.start) {
+            Ok(idx) => idx,
+            Err(idx) => idx - 1,
+        };
+        let line_start = self.line_starts[line_idx];
+        let column = text.get(line_start..span.start)?.chars().count() + 1;
+
+        // The span starts with `<`. Skip it and a namespace prefix, if any.
+        let qname = text
+            .get(span.start + 1..span.end)?
+            .split(|c: char| c.is_whitespace() || c == '/' || c == '>')
+            .next()?;
+        let tag_name = qname.rsplit(':').next().unwrap_or(qname);
+
+        Some(SourceLocation {
+            span,
+            line: (line_idx + 1) as u32,
+            column: column as u32,
+            tag_name: tag_name.to_string(),
+        })
+    }
+}
+
 // TODO: is there a simpler way?
 fn string_hash(s: &str) -> u64 {
     let mut h = std::collections::hash_map::DefaultHasher::new();
@@ -203,6 +248,7 @@ pub(crate) fn convert_doc(svg_doc: &svgtree::Document, opt: &Options) -> Result<
     let state = State {
         parent_clip_path: None,
         parent_markers: Vec::new(),
+        instances: Vec::new(),
         fe_image_link: false,
         view_box: view_box.rect,
         use_size: (None, None),
@@ -222,6 +268,10 @@ pub(crate) fn convert_doc(svg_doc: &svgtree::Document, opt: &Options) -> Result<
 
     convert_children(svg_doc.root(), &state, &mut cache, &mut tree.root);
 
+    if let Some(e) = cache.error {
+        return Err(e);
+    }
+
     remove_empty_groups(&mut tree);
 
     if restore_viewbox {
@@ -235,6 +285,7 @@ fn resolve_svg_size(svg: &SvgNode, opt: &Options) -> (Result<Size, Error>, bool)
     let mut state = State {
         parent_clip_path: None,
         parent_markers: Vec::new(),
+        instances: Vec::new(),
         fe_image_link: false,
         view_box: NonZeroRect::from_xywh(0.0, 0.0, 100.0, 100.0).unwrap(),
         use_size: (None, None),
@@ -327,6 +378,41 @@ fn calculate_svg_bbox(tree: &mut Tree) {
     }
 }
 
+/// Returns elements that instantiated `node`, outermost first.
+pub(crate) fn instances<'a>(node: SvgNode<'a, 'a>, state: &State<'a>) -> Vec<SvgNode<'a, 'a>> {
+    // Elements referenced by `use` are copied into it,
+    // therefore `use` and `switch` elements are simply node's ancestors.
+    let mut ancestors: Vec<_> = node
+        .ancestors()
+        .skip(1)
+        .filter(|n| matches!(n.tag_name(), Some(EId::Use) | Some(EId::Switch)))
+        .collect();
+    ancestors.reverse();
+
+    let mut list = state.instances.clone();
+    list.extend(ancestors);
+    list
+}
+
+/// Returns node's origin, when `Options::keep_origins` is set.
+pub(crate) fn convert_origin(
+    node: SvgNode,
+    state: &State,
+    cache: &mut Cache,
+) -> Option<Shared<Origin>> {
+    if !state.opt.keep_origins {
+        return None;
+    }
+
+    let element = cache.source_location(node)?;
+    let instances = instances(node, state)
+        .into_iter()
+        .filter_map(|n| cache.source_location(n))
+        .collect();
+
+    Some(Shared::new(Origin { element, instances }))
+}
+
 #[inline(never)]
 pub(crate) fn convert_children(
     parent_node: SvgNode,
@@ -385,7 +471,7 @@ pub(crate) fn convert_element(
             }
         }
         EId::Image => {
-            crate::image::convert(node, state, parent);
+            crate::image::convert(node, state, cache, parent);
         }
         EId::Text => {
             crate::text::convert(node, state, cache, parent);
@@ -453,7 +539,12 @@ pub(crate) fn convert_clip_path_elements(
                 crate::text::convert(node, state, cache, parent);
             }
             _ => {
-                log::warn!("'{}' is no a valid 'clip-path' child.", tag_name);
+                report!(
+                    node,
+                    InvalidElement,
+                    "'{}' is no a valid 'clip-path' child.",
+                    tag_name
+                );
             }
         }
     }
@@ -590,9 +681,16 @@ pub(crate) fn convert_group(
             String::new()
         };
 
+        let classes = if is_g_or_use {
+            node.element_classes()
+        } else {
+            Vec::new()
+        };
+
         let title = node.title().map(ToOwned::to_owned);
         let g = parent.append_kind(NodeKind::Group(Group {
             id,
+            classes,
             transform,
             opacity,
             blend_mode,
@@ -600,7 +698,9 @@ pub(crate) fn convert_group(
             clip_path,
             mask,
             filters,
+            marker_instance: false,
             title,
+            origin: convert_origin(node, state, cache),
         }));
 
         GroupKind::Create(g)
@@ -649,7 +749,7 @@ fn remove_empty_groups(tree: &mut Tree) {
 
 fn convert_path(
     node: SvgNode,
-    path: Rc<tiny_skia_path::Path>,
+    path: Shared<tiny_skia_path::Path>,
     state: &State,
     cache: &mut Cache,
     parent: &mut Node,
@@ -696,8 +796,10 @@ fn convert_path(
 
     parent.append_kind(NodeKind::Path(Path {
         id,
+        classes: node.element_classes(),
         transform: Default::default(),
         visibility,
+        pointer_events: node.find_attribute(AId::PointerEvents).unwrap_or_default(),
         fill,
         stroke,
         paint_order,
@@ -706,6 +808,7 @@ fn convert_path(
         text_bbox: None,
         data: path,
         title,
+        origin: convert_origin(node, state, cache),
     }));
 
     if raw_paint_order.order[2] == svgtypes::PaintOrderKind::Markers {
@@ -0,0 +1,119 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! DTD entities expansion checker.
+//!
+//! `roxmltree` expands entities during parsing, therefore we have to check
+//! how much data they will produce beforehand.
+//!
+//! The document is tokenized by `xmlparser`, exactly like `roxmltree` does,
+//! so comments, CDATA and processing instructions are handled the same way.
+
+use std::collections::HashMap;
+
+use xmlparser::{EntityDefinition, Reference, Stream, Token, Tokenizer};
+
+use crate::{Error, Limits};
+
+/// Checks that entities defined in the internal DTD subset
+/// do not exceed `Limits::max_entity_expansion` and `Limits::max_entity_depth`.
+///
+/// External DTDs are never loaded, so they are ignored.
+pub(crate) fn check_entities(text: &str, limits: &Limits) -> Result<(), Error> {
+    let mut entities = HashMap::new();
+    let mut sizes = HashMap::new();
+    let mut total: u64 = 0;
+
+    for token in Tokenizer::from(text) {
+        // Malformed documents will be rejected by `roxmltree`,
+        // which stops at the same token.
+        let token = match token {
+            Ok(v) => v,
+            Err(_) => break,
+        };
+
+        let text = match token {
+            Token::EntityDeclaration {
+                name,
+                definition: EntityDefinition::EntityValue(value),
+                ..
+            } => {
+                // The first declaration is binding.
+                entities.entry(name.as_str()).or_insert(value.as_str());
+                continue;
+            }
+            Token::Text { text } => text,
+            Token::Attribute { value, .. } => value,
+            _ => continue,
+        };
+
+        if entities.is_empty() {
+            continue;
+        }
+
+        for name in references(text.as_str()) {
+            let (size, _) = expanded_size(name, &entities, &mut sizes, 1, limits)?;
+            total = total.saturating_add(size);
+            if total > limits.max_entity_expansion {
+                return Err(Error::EntityExpansionLimitReached);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns names of all general entity references in the text.
+fn references(text: &str) -> impl Iterator<Item = &str> {
+    text.match_indices('&').filter_map(move |(idx, _)| {
+        match Stream::from(&text[idx..]).consume_reference() {
+            Ok(Reference::Entity(name)) => Some(name),
+            _ => None,
+        }
+    })
+}
+
+/// Returns the expanded size of an entity and the number of nested entity levels in it.
+fn expanded_size<'a>(
+    name: &'a str,
+    entities: &HashMap<&'a str, &'a str>,
+    sizes: &mut HashMap<&'a str, (u64, u32)>,
+    depth: u32,
+    limits: &Limits,
+) -> Result<(u64, u32), Error> {
+    // Also prevents reference loops.
+    if depth > limits.max_entity_depth {
+        return Err(Error::EntityExpansionLimitReached);
+    }
+
+    if let Some(&(size, height)) = sizes.get(name) {
+        // The same entity can be referenced at a different depth.
+        if depth.saturating_add(height) > limits.max_entity_depth {
+            return Err(Error::EntityExpansionLimitReached);
+        }
+
+        return Ok((size, height));
+    }
+
+    let value = match entities.get(name) {
+        Some(v) => *v,
+        // Predefined or undefined entities are not expanded.
+        None => return Ok((0, 0)),
+    };
+
+    let mut size = value.len() as u64;
+    let mut height = 0;
+    for child in references(value) {
+        let (child_size, child_height) = expanded_size(child, entities, sizes, depth + 1, limits)?;
+        size = size.saturating_add(child_size);
+        if size > limits.max_entity_expansion {
+            return Err(Error::EntityExpansionLimitReached);
+        }
+
+        height = height.max(child_height + 1);
+    }
+
+    sizes.insert(name, (size, height));
+    Ok((size, height))
+}
@@ -5,7 +5,6 @@
 //! A collection of SVG filters.
 
 use std::collections::HashSet;
-use std::rc::Rc;
 use std::str::FromStr;
 
 use strict_num::PositiveF32;
@@ -13,7 +12,7 @@ use svgtypes::{Length, LengthUnit as Unit};
 use usvg_tree::filter::*;
 use usvg_tree::{
     strict_num, ApproxZeroUlps, Color, Group, Node, NodeKind, NonZeroF32, NonZeroRect, Opacity,
-    Units,
+    Shared, Units,
 };
 
 use crate::converter::SvgColorExt;
@@ -35,7 +34,7 @@ pub(crate) fn convert(
     node: SvgNode,
     state: &converter::State,
     cache: &mut converter::Cache,
-) -> Result<Vec<Rc<Filter>>, ()> {
+) -> Result<Vec<Shared<Filter>>, ()> {
     let value = match node.attribute::<&str>(AId::Filter) {
         Some(v) => v,
         None => return Ok(Vec::new()),
@@ -45,7 +44,7 @@ pub(crate) fn convert(
     let mut filters = Vec::new();
 
     let create_base_filter_func =
-        |kind, filters: &mut Vec<Rc<Filter>>, cache: &mut converter::Cache| {
+        |kind, filters: &mut Vec<Shared<Filter>>, cache: &mut converter::Cache| {
             // Filter functions, unlike `filter` elements, do not have a filter region.
             // We're currently do not support an unlimited region, so we simply use a fairly large one.
             // This if far from ideal, but good for now.
@@ -57,7 +56,7 @@ pub(crate) fn convert(
                 _ => NonZeroRect::from_xywh(-0.1, -0.1, 1.2, 1.2).unwrap(),
             };
 
-            filters.push(Rc::new(Filter {
+            filters.push(Shared::new(Filter {
                 id: cache.gen_filter_id(),
                 units: Units::ObjectBoundingBox,
                 primitive_units: Units::UserSpaceOnUse,
@@ -80,7 +79,12 @@ pub(crate) fn convert(
             Ok(v) => v,
             Err(e) => {
                 // Skip the whole attribute list on error.
-                log::warn!("Failed to parse a filter value cause {}. Skipping.", e);
+                report!(
+                    node,
+                    InvalidAttribute,
+                    "Failed to parse a filter value cause {}. Skipping.",
+                    e
+                );
                 return Ok(Vec::new());
             }
         };
@@ -156,7 +160,7 @@ fn convert_url(
     node: SvgNode,
     state: &converter::State,
     cache: &mut converter::Cache,
-) -> Result<Option<Rc<Filter>>, ()> {
+) -> Result<Option<Shared<Filter>>, ()> {
     if let Some(filter) = cache.filters.get(node.element_id()) {
         return Ok(Some(filter.clone()));
     }
@@ -196,7 +200,9 @@ fn convert_url(
     );
     let rect = rect
         .log_none(|| {
-            log::warn!(
+            report!(
+                node,
+                InvalidSize,
                 "Filter '{}' has an invalid region. Skipped.",
                 node.element_id()
             )
@@ -212,7 +218,7 @@ fn convert_url(
         return Err(());
     }
 
-    let filter = Rc::new(Filter {
+    let filter = Shared::new(Filter {
         id: node.element_id().to_string(),
         units,
         primitive_units,
@@ -230,7 +236,9 @@ fn convert_url(
 fn find_filter_with_primitives<'a>(node: SvgNode<'a, 'a>) -> Option<SvgNode<'a, 'a>> {
     for link in node.href_iter() {
         if link.tag_name() != Some(EId::Filter) {
-            log::warn!(
+            report!(
+                node,
+                InvalidLink,
                 "Filter '{}' cannot reference '{}' via 'xlink:href'.",
                 node.element_id(),
                 link.tag_name().unwrap()
@@ -293,7 +301,12 @@ fn collect_children(
                 EId::FeSpecularLighting => convert_specular_lighting(child, &primitives)
                     .unwrap_or_else(create_dummy_primitive),
                 tag_name => {
-                    log::warn!("'{}' is not a valid filter primitive. Skipped.", tag_name);
+                    report!(
+                        child,
+                        InvalidElement,
+                        "'{}' is not a valid filter primitive. Skipped.",
+                        tag_name
+                    );
                     continue;
                 }
             };
@@ -378,7 +391,11 @@ fn parse_in(s: &str) -> Input {
         "SourceGraphic" => Input::SourceGraphic,
         "SourceAlpha" => Input::SourceAlpha,
         "BackgroundImage" | "BackgroundAlpha" | "FillPaint" | "StrokePaint" => {
-            log::warn!("{} filter input isn't supported and not planed.", s);
+            report!(
+                Unsupported,
+                "{} filter input isn't supported and not planed.",
+                s
+            );
             Input::SourceGraphic
         }
         _ => Input::Reference(s.to_string()),
@@ -717,12 +734,16 @@ fn convert_image(fe: SvgNode, state: &converter::State, cache: &mut converter::C
     let href = match fe.attribute(AId::Href) {
         Some(s) => s,
         _ => {
-            log::warn!("The 'feImage' element lacks the 'xlink:href' attribute. Skipped.");
+            report!(
+                fe,
+                MissingAttribute,
+                "The 'feImage' element lacks the 'xlink:href' attribute. Skipped."
+            );
             return create_dummy_primitive();
         }
     };
 
-    let href = crate::image::get_href_data(href, state.opt);
+    let href = crate::image::get_href_data(href, state.opt, cache);
     let img_data = match href {
         Some(data) => data,
         None => return create_dummy_primitive(),
@@ -782,7 +803,12 @@ fn convert_lighting_color(node: SvgNode) -> Color {
             if let Ok(c) = svgtypes::Color::from_str(value) {
                 c.split_alpha().0
             } else {
-                log::warn!("Failed to parse lighting-color value: '{}'.", value);
+                report!(
+                    node,
+                    InvalidAttribute,
+                    "Failed to parse lighting-color value: '{}'.",
+                    value
+                );
                 Color::white()
             }
         }
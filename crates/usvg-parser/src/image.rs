@@ -2,13 +2,14 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use svgtypes::Length;
 use usvg_tree::{Image, ImageKind, Node, NodeExt, NodeKind, NonZeroRect, Size, Tree, ViewBox};
 
 use crate::svgtree::{AId, SvgNode};
-use crate::{converter, OptionLog, Options, TreeParsing};
+use crate::{converter, Error, OptionLog, Options, TreeParsing};
 
 /// A shorthand for [ImageHrefResolver]'s data function.
 pub type ImageHrefDataResolverFn =
@@ -76,31 +77,50 @@ impl ImageHrefResolver {
     ///
     /// Paths have to be absolute or relative to the input SVG file or relative to
     /// [Options::resources_dir](crate::Options::resources_dir).
+    ///
+    /// Respects [Options::file_access](crate::Options::file_access).
     pub fn default_string_resolver() -> ImageHrefStringResolverFn {
         Box::new(move |href: &str, opts: &Options| {
-            let path = opts.get_abs_path(std::path::Path::new(href));
+            let path = opts.file_access.resolve_path(href, opts)?;
 
             if path.exists() {
                 let data = match std::fs::read(&path) {
                     Ok(data) => data,
                     Err(_) => {
-                        log::warn!("Failed to load '{}'. Skipped.", href);
+                        report!(ImageLoadingFailed, "Failed to load '{}'. Skipped.", href);
                         return None;
                     }
                 };
 
-                match get_image_file_format(&path, &data) {
+                let format = get_image_file_format(&path, &data);
+                if let Some(format) = format {
+                    if !opts.file_access.is_mime_type_allowed(format.mime_type()) {
+                        report!(
+                            ImageLoadingFailed,
+                            "'{}' has a forbidden MIME type: {}. Skipped.",
+                            href,
+                            format.mime_type()
+                        );
+                        return None;
+                    }
+                }
+
+                match format {
                     Some(ImageFormat::JPEG) => Some(ImageKind::JPEG(Arc::new(data))),
                     Some(ImageFormat::PNG) => Some(ImageKind::PNG(Arc::new(data))),
                     Some(ImageFormat::GIF) => Some(ImageKind::GIF(Arc::new(data))),
                     Some(ImageFormat::SVG) => load_sub_svg(&data, opts),
                     _ => {
-                        log::warn!("'{}' is not a PNG, JPEG, GIF or SVG(Z) image.", href);
+                        report!(
+                            ImageLoadingFailed,
+                            "'{}' is not a PNG, JPEG, GIF or SVG(Z) image.",
+                            href
+                        );
                         None
                     }
                 }
             } else {
-                log::warn!("'{}' is not a path to an image.", href);
+                report!(ImageLoadingFailed, "'{}' is not a path to an image.", href);
                 None
             }
         })
@@ -113,6 +133,155 @@ impl std::fmt::Debug for ImageHrefResolver {
     }
 }
 
+/// A file access mode.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FileAccess {
+    /// Any file can be loaded.
+    Unrestricted,
+    /// Only files inside [Options::resources_dir](crate::Options::resources_dir) can be loaded.
+    ///
+    /// Paths are canonicalized before the check, therefore neither `..`
+    /// nor symlinks can escape the directory.
+    /// No files can be loaded when `resources_dir` is not set.
+    ResourcesDir,
+    /// No files can be loaded.
+    Denied,
+}
+
+/// A file access policy.
+///
+/// Used by [ImageHrefResolver::default_string_resolver] and therefore applies
+/// to `image` and `feImage` elements, including the ones inside nested SVG images.
+/// Custom resolvers can use [FileAccessPolicy::resolve_path] to follow the same rules.
+///
+/// Data URLs are not affected.
+#[derive(Clone, PartialEq, Debug)]
+pub struct FileAccessPolicy {
+    /// A file access mode.
+    ///
+    /// Default: `Unrestricted`
+    pub access: FileAccess,
+
+    /// A list of allowed file extensions. Case-insensitive.
+    ///
+    /// Files without an extension are not allowed when set.
+    /// An empty list allows any extension.
+    ///
+    /// Default: empty
+    pub extensions: Vec<String>,
+
+    /// A list of allowed MIME types.
+    ///
+    /// Matched against a format detected from the file content.
+    /// Like `image/png`, `image/jpeg`, `image/gif` and `image/svg+xml`.
+    /// An empty list allows any supported format.
+    ///
+    /// Default: empty
+    pub mime_types: Vec<String>,
+}
+
+impl Default for FileAccessPolicy {
+    fn default() -> Self {
+        FileAccessPolicy {
+            access: FileAccess::Unrestricted,
+            extensions: Vec::new(),
+            mime_types: Vec::new(),
+        }
+    }
+}
+
+impl FileAccessPolicy {
+    /// Creates a policy that allows only files inside `Options::resources_dir`.
+    pub fn resources_dir() -> Self {
+        FileAccessPolicy {
+            access: FileAccess::ResourcesDir,
+            ..FileAccessPolicy::default()
+        }
+    }
+
+    /// Creates a policy that denies any file access.
+    pub fn denied() -> Self {
+        FileAccessPolicy {
+            access: FileAccess::Denied,
+            ..FileAccessPolicy::default()
+        }
+    }
+
+    /// Resolves `href` into a file path according to the policy.
+    ///
+    /// Returns `None` and logs a warning when access is denied.
+    pub fn resolve_path(&self, href: &str, opts: &Options) -> Option<PathBuf> {
+        let path = match self.access {
+            FileAccess::Unrestricted => opts.get_abs_path(Path::new(href)),
+            FileAccess::ResourcesDir => {
+                let path = opts
+                    .resources_dir
+                    .as_ref()
+                    .and_then(|dir| resolve_inside(dir, Path::new(href)));
+                match path {
+                    Some(path) => path,
+                    None => {
+                        report!(
+                            ImageLoadingFailed,
+                            "'{}' is not a file inside the resources directory. Skipped.",
+                            href
+                        );
+                        return None;
+                    }
+                }
+            }
+            FileAccess::Denied => {
+                report!(
+                    ImageLoadingFailed,
+                    "Access to '{}' is denied. Skipped.",
+                    href
+                );
+                return None;
+            }
+        };
+
+        if !self.is_extension_allowed(&path) {
+            report!(
+                ImageLoadingFailed,
+                "'{}' has a forbidden extension. Skipped.",
+                href
+            );
+            return None;
+        }
+
+        Some(path)
+    }
+
+    fn is_extension_allowed(&self, path: &Path) -> bool {
+        if self.extensions.is_empty() {
+            return true;
+        }
+
+        let ext = match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) => ext,
+            None => return false,
+        };
+
+        self.extensions.iter().any(|e| e.eq_ignore_ascii_case(ext))
+    }
+
+    /// Checks that a MIME type is allowed.
+    pub fn is_mime_type_allowed(&self, mime: &str) -> bool {
+        self.mime_types.is_empty() || self.mime_types.iter().any(|m| m == mime)
+    }
+}
+
+/// Returns a canonical path to a file inside `dir`.
+fn resolve_inside(dir: &Path, rel_path: &Path) -> Option<PathBuf> {
+    let dir = dir.canonicalize().ok()?;
+    let path = dir.join(rel_path).canonicalize().ok()?;
+    if path.starts_with(&dir) && path.is_file() {
+        Some(path)
+    } else {
+        None
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Debug)]
 enum ImageFormat {
     PNG,
@@ -121,12 +290,32 @@ enum ImageFormat {
     SVG,
 }
 
-pub(crate) fn convert(node: SvgNode, state: &converter::State, parent: &mut Node) -> Option<()> {
-    let href = node
-        .attribute(AId::Href)
-        .log_none(|| log::warn!("Image lacks the 'xlink:href' attribute. Skipped."))?;
+impl ImageFormat {
+    fn mime_type(self) -> &'static str {
+        match self {
+            ImageFormat::PNG => "image/png",
+            ImageFormat::JPEG => "image/jpeg",
+            ImageFormat::GIF => "image/gif",
+            ImageFormat::SVG => "image/svg+xml",
+        }
+    }
+}
+
+pub(crate) fn convert(
+    node: SvgNode,
+    state: &converter::State,
+    cache: &mut converter::Cache,
+    parent: &mut Node,
+) -> Option<()> {
+    let href = node.attribute(AId::Href).log_none(|| {
+        report!(
+            node,
+            MissingAttribute,
+            "Image lacks the 'xlink:href' attribute. Skipped."
+        )
+    })?;
 
-    let kind = get_href_data(href, state.opt)?;
+    let kind = get_href_data(href, state.opt, cache)?;
 
     let visibility = node.find_attribute(AId::Visibility).unwrap_or_default();
     let rendering_mode = node
@@ -138,7 +327,7 @@ pub(crate) fn convert(node: SvgNode, state: &converter::State, parent: &mut Node
             imagesize::blob_size(data)
                 .ok()
                 .and_then(|size| Size::from_wh(size.width as f32, size.height as f32))
-                .log_none(|| log::warn!("Image has an invalid size. Skipped."))?
+                .log_none(|| report!(node, InvalidSize, "Image has an invalid size. Skipped."))?
         }
         ImageKind::SVG(ref svg) => svg.size,
     };
@@ -157,7 +346,8 @@ pub(crate) fn convert(node: SvgNode, state: &converter::State, parent: &mut Node
             Length::new_number(actual_size.height() as f64),
         ),
     );
-    let rect = rect.log_none(|| log::warn!("Image has an invalid size. Skipped."))?;
+    let rect =
+        rect.log_none(|| report!(node, InvalidSize, "Image has an invalid size. Skipped."))?;
 
     let view_box = ViewBox {
         rect,
@@ -174,18 +364,42 @@ pub(crate) fn convert(node: SvgNode, state: &converter::State, parent: &mut Node
     let title = node.title().map(ToOwned::to_owned);
     parent.append_kind(NodeKind::Image(Image {
         id,
+        classes: node.element_classes(),
         transform: Default::default(),
         visibility,
+        pointer_events: node.find_attribute(AId::PointerEvents).unwrap_or_default(),
         view_box,
         rendering_mode,
+        vector_effect: crate::style::resolve_vector_effect(node),
         kind,
         title,
+        origin: converter::convert_origin(node, state, cache),
     }));
 
     Some(())
 }
 
-pub(crate) fn get_href_data(href: &str, opt: &Options) -> Option<ImageKind> {
+pub(crate) fn get_href_data(
+    href: &str,
+    opt: &Options,
+    cache: &mut converter::Cache,
+) -> Option<ImageKind> {
+    let kind = resolve_href(href, opt)?;
+
+    if let ImageKind::JPEG(ref data) | ImageKind::PNG(ref data) | ImageKind::GIF(ref data) = kind {
+        if let Ok(size) = imagesize::blob_size(data) {
+            let pixels = size.width as u64 * size.height as u64;
+            if pixels > opt.limits.max_image_pixels {
+                cache.error = Some(Error::ImageSizeLimitReached);
+                return None;
+            }
+        }
+    }
+
+    Some(kind)
+}
+
+fn resolve_href(href: &str, opt: &Options) -> Option<ImageKind> {
     if let Ok(url) = data_url::DataUrl::process(href) {
         let (data, _) = url.decode_to_vec().ok()?;
 
@@ -227,20 +441,34 @@ fn get_image_data_format(data: &[u8]) -> Option<ImageFormat> {
 /// Unlike `Tree::from_*` methods, this one will also remove all `image` elements
 /// from the loaded SVG, as required by the spec.
 pub(crate) fn load_sub_svg(data: &[u8], opt: &Options) -> Option<ImageKind> {
-    let mut sub_opt = Options::default();
-    sub_opt.resources_dir = None;
-    sub_opt.dpi = opt.dpi;
-    sub_opt.font_size = opt.font_size;
-    sub_opt.languages = opt.languages.clone();
-    sub_opt.shape_rendering = opt.shape_rendering;
-    sub_opt.text_rendering = opt.text_rendering;
-    sub_opt.image_rendering = opt.image_rendering;
-    sub_opt.default_size = opt.default_size;
+    // All options are inherited, except the resources directory.
+    // `Options` cannot be cloned because of the resolver, which is not needed anyway,
+    // since nested images are removed. No `..Default::default()` here,
+    // so new options would not be lost silently.
+    let sub_opt = Options {
+        resources_dir: None,
+        dpi: opt.dpi,
+        font_family: opt.font_family.clone(),
+        font_size: opt.font_size,
+        languages: opt.languages.clone(),
+        shape_rendering: opt.shape_rendering,
+        text_rendering: opt.text_rendering,
+        image_rendering: opt.image_rendering,
+        default_size: opt.default_size,
+        image_href_resolver: ImageHrefResolver::default(),
+        file_access: opt.file_access.clone(),
+        forgiving: opt.forgiving,
+        allow_dtd: opt.allow_dtd,
+        diagnostics: opt.diagnostics.clone(),
+        keep_origins: opt.keep_origins,
+        limits: opt.limits,
+        animation_time: opt.animation_time,
+    };
 
     let tree = match Tree::from_data(data, &sub_opt) {
         Ok(tree) => tree,
         Err(_) => {
-            log::warn!("Failed to load subsvg image.");
+            report!(ImageLoadingFailed, "Failed to load subsvg image.");
             return None;
         }
     };
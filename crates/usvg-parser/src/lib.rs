@@ -21,8 +21,12 @@
 #![allow(clippy::too_many_arguments)]
 #![allow(clippy::upper_case_acronyms)]
 
+#[macro_use]
+extern crate usvg_tree;
+
 mod clippath;
 mod converter;
+mod dtd;
 mod filter;
 mod image;
 mod marker;
@@ -41,7 +45,7 @@ use std::borrow::Cow;
 use std::convert::TryInto;
 
 pub use crate::options::*;
-pub use image::ImageHrefResolver;
+pub use image::{FileAccess, FileAccessPolicy, ImageHrefResolver};
 pub use roxmltree;
 pub use svgtree::{AId, EId};
 
@@ -54,9 +58,24 @@ pub enum Error {
     /// Compressed SVG must use the GZip algorithm.
     MalformedGZip,
 
-    /// We do not allow SVG with more than 1_000_000 elements for security reasons.
+    /// SVG has more elements than allowed by `Limits::max_elements`.
     ElementsLimitReached,
 
+    /// SVG elements nesting is deeper than allowed by `Limits::max_nesting_depth`.
+    NestingDepthLimitReached,
+
+    /// A raster image is bigger than allowed by `Limits::max_image_pixels`.
+    ImageSizeLimitReached,
+
+    /// Compressed SVG expands more than allowed by `Limits::max_gzip_ratio`.
+    GZipRatioLimitReached,
+
+    /// DTD entities expand more than allowed by `Limits::max_entity_expansion`
+    /// or are nested deeper than `Limits::max_entity_depth`.
+    ///
+    /// Also occurs on recursive entities.
+    EntityExpansionLimitReached,
+
     /// SVG doesn't have a valid size.
     ///
     /// Occurs when width and/or height are <= 0.
@@ -86,6 +105,18 @@ impl std::fmt::Display for Error {
             Error::ElementsLimitReached => {
                 write!(f, "the maximum number of SVG elements has been reached")
             }
+            Error::NestingDepthLimitReached => {
+                write!(f, "the maximum SVG elements nesting depth has been reached")
+            }
+            Error::ImageSizeLimitReached => {
+                write!(f, "the maximum raster image size has been reached")
+            }
+            Error::GZipRatioLimitReached => {
+                write!(f, "the maximum GZip expansion ratio has been reached")
+            }
+            Error::EntityExpansionLimitReached => {
+                write!(f, "the maximum DTD entities expansion has been reached")
+            }
             Error::InvalidSize => {
                 write!(f, "SVG has an invalid size")
             }
@@ -131,7 +162,10 @@ pub fn preprocess_text<'a>(text: &'a str, opt: &Options) -> Cow<'a, str> {
     if opt.forgiving {
         let processed_text = text.replace("\0", "");
         if processed_text.len() != text.len() {
-            log::warn!("Found one or more invalid characters in input.");
+            report!(
+                InvalidInput,
+                "Found one or more invalid characters in input."
+            );
         }
         Cow::Owned(processed_text)
     } else {
@@ -142,14 +176,20 @@ pub fn preprocess_text<'a>(text: &'a str, opt: &Options) -> Cow<'a, str> {
 // TODO: exporting this as a public API of usvg is not great.
 /// Tries to parse bytes as a UTF-16 string
 pub fn string_from_utf16_bytes(bytes: &[u8]) -> Result<String, std::string::FromUtf16Error> {
-    fn without_bom(bytes: &[u8], to_u16: fn([u8; 2]) -> u16) -> Result<String, std::string::FromUtf16Error> {
+    fn without_bom(
+        bytes: &[u8],
+        to_u16: fn([u8; 2]) -> u16,
+    ) -> Result<String, std::string::FromUtf16Error> {
         let values = bytes
             .chunks_exact(2)
             .map(|chunk| to_u16(chunk.try_into().unwrap()))
             .collect::<Vec<_>>();
         String::from_utf16(&values)
     }
-    fn with_bom(bytes: &[u8], to_u16: fn([u8; 2]) -> u16) -> Result<String, std::string::FromUtf16Error> {
+    fn with_bom(
+        bytes: &[u8],
+        to_u16: fn([u8; 2]) -> u16,
+    ) -> Result<String, std::string::FromUtf16Error> {
         if bytes.len() == 2 {
             return Ok(String::new());
         }
@@ -162,8 +202,7 @@ pub fn string_from_utf16_bytes(bytes: &[u8]) -> Result<String, std::string::From
         with_bom(bytes, u16::from_be_bytes)
     } else {
         // Try both
-        without_bom(bytes, u16::from_le_bytes)
-            .or_else(|_| without_bom(bytes, u16::from_be_bytes))
+        without_bom(bytes, u16::from_le_bytes).or_else(|_| without_bom(bytes, u16::from_be_bytes))
     }
 }
 
@@ -179,7 +218,7 @@ impl TreeParsing for usvg_tree::Tree {
                 .map_err(|_: std::string::FromUtf16Error| Error::UnrecognizedEncoding)
         }
         if data.starts_with(&[0x1f, 0x8b]) {
-            let data = decompress_svgz(data)?;
+            let data = decompress_svgz_with_limits(data, &opt.limits)?;
             let text = to_text(&data)?;
             Self::from_str(&text, opt)
         } else {
@@ -190,28 +229,59 @@ impl TreeParsing for usvg_tree::Tree {
 
     /// Parses `Tree` from an SVG string.
     fn from_str(text: &str, opt: &Options) -> Result<Self, Error> {
-        let text = preprocess_text(text, opt);
-
-        let xml_opt = roxmltree::ParsingOptions {
-            allow_dtd: true,
-            forgiving: opt.forgiving,
-            ..Default::default()
-        };
-
-        let doc =
-            roxmltree::Document::parse_with_options(&text, xml_opt).map_err(Error::ParsingFailed)?;
+        if let Some(ref diagnostics) = opt.diagnostics {
+            return diagnostics.scope(|| parse_str(text, opt));
+        }
 
-        Self::from_xmltree(&doc, opt)
+        parse_str(text, opt)
     }
 
     /// Parses `Tree` from `roxmltree::Document`.
     fn from_xmltree(doc: &roxmltree::Document, opt: &Options) -> Result<Self, Error> {
-        let doc = svgtree::Document::parse_tree(doc)?;
-        crate::converter::convert_doc(&doc, opt)
+        if let Some(ref diagnostics) = opt.diagnostics {
+            return diagnostics.scope(|| parse_xmltree(doc, opt));
+        }
+
+        parse_xmltree(doc, opt)
     }
 }
 
+fn parse_str(text: &str, opt: &Options) -> Result<usvg_tree::Tree, Error> {
+    let text = preprocess_text(text, opt);
+    let doc = parse_xml(&text, opt)?;
+    parse_xmltree(&doc, opt)
+}
+
+/// Parses an XML document the same way as `TreeParsing::from_str` does.
+///
+/// Takes `Options::allow_dtd`, `Options::forgiving` and DTD entities limits into account.
+/// The text must be already preprocessed.
+pub fn parse_xml<'a>(text: &'a str, opt: &Options) -> Result<roxmltree::Document<'a>, Error> {
+    if opt.allow_dtd {
+        dtd::check_entities(text, &opt.limits)?;
+    }
+
+    let xml_opt = roxmltree::ParsingOptions {
+        allow_dtd: opt.allow_dtd,
+        forgiving: opt.forgiving,
+        ..Default::default()
+    };
+
+    roxmltree::Document::parse_with_options(text, xml_opt).map_err(|e| match e {
+        roxmltree::Error::EntityReferenceLoop(_) => Error::EntityExpansionLimitReached,
+        e => Error::ParsingFailed(e),
+    })
+}
+
+fn parse_xmltree(doc: &roxmltree::Document, opt: &Options) -> Result<usvg_tree::Tree, Error> {
+    let doc = svgtree::Document::parse_tree(doc, &opt.limits, opt.animation_time)?;
+    crate::converter::convert_doc(&doc, opt)
+}
+
 /// Decompresses an SVGZ file.
+///
+/// The decompressed size is not limited.
+/// Use [`decompress_svgz_with_limits`] for untrusted data.
 pub fn decompress_svgz(data: &[u8]) -> Result<Vec<u8>, Error> {
     use std::io::Read;
 
@@ -223,6 +293,30 @@ pub fn decompress_svgz(data: &[u8]) -> Result<Vec<u8>, Error> {
     Ok(decoded)
 }
 
+/// Decompresses an SVGZ file, checking `Limits::max_gzip_ratio`.
+pub fn decompress_svgz_with_limits(data: &[u8], limits: &Limits) -> Result<Vec<u8>, Error> {
+    use std::io::Read;
+
+    // Data that is smaller than 1 MiB after decompression is always allowed.
+    let max_len = (data.len() as u64)
+        .saturating_mul(limits.max_gzip_ratio as u64)
+        .max(1024 * 1024);
+
+    // Read one byte past the limit to detect an overflow.
+    let decoder = flate2::read::GzDecoder::new(data);
+    let mut decoded = Vec::with_capacity(data.len() * 2);
+    decoder
+        .take(max_len + 1)
+        .read_to_end(&mut decoded)
+        .map_err(|_| Error::MalformedGZip)?;
+
+    if decoded.len() as u64 > max_len {
+        return Err(Error::GZipRatioLimitReached);
+    }
+
+    Ok(decoded)
+}
+
 #[inline]
 pub(crate) fn f32_bound(min: f32, val: f32, max: f32) -> f32 {
     debug_assert!(min.is_finite());
@@ -2,14 +2,12 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use std::rc::Rc;
-
 use strict_num::NonZeroPositiveF32;
 use svgtypes::Length;
 use tiny_skia_path::Point;
 use usvg_tree::{
     strict_num, tiny_skia_path, ApproxEqUlps, ApproxZeroUlps, ClipPath, Group, Node, NodeExt,
-    NodeKind, NonZeroRect, Path, Size, Transform, ViewBox,
+    NodeKind, NonZeroRect, Path, Shared, Size, Transform, ViewBox,
 };
 
 use crate::converter;
@@ -64,7 +62,12 @@ pub(crate) fn convert(
             // TODO: move to svgtree
             // Check for recursive marker.
             if state.parent_markers.contains(&marker) {
-                log::warn!("Recursive marker detected: {}", marker.element_id());
+                report!(
+                    marker,
+                    RecursiveReference,
+                    "Recursive marker detected: {}",
+                    marker.element_id()
+                );
                 continue;
             }
 
@@ -121,14 +124,14 @@ fn resolve(
         let mut clip_path = ClipPath::default();
         clip_path.id = cache.gen_clip_path_id();
 
-        let mut path = Path::new(Rc::new(tiny_skia_path::PathBuilder::from_rect(
+        let mut path = Path::new(Shared::new(tiny_skia_path::PathBuilder::from_rect(
             clip_rect.to_rect(),
         )));
         path.fill = Some(usvg_tree::Fill::default());
 
         clip_path.root.append_kind(NodeKind::Path(path));
 
-        Some(Rc::new(clip_path))
+        Some(Shared::new(clip_path))
     } else {
         None
     };
@@ -187,17 +190,22 @@ fn resolve(
 
         ts = ts.pre_translate(-r.x(), -r.y());
 
+        let mut marker_state = state.clone();
+        marker_state.parent_markers.push(marker_node);
+        marker_state.instances = converter::instances(shape_node, state);
+        marker_state.instances.push(shape_node);
+
         // TODO: do not create a group when no clipPath
         let title: Option<String> = shape_node.title().map(ToOwned::to_owned);
         let mut g_node = parent.append_kind(NodeKind::Group(Group {
             transform: ts,
             clip_path: clip_path.clone(),
+            marker_instance: true,
             title,
+            origin: converter::convert_origin(marker_node, &marker_state, cache),
             ..Group::default()
         }));
 
-        let mut marker_state = state.clone();
-        marker_state.parent_markers.push(marker_node);
         converter::convert_children(marker_node, &marker_state, cache, &mut g_node);
 
         if !g_node.has_children() {
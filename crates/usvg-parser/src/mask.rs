@@ -2,10 +2,8 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use std::rc::Rc;
-
 use svgtypes::{Length, LengthUnit as Unit};
-use usvg_tree::{Group, Mask, MaskType, Node, NodeKind, NonZeroRect, Units};
+use usvg_tree::{Group, Mask, MaskType, Node, NodeKind, NonZeroRect, Shared, Units};
 
 use crate::svgtree::{AId, EId, SvgNode};
 use crate::{converter, OptionLog};
@@ -14,7 +12,7 @@ pub(crate) fn convert(
     node: SvgNode,
     state: &converter::State,
     cache: &mut converter::Cache,
-) -> Option<Rc<Mask>> {
+) -> Option<Shared<Mask>> {
     // A `mask` attribute must reference a `mask` element.
     if node.tag_name() != Some(EId::Mask) {
         return None;
@@ -39,8 +37,14 @@ pub(crate) fn convert(
         node.convert_length(AId::Width, units, state, Length::new(120.0, Unit::Percent)),
         node.convert_length(AId::Height, units, state, Length::new(120.0, Unit::Percent)),
     );
-    let rect =
-        rect.log_none(|| log::warn!("Mask '{}' has an invalid size. Skipped.", node.element_id()))?;
+    let rect = rect.log_none(|| {
+        report!(
+            node,
+            InvalidSize,
+            "Mask '{}' has an invalid size. Skipped.",
+            node.element_id()
+        )
+    })?;
 
     // Resolve linked mask.
     let mut mask = None;
@@ -72,7 +76,7 @@ pub(crate) fn convert(
     converter::convert_children(node, state, cache, &mut mask.root);
 
     if mask.root.has_children() {
-        let mask = Rc::new(mask);
+        let mask = Shared::new(mask);
         cache
             .masks
             .insert(node.element_id().to_string(), mask.clone());
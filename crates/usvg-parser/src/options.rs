@@ -2,9 +2,9 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use usvg_tree::{ImageRendering, ShapeRendering, Size, TextRendering};
+use usvg_tree::{Diagnostics, ImageRendering, ShapeRendering, Size, TextRendering};
 
-use crate::ImageHrefResolver;
+use crate::{FileAccessPolicy, ImageHrefResolver};
 
 /// Processing options.
 #[derive(Debug)]
@@ -79,8 +79,51 @@ pub struct Options {
     /// Default: see type's documentation for details
     pub image_href_resolver: ImageHrefResolver,
 
+    /// Specifies which files can be loaded by the default `xlink:href` resolver.
+    ///
+    /// Default: unrestricted
+    pub file_access: FileAccessPolicy,
+
     /// Specifies whether or not to continue in the face of some parse errors.
     pub forgiving: bool,
+
+    /// Allow a DTD in the source SVG.
+    ///
+    /// When disabled, documents with a DOCTYPE will be rejected.
+    /// Otherwise, entities expansion is bound by `Limits::max_entity_expansion`
+    /// and `Limits::max_entity_depth`.
+    ///
+    /// Default: true
+    pub allow_dtd: bool,
+
+    /// A diagnostics collector.
+    ///
+    /// When set, all recoverable issues found during parsing will be reported to it,
+    /// in addition to the `log` crate.
+    ///
+    /// Default: `None`
+    pub diagnostics: Option<Diagnostics>,
+
+    /// Keep nodes origin in the source SVG.
+    ///
+    /// When set, each node will have an `origin` pointing to the element
+    /// it was created from.
+    ///
+    /// Default: false
+    pub keep_origins: bool,
+
+    /// Resource limits.
+    ///
+    /// Default: see type's documentation for details
+    pub limits: Limits,
+
+    /// A document time in seconds at which SMIL animations are evaluated.
+    ///
+    /// `animate`, `set`, `animateTransform`, `animateMotion` and `animateColor`
+    /// elements are ignored when not set.
+    ///
+    /// Default: `None`
+    pub animation_time: Option<f32>,
 }
 
 impl Default for Options {
@@ -97,7 +140,13 @@ impl Default for Options {
             image_rendering: ImageRendering::default(),
             default_size: Size::from_wh(100.0, 100.0).unwrap(),
             image_href_resolver: ImageHrefResolver::default(),
+            file_access: FileAccessPolicy::default(),
             forgiving: false,
+            allow_dtd: true,
+            diagnostics: None,
+            keep_origins: false,
+            limits: Limits::default(),
+            animation_time: None,
         }
     }
 }
@@ -113,3 +162,69 @@ impl Options {
         }
     }
 }
+
+/// Resource limits.
+///
+/// Used to prevent excessive memory usage while processing untrusted files.
+/// Exceeding any limit will result in a dedicated error.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Limits {
+    /// Maximum number of elements.
+    ///
+    /// Includes elements instantiated by `use`.
+    ///
+    /// Default: 1_000_000
+    pub max_elements: u32,
+
+    /// Maximum elements nesting depth.
+    ///
+    /// Each `use` reference counts as an additional nesting level.
+    ///
+    /// Default: 1024
+    pub max_nesting_depth: u32,
+
+    /// Maximum raster image size in pixels.
+    ///
+    /// Applies to `image` and `feImage` elements.
+    ///
+    /// Default: 268_435_456 (16384x16384)
+    pub max_image_pixels: u64,
+
+    /// Maximum filter region or layer size in pixels.
+    ///
+    /// Unlike other limits, checked during rendering.
+    ///
+    /// Default: 268_435_456 (16384x16384)
+    pub max_surface_pixels: u64,
+
+    /// Maximum expansion ratio of a compressed SVG data.
+    ///
+    /// SVG data that is smaller than 1 MiB after decompression is always allowed.
+    ///
+    /// Default: 100
+    pub max_gzip_ratio: u32,
+
+    /// Maximum total size of the text produced by DTD entities expansion in bytes.
+    ///
+    /// Default: 10_485_760 (10 MiB)
+    pub max_entity_expansion: u64,
+
+    /// Maximum DTD entities nesting depth.
+    ///
+    /// Default: 10
+    pub max_entity_depth: u32,
+}
+
+impl Default for Limits {
+    fn default() -> Limits {
+        Limits {
+            max_elements: 1_000_000,
+            max_nesting_depth: 1024,
+            max_image_pixels: 16384 * 16384,
+            max_surface_pixels: 16384 * 16384,
+            max_gzip_ratio: 100,
+            max_entity_expansion: 10 * 1024 * 1024,
+            max_entity_depth: 10,
+        }
+    }
+}
@@ -2,7 +2,6 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use std::rc::Rc;
 use std::str::FromStr;
 
 use strict_num::PositiveF32;
@@ -77,7 +76,7 @@ fn convert_linear(node: SvgNode, state: &converter::State) -> Option<ServerOrCol
         },
     };
 
-    Some(ServerOrColor::Server(Paint::LinearGradient(Rc::new(
+    Some(ServerOrColor::Server(Paint::LinearGradient(Shared::new(
         gradient,
     ))))
 }
@@ -140,7 +139,7 @@ fn convert_radial(node: SvgNode, state: &converter::State) -> Option<ServerOrCol
         },
     };
 
-    Some(ServerOrColor::Server(Paint::RadialGradient(Rc::new(
+    Some(ServerOrColor::Server(Paint::RadialGradient(Shared::new(
         gradient,
     ))))
 }
@@ -176,7 +175,9 @@ fn convert_pattern(
         resolve_number(node, AId::Height, units, state, Length::zero()),
     );
     let rect = rect.log_none(|| {
-        log::warn!(
+        report!(
+            node,
+            InvalidSize,
             "Pattern '{}' has an invalid size. Skipped.",
             node.element_id()
         )
@@ -198,7 +199,7 @@ fn convert_pattern(
         return None;
     }
 
-    Some(ServerOrColor::Server(Paint::Pattern(Rc::new(patt))))
+    Some(ServerOrColor::Server(Paint::Pattern(Shared::new(patt))))
 }
 
 fn convert_spread_method(node: SvgNode) -> SpreadMethod {
@@ -216,7 +217,9 @@ fn find_gradient_with_stops<'a, 'input: 'a>(
 ) -> Option<SvgNode<'a, 'input>> {
     for link in node.href_iter() {
         if !link.tag_name().unwrap().is_gradient() {
-            log::warn!(
+            report!(
+                node,
+                InvalidLink,
                 "Gradient '{}' cannot reference '{}' via 'xlink:href'.",
                 node.element_id(),
                 link.tag_name().unwrap()
@@ -237,7 +240,9 @@ fn find_pattern_with_children<'a, 'input: 'a>(
 ) -> Option<SvgNode<'a, 'input>> {
     for link in node.href_iter() {
         if link.tag_name() != Some(EId::Pattern) {
-            log::warn!(
+            report!(
+                node,
+                InvalidLink,
                 "Pattern '{}' cannot reference '{}' via 'xlink:href'.",
                 node.element_id(),
                 link.tag_name().unwrap()
@@ -260,7 +265,12 @@ fn convert_stops(grad: SvgNode) -> Vec<Stop> {
         let mut prev_offset = Length::zero();
         for stop in grad.children() {
             if stop.tag_name() != Some(EId::Stop) {
-                log::warn!("Invalid gradient child: '{:?}'.", stop.tag_name().unwrap());
+                report!(
+                    stop,
+                    InvalidElement,
+                    "Invalid gradient child: '{:?}'.",
+                    stop.tag_name().unwrap()
+                );
                 continue;
             }
 
@@ -282,7 +292,12 @@ fn convert_stops(grad: SvgNode) -> Vec<Stop> {
                     if let Ok(c) = svgtypes::Color::from_str(value) {
                         c
                     } else {
-                        log::warn!("Failed to parse stop-color value: '{}'.", value);
+                        report!(
+                            stop,
+                            InvalidAttribute,
+                            "Failed to parse stop-color value: '{}'.",
+                            value
+                        );
                         svgtypes::Color::black()
                     }
                 }
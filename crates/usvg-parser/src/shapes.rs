@@ -2,16 +2,14 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use std::rc::Rc;
-
 use svgtypes::Length;
 use tiny_skia_path::Path;
-use usvg_tree::{tiny_skia_path, ApproxEqUlps, IsValidLength};
+use usvg_tree::{tiny_skia_path, ApproxEqUlps, IsValidLength, Shared};
 
 use crate::svgtree::{AId, EId, SvgNode};
 use crate::{converter, units};
 
-pub(crate) fn convert(node: SvgNode, state: &converter::State) -> Option<Rc<Path>> {
+pub(crate) fn convert(node: SvgNode, state: &converter::State) -> Option<Shared<Path>> {
     match node.tag_name()? {
         EId::Rect => convert_rect(node, state),
         EId::Circle => convert_circle(node, state),
@@ -24,7 +22,7 @@ pub(crate) fn convert(node: SvgNode, state: &converter::State) -> Option<Rc<Path
     }
 }
 
-pub(crate) fn convert_path(node: SvgNode) -> Option<Rc<Path>> {
+pub(crate) fn convert_path(node: SvgNode) -> Option<Shared<Path>> {
     let value: &str = node.attribute(AId::D)?;
     let mut builder = tiny_skia_path::PathBuilder::new();
     for segment in svgtypes::SimplifyingPathParser::from(value) {
@@ -61,22 +59,26 @@ pub(crate) fn convert_path(node: SvgNode) -> Option<Rc<Path>> {
         }
     }
 
-    builder.finish().map(Rc::new)
+    builder.finish().map(Shared::new)
 }
 
-fn convert_rect(node: SvgNode, state: &converter::State) -> Option<Rc<Path>> {
+fn convert_rect(node: SvgNode, state: &converter::State) -> Option<Shared<Path>> {
     // 'width' and 'height' attributes must be positive and non-zero.
     let width = node.convert_user_length(AId::Width, state, Length::zero());
     let height = node.convert_user_length(AId::Height, state, Length::zero());
     if !width.is_valid_length() {
-        log::warn!(
+        report!(
+            node,
+            InvalidAttribute,
             "Rect '{}' has an invalid 'width' value. Skipped.",
             node.element_id()
         );
         return None;
     }
     if !height.is_valid_length() {
-        log::warn!(
+        report!(
+            node,
+            InvalidAttribute,
             "Rect '{}' has an invalid 'height' value. Skipped.",
             node.element_id()
         );
@@ -122,7 +124,7 @@ fn convert_rect(node: SvgNode, state: &converter::State) -> Option<Rc<Path>> {
         builder.finish()?
     };
 
-    Some(Rc::new(path))
+    Some(Shared::new(path))
 }
 
 fn resolve_rx_ry(node: SvgNode, state: &converter::State) -> (f32, f32) {
@@ -160,7 +162,7 @@ fn resolve_rx_ry(node: SvgNode, state: &converter::State) -> (f32, f32) {
     }
 }
 
-fn convert_line(node: SvgNode, state: &converter::State) -> Option<Rc<Path>> {
+fn convert_line(node: SvgNode, state: &converter::State) -> Option<Shared<Path>> {
     let x1 = node.convert_user_length(AId::X1, state, Length::zero());
     let y1 = node.convert_user_length(AId::Y1, state, Length::zero());
     let x2 = node.convert_user_length(AId::X2, state, Length::zero());
@@ -169,18 +171,18 @@ fn convert_line(node: SvgNode, state: &converter::State) -> Option<Rc<Path>> {
     let mut builder = tiny_skia_path::PathBuilder::new();
     builder.move_to(x1, y1);
     builder.line_to(x2, y2);
-    builder.finish().map(Rc::new)
+    builder.finish().map(Shared::new)
 }
 
-fn convert_polyline(node: SvgNode) -> Option<Rc<Path>> {
+fn convert_polyline(node: SvgNode) -> Option<Shared<Path>> {
     let builder = points_to_path(node, "Polyline")?;
-    builder.finish().map(Rc::new)
+    builder.finish().map(Shared::new)
 }
 
-fn convert_polygon(node: SvgNode) -> Option<Rc<Path>> {
+fn convert_polygon(node: SvgNode) -> Option<Shared<Path>> {
     let mut builder = points_to_path(node, "Polygon")?;
     builder.close();
-    builder.finish().map(Rc::new)
+    builder.finish().map(Shared::new)
 }
 
 fn points_to_path(node: SvgNode, eid: &str) -> Option<tiny_skia_path::PathBuilder> {
@@ -198,7 +200,9 @@ fn points_to_path(node: SvgNode, eid: &str) -> Option<tiny_skia_path::PathBuilde
             }
         }
         _ => {
-            log::warn!(
+            report!(
+                node,
+                InvalidAttribute,
                 "{} '{}' has an invalid 'points' value. Skipped.",
                 eid,
                 node.element_id()
@@ -209,7 +213,9 @@ fn points_to_path(node: SvgNode, eid: &str) -> Option<tiny_skia_path::PathBuilde
 
     // 'polyline' and 'polygon' elements must contain at least 2 points.
     if builder.len() < 2 {
-        log::warn!(
+        report!(
+            node,
+            InvalidAttribute,
             "{} '{}' has less than 2 points. Skipped.",
             eid,
             node.element_id()
@@ -220,13 +226,15 @@ fn points_to_path(node: SvgNode, eid: &str) -> Option<tiny_skia_path::PathBuilde
     Some(builder)
 }
 
-fn convert_circle(node: SvgNode, state: &converter::State) -> Option<Rc<Path>> {
+fn convert_circle(node: SvgNode, state: &converter::State) -> Option<Shared<Path>> {
     let cx = node.convert_user_length(AId::Cx, state, Length::zero());
     let cy = node.convert_user_length(AId::Cy, state, Length::zero());
     let r = node.convert_user_length(AId::R, state, Length::zero());
 
     if !r.is_valid_length() {
-        log::warn!(
+        report!(
+            node,
+            InvalidAttribute,
             "Circle '{}' has an invalid 'r' value. Skipped.",
             node.element_id()
         );
@@ -236,13 +244,15 @@ fn convert_circle(node: SvgNode, state: &converter::State) -> Option<Rc<Path>> {
     ellipse_to_path(cx, cy, r, r)
 }
 
-fn convert_ellipse(node: SvgNode, state: &converter::State) -> Option<Rc<Path>> {
+fn convert_ellipse(node: SvgNode, state: &converter::State) -> Option<Shared<Path>> {
     let cx = node.convert_user_length(AId::Cx, state, Length::zero());
     let cy = node.convert_user_length(AId::Cy, state, Length::zero());
     let (rx, ry) = resolve_rx_ry(node, state);
 
     if !rx.is_valid_length() {
-        log::warn!(
+        report!(
+            node,
+            InvalidAttribute,
             "Ellipse '{}' has an invalid 'rx' value. Skipped.",
             node.element_id()
         );
@@ -250,7 +260,9 @@ fn convert_ellipse(node: SvgNode, state: &converter::State) -> Option<Rc<Path>>
     }
 
     if !ry.is_valid_length() {
-        log::warn!(
+        report!(
+            node,
+            InvalidAttribute,
             "Ellipse '{}' has an invalid 'ry' value. Skipped.",
             node.element_id()
         );
@@ -260,7 +272,7 @@ fn convert_ellipse(node: SvgNode, state: &converter::State) -> Option<Rc<Path>>
     ellipse_to_path(cx, cy, rx, ry)
 }
 
-fn ellipse_to_path(cx: f32, cy: f32, rx: f32, ry: f32) -> Option<Rc<Path>> {
+fn ellipse_to_path(cx: f32, cy: f32, rx: f32, ry: f32) -> Option<Shared<Path>> {
     let mut builder = tiny_skia_path::PathBuilder::new();
     builder.move_to(cx + rx, cy);
     builder.arc_to(rx, ry, 0.0, false, true, cx, cy + ry);
@@ -268,7 +280,7 @@ fn ellipse_to_path(cx: f32, cy: f32, rx: f32, ry: f32) -> Option<Rc<Path>> {
     builder.arc_to(rx, ry, 0.0, false, true, cx, cy - ry);
     builder.arc_to(rx, ry, 0.0, false, true, cx + rx, cy);
     builder.close();
-    builder.finish().map(Rc::new)
+    builder.finish().map(Shared::new)
 }
 
 trait PathBuilderExt {
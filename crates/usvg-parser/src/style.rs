@@ -2,7 +2,9 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use usvg_tree::{Color, Fill, Opacity, Paint, Stroke, StrokeMiterlimit, Units, VectorEffect, ApproxEqUlps};
+use usvg_tree::{
+    ApproxEqUlps, Color, Fill, Opacity, Paint, Stroke, StrokeMiterlimit, Units, VectorEffect,
+};
 
 use crate::converter::SvgColorExt;
 use crate::svgtree::{AId, FromValue, SvgNode};
@@ -129,10 +131,12 @@ pub(crate) fn resolve_stroke(
     Some(stroke)
 }
 
-pub(crate) fn resolve_vector_effect(
-    node: SvgNode,
-) -> VectorEffect {
-    if let Some(n) = node.ancestors().find(|n| n.has_attribute(AId::VectorEffect)) {
+// Vector effects on groups are not supported, therefore they are applied to the group's children.
+pub(crate) fn resolve_vector_effect(node: SvgNode) -> VectorEffect {
+    if let Some(n) = node
+        .ancestors()
+        .find(|n| n.has_attribute(AId::VectorEffect))
+    {
         n.find_attribute(AId::VectorEffect).unwrap_or_default()
     } else {
         VectorEffect::default()
@@ -152,7 +156,9 @@ fn convert_paint(
         Ok(v) => v,
         Err(_) => {
             if aid == AId::Fill {
-                log::warn!(
+                report!(
+                    node,
+                    InvalidAttribute,
                     "Failed to parse fill value: '{}'. Fallback to black.",
                     value
                 );
@@ -202,7 +208,13 @@ fn convert_paint(
                         None => from_fallback(node, fallback, opacity),
                     }
                 } else {
-                    log::warn!("'{}' cannot be used to {} a shape.", tag_name, aid);
+                    report!(
+                        node,
+                        InvalidLink,
+                        "'{}' cannot be used to {} a shape.",
+                        tag_name,
+                        aid
+                    );
                     None
                 }
             } else {
@@ -0,0 +1,1238 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! SMIL animations evaluation.
+//!
+//! Animation elements are not a part of the tree. Instead, animated values
+//! at the requested time are written into target elements during parsing.
+//! CSS animations are evaluated by the `css_animation` module.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use kurbo::{ParamCurve, ParamCurveArclen};
+
+use super::css_animation::{self, KeyframesMap};
+use super::parse::{SVG_NS, XLINK_NS};
+use super::AId;
+
+/// Limits the number of intervals per animation.
+const MAX_INTERVALS: usize = 1000;
+
+/// Limits the number of passes during syncbase values resolving.
+const MAX_PASSES: usize = 1000;
+
+/// Motion path length accuracy.
+const ARCLEN_ACCURACY: f64 = 0.01;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Kind {
+    /// `animate` and `animateColor`.
+    Animate,
+    Set,
+    Transform,
+    Motion,
+}
+
+fn parse_kind(node: roxmltree::Node) -> Option<Kind> {
+    if !node.is_element() || node.tag_name().namespace() != Some(SVG_NS) {
+        return None;
+    }
+
+    match node.tag_name().name() {
+        "animate" | "animateColor" => Some(Kind::Animate),
+        "set" => Some(Kind::Set),
+        "animateTransform" => Some(Kind::Transform),
+        "animateMotion" => Some(Kind::Motion),
+        _ => None,
+    }
+}
+
+/// Animations state at a specific time.
+#[derive(Default, Debug)]
+pub(crate) struct Animations {
+    /// Active and frozen animations by target element, in document order.
+    effects: HashMap<roxmltree::NodeId, Vec<Effect>>,
+    /// CSS `@keyframes` rules by name.
+    keyframes: KeyframesMap,
+    /// Document time in seconds.
+    time: f64,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Effect {
+    node: roxmltree::NodeId,
+    kind: Kind,
+    /// Position within the simple duration, in the 0..=1 range.
+    progress: f64,
+    /// Repeat iteration.
+    iteration: u32,
+    /// An element referenced by an `mpath` child of `animateMotion`.
+    motion_path: Option<roxmltree::NodeId>,
+}
+
+impl Animations {
+    /// Evaluates timing of all animations in the document.
+    pub(crate) fn collect<'input>(
+        xml: &roxmltree::Document<'input>,
+        time: f32,
+        id_map: &HashMap<&str, roxmltree::Node<'_, 'input>>,
+    ) -> Self {
+        let time = time as f64;
+        let keyframes = css_animation::collect_keyframes(xml);
+
+        let mut animations = Vec::new();
+        for node in xml.descendants() {
+            let kind = match parse_kind(node) {
+                Some(v) => v,
+                None => continue,
+            };
+
+            let target = match href(node) {
+                Some(link) => svgtypes::IRI::from_str(link)
+                    .ok()
+                    .and_then(|iri| id_map.get(iri.0).copied()),
+                None => node.parent_element(),
+            };
+
+            if let Some(target) = target {
+                animations.push((node, kind, target));
+            }
+        }
+
+        if animations.is_empty() {
+            return Animations {
+                effects: HashMap::new(),
+                keyframes,
+                time,
+            };
+        }
+
+        // Syncbase values can reference only other animations.
+        let mut ids = HashMap::new();
+        for (i, (node, _, _)) in animations.iter().enumerate() {
+            if let Some(id) = node.attribute("id") {
+                ids.entry(id).or_insert(i);
+            }
+        }
+
+        let timings: Vec<_> = animations
+            .iter()
+            .map(|(node, _, _)| Timing::parse(*node, &ids))
+            .collect();
+        let intervals = resolve_intervals(&timings, time);
+
+        let mut effects: HashMap<_, Vec<_>> = HashMap::new();
+        for (i, (node, kind, target)) in animations.iter().enumerate() {
+            let (progress, iteration) = match timings[i].state(&intervals[i], time) {
+                Some(v) => v,
+                None => continue,
+            };
+
+            let motion_path = if *kind == Kind::Motion {
+                node.children()
+                    .find(|n| n.has_tag_name((SVG_NS, "mpath")))
+                    .and_then(href)
+                    .and_then(|link| svgtypes::IRI::from_str(link).ok())
+                    .and_then(|iri| id_map.get(iri.0))
+                    .map(|n| n.id())
+            } else {
+                None
+            };
+
+            effects.entry(target.id()).or_default().push(Effect {
+                node: node.id(),
+                kind: *kind,
+                progress,
+                iteration,
+                motion_path,
+            });
+        }
+
+        Animations {
+            effects,
+            keyframes,
+            time,
+        }
+    }
+
+    /// Returns attributes of an element animated by CSS animations.
+    ///
+    /// `declarations` are element's `animation` and `transform-origin` declarations
+    /// in the cascade order. `base` returns a not animated attribute value.
+    pub(crate) fn resolve_css(
+        &self,
+        declarations: &[(&str, &str)],
+        base: impl Fn(AId) -> Option<String>,
+    ) -> Vec<(AId, String)> {
+        if self.keyframes.is_empty() || declarations.is_empty() {
+            return Vec::new();
+        }
+
+        css_animation::resolve(&self.keyframes, self.time, declarations, base)
+    }
+
+    /// Returns animated attributes of an element.
+    ///
+    /// `base` returns a not animated attribute value.
+    pub(crate) fn resolve(
+        &self,
+        node: roxmltree::Node,
+        base: impl Fn(AId) -> Option<String>,
+    ) -> Vec<(AId, String)> {
+        let effects = match self.effects.get(&node.id()) {
+            Some(v) => v,
+            None => return Vec::new(),
+        };
+
+        let xml = node.document();
+        let mut values: Vec<(AId, String)> = Vec::new();
+        let mut motion: Option<String> = None;
+        for effect in effects {
+            let anim = match xml.get_node(effect.node) {
+                Some(v) => v,
+                None => continue,
+            };
+
+            match effect.kind {
+                Kind::Animate | Kind::Set => {
+                    let aid = match anim.attribute("attributeName").and_then(AId::from_str) {
+                        Some(v) => v,
+                        None => continue,
+                    };
+
+                    // Transforms can be animated only by `animateTransform`.
+                    if matches!(
+                        aid,
+                        AId::Id | AId::Transform | AId::GradientTransform | AId::PatternTransform
+                    ) {
+                        continue;
+                    }
+
+                    let base = current_value(&values, aid).or_else(|| base(aid));
+                    if let Some(value) = animate_value(anim, effect, aid, base.as_deref()) {
+                        set_value(&mut values, aid, value);
+                    }
+                }
+                Kind::Transform => {
+                    let aid = match anim.attribute("attributeName") {
+                        Some(name) => match AId::from_str(name) {
+                            Some(
+                                aid @ (AId::Transform
+                                | AId::GradientTransform
+                                | AId::PatternTransform),
+                            ) => aid,
+                            _ => continue,
+                        },
+                        None => AId::Transform,
+                    };
+
+                    let base = current_value(&values, aid).or_else(|| base(aid));
+                    if let Some(value) = transform_value(anim, effect, base.as_deref()) {
+                        set_value(&mut values, aid, value);
+                    }
+                }
+                Kind::Motion => {
+                    if let Some(value) = motion_value(anim, effect) {
+                        motion = match motion {
+                            Some(prev) if is_additive(anim) => Some(format!("{} {}", prev, value)),
+                            _ => Some(value),
+                        };
+                    }
+                }
+            }
+        }
+
+        // Motion is applied on top of the `transform` attribute.
+        if let Some(motion) = motion {
+            let value =
+                match current_value(&values, AId::Transform).or_else(|| base(AId::Transform)) {
+                    Some(ts) => format!("{} {}", motion, ts),
+                    None => motion,
+                };
+
+            set_value(&mut values, AId::Transform, value);
+        }
+
+        values
+    }
+}
+
+fn href<'a>(node: roxmltree::Node<'a, '_>) -> Option<&'a str> {
+    node.attribute((XLINK_NS, "href"))
+        .or_else(|| node.attribute("href"))
+}
+
+pub(super) fn current_value(values: &[(AId, String)], aid: AId) -> Option<String> {
+    values
+        .iter()
+        .find(|(name, _)| *name == aid)
+        .map(|(_, value)| value.clone())
+}
+
+pub(super) fn set_value(values: &mut Vec<(AId, String)>, aid: AId, value: String) {
+    match values.iter_mut().find(|(name, _)| *name == aid) {
+        Some(item) => item.1 = value,
+        None => values.push((aid, value)),
+    }
+}
+
+fn is_additive(anim: roxmltree::Node) -> bool {
+    anim.attribute("additive") == Some("sum")
+}
+
+fn is_accumulative(anim: roxmltree::Node) -> bool {
+    anim.attribute("accumulate") == Some("sum")
+}
+
+#[derive(Clone, Copy, Debug)]
+enum TimeValue {
+    Offset(f64),
+    /// `id.begin` or `id.end` with an offset.
+    Sync {
+        index: usize,
+        end: bool,
+        offset: f64,
+    },
+}
+
+#[derive(Debug)]
+struct Timing {
+    begin: Vec<TimeValue>,
+    end: Vec<TimeValue>,
+    /// Simple duration. `None` when indefinite.
+    dur: Option<f64>,
+    /// Active duration. `None` when indefinite.
+    active_dur: Option<f64>,
+    freeze: bool,
+}
+
+impl Timing {
+    fn parse(node: roxmltree::Node, ids: &HashMap<&str, usize>) -> Self {
+        let begin = match node.attribute("begin") {
+            Some(value) => parse_time_list(value, ids),
+            None => vec![TimeValue::Offset(0.0)],
+        };
+
+        let end = node
+            .attribute("end")
+            .map(|value| parse_time_list(value, ids))
+            .unwrap_or_default();
+
+        let dur = node
+            .attribute("dur")
+            .and_then(parse_clock_value)
+            .filter(|dur| *dur > 0.0);
+
+        let repeat_count = node.attribute("repeatCount").and_then(|value| {
+            let value = value.trim();
+            if value == "indefinite" {
+                Some(f64::INFINITY)
+            } else {
+                value.parse::<f64>().ok().filter(|n| *n > 0.0)
+            }
+        });
+
+        let repeat_dur = node.attribute("repeatDur").and_then(|value| {
+            if value.trim() == "indefinite" {
+                Some(f64::INFINITY)
+            } else {
+                parse_clock_value(value)
+            }
+        });
+
+        let active_dur = if repeat_count.is_none() && repeat_dur.is_none() {
+            dur
+        } else {
+            let by_count = match (repeat_count, dur) {
+                (Some(n), Some(dur)) => n * dur,
+                _ => f64::INFINITY,
+            };
+
+            Some(by_count.min(repeat_dur.unwrap_or(f64::INFINITY))).filter(|d| d.is_finite())
+        };
+
+        Timing {
+            begin,
+            end,
+            dur,
+            active_dur,
+            freeze: node.attribute("fill") == Some("freeze"),
+        }
+    }
+
+    /// Calculates intervals that begin before `time`.
+    fn intervals(&self, all: &[Vec<(f64, f64)>], time: f64) -> Vec<(f64, f64)> {
+        let mut begins = instance_times(&self.begin, all);
+        begins.retain(|begin| *begin <= time);
+        begins.sort_by(|a, b| a.total_cmp(b));
+        begins.dedup();
+
+        let mut ends = instance_times(&self.end, all);
+        ends.sort_by(|a, b| a.total_cmp(b));
+
+        let mut intervals = Vec::with_capacity(begins.len());
+        for (i, &begin) in begins.iter().enumerate() {
+            let mut end = match self.active_dur {
+                Some(dur) => begin + dur,
+                None => f64::INFINITY,
+            };
+
+            if let Some(&value) = ends.iter().find(|value| **value >= begin) {
+                end = end.min(value);
+            }
+
+            // A new begin restarts the animation.
+            if let Some(&next) = begins.get(i + 1) {
+                end = end.min(next);
+            }
+
+            intervals.push((begin, end));
+        }
+
+        if intervals.len() > MAX_INTERVALS {
+            intervals.drain(..intervals.len() - MAX_INTERVALS);
+        }
+
+        intervals
+    }
+
+    /// Returns the progress and the iteration at `time`.
+    ///
+    /// Returns `None` when the animation has no effect.
+    fn state(&self, intervals: &[(f64, f64)], time: f64) -> Option<(f64, u32)> {
+        let (begin, end) = *intervals.last()?;
+        let (local_time, is_frozen) = if time < end {
+            (time - begin, false)
+        } else if self.freeze {
+            (end - begin, true)
+        } else {
+            return None;
+        };
+
+        let dur = match self.dur {
+            Some(v) => v,
+            None => return Some((0.0, 0)),
+        };
+
+        let n = local_time / dur;
+        let mut iteration = n.floor();
+        let mut progress = n - iteration;
+        // An animation frozen at the end of an iteration keeps its last value.
+        if is_frozen && progress < 1e-9 && iteration > 0.0 {
+            iteration -= 1.0;
+            progress = 1.0;
+        }
+
+        Some((progress, iteration.min(u32::MAX as f64) as u32))
+    }
+}
+
+fn instance_times(values: &[TimeValue], all: &[Vec<(f64, f64)>]) -> Vec<f64> {
+    let mut times = Vec::new();
+    for value in values {
+        match *value {
+            TimeValue::Offset(offset) => times.push(offset),
+            TimeValue::Sync { index, end, offset } => {
+                for &(b, e) in &all[index] {
+                    let time = if end { e } else { b };
+                    if time.is_finite() {
+                        times.push(time + offset);
+                    }
+                }
+            }
+        }
+    }
+
+    times
+}
+
+/// Resolves animation intervals, including the ones that depend on other animations.
+fn resolve_intervals(timings: &[Timing], time: f64) -> Vec<Vec<(f64, f64)>> {
+    let mut intervals = vec![Vec::new(); timings.len()];
+    for _ in 0..MAX_PASSES {
+        let mut changed = false;
+        for (i, timing) in timings.iter().enumerate() {
+            let new_intervals = timing.intervals(&intervals, time);
+            if new_intervals != intervals[i] {
+                intervals[i] = new_intervals;
+                changed = true;
+            }
+        }
+
+        if !changed {
+            break;
+        }
+    }
+
+    intervals
+}
+
+fn parse_time_list(text: &str, ids: &HashMap<&str, usize>) -> Vec<TimeValue> {
+    text.split(';')
+        .filter_map(|value| parse_time_value(value.trim(), ids))
+        .collect()
+}
+
+/// Parses an offset or a syncbase value.
+///
+/// Event, repeat, access key and wallclock values are not supported.
+fn parse_time_value(text: &str, ids: &HashMap<&str, usize>) -> Option<TimeValue> {
+    let first = text.bytes().next()?;
+    if first.is_ascii_digit() || matches!(first, b'+' | b'-' | b'.') {
+        return parse_offset(text).map(TimeValue::Offset);
+    }
+
+    let (id, end, rest) = if let Some(idx) = text.find(".begin") {
+        (&text[..idx], false, &text[idx + 6..])
+    } else if let Some(idx) = text.find(".end") {
+        (&text[..idx], true, &text[idx + 4..])
+    } else {
+        return None;
+    };
+
+    let rest = rest.trim();
+    let offset = if rest.is_empty() {
+        0.0
+    } else if rest.starts_with('+') || rest.starts_with('-') {
+        parse_offset(rest)?
+    } else {
+        return None;
+    };
+
+    Some(TimeValue::Sync {
+        index: *ids.get(id)?,
+        end,
+        offset,
+    })
+}
+
+fn parse_offset(text: &str) -> Option<f64> {
+    if let Some(text) = text.strip_prefix('-') {
+        parse_clock_value(text).map(|v| -v)
+    } else {
+        parse_clock_value(text.strip_prefix('+').unwrap_or(text))
+    }
+}
+
+/// Parses a clock value in seconds.
+fn parse_clock_value(text: &str) -> Option<f64> {
+    let text = text.trim();
+    let value = if text.contains(':') {
+        let mut value = 0.0;
+        let mut count = 0;
+        for part in text.split(':') {
+            value = value * 60.0 + part.trim().parse::<f64>().ok()?;
+            count += 1;
+        }
+
+        if count > 3 {
+            return None;
+        }
+
+        value
+    } else {
+        let (number, scale) = if let Some(n) = text.strip_suffix("ms") {
+            (n, 0.001)
+        } else if let Some(n) = text.strip_suffix("min") {
+            (n, 60.0)
+        } else if let Some(n) = text.strip_suffix('h') {
+            (n, 3600.0)
+        } else if let Some(n) = text.strip_suffix('s') {
+            (n, 1.0)
+        } else {
+            (text, 1.0)
+        };
+
+        number.trim().parse::<f64>().ok()? * scale
+    };
+
+    if value.is_finite() && value >= 0.0 {
+        Some(value)
+    } else {
+        None
+    }
+}
+
+/// Evaluates `animate` and `set`.
+fn animate_value(
+    anim: roxmltree::Node,
+    effect: &Effect,
+    aid: AId,
+    base: Option<&str>,
+) -> Option<String> {
+    let parse = |text: &str| Some(Value::from_attribute(aid, text.trim()));
+
+    if effect.kind == Kind::Set {
+        return parse(anim.attribute("to")?).map(|v| v.to_string());
+    }
+
+    let base = base.and_then(parse);
+    let (values, is_additive) = animation_values(anim, parse, base.as_ref())?;
+    let mut value = sample(anim, &values, effect.progress, CalcMode::Linear)?;
+
+    if is_accumulative(anim) && effect.iteration != 0 {
+        let last = values.last()?.scale(effect.iteration as f64);
+        value = value.add(&last).unwrap_or(value);
+    }
+
+    if is_additive {
+        if let Some(base) = base {
+            value = base.add(&value).unwrap_or(value);
+        }
+    }
+
+    Some(value.to_string())
+}
+
+/// Evaluates `animateTransform`.
+fn transform_value(anim: roxmltree::Node, effect: &Effect, base: Option<&str>) -> Option<String> {
+    let kind = anim.attribute("type").unwrap_or("translate").trim();
+    let identity: &[f64] = match kind {
+        "translate" => &[0.0, 0.0],
+        "scale" => &[1.0, 1.0],
+        "rotate" => &[0.0, 0.0, 0.0],
+        "skewX" | "skewY" => &[0.0],
+        _ => return None,
+    };
+
+    let parse = |text: &str| {
+        let mut list = parse_number_list(text)?;
+        match (kind, list.len()) {
+            ("translate", 1) => list.push(0.0),
+            ("scale", 1) => list.push(list[0]),
+            ("rotate", 1) => list.extend_from_slice(&[0.0, 0.0]),
+            (_, n) if n == identity.len() => {}
+            _ => return None,
+        }
+
+        Some(Value::Numbers(Numbers::from_list(&list)))
+    };
+
+    let identity = Value::Numbers(Numbers::from_list(identity));
+    let (values, is_additive) = animation_values(anim, parse, Some(&identity))?;
+    let mut value = sample(anim, &values, effect.progress, CalcMode::Linear)?;
+
+    if is_accumulative(anim) && effect.iteration != 0 {
+        let last = values.last()?.scale(effect.iteration as f64);
+        value = value.add(&last).unwrap_or(value);
+    }
+
+    let value = format!("{}({})", kind, value);
+    match base {
+        Some(base) if is_additive => Some(format!("{} {}", base, value)),
+        _ => Some(value),
+    }
+}
+
+/// Evaluates `animateMotion`.
+fn motion_value(anim: roxmltree::Node, effect: &Effect) -> Option<String> {
+    let path_data = match effect.motion_path {
+        Some(id) => anim.document().get_node(id)?.attribute("d"),
+        None => anim.attribute("path"),
+    };
+
+    let (x, y, angle) = if let Some(data) = path_data {
+        let path = MotionPath::new(data)?;
+
+        let mut distance = effect.progress;
+        if let Some(text) = anim.attribute("keyPoints") {
+            if parse_calc_mode(anim, CalcMode::Paced) != CalcMode::Paced {
+                let points = parse_list(text)?;
+                if points.iter().any(|p| !(0.0..=1.0).contains(p)) {
+                    return None;
+                }
+
+                let points: Vec<_> = points
+                    .iter()
+                    .map(|p| Value::Numbers(Numbers::from_list(&[*p])))
+                    .collect();
+                match sample(anim, &points, effect.progress, CalcMode::Paced)? {
+                    Value::Numbers(n) => distance = n.numbers[0],
+                    _ => return None,
+                }
+            }
+        }
+
+        path.at(distance * path.length)
+    } else {
+        let parse = |text: &str| {
+            let list = parse_number_list(text)?;
+            if list.len() == 2 {
+                Some(Value::Numbers(Numbers::from_list(&list)))
+            } else {
+                None
+            }
+        };
+
+        let origin = Value::Numbers(Numbers::from_list(&[0.0, 0.0]));
+        let (values, _) = animation_values(anim, parse, Some(&origin))?;
+        let (idx, t) = locate(anim, &values, effect.progress, CalcMode::Paced)?;
+
+        let point = |value: &Value| match value {
+            Value::Numbers(n) => (n.numbers[0], n.numbers[1]),
+            _ => (0.0, 0.0),
+        };
+
+        let (x1, y1) = point(&values[idx]);
+        let (x2, y2) = values.get(idx + 1).map(point).unwrap_or((x1, y1));
+        let x = x1 + (x2 - x1) * t;
+        let y = y1 + (y2 - y1) * t;
+        (x, y, (y2 - y1).atan2(x2 - x1).to_degrees())
+    };
+
+    let angle = match anim.attribute("rotate").map(str::trim) {
+        Some("auto") => angle,
+        Some("auto-reverse") => angle + 180.0,
+        Some(value) => value.parse::<f64>().unwrap_or(0.0),
+        None => 0.0,
+    };
+
+    if angle != 0.0 {
+        Some(format!("translate({} {}) rotate({})", x, y, angle))
+    } else {
+        Some(format!("translate({} {})", x, y))
+    }
+}
+
+/// Returns animation values and whether they should be added to the base value.
+fn animation_values(
+    anim: roxmltree::Node,
+    parse: impl Fn(&str) -> Option<Value>,
+    base: Option<&Value>,
+) -> Option<(Vec<Value>, bool)> {
+    let is_additive = is_additive(anim);
+
+    if let Some(text) = anim.attribute("values") {
+        let values = text
+            .split(';')
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(&parse)
+            .collect::<Option<Vec<_>>>()?;
+
+        if values.is_empty() {
+            return None;
+        }
+
+        return Some((values, is_additive));
+    }
+
+    let from = anim.attribute("from").map(&parse);
+    let to = anim.attribute("to").map(&parse);
+    let by = anim.attribute("by").map(&parse);
+    match (from, to, by) {
+        (Some(from), Some(to), _) => Some((vec![from?, to?], is_additive)),
+        (Some(from), None, Some(by)) => {
+            let from = from?;
+            let to = from.add(&by?)?;
+            Some((vec![from, to], is_additive))
+        }
+        // A by-animation is always additive.
+        (None, None, Some(by)) => {
+            let by = by?;
+            Some((vec![by.zero(), by], true))
+        }
+        // A to-animation is never additive.
+        (None, Some(to), _) => {
+            let to = to?;
+            let from = match base {
+                Some(base) => base.clone(),
+                None => to.zero(),
+            };
+
+            Some((vec![from, to], false))
+        }
+        _ => None,
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum CalcMode {
+    Discrete,
+    Linear,
+    Paced,
+    Spline,
+}
+
+fn parse_calc_mode(anim: roxmltree::Node, default: CalcMode) -> CalcMode {
+    match anim.attribute("calcMode") {
+        Some("discrete") => CalcMode::Discrete,
+        Some("linear") => CalcMode::Linear,
+        Some("paced") => CalcMode::Paced,
+        Some("spline") => CalcMode::Spline,
+        _ => default,
+    }
+}
+
+/// Returns a value at the specified progress.
+fn sample(
+    anim: roxmltree::Node,
+    values: &[Value],
+    progress: f64,
+    default_mode: CalcMode,
+) -> Option<Value> {
+    let (idx, t) = locate(anim, values, progress, default_mode)?;
+    if t <= 0.0 {
+        Some(values[idx].clone())
+    } else if t >= 1.0 {
+        Some(values[idx + 1].clone())
+    } else {
+        Some(values[idx].lerp(&values[idx + 1], t))
+    }
+}
+
+/// Returns a values segment and a position within it at the specified progress.
+///
+/// Returns `None` when timing attributes are invalid.
+fn locate(
+    anim: roxmltree::Node,
+    values: &[Value],
+    progress: f64,
+    default_mode: CalcMode,
+) -> Option<(usize, f64)> {
+    let n = values.len();
+    if n == 1 {
+        return Some((0, 0.0));
+    }
+
+    let mut mode = parse_calc_mode(anim, default_mode);
+    // Values that cannot be interpolated are switched.
+    if mode != CalcMode::Discrete && !values.windows(2).all(|w| w[0].is_compatible(&w[1])) {
+        mode = CalcMode::Discrete;
+    }
+
+    let key_times = match mode {
+        CalcMode::Paced => {
+            paced_key_times(values).unwrap_or_else(|| default_key_times(n, CalcMode::Linear))
+        }
+        _ => match anim.attribute("keyTimes") {
+            Some(text) => parse_key_times(text, n, mode)?,
+            None => default_key_times(n, mode),
+        },
+    };
+
+    if mode == CalcMode::Discrete {
+        let idx = key_times.iter().rposition(|k| *k <= progress).unwrap_or(0);
+        return Some((idx, 0.0));
+    }
+
+    let idx = key_times[..n - 1]
+        .iter()
+        .rposition(|k| *k <= progress)
+        .unwrap_or(0);
+    let len = key_times[idx + 1] - key_times[idx];
+    let mut t = if len > 0.0 {
+        ((progress - key_times[idx]) / len).clamp(0.0, 1.0)
+    } else {
+        1.0
+    };
+
+    if mode == CalcMode::Spline {
+        let splines = parse_key_splines(anim.attribute("keySplines")?, n - 1)?;
+        t = eval_spline(splines[idx], t);
+    }
+
+    Some((idx, t))
+}
+
+fn default_key_times(n: usize, mode: CalcMode) -> Vec<f64> {
+    let count = if mode == CalcMode::Discrete { n } else { n - 1 };
+    (0..n).map(|i| i as f64 / count as f64).collect()
+}
+
+fn paced_key_times(values: &[Value]) -> Option<Vec<f64>> {
+    let mut times = vec![0.0];
+    let mut total = 0.0;
+    for w in values.windows(2) {
+        total += w[0].distance(&w[1])?;
+        times.push(total);
+    }
+
+    if total <= 0.0 {
+        return None;
+    }
+
+    times.iter_mut().for_each(|t| *t /= total);
+    Some(times)
+}
+
+fn parse_key_times(text: &str, n: usize, mode: CalcMode) -> Option<Vec<f64>> {
+    let times = parse_list(text)?;
+    let is_valid = times.len() == n
+        && times[0] == 0.0
+        && times.windows(2).all(|w| w[0] <= w[1])
+        && (mode == CalcMode::Discrete || times[n - 1] == 1.0)
+        && times[n - 1] <= 1.0;
+
+    if is_valid {
+        Some(times)
+    } else {
+        None
+    }
+}
+
+fn parse_key_splines(text: &str, n: usize) -> Option<Vec<[f64; 4]>> {
+    let mut splines = Vec::new();
+    for spline in text.split(';').map(str::trim).filter(|v| !v.is_empty()) {
+        let list = parse_number_list(spline)?;
+        if list.len() != 4 || list.iter().any(|v| !(0.0..=1.0).contains(v)) {
+            return None;
+        }
+
+        splines.push([list[0], list[1], list[2], list[3]]);
+    }
+
+    if splines.len() == n {
+        Some(splines)
+    } else {
+        None
+    }
+}
+
+/// Evaluates a `keySplines` easing curve.
+pub(super) fn eval_spline(spline: [f64; 4], x: f64) -> f64 {
+    let [x1, y1, x2, y2] = spline;
+    let bezier = |s: f64, p1: f64, p2: f64| {
+        let r = 1.0 - s;
+        3.0 * r * r * s * p1 + 3.0 * r * s * s * p2 + s * s * s
+    };
+
+    // The curve is monotonic in X, so bisection is enough.
+    let mut lo = 0.0;
+    let mut hi = 1.0;
+    for _ in 0..32 {
+        let mid = (lo + hi) * 0.5;
+        if bezier(mid, x1, x2) < x {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+
+    bezier((lo + hi) * 0.5, y1, y2)
+}
+
+/// Parses a semicolon-separated list of numbers.
+fn parse_list(text: &str) -> Option<Vec<f64>> {
+    let list = text
+        .split(';')
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(|v| v.parse::<f64>().ok().filter(|n| n.is_finite()))
+        .collect::<Option<Vec<_>>>()?;
+
+    if list.is_empty() {
+        None
+    } else {
+        Some(list)
+    }
+}
+
+/// Parses a whitespace or comma separated list of numbers.
+fn parse_number_list(text: &str) -> Option<Vec<f64>> {
+    text.split(|c: char| c.is_ascii_whitespace() || c == ',')
+        .filter(|v| !v.is_empty())
+        .map(|v| v.parse::<f64>().ok().filter(|n| n.is_finite()))
+        .collect()
+}
+
+/// An animation value.
+#[derive(Clone, Debug)]
+pub(super) enum Value {
+    /// A value with numbers that can be interpolated, like a length, a list or path data.
+    Numbers(Numbers),
+    /// RGBA color components in the 0..=255 range.
+    Color([f64; 4]),
+    /// A value that can only be switched.
+    Other(String),
+}
+
+impl Value {
+    /// Parses a value of the specified attribute.
+    pub(super) fn from_attribute(aid: AId, text: &str) -> Self {
+        let is_color = matches!(
+            aid,
+            AId::Color
+                | AId::Fill
+                | AId::FloodColor
+                | AId::LightingColor
+                | AId::StopColor
+                | AId::Stroke
+        );
+
+        Value::parse(text, is_color, aid == AId::D)
+    }
+
+    fn parse(text: &str, is_color: bool, is_path: bool) -> Self {
+        if is_color {
+            return match svgtypes::Color::from_str(text) {
+                Ok(c) => {
+                    Value::Color([c.red as f64, c.green as f64, c.blue as f64, c.alpha as f64])
+                }
+                Err(_) => Value::Other(text.to_string()),
+            };
+        }
+
+        let numbers = Numbers::parse(text, is_path);
+        if numbers.numbers.is_empty() {
+            Value::Other(text.to_string())
+        } else {
+            Value::Numbers(numbers)
+        }
+    }
+
+    pub(super) fn is_compatible(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Numbers(a), Value::Numbers(b)) => a.is_compatible(b),
+            (Value::Color(_), Value::Color(_)) => true,
+            _ => false,
+        }
+    }
+
+    fn zip(&self, other: &Value, f: impl Fn(f64, f64) -> f64) -> Option<Value> {
+        match (self, other) {
+            (Value::Numbers(a), Value::Numbers(b)) if a.is_compatible(b) => {
+                Some(Value::Numbers(Numbers {
+                    text: a.text.clone(),
+                    numbers: a
+                        .numbers
+                        .iter()
+                        .zip(&b.numbers)
+                        .map(|(a, b)| f(*a, *b))
+                        .collect(),
+                }))
+            }
+            (Value::Color(a), Value::Color(b)) => Some(Value::Color([
+                f(a[0], b[0]),
+                f(a[1], b[1]),
+                f(a[2], b[2]),
+                f(a[3], b[3]),
+            ])),
+            _ => None,
+        }
+    }
+
+    pub(super) fn lerp(&self, other: &Value, t: f64) -> Value {
+        self.zip(other, |a, b| a + (b - a) * t)
+            .unwrap_or_else(|| self.clone())
+    }
+
+    fn add(&self, other: &Value) -> Option<Value> {
+        self.zip(other, |a, b| a + b)
+    }
+
+    fn scale(&self, k: f64) -> Value {
+        self.zip(self, |a, _| a * k).unwrap_or_else(|| self.clone())
+    }
+
+    pub(super) fn zero(&self) -> Value {
+        self.scale(0.0)
+    }
+
+    fn distance(&self, other: &Value) -> Option<f64> {
+        match self.zip(other, |a, b| (b - a) * (b - a))? {
+            Value::Numbers(n) => Some(n.numbers.iter().sum::<f64>().sqrt()),
+            Value::Color(c) => Some((c[0] + c[1] + c[2]).sqrt()),
+            Value::Other(_) => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Value::Numbers(n) => {
+                for (text, number) in n.text.iter().zip(&n.numbers) {
+                    write!(f, "{}{}", text, number)?;
+                }
+
+                write!(f, "{}", n.text.last().unwrap())
+            }
+            Value::Color(c) => {
+                let c = c.map(|n| n.round().clamp(0.0, 255.0) as u8);
+                if c[3] == 255 {
+                    write!(f, "#{:02x}{:02x}{:02x}", c[0], c[1], c[2])
+                } else {
+                    write!(
+                        f,
+                        "rgba({}, {}, {}, {})",
+                        c[0],
+                        c[1],
+                        c[2],
+                        c[3] as f32 / 255.0
+                    )
+                }
+            }
+            Value::Other(text) => write!(f, "{}", text),
+        }
+    }
+}
+
+/// A text with numbers.
+#[derive(Clone, Debug)]
+pub(super) struct Numbers {
+    /// Text around numbers. Has one item more than `numbers`.
+    text: Vec<String>,
+    numbers: Vec<f64>,
+}
+
+impl Numbers {
+    fn parse(text: &str, is_path: bool) -> Self {
+        let bytes = text.as_bytes();
+        let mut parts = Vec::new();
+        let mut numbers = Vec::new();
+        let mut start = 0;
+        let mut i = 0;
+        while i < bytes.len() {
+            // Do not split identifiers like `url(#id1)`.
+            let can_start = match i.checked_sub(1).map(|i| bytes[i]) {
+                Some(b'#') => false,
+                Some(c) if !is_path => {
+                    !(c.is_ascii_alphanumeric() || matches!(c, b'_' | b'-' | b'.'))
+                }
+                _ => true,
+            };
+
+            if can_start {
+                if let Some(len) = scan_number(&bytes[i..]) {
+                    if let Ok(number) = text[i..i + len].parse::<f64>() {
+                        parts.push(text[start..i].to_string());
+                        numbers.push(number);
+                        i += len;
+                        start = i;
+                        continue;
+                    }
+                }
+            }
+
+            i += 1;
+        }
+
+        parts.push(text[start..].to_string());
+        Numbers {
+            text: parts,
+            numbers,
+        }
+    }
+
+    fn from_list(list: &[f64]) -> Self {
+        let mut text = vec![String::new()];
+        for _ in 1..list.len() {
+            text.push(" ".to_string());
+        }
+        text.push(String::new());
+
+        Numbers {
+            text,
+            numbers: list.to_vec(),
+        }
+    }
+
+    /// Checks that both values have the same structure, ignoring separators.
+    fn is_compatible(&self, other: &Numbers) -> bool {
+        fn normalize(text: &str) -> impl Iterator<Item = char> + '_ {
+            text.chars().filter(|c| !c.is_whitespace() && *c != ',')
+        }
+
+        self.numbers.len() == other.numbers.len()
+            && self
+                .text
+                .iter()
+                .zip(&other.text)
+                .all(|(a, b)| normalize(a).eq(normalize(b)))
+    }
+}
+
+/// Returns the length of a number at the start of the data.
+fn scan_number(data: &[u8]) -> Option<usize> {
+    let is_digit = |i: usize| matches!(data.get(i), Some(c) if c.is_ascii_digit());
+
+    let mut i = 0;
+    if matches!(data.first(), Some(b'+') | Some(b'-')) {
+        i += 1;
+    }
+
+    let digits_start = i;
+    while is_digit(i) {
+        i += 1;
+    }
+
+    if data.get(i) == Some(&b'.') && is_digit(i + 1) {
+        i += 1;
+        while is_digit(i) {
+            i += 1;
+        }
+    }
+
+    if i == digits_start {
+        return None;
+    }
+
+    // Do not confuse an exponent with units like `em` or `ex`.
+    if matches!(data.get(i), Some(b'e') | Some(b'E')) {
+        let mut j = i + 1;
+        if matches!(data.get(j), Some(b'+') | Some(b'-')) {
+            j += 1;
+        }
+
+        if is_digit(j) {
+            while is_digit(j) {
+                j += 1;
+            }
+
+            i = j;
+        }
+    }
+
+    Some(i)
+}
+
+/// A path for `animateMotion`.
+struct MotionPath {
+    segments: Vec<(kurbo::PathSeg, f64)>,
+    length: f64,
+}
+
+impl MotionPath {
+    fn new(data: &str) -> Option<Self> {
+        let path = kurbo::BezPath::from_svg(data).ok()?;
+        let segments: Vec<_> = path
+            .segments()
+            .map(|seg| (seg, seg.arclen(ARCLEN_ACCURACY)))
+            .collect();
+
+        if segments.is_empty() {
+            return None;
+        }
+
+        let length = segments.iter().map(|(_, len)| len).sum();
+        Some(MotionPath { segments, length })
+    }
+
+    /// Returns a point and a tangent angle in degrees at the specified distance.
+    fn at(&self, distance: f64) -> (f64, f64, f64) {
+        let mut distance = distance.clamp(0.0, self.length);
+        for (i, (seg, len)) in self.segments.iter().enumerate() {
+            if distance > *len && i + 1 != self.segments.len() {
+                distance -= len;
+                continue;
+            }
+
+            let t = if *len > 0.0 {
+                seg.inv_arclen(distance.min(*len), ARCLEN_ACCURACY)
+            } else {
+                0.0
+            };
+
+            let p = seg.eval(t);
+            let p1 = seg.eval((t - 0.001).max(0.0));
+            let p2 = seg.eval((t + 0.001).min(1.0));
+            let angle = (p2.y - p1.y).atan2(p2.x - p1.x).to_degrees();
+            return (p.x, p.y, angle);
+        }
+
+        (0.0, 0.0, 0.0)
+    }
+}
@@ -0,0 +1,885 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! CSS animations evaluation.
+//!
+//! `simplecss` ignores at-rules, so `@keyframes` are parsed here.
+//! Like SMIL animations, animated values at the requested time are written
+//! into target elements during parsing.
+//!
+//! CSS transitions require a style change, which never happens in a static document,
+//! so they are ignored.
+
+use std::collections::HashMap;
+
+use super::animation::{current_value, eval_spline, set_value, Value};
+use super::AId;
+
+/// `@keyframes` rules by name.
+pub(crate) type KeyframesMap = HashMap<String, Vec<Keyframe>>;
+
+/// A keyframe with a single offset.
+#[derive(Clone, Debug)]
+pub(crate) struct Keyframe {
+    /// Position within an iteration, in the 0..=1 range.
+    offset: f64,
+    timing_function: Option<TimingFunction>,
+    declarations: Vec<(AId, String)>,
+}
+
+impl Keyframe {
+    fn value(&self, aid: AId) -> Option<&str> {
+        self.declarations
+            .iter()
+            .rev()
+            .find(|(name, _)| *name == aid)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Checks that a CSS property affects CSS animations.
+pub(crate) fn is_animation_property(name: &str) -> bool {
+    name == "animation" || name.starts_with("animation-") || name == "transform-origin"
+}
+
+/// Collects `@keyframes` rules from all `style` elements.
+pub(crate) fn collect_keyframes(xml: &roxmltree::Document) -> KeyframesMap {
+    let mut keyframes = HashMap::new();
+    for node in xml.descendants().filter(|n| n.has_tag_name("style")) {
+        match node.attribute("type") {
+            Some("text/css") => {}
+            Some(_) => continue,
+            None => {}
+        }
+
+        if let Some(text) = node.text() {
+            parse_keyframes_rules(&remove_comments(text), &mut keyframes);
+        }
+    }
+
+    keyframes
+}
+
+fn remove_comments(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("/*") {
+        result.push_str(&rest[..start]);
+        result.push(' ');
+        rest = match rest[start + 2..].find("*/") {
+            Some(end) => &rest[start + end + 4..],
+            None => "",
+        };
+    }
+
+    result.push_str(rest);
+    result
+}
+
+fn parse_keyframes_rules(text: &str, keyframes: &mut KeyframesMap) {
+    let mut rest = text;
+    while let Some(idx) = rest.find('@') {
+        rest = &rest[idx + 1..];
+
+        let len = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+            .unwrap_or(rest.len());
+        let at_rule = &rest[..len];
+        rest = &rest[len..];
+
+        // Other at-rules, like `@media`, are skipped, but rules inside them are not.
+        if !at_rule.eq_ignore_ascii_case("keyframes")
+            && !at_rule.eq_ignore_ascii_case("-webkit-keyframes")
+        {
+            continue;
+        }
+
+        let open = match rest.find('{') {
+            Some(v) => v,
+            None => break,
+        };
+
+        let name = unquote(&rest[..open]);
+        let (block, tail) = split_block(&rest[open + 1..]);
+        rest = tail;
+
+        // The last rule with the same name wins.
+        if !name.is_empty() && name != "none" {
+            keyframes.insert(name.to_string(), parse_keyframes(block));
+        }
+    }
+}
+
+fn parse_keyframes(text: &str) -> Vec<Keyframe> {
+    let mut keyframes = Vec::new();
+    let mut rest = text;
+    while let Some(open) = rest.find('{') {
+        let selectors = &rest[..open];
+        let (block, tail) = split_block(&rest[open + 1..]);
+        rest = tail;
+
+        let mut timing_function = None;
+        let mut declarations = Vec::new();
+        for declaration in simplecss::DeclarationTokenizer::from(block) {
+            // `!important` declarations are ignored inside keyframes.
+            if declaration.important {
+                continue;
+            }
+
+            if declaration.name == "animation-timing-function" {
+                timing_function = TimingFunction::parse(declaration.value);
+            } else if let Some(aid) = AId::from_str(declaration.name) {
+                if aid.is_presentation() {
+                    declarations.push((aid, declaration.value.to_string()));
+                }
+            }
+        }
+
+        for selector in selectors.split(',') {
+            let offset = match selector.trim().to_ascii_lowercase().as_str() {
+                "from" => Some(0.0),
+                "to" => Some(1.0),
+                s => s
+                    .strip_suffix('%')
+                    .and_then(|n| n.trim().parse::<f64>().ok())
+                    .map(|n| n / 100.0),
+            };
+
+            if let Some(offset) = offset.filter(|n| (0.0..=1.0).contains(n)) {
+                keyframes.push(Keyframe {
+                    offset,
+                    timing_function,
+                    declarations: declarations.clone(),
+                });
+            }
+        }
+    }
+
+    // A stable sort, so keyframes with the same offset preserve their order.
+    keyframes.sort_by(|a, b| a.offset.partial_cmp(&b.offset).unwrap());
+    keyframes
+}
+
+/// Splits a text after an opening brace into the block content and the rest.
+fn split_block(text: &str) -> (&str, &str) {
+    let mut depth = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' if depth == 0 => return (&text[..i], &text[i + 1..]),
+            '}' => depth -= 1,
+            _ => {}
+        }
+    }
+
+    (text, "")
+}
+
+/// Splits a text by separators outside of parentheses.
+fn split_top_level(text: &str, is_separator: impl Fn(char) -> bool) -> Vec<&str> {
+    let mut items = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            c if depth == 0 && is_separator(c) => {
+                items.push(text[start..i].trim());
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+
+    items.push(text[start..].trim());
+    items.retain(|item| !item.is_empty());
+    items
+}
+
+fn unquote(text: &str) -> &str {
+    let text = text.trim();
+    for quote in ['"', '\''] {
+        if let Some(v) = text.strip_prefix(quote).and_then(|v| v.strip_suffix(quote)) {
+            return v;
+        }
+    }
+
+    text
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum TimingFunction {
+    CubicBezier([f64; 4]),
+    Steps(u32, StepPosition),
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum StepPosition {
+    Start,
+    End,
+    None,
+    Both,
+}
+
+impl TimingFunction {
+    const EASE: Self = TimingFunction::CubicBezier([0.25, 0.1, 0.25, 1.0]);
+
+    fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        match text {
+            "linear" => return Some(TimingFunction::CubicBezier([0.0, 0.0, 1.0, 1.0])),
+            "ease" => return Some(Self::EASE),
+            "ease-in" => return Some(TimingFunction::CubicBezier([0.42, 0.0, 1.0, 1.0])),
+            "ease-out" => return Some(TimingFunction::CubicBezier([0.0, 0.0, 0.58, 1.0])),
+            "ease-in-out" => return Some(TimingFunction::CubicBezier([0.42, 0.0, 0.58, 1.0])),
+            "step-start" => return Some(TimingFunction::Steps(1, StepPosition::Start)),
+            "step-end" => return Some(TimingFunction::Steps(1, StepPosition::End)),
+            _ => {}
+        }
+
+        let (name, args) = text.strip_suffix(')')?.split_once('(')?;
+        let args: Vec<_> = args.split(',').map(str::trim).collect();
+        match (name.trim(), args.as_slice()) {
+            ("cubic-bezier", [_, _, _, _]) => {
+                let mut points = [0.0; 4];
+                for (n, text) in points.iter_mut().zip(&args) {
+                    *n = text.parse::<f64>().ok().filter(|n| n.is_finite())?;
+                }
+
+                // X coordinates must be in the 0..=1 range.
+                if !(0.0..=1.0).contains(&points[0]) || !(0.0..=1.0).contains(&points[2]) {
+                    return None;
+                }
+
+                Some(TimingFunction::CubicBezier(points))
+            }
+            ("steps", [count, position @ ..]) if position.len() <= 1 => {
+                let count: u32 = count.parse().ok()?;
+                let position = match position.first().copied() {
+                    None | Some("end") | Some("jump-end") => StepPosition::End,
+                    Some("start") | Some("jump-start") => StepPosition::Start,
+                    Some("jump-none") => StepPosition::None,
+                    Some("jump-both") => StepPosition::Both,
+                    _ => return None,
+                };
+
+                let min_count = if position == StepPosition::None { 2 } else { 1 };
+                if count < min_count {
+                    return None;
+                }
+
+                Some(TimingFunction::Steps(count, position))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns an eased progress.
+    fn apply(&self, t: f64) -> f64 {
+        match *self {
+            // Control points on the diagonal, like in `linear`.
+            TimingFunction::CubicBezier([x1, y1, x2, y2]) if x1 == y1 && x2 == y2 => t,
+            TimingFunction::CubicBezier(points) => eval_spline(points, t),
+            TimingFunction::Steps(count, position) => {
+                let count = count as f64;
+                let mut step = (t * count).floor();
+                if matches!(position, StepPosition::Start | StepPosition::Both) {
+                    step += 1.0;
+                }
+
+                let jumps = match position {
+                    StepPosition::Start | StepPosition::End => count,
+                    StepPosition::None => count - 1.0,
+                    StepPosition::Both => count + 1.0,
+                };
+
+                step.min(jumps) / jumps
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Direction {
+    Normal,
+    Reverse,
+    Alternate,
+    AlternateReverse,
+}
+
+fn parse_direction(text: &str) -> Option<Direction> {
+    match text {
+        "normal" => Some(Direction::Normal),
+        "reverse" => Some(Direction::Reverse),
+        "alternate" => Some(Direction::Alternate),
+        "alternate-reverse" => Some(Direction::AlternateReverse),
+        _ => None,
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum FillMode {
+    None,
+    Forwards,
+    Backwards,
+    Both,
+}
+
+fn parse_fill_mode(text: &str) -> Option<FillMode> {
+    match text {
+        "none" => Some(FillMode::None),
+        "forwards" => Some(FillMode::Forwards),
+        "backwards" => Some(FillMode::Backwards),
+        "both" => Some(FillMode::Both),
+        _ => None,
+    }
+}
+
+/// Parses a play state. Returns `true` for a paused animation.
+fn parse_play_state(text: &str) -> Option<bool> {
+    match text {
+        "running" => Some(false),
+        "paused" => Some(true),
+        _ => None,
+    }
+}
+
+/// Parses a time in seconds.
+fn parse_time(text: &str) -> Option<f64> {
+    let (number, scale) = if let Some(n) = text.strip_suffix("ms") {
+        (n, 0.001)
+    } else if let Some(n) = text.strip_suffix('s') {
+        (n, 1.0)
+    } else {
+        return None;
+    };
+
+    let n = number.parse::<f64>().ok().filter(|n| n.is_finite())?;
+    Some(n * scale)
+}
+
+fn parse_duration(text: &str) -> Option<f64> {
+    parse_time(text).filter(|n| *n >= 0.0)
+}
+
+fn parse_iteration_count(text: &str) -> Option<f64> {
+    if text == "infinite" {
+        return Some(f64::INFINITY);
+    }
+
+    text.parse::<f64>()
+        .ok()
+        .filter(|n| n.is_finite() && *n >= 0.0)
+}
+
+/// `animation-*` properties of an element.
+///
+/// Each property is a list, which is repeated to match the number of names.
+struct Properties {
+    names: Vec<String>,
+    durations: Vec<f64>,
+    delays: Vec<f64>,
+    iteration_counts: Vec<f64>,
+    directions: Vec<Direction>,
+    fill_modes: Vec<FillMode>,
+    timing_functions: Vec<TimingFunction>,
+    play_states: Vec<bool>,
+}
+
+impl Default for Properties {
+    fn default() -> Self {
+        Properties {
+            names: vec!["none".to_string()],
+            durations: vec![0.0],
+            delays: vec![0.0],
+            iteration_counts: vec![1.0],
+            directions: vec![Direction::Normal],
+            fill_modes: vec![FillMode::None],
+            timing_functions: vec![TimingFunction::EASE],
+            play_states: vec![false],
+        }
+    }
+}
+
+impl Properties {
+    /// Applies a declaration. Invalid values are ignored.
+    fn set(&mut self, name: &str, value: &str) {
+        fn update<T>(list: &mut Vec<T>, items: &[&str], f: impl Fn(&str) -> Option<T>) {
+            if let Some(values) = items.iter().map(|v| f(v)).collect::<Option<Vec<_>>>() {
+                if !values.is_empty() {
+                    *list = values;
+                }
+            }
+        }
+
+        let items = split_top_level(value, |c| c == ',');
+        match name {
+            "animation" => {
+                if let Some(properties) = parse_shorthand(&items) {
+                    *self = properties;
+                }
+            }
+            "animation-name" => update(&mut self.names, &items, |v| Some(unquote(v).to_string())),
+            "animation-duration" => update(&mut self.durations, &items, parse_duration),
+            "animation-delay" => update(&mut self.delays, &items, parse_time),
+            "animation-iteration-count" => {
+                update(&mut self.iteration_counts, &items, parse_iteration_count)
+            }
+            "animation-direction" => update(&mut self.directions, &items, parse_direction),
+            "animation-fill-mode" => update(&mut self.fill_modes, &items, parse_fill_mode),
+            "animation-timing-function" => {
+                update(&mut self.timing_functions, &items, TimingFunction::parse)
+            }
+            "animation-play-state" => update(&mut self.play_states, &items, parse_play_state),
+            _ => {}
+        }
+    }
+
+    fn animation(&self, idx: usize) -> Animation {
+        fn item<T: Copy>(list: &[T], idx: usize) -> T {
+            list[idx % list.len()]
+        }
+
+        Animation {
+            duration: item(&self.durations, idx),
+            delay: item(&self.delays, idx),
+            iteration_count: item(&self.iteration_counts, idx),
+            direction: item(&self.directions, idx),
+            fill_mode: item(&self.fill_modes, idx),
+            timing_function: item(&self.timing_functions, idx),
+            is_paused: item(&self.play_states, idx),
+        }
+    }
+}
+
+/// Parses the `animation` shorthand.
+fn parse_shorthand(items: &[&str]) -> Option<Properties> {
+    let mut properties = Properties {
+        names: Vec::new(),
+        durations: Vec::new(),
+        delays: Vec::new(),
+        iteration_counts: Vec::new(),
+        directions: Vec::new(),
+        fill_modes: Vec::new(),
+        timing_functions: Vec::new(),
+        play_states: Vec::new(),
+    };
+
+    for item in items {
+        let mut name = None;
+        let mut duration = None;
+        let mut delay = None;
+        let mut iteration_count = None;
+        let mut direction = None;
+        let mut fill_mode = None;
+        let mut timing_function = None;
+        let mut play_state = None;
+        for token in split_top_level(item, |c| c.is_ascii_whitespace()) {
+            // The first time is a duration and the second one is a delay.
+            if let Some(time) = parse_time(token) {
+                if duration.is_none() {
+                    duration = Some(parse_duration(token)?);
+                } else if delay.is_none() {
+                    delay = Some(time);
+                } else {
+                    return None;
+                }
+            } else if let (None, Some(v)) = (timing_function, TimingFunction::parse(token)) {
+                timing_function = Some(v);
+            } else if let (None, Some(v)) = (iteration_count, parse_iteration_count(token)) {
+                iteration_count = Some(v);
+            } else if let (None, Some(v)) = (direction, parse_direction(token)) {
+                direction = Some(v);
+            } else if let (None, Some(v)) = (fill_mode, parse_fill_mode(token)) {
+                fill_mode = Some(v);
+            } else if let (None, Some(v)) = (play_state, parse_play_state(token)) {
+                play_state = Some(v);
+            } else if name.is_none() {
+                name = Some(unquote(token).to_string());
+            } else {
+                return None;
+            }
+        }
+
+        properties
+            .names
+            .push(name.unwrap_or_else(|| "none".to_string()));
+        properties.durations.push(duration.unwrap_or(0.0));
+        properties.delays.push(delay.unwrap_or(0.0));
+        properties
+            .iteration_counts
+            .push(iteration_count.unwrap_or(1.0));
+        properties
+            .directions
+            .push(direction.unwrap_or(Direction::Normal));
+        properties
+            .fill_modes
+            .push(fill_mode.unwrap_or(FillMode::None));
+        properties
+            .timing_functions
+            .push(timing_function.unwrap_or(TimingFunction::EASE));
+        properties.play_states.push(play_state.unwrap_or(false));
+    }
+
+    if properties.names.is_empty() {
+        None
+    } else {
+        Some(properties)
+    }
+}
+
+struct Animation {
+    duration: f64,
+    delay: f64,
+    iteration_count: f64,
+    direction: Direction,
+    fill_mode: FillMode,
+    timing_function: TimingFunction,
+    is_paused: bool,
+}
+
+impl Animation {
+    /// Returns a directed iteration progress at the specified time.
+    ///
+    /// Returns `None` when the animation has no effect.
+    fn progress(&self, time: f64) -> Option<f64> {
+        // A paused animation never leaves its start.
+        let time = if self.is_paused { 0.0 } else { time };
+        let active_time = time - self.delay;
+        let active_duration = if self.duration > 0.0 {
+            self.duration * self.iteration_count
+        } else {
+            0.0
+        };
+
+        let is_after = active_time >= active_duration;
+        let overall = if active_time < 0.0 {
+            if !matches!(self.fill_mode, FillMode::Backwards | FillMode::Both) {
+                return None;
+            }
+
+            0.0
+        } else if is_after {
+            if !matches!(self.fill_mode, FillMode::Forwards | FillMode::Both) {
+                return None;
+            }
+
+            self.iteration_count
+        } else {
+            active_time / self.duration
+        };
+
+        let (iteration, mut progress) = if overall.is_infinite() {
+            (0.0, 1.0)
+        } else if is_after && overall > 0.0 && overall.fract() == 0.0 {
+            // Stay at the end of the last iteration.
+            (overall - 1.0, 1.0)
+        } else {
+            (overall.floor(), overall.fract())
+        };
+
+        let is_odd = iteration % 2.0 == 1.0;
+        let is_reversed = match self.direction {
+            Direction::Normal => false,
+            Direction::Reverse => true,
+            Direction::Alternate => is_odd,
+            Direction::AlternateReverse => !is_odd,
+        };
+
+        if is_reversed {
+            progress = 1.0 - progress;
+        }
+
+        Some(progress)
+    }
+}
+
+/// Returns attributes animated by CSS animations at the specified time.
+pub(crate) fn resolve(
+    keyframes: &KeyframesMap,
+    time: f64,
+    declarations: &[(&str, &str)],
+    base: impl Fn(AId) -> Option<String>,
+) -> Vec<(AId, String)> {
+    let mut properties = Properties::default();
+    let mut origin = None;
+    for (name, value) in declarations {
+        if *name == "transform-origin" {
+            origin = parse_transform_origin(value);
+        } else {
+            properties.set(name, value);
+        }
+    }
+
+    // Later animations override earlier ones.
+    let mut values: Vec<(AId, String)> = Vec::new();
+    for (idx, name) in properties.names.iter().enumerate() {
+        let frames = match keyframes.get(name) {
+            Some(v) => v,
+            None => continue,
+        };
+
+        let animation = properties.animation(idx);
+        let progress = match animation.progress(time) {
+            Some(v) => v,
+            None => continue,
+        };
+
+        let mut aids: Vec<AId> = Vec::new();
+        for (aid, _) in frames.iter().flat_map(|f| &f.declarations) {
+            if !aids.contains(aid) {
+                aids.push(*aid);
+            }
+        }
+
+        for aid in aids {
+            let base = current_value(&values, aid).or_else(|| base(aid));
+            let value = sample(
+                frames,
+                aid,
+                progress,
+                animation.timing_function,
+                base.as_deref(),
+            );
+            if let Some(value) = value {
+                set_value(&mut values, aid, value);
+            }
+        }
+    }
+
+    // Only absolute origins are supported, since others depend on the bounding box.
+    if let Some((x, y)) = origin {
+        if let Some(item) = values.iter_mut().find(|(aid, _)| *aid == AId::Transform) {
+            item.1 = format!("translate({} {}) {} translate({} {})", x, y, item.1, -x, -y);
+        }
+    }
+
+    values
+}
+
+/// Returns a property value at the specified iteration progress.
+fn sample(
+    frames: &[Keyframe],
+    aid: AId,
+    progress: f64,
+    timing_function: TimingFunction,
+    base: Option<&str>,
+) -> Option<String> {
+    // Keyframes that set the property. `None` is the not animated value.
+    let mut points: Vec<(f64, Option<&str>, TimingFunction)> = Vec::new();
+    for frame in frames {
+        if let Some(value) = frame.value(aid) {
+            let point = (
+                frame.offset,
+                Some(value),
+                frame.timing_function.unwrap_or(timing_function),
+            );
+            match points.last_mut() {
+                Some(last) if last.0 == frame.offset => *last = point,
+                _ => points.push(point),
+            }
+        }
+    }
+
+    // Implicit `from` and `to` keyframes.
+    if points.first()?.0 > 0.0 {
+        points.insert(0, (0.0, None, timing_function));
+    }
+    if points.last()?.0 < 1.0 {
+        points.push((1.0, None, timing_function));
+    }
+
+    if points.len() == 1 {
+        return interpolate(aid, points[0].1.or(base), None, 0.0);
+    }
+
+    let idx = points[..points.len() - 1]
+        .iter()
+        .rposition(|p| p.0 <= progress)
+        .unwrap_or(0);
+    let (from, to) = (&points[idx], &points[idx + 1]);
+    let len = to.0 - from.0;
+    let t = if len > 0.0 {
+        ((progress - from.0) / len).clamp(0.0, 1.0)
+    } else {
+        1.0
+    };
+
+    interpolate(aid, from.1.or(base), to.1.or(base), from.2.apply(t))
+}
+
+fn interpolate(aid: AId, from: Option<&str>, to: Option<&str>, t: f64) -> Option<String> {
+    let transforms = if aid == AId::Transform {
+        transform_pair(from, to)
+    } else {
+        None
+    };
+    let (from, to) = match transforms {
+        Some((ref a, ref b)) => (Some(a.as_str()), Some(b.as_str())),
+        None => (from, to),
+    };
+
+    let parse = |text: &str| Value::from_attribute(aid, text.trim());
+    let (from, to) = match (from.map(parse), to.map(parse)) {
+        (Some(a), Some(b)) => (a, b),
+        (Some(a), None) => {
+            let b = a.zero();
+            (a, b)
+        }
+        (None, Some(b)) => (b.zero(), b),
+        (None, None) => return None,
+    };
+
+    let value = if from.is_compatible(&to) {
+        from.lerp(&to, t)
+    } else if t < 0.5 {
+        // Values that cannot be interpolated are switched in the middle.
+        from
+    } else {
+        to
+    };
+
+    Some(value.to_string())
+}
+
+/// Converts CSS transforms into the SVG syntax.
+///
+/// A missing transform or `none` is replaced by an identity transform
+/// with the same functions as the other one.
+fn transform_pair(from: Option<&str>, to: Option<&str>) -> Option<(String, String)> {
+    let parse = |text: Option<&str>| match text.map(str::trim) {
+        None | Some("none") => Some(None),
+        Some(text) => parse_css_transform(text).map(Some),
+    };
+
+    let (from, to) = match (parse(from)?, parse(to)?) {
+        (Some(a), Some(b)) => (a, b),
+        (Some(a), None) => {
+            let b = identity_transform(&a);
+            (a, b)
+        }
+        (None, Some(b)) => (identity_transform(&b), b),
+        (None, None) => return None,
+    };
+
+    Some((format_transform(&from), format_transform(&to)))
+}
+
+/// Parses a CSS transform into a list of SVG transform functions.
+///
+/// Unlike SVG, CSS allows units, `translateX`, `scaleX` and similar functions.
+fn parse_css_transform(text: &str) -> Option<Vec<(&'static str, Vec<f64>)>> {
+    let mut list = Vec::new();
+    let mut rest = text.trim();
+    while !rest.is_empty() {
+        let open = rest.find('(')?;
+        let close = rest.find(')')?;
+        let name = rest[..open].trim();
+        let args = rest
+            .get(open + 1..close)?
+            .split(|c: char| c.is_ascii_whitespace() || c == ',')
+            .filter(|v| !v.is_empty());
+        rest = rest[close + 1..].trim_start_matches(|c: char| c.is_ascii_whitespace() || c == ',');
+
+        let is_angle = matches!(name, "rotate" | "skew" | "skewX" | "skewY");
+        let args = args
+            .map(|v| {
+                if is_angle {
+                    parse_angle(v)
+                } else {
+                    parse_length(v)
+                }
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        let item = match (name, args.as_slice()) {
+            ("translate", &[x]) => ("translate", vec![x, 0.0]),
+            ("translate", &[x, y]) => ("translate", vec![x, y]),
+            ("translateX", &[x]) => ("translate", vec![x, 0.0]),
+            ("translateY", &[y]) => ("translate", vec![0.0, y]),
+            ("scale", &[x]) => ("scale", vec![x, x]),
+            ("scale", &[x, y]) => ("scale", vec![x, y]),
+            ("scaleX", &[x]) => ("scale", vec![x, 1.0]),
+            ("scaleY", &[y]) => ("scale", vec![1.0, y]),
+            ("rotate", &[a]) => ("rotate", vec![a]),
+            ("skewX", &[a]) => ("skewX", vec![a]),
+            ("skewY", &[a]) => ("skewY", vec![a]),
+            ("skew", &[a]) => ("skewX", vec![a]),
+            ("skew", &[a, b]) => {
+                let (a, b) = (a.to_radians().tan(), b.to_radians().tan());
+                ("matrix", vec![1.0, b, a, 1.0, 0.0, 0.0])
+            }
+            ("matrix", &[_, _, _, _, _, _]) => ("matrix", args.clone()),
+            _ => return None,
+        };
+
+        list.push(item);
+    }
+
+    Some(list)
+}
+
+/// Returns an identity transform with the same functions.
+fn identity_transform(list: &[(&'static str, Vec<f64>)]) -> Vec<(&'static str, Vec<f64>)> {
+    list.iter()
+        .map(|(name, args)| {
+            let args = match *name {
+                "scale" => vec![1.0, 1.0],
+                "matrix" => vec![1.0, 0.0, 0.0, 1.0, 0.0, 0.0],
+                _ => vec![0.0; args.len()],
+            };
+
+            (*name, args)
+        })
+        .collect()
+}
+
+fn format_transform(list: &[(&'static str, Vec<f64>)]) -> String {
+    let functions: Vec<_> = list
+        .iter()
+        .map(|(name, args)| {
+            let args: Vec<_> = args.iter().map(|n| n.to_string()).collect();
+            format!("{}({})", name, args.join(" "))
+        })
+        .collect();
+    functions.join(" ")
+}
+
+/// Parses an angle in degrees.
+fn parse_angle(text: &str) -> Option<f64> {
+    let units = [
+        ("deg", 1.0),
+        ("grad", 0.9),
+        ("rad", 180.0 / std::f64::consts::PI),
+        ("turn", 360.0),
+    ];
+
+    // Unlike CSS, SVG angles have no units.
+    let (number, scale) = units
+        .iter()
+        .find_map(|(unit, scale)| text.strip_suffix(unit).map(|n| (n, *scale)))
+        .unwrap_or((text, 1.0));
+    let n = number.parse::<f64>().ok().filter(|n| n.is_finite())?;
+    Some(n * scale)
+}
+
+/// Parses a length in pixels.
+fn parse_length(text: &str) -> Option<f64> {
+    let number = text.strip_suffix("px").unwrap_or(text);
+    number.parse::<f64>().ok().filter(|n| n.is_finite())
+}
+
+/// Parses an absolute `transform-origin`.
+fn parse_transform_origin(text: &str) -> Option<(f64, f64)> {
+    let mut values = text.split_ascii_whitespace().map(|v| match v {
+        "left" | "top" => Some(0.0),
+        _ => parse_length(v),
+    });
+
+    // A missing vertical value is `center`, which is not absolute.
+    let x = values.next()??;
+    let y = values.next()??;
+    Some((x, y))
+}
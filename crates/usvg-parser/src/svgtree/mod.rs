@@ -6,6 +6,10 @@ use std::collections::HashMap;
 use std::num::NonZeroU32;
 use std::str::FromStr;
 
+use usvg_tree::{Diagnostic, DiagnosticCode};
+
+mod animation;
+mod css_animation;
 #[rustfmt::skip] mod names;
 mod parse;
 mod text;
@@ -17,12 +21,21 @@ pub use names::{AId, EId};
 /// Contains only element and text nodes.
 /// Text nodes are present only inside the `text` element.
 pub struct Document<'input> {
+    text: &'input str,
     nodes: Vec<NodeData>,
     attrs: Vec<Attribute<'input>>,
     links: HashMap<String, NodeId>,
+    /// Animations state. Used only during parsing.
+    animations: animation::Animations,
 }
 
 impl<'input> Document<'input> {
+    /// Returns the original XML text.
+    #[inline]
+    pub fn input_text(&self) -> &'input str {
+        self.text
+    }
+
     /// Returns the root node.
     #[inline]
     pub fn root<'a>(&'a self) -> SvgNode<'a, 'input> {
@@ -181,6 +194,8 @@ pub(crate) enum NodeKind {
     Element {
         tag_name: EId,
         attributes: ShortRange,
+        /// Element's byte range in the original XML.
+        range: ShortRange,
     },
     Text(String),
 }
@@ -260,6 +275,38 @@ impl<'a, 'input: 'a> SvgNode<'a, 'input> {
             _ => None,
         }
     }
+
+    /// Returns element's byte range in the original XML.
+    ///
+    /// Elements created by `use` point to the referenced element.
+    #[inline]
+    pub fn range(&self) -> Option<std::ops::Range<usize>> {
+        match self.d.kind {
+            NodeKind::Element { range, .. } => Some(range.to_urange()),
+            _ => None,
+        }
+    }
+
+    /// Returns element's byte position in the original XML.
+    ///
+    /// Elements created by `use` point to the referenced element.
+    #[inline]
+    pub fn position(&self) -> Option<usize> {
+        self.range().map(|r| r.start)
+    }
+
+    /// Creates a diagnostic with element's info.
+    pub(crate) fn diagnostic(&self, code: DiagnosticCode, message: String) -> Diagnostic {
+        let mut diagnostic = Diagnostic::new(code, message);
+        diagnostic.element_id = self.element_id().to_string();
+        diagnostic.element_tag = self
+            .tag_name()
+            .map(|t| t.to_str().to_string())
+            .unwrap_or_default();
+        diagnostic.offset = self.position();
+        diagnostic
+    }
+
     /// Returns element's `id` attribute value.
     ///
     /// Returns an empty string otherwise.
@@ -268,6 +315,15 @@ impl<'a, 'input: 'a> SvgNode<'a, 'input> {
         self.attribute(AId::Id).unwrap_or("")
     }
 
+    /// Returns element's `class` attribute value split into separate classes.
+    ///
+    /// Returns an empty list otherwise.
+    pub fn element_classes(&self) -> Vec<String> {
+        self.attribute::<&str>(AId::Class)
+            .map(|s| s.split_ascii_whitespace().map(ToOwned::to_owned).collect())
+            .unwrap_or_default()
+    }
+
     /// Returns an attribute value.
     pub fn attribute<T: FromValue<'a, 'input>>(&self, aid: AId) -> Option<T> {
         let value = self
@@ -278,8 +334,13 @@ impl<'a, 'input: 'a> SvgNode<'a, 'input> {
         match T::parse(*self, aid, value) {
             Some(v) => Some(v),
             None => {
-                // TODO: show position in XML
-                log::warn!("Failed to parse {} value: '{}'.", aid, value);
+                report!(
+                    self,
+                    InvalidAttribute,
+                    "Failed to parse {} value: '{}'.",
+                    aid,
+                    value
+                );
                 None
             }
         }
@@ -607,9 +668,12 @@ impl<'a, 'input: 'a> Iterator for HrefIter<'a, 'input> {
 
         if let Some(link) = self.doc.get(self.curr).node_attribute(AId::Href) {
             if link.id() == self.curr || link.id() == self.origin {
-                log::warn!(
+                let origin = self.doc.get(self.origin);
+                report!(
+                    origin,
+                    RecursiveReference,
                     "Element '#{}' cannot reference itself via 'xlink:href'.",
-                    self.doc.get(self.origin).element_id()
+                    origin.element_id()
                 );
                 self.is_finished = true;
                 return None;
@@ -701,6 +765,7 @@ impl AId {
                 | AId::Opacity
                 | AId::Overflow
                 | AId::PaintOrder
+                | AId::PointerEvents
                 | AId::ShapeRendering
                 | AId::StopColor
                 | AId::StopOpacity
@@ -769,6 +834,7 @@ impl AId {
                 | AId::Mask
                 | AId::Opacity
                 | AId::Overflow
+                | AId::PointerEvents
                 | AId::ShapeRendering
                 | AId::StopColor
                 | AId::StopOpacity
@@ -964,6 +1030,23 @@ impl<'a, 'input: 'a> FromValue<'a, 'input> for usvg_tree::Visibility {
     }
 }
 
+impl<'a, 'input: 'a> FromValue<'a, 'input> for usvg_tree::PointerEvents {
+    fn parse(_: SvgNode, _: AId, value: &str) -> Option<Self> {
+        match value {
+            "visiblePainted" => Some(usvg_tree::PointerEvents::VisiblePainted),
+            "visibleFill" => Some(usvg_tree::PointerEvents::VisibleFill),
+            "visibleStroke" => Some(usvg_tree::PointerEvents::VisibleStroke),
+            "visible" => Some(usvg_tree::PointerEvents::Visible),
+            "painted" => Some(usvg_tree::PointerEvents::Painted),
+            "fill" => Some(usvg_tree::PointerEvents::Fill),
+            "stroke" => Some(usvg_tree::PointerEvents::Stroke),
+            "all" => Some(usvg_tree::PointerEvents::All),
+            "none" => Some(usvg_tree::PointerEvents::None),
+            _ => None,
+        }
+    }
+}
+
 impl<'a, 'input: 'a> FromValue<'a, 'input> for usvg_tree::SpreadMethod {
     fn parse(_: SvgNode, _: AId, value: &str) -> Option<Self> {
         match value {
@@ -283,6 +283,7 @@ pub enum AId {
     PatternContentUnits,
     PatternTransform,
     PatternUnits,
+    PointerEvents,
     Points,
     PointsAtX,
     PointsAtY,
@@ -374,261 +375,262 @@ pub enum AId {
 }
 
 static ATTRIBUTES: Map<AId> = Map {
-    key: 3347381344252206323,
+    key: 9181757771948286951,
     disps: &[
+        (0, 0),
+        (0, 11),
+        (1, 5),
+        (0, 0),
+        (0, 0),
+        (0, 61),
+        (1, 2),
+        (1, 8),
+        (0, 162),
+        (0, 0),
+        (2, 57),
+        (0, 5),
+        (0, 36),
+        (2, 180),
+        (0, 136),
+        (0, 0),
+        (0, 85),
+        (0, 70),
+        (0, 37),
+        (0, 1),
+        (0, 26),
+        (0, 62),
+        (10, 75),
+        (0, 145),
+        (0, 140),
+        (1, 17),
+        (6, 180),
+        (1, 147),
+        (2, 61),
+        (2, 186),
+        (0, 2),
+        (2, 15),
         (0, 166),
         (0, 2),
-        (0, 36),
-        (0, 48),
-        (0, 27),
-        (0, 100),
-        (0, 5),
-        (0, 197),
-        (0, 22),
-        (8, 79),
         (0, 52),
-        (1, 180),
-        (0, 21),
-        (10, 120),
-        (0, 40),
-        (1, 30),
-        (0, 24),
-        (0, 1),
-        (0, 138),
-        (0, 10),
-        (0, 0),
-        (0, 4),
-        (0, 35),
-        (1, 160),
-        (11, 71),
-        (0, 13),
-        (0, 54),
-        (2, 39),
-        (57, 121),
-        (0, 53),
-        (0, 24),
-        (44, 157),
-        (0, 14),
-        (0, 76),
-        (0, 15),
-        (0, 163),
-        (0, 9),
-        (15, 185),
-        (0, 17),
-        (2, 115),
-        (32, 70),
-        (1, 1),
+        (2, 72),
+        (0, 38),
+        (8, 112),
+        (0, 205),
+        (7, 180),
+        (0, 131),
+        (0, 19),
     ],
     entries: &[
-        ("mask-border-source", AId::MaskBorderSource),
-        ("stop-opacity", AId::StopOpacity),
-        ("stroke-linejoin", AId::StrokeLinejoin),
-        ("dominant-baseline", AId::DominantBaseline),
-        ("spreadMethod", AId::SpreadMethod),
-        ("points", AId::Points),
-        ("stroke", AId::Stroke),
-        ("values", AId::Values),
-        ("text-align", AId::TextAlign),
-        ("font-size", AId::FontSize),
-        ("maskContentUnits", AId::MaskContentUnits),
-        ("mask-size", AId::MaskSize),
-        ("path", AId::Path),
-        ("specularConstant", AId::SpecularConstant),
-        ("color", AId::Color),
-        ("display", AId::Display),
-        ("fill-opacity", AId::FillOpacity),
-        ("word-spacing", AId::WordSpacing),
-        ("cy", AId::Cy),
+        ("clipPathUnits", AId::ClipPathUnits),
         ("startOffset", AId::StartOffset),
-        ("mask-origin", AId::MaskOrigin),
-        ("lengthAdjust", AId::LengthAdjust),
-        ("white-space", AId::WhiteSpace),
-        ("opacity", AId::Opacity),
-        ("divisor", AId::Divisor),
+        ("side", AId::Side),
+        ("patternUnits", AId::PatternUnits),
+        ("font-variant-caps", AId::FontVariantCaps),
+        ("offset", AId::Offset),
+        ("markerHeight", AId::MarkerHeight),
+        ("y1", AId::Y1),
+        ("markerUnits", AId::MarkerUnits),
+        ("edgeMode", AId::EdgeMode),
+        ("font-feature-settings", AId::FontFeatureSettings),
+        ("cy", AId::Cy),
+        ("clip-rule", AId::ClipRule),
         ("gradientUnits", AId::GradientUnits),
-        ("stroke-dashoffset", AId::StrokeDashoffset),
-        ("fill", AId::Fill),
-        ("space", AId::Space),
-        ("baseline-shift", AId::BaselineShift),
-        ("slope", AId::Slope),
+        ("patternTransform", AId::PatternTransform),
         ("color-rendering", AId::ColorRendering),
-        ("unicode-range", AId::UnicodeRange),
-        ("font-variant-caps", AId::FontVariantCaps),
-        ("text-rendering", AId::TextRendering),
-        ("exponent", AId::Exponent),
+        ("in2", AId::In2),
+        ("opacity", AId::Opacity),
+        ("stroke-width", AId::StrokeWidth),
+        ("shape-rendering", AId::ShapeRendering),
+        ("xChannelSelector", AId::XChannelSelector),
+        ("visibility", AId::Visibility),
         ("text-decoration-color", AId::TextDecorationColor),
-        ("refX", AId::RefX),
-        ("x1", AId::X1),
-        ("scale", AId::Scale),
-        ("clip-rule", AId::ClipRule),
-        ("kerning", AId::Kerning),
-        ("mix-blend-mode", AId::MixBlendMode),
+        ("text-decoration-stroke", AId::TextDecorationStroke),
+        ("baseline-shift", AId::BaselineShift),
         ("mask-clip", AId::MaskClip),
-        ("mask-mode", AId::MaskMode),
-        ("type", AId::Type),
-        ("mask-border", AId::MaskBorder),
-        ("mode", AId::Mode),
-        ("mask-border-repeat", AId::MaskBorderRepeat),
-        ("stroke-miterlimit", AId::StrokeMiterlimit),
-        ("letter-spacing", AId::LetterSpacing),
-        ("font-stretch", AId::FontStretch),
-        ("offset", AId::Offset),
-        ("clip-path", AId::ClipPath),
-        ("markerHeight", AId::MarkerHeight),
-        ("text-underline-position", AId::TextUnderlinePosition),
-        ("text-align-last", AId::TextAlignLast),
-        ("width", AId::Width),
-        ("font", AId::Font),
-        ("font-family", AId::FontFamily),
-        ("mask-position", AId::MaskPosition),
-        ("result", AId::Result),
-        ("font-size-adjust", AId::FontSizeAdjust),
-        ("shape-margin", AId::ShapeMargin),
-        ("direction", AId::Direction),
-        ("font-variant", AId::FontVariant),
-        ("radius", AId::Radius),
-        ("maskUnits", AId::MaskUnits),
-        ("clipPathUnits", AId::ClipPathUnits),
-        ("text-orientation", AId::TextOrientation),
-        ("amplitude", AId::Amplitude),
         ("ry", AId::Ry),
-        ("mask-type", AId::MaskType),
-        ("filter", AId::Filter),
-        ("in", AId::In),
-        ("dx", AId::Dx),
-        ("seed", AId::Seed),
-        ("class", AId::Class),
-        ("color-profile", AId::ColorProfile),
-        ("x", AId::X),
-        ("href", AId::Href),
-        ("font-feature-settings", AId::FontFeatureSettings),
-        ("fill-rule", AId::FillRule),
-        ("fr", AId::Fr),
-        ("mask-border-mode", AId::MaskBorderMode),
         ("text-decoration-style", AId::TextDecorationStyle),
-        ("numOctaves", AId::NumOctaves),
-        ("shape-padding", AId::ShapePadding),
-        ("visibility", AId::Visibility),
-        ("id", AId::Id),
+        ("alignment-baseline", AId::AlignmentBaseline),
+        ("limitingConeAngle", AId::LimitingConeAngle),
+        ("dominant-baseline", AId::DominantBaseline),
+        ("font-variant-ligatures", AId::FontVariantLigatures),
+        ("font-style", AId::FontStyle),
+        ("refY", AId::RefY),
+        ("z", AId::Z),
+        ("spreadMethod", AId::SpreadMethod),
+        ("overflow", AId::Overflow),
+        ("glyph-orientation-horizontal", AId::GlyphOrientationHorizontal),
         ("targetX", AId::TargetX),
-        ("transform-box", AId::TransformBox),
-        ("stitchTiles", AId::StitchTiles),
+        ("mask-border-slice", AId::MaskBorderSlice),
+        ("preserveAlpha", AId::PreserveAlpha),
+        ("font-variant-position", AId::FontVariantPosition),
+        ("unicode-range", AId::UnicodeRange),
+        ("fill-opacity", AId::FillOpacity),
+        ("mix-blend-mode", AId::MixBlendMode),
+        ("inline-size", AId::InlineSize),
+        ("color-interpolation-filters", AId::ColorInterpolationFilters),
+        ("targetY", AId::TargetY),
+        ("font-variant", AId::FontVariant),
+        ("font-variant-numeric", AId::FontVariantNumeric),
+        ("text-rendering", AId::TextRendering),
+        ("x", AId::X),
+        ("radius", AId::Radius),
+        ("flood-color", AId::FloodColor),
+        ("marker-mid", AId::MarkerMid),
+        ("pointsAtX", AId::PointsAtX),
+        ("text-decoration-fill", AId::TextDecorationFill),
+        ("writing-mode", AId::WritingMode),
+        ("viewBox", AId::ViewBox),
+        ("filter", AId::Filter),
+        ("scale", AId::Scale),
+        ("text-align-last", AId::TextAlignLast),
+        ("mask-type", AId::MaskType),
         ("text-decoration-line", AId::TextDecorationLine),
+        ("k3", AId::K3),
+        ("textLength", AId::TextLength),
         ("requiredFeatures", AId::RequiredFeatures),
+        ("slope", AId::Slope),
+        ("marker-end", AId::MarkerEnd),
+        ("k4", AId::K4),
+        ("patternContentUnits", AId::PatternContentUnits),
+        ("direction", AId::Direction),
+        ("order", AId::Order),
+        ("mask-mode", AId::MaskMode),
+        ("mode", AId::Mode),
+        ("operator", AId::Operator),
+        ("gradientTransform", AId::GradientTransform),
+        ("text-anchor", AId::TextAnchor),
         ("intercept", AId::Intercept),
+        ("shape-padding", AId::ShapePadding),
+        ("mask-border-source", AId::MaskBorderSource),
+        ("diffuseConstant", AId::DiffuseConstant),
+        ("shape-inside", AId::ShapeInside),
+        ("unicode-bidi", AId::UnicodeBidi),
+        ("stroke-dashoffset", AId::StrokeDashoffset),
+        ("rx", AId::Rx),
+        ("numOctaves", AId::NumOctaves),
+        ("text-underline-position", AId::TextUnderlinePosition),
+        ("enable-background", AId::EnableBackground),
+        ("fill-rule", AId::FillRule),
+        ("display", AId::Display),
+        ("x2", AId::X2),
+        ("mask-border", AId::MaskBorder),
+        ("orient", AId::Orient),
         ("tableValues", AId::TableValues),
-        ("text-overflow", AId::TextOverflow),
-        ("paint-order", AId::PaintOrder),
-        ("azimuth", AId::Azimuth),
-        ("text-indent", AId::TextIndent),
-        ("isolation", AId::Isolation),
-        ("text-decoration-stroke", AId::TextDecorationStroke),
-        ("gradientTransform", AId::GradientTransform),
-        ("stroke-linecap", AId::StrokeLinecap),
+        ("in", AId::In),
+        ("mask-border-outset", AId::MaskBorderOutset),
+        ("r", AId::R),
+        ("stop-color", AId::StopColor),
+        ("word-spacing", AId::WordSpacing),
+        ("fy", AId::Fy),
+        ("stroke-dasharray", AId::StrokeDasharray),
+        ("preserveAspectRatio", AId::PreserveAspectRatio),
+        ("glyph-orientation-vertical", AId::GlyphOrientationVertical),
+        ("k2", AId::K2),
         ("systemLanguage", AId::SystemLanguage),
-        ("stroke-width", AId::StrokeWidth),
-        ("requiredExtensions", AId::RequiredExtensions),
-        ("writing-mode", AId::WritingMode),
-        ("order", AId::Order),
-        ("height", AId::Height),
-        ("shape-image-threshold", AId::ShapeImageThreshold),
-        ("style", AId::Style),
+        ("azimuth", AId::Azimuth),
+        ("d", AId::D),
+        ("pathLength", AId::PathLength),
+        ("mask-border-width", AId::MaskBorderWidth),
+        ("mask-image", AId::MaskImage),
+        ("mask-size", AId::MaskSize),
+        ("stroke-opacity", AId::StrokeOpacity),
+        ("maskContentUnits", AId::MaskContentUnits),
+        ("kernelUnitLength", AId::KernelUnitLength),
+        ("stroke-miterlimit", AId::StrokeMiterlimit),
+        ("filterUnits", AId::FilterUnits),
+        ("values", AId::Values),
+        ("fr", AId::Fr),
+        ("amplitude", AId::Amplitude),
+        ("paint-order", AId::PaintOrder),
+        ("stroke-linejoin", AId::StrokeLinejoin),
+        ("bias", AId::Bias),
+        ("shape-margin", AId::ShapeMargin),
+        ("font-variant-east-asian", AId::FontVariantEastAsian),
+        ("mask-composite", AId::MaskComposite),
+        ("stop-opacity", AId::StopOpacity),
+        ("divisor", AId::Divisor),
+        ("font", AId::Font),
+        ("image-rendering", AId::ImageRendering),
+        ("transform-box", AId::TransformBox),
         ("pointsAtZ", AId::PointsAtZ),
-        ("rx", AId::Rx),
-        ("font-synthesis", AId::FontSynthesis),
-        ("markerUnits", AId::MarkerUnits),
-        ("lighting-color", AId::LightingColor),
-        ("cx", AId::Cx),
-        ("alignment-baseline", AId::AlignmentBaseline),
+        ("pointsAtY", AId::PointsAtY),
         ("font-kerning", AId::FontKerning),
+        ("text-indent", AId::TextIndent),
+        ("surfaceScale", AId::SurfaceScale),
+        ("stitchTiles", AId::StitchTiles),
+        ("y", AId::Y),
         ("kernelMatrix", AId::KernelMatrix),
-        ("glyph-orientation-vertical", AId::GlyphOrientationVertical),
-        ("mask-border-outset", AId::MaskBorderOutset),
+        ("clip-path", AId::ClipPath),
+        ("text-overflow", AId::TextOverflow),
+        ("specularExponent", AId::SpecularExponent),
+        ("font-size-adjust", AId::FontSizeAdjust),
         ("primitiveUnits", AId::PrimitiveUnits),
-        ("textLength", AId::TextLength),
-        ("text-decoration-fill", AId::TextDecorationFill),
-        ("fy", AId::Fy),
-        ("unicode-bidi", AId::UnicodeBidi),
-        ("k3", AId::K3),
-        ("font-variant-numeric", AId::FontVariantNumeric),
-        ("orient", AId::Orient),
-        ("k1", AId::K1),
-        ("refY", AId::RefY),
-        ("edgeMode", AId::EdgeMode),
-        ("shape-rendering", AId::ShapeRendering),
-        ("font-variant-ligatures", AId::FontVariantLigatures),
-        ("y", AId::Y),
-        ("marker-end", AId::MarkerEnd),
-        ("rotate", AId::Rotate),
-        ("transform", AId::Transform),
-        ("patternContentUnits", AId::PatternContentUnits),
-        ("mask", AId::Mask),
-        ("font-variant-position", AId::FontVariantPosition),
-        ("elevation", AId::Elevation),
-        ("pathLength", AId::PathLength),
-        ("text-decoration", AId::TextDecoration),
-        ("shape-subtract", AId::ShapeSubtract),
-        ("preserveAspectRatio", AId::PreserveAspectRatio),
-        ("y2", AId::Y2),
         ("dy", AId::Dy),
-        ("yChannelSelector", AId::YChannelSelector),
-        ("xChannelSelector", AId::XChannelSelector),
-        ("image-rendering", AId::ImageRendering),
-        ("z", AId::Z),
-        ("patternUnits", AId::PatternUnits),
+        ("clip", AId::Clip),
+        ("width", AId::Width),
+        ("label", AId::Label),
         ("marker-start", AId::MarkerStart),
-        ("pointsAtY", AId::PointsAtY),
-        ("d", AId::D),
-        ("shape-inside", AId::ShapeInside),
-        ("preserveAlpha", AId::PreserveAlpha),
-        ("color-interpolation", AId::ColorInterpolation),
-        ("operator", AId::Operator),
-        ("marker-mid", AId::MarkerMid),
-        ("kernelUnitLength", AId::KernelUnitLength),
-        ("bias", AId::Bias),
-        ("mask-border-slice", AId::MaskBorderSlice),
-        ("pointsAtX", AId::PointsAtX),
-        ("stop-color", AId::StopColor),
+        ("cx", AId::Cx),
+        ("vector-effect", AId::VectorEffect),
+        ("pointer-events", AId::PointerEvents),
+        ("mask-position", AId::MaskPosition),
+        ("mask", AId::Mask),
+        ("seed", AId::Seed),
+        ("color", AId::Color),
         ("line-height", AId::LineHeight),
-        ("label", AId::Label),
-        ("fx", AId::Fx),
-        ("patternTransform", AId::PatternTransform),
-        ("glyph-orientation-horizontal", AId::GlyphOrientationHorizontal),
-        ("transform-origin", AId::TransformOrigin),
+        ("font-family", AId::FontFamily),
+        ("white-space", AId::WhiteSpace),
+        ("id", AId::Id),
         ("font-weight", AId::FontWeight),
-        ("overflow", AId::Overflow),
-        ("y1", AId::Y1),
-        ("r", AId::R),
-        ("k2", AId::K2),
-        ("text-anchor", AId::TextAnchor),
-        ("inline-size", AId::InlineSize),
-        ("x2", AId::X2),
-        ("limitingConeAngle", AId::LimitingConeAngle),
-        ("color-interpolation-filters", AId::ColorInterpolationFilters),
+        ("text-decoration", AId::TextDecoration),
+        ("space", AId::Space),
+        ("shape-subtract", AId::ShapeSubtract),
+        ("class", AId::Class),
         ("stdDeviation", AId::StdDeviation),
+        ("rotate", AId::Rotate),
+        ("color-interpolation", AId::ColorInterpolation),
+        ("font-synthesis", AId::FontSynthesis),
+        ("points", AId::Points),
+        ("text-orientation", AId::TextOrientation),
+        ("x1", AId::X1),
+        ("type", AId::Type),
+        ("elevation", AId::Elevation),
         ("baseFrequency", AId::BaseFrequency),
-        ("surfaceScale", AId::SurfaceScale),
-        ("font-variant-east-asian", AId::FontVariantEastAsian),
-        ("mask-image", AId::MaskImage),
-        ("clip", AId::Clip),
+        ("isolation", AId::Isolation),
+        ("kerning", AId::Kerning),
+        ("shape-image-threshold", AId::ShapeImageThreshold),
+        ("fx", AId::Fx),
+        ("height", AId::Height),
+        ("mask-border-mode", AId::MaskBorderMode),
+        ("color-profile", AId::ColorProfile),
+        ("href", AId::Href),
+        ("k1", AId::K1),
         ("markerWidth", AId::MarkerWidth),
-        ("flood-color", AId::FloodColor),
-        ("in2", AId::In2),
-        ("side", AId::Side),
-        ("mask-composite", AId::MaskComposite),
-        ("k4", AId::K4),
-        ("mask-border-width", AId::MaskBorderWidth),
-        ("vector-effect", AId::VectorEffect),
-        ("viewBox", AId::ViewBox),
-        ("font-style", AId::FontStyle),
-        ("enable-background", AId::EnableBackground),
-        ("filterUnits", AId::FilterUnits),
-        ("stroke-dasharray", AId::StrokeDasharray),
-        ("specularExponent", AId::SpecularExponent),
-        ("stroke-opacity", AId::StrokeOpacity),
-        ("targetY", AId::TargetY),
+        ("requiredExtensions", AId::RequiredExtensions),
+        ("stroke", AId::Stroke),
+        ("lengthAdjust", AId::LengthAdjust),
+        ("dx", AId::Dx),
+        ("maskUnits", AId::MaskUnits),
+        ("y2", AId::Y2),
+        ("transform-origin", AId::TransformOrigin),
+        ("style", AId::Style),
+        ("path", AId::Path),
+        ("lighting-color", AId::LightingColor),
+        ("specularConstant", AId::SpecularConstant),
+        ("result", AId::Result),
+        ("exponent", AId::Exponent),
+        ("stroke-linecap", AId::StrokeLinecap),
+        ("letter-spacing", AId::LetterSpacing),
+        ("fill", AId::Fill),
+        ("font-stretch", AId::FontStretch),
+        ("mask-origin", AId::MaskOrigin),
+        ("refX", AId::RefX),
+        ("transform", AId::Transform),
+        ("font-size", AId::FontSize),
+        ("text-align", AId::TextAlign),
+        ("yChannelSelector", AId::YChannelSelector),
         ("flood-opacity", AId::FloodOpacity),
-        ("diffuseConstant", AId::DiffuseConstant),
+        ("mask-border-repeat", AId::MaskBorderRepeat),
     ],
 };
 
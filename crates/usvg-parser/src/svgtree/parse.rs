@@ -4,19 +4,28 @@
 
 use std::collections::HashMap;
 
-use roxmltree::Error;
+use usvg_tree::{Diagnostic, DiagnosticCode};
 
+use super::animation::Animations;
+use super::css_animation::is_animation_property;
 use super::{AId, Attribute, Document, EId, NodeData, NodeId, NodeKind, ShortRange};
+use crate::{Error, Limits};
 
-const SVG_NS: &str = "http://www.w3.org/2000/svg";
-const XLINK_NS: &str = "http://www.w3.org/1999/xlink";
+pub(crate) const SVG_NS: &str = "http://www.w3.org/2000/svg";
+pub(crate) const XLINK_NS: &str = "http://www.w3.org/1999/xlink";
 const XML_NAMESPACE_NS: &str = "http://www.w3.org/XML/1998/namespace";
 const INKSCAPE_NS: &str = "http://www.inkscape.org/namespaces/inkscape";
 
 impl<'input> Document<'input> {
     /// Parses a [`Document`] from a [`roxmltree::Document`].
-    pub fn parse_tree(xml: &roxmltree::Document<'input>) -> Result<Document<'input>, Error> {
-        parse(xml)
+    ///
+    /// When `animation_time` is set, animated attributes are resolved at this time.
+    pub fn parse_tree(
+        xml: &roxmltree::Document<'input>,
+        limits: &Limits,
+        animation_time: Option<f32>,
+    ) -> Result<Document<'input>, Error> {
+        parse(xml, limits, animation_time)
     }
 
     pub(crate) fn append(&mut self, parent_id: NodeId, kind: NodeKind) -> NodeId {
@@ -50,11 +59,17 @@ impl<'input> Document<'input> {
     }
 }
 
-fn parse<'input>(xml: &roxmltree::Document<'input>) -> Result<Document<'input>, Error> {
+fn parse<'input>(
+    xml: &roxmltree::Document<'input>,
+    limits: &Limits,
+    animation_time: Option<f32>,
+) -> Result<Document<'input>, Error> {
     let mut doc = Document {
+        text: xml.input_text(),
         nodes: Vec::new(),
         attrs: Vec::new(),
         links: HashMap::new(),
+        animations: Animations::default(),
     };
 
     // build a map of id -> node for resolve_href
@@ -75,6 +90,10 @@ fn parse<'input>(xml: &roxmltree::Document<'input>) -> Result<Document<'input>,
         kind: NodeKind::Root,
     });
 
+    if let Some(time) = animation_time {
+        doc.animations = Animations::collect(xml, time, &id_map);
+    }
+
     let style_sheet = resolve_css(xml);
 
     parse_xml_node_children(
@@ -84,18 +103,22 @@ fn parse<'input>(xml: &roxmltree::Document<'input>) -> Result<Document<'input>,
         &style_sheet,
         false,
         0,
+        limits,
         &mut doc,
         &id_map,
     )?;
 
+    // Animated values are already applied.
+    doc.animations = Animations::default();
+
     // Check that the root element is `svg`.
     match doc.root().first_element_child() {
         Some(child) => {
             if child.tag_name() != Some(EId::Svg) {
-                return Err(roxmltree::Error::NoRootNode);
+                return Err(roxmltree::Error::NoRootNode.into());
             }
         }
-        None => return Err(roxmltree::Error::NoRootNode),
+        None => return Err(roxmltree::Error::NoRootNode.into()),
     }
 
     // Collect all elements with `id` attribute.
@@ -135,6 +158,7 @@ fn parse_xml_node_children<'input>(
     style_sheet: &simplecss::StyleSheet,
     ignore_ids: bool,
     depth: u32,
+    limits: &Limits,
     doc: &mut Document<'input>,
     id_map: &HashMap<&str, roxmltree::Node<'_, 'input>>,
 ) -> Result<(), Error> {
@@ -146,6 +170,7 @@ fn parse_xml_node_children<'input>(
             style_sheet,
             ignore_ids,
             depth,
+            limits,
             doc,
             id_map,
         )?;
@@ -161,11 +186,12 @@ fn parse_xml_node<'input>(
     style_sheet: &simplecss::StyleSheet,
     ignore_ids: bool,
     depth: u32,
+    limits: &Limits,
     doc: &mut Document<'input>,
     id_map: &HashMap<&str, roxmltree::Node<'_, 'input>>,
 ) -> Result<(), Error> {
-    if depth > 1024 {
-        return Err(Error::NodesLimitReached);
+    if depth > limits.max_nesting_depth {
+        return Err(Error::NestingDepthLimitReached);
     }
 
     let mut tag_name = match parse_tag_name(node) {
@@ -184,12 +210,26 @@ fn parse_xml_node<'input>(
     }
 
     let node_id = parse_svg_element(node, parent_id, tag_name, style_sheet, ignore_ids, doc)?;
+    // Elements created by `use` are counted as well.
+    if doc.nodes.len() > limits.max_elements as usize {
+        return Err(Error::ElementsLimitReached);
+    }
+
     if tag_name == EId::Text {
         super::text::parse_svg_text_element(node, node_id, style_sheet, doc)?;
     } else if tag_name == EId::Title {
         super::text::parse_svg_title_element(node, node_id, doc)?;
     } else if tag_name == EId::Use {
-        parse_svg_use_element(node, origin, node_id, style_sheet, depth + 1, doc, id_map)?;
+        parse_svg_use_element(
+            node,
+            origin,
+            node_id,
+            style_sheet,
+            depth + 1,
+            limits,
+            doc,
+            id_map,
+        )?;
     } else {
         parse_xml_node_children(
             node,
@@ -198,6 +238,7 @@ fn parse_xml_node<'input>(
             style_sheet,
             ignore_ids,
             depth + 1,
+            limits,
             doc,
             id_map,
         )?;
@@ -213,7 +254,7 @@ pub(crate) fn parse_svg_element<'input>(
     style_sheet: &simplecss::StyleSheet,
     ignore_ids: bool,
     doc: &mut Document<'input>,
-) -> Result<NodeId, Error> {
+) -> Result<NodeId, roxmltree::Error> {
     let attrs_start_idx = doc.attrs.len();
 
     // Copy presentational attributes first.
@@ -242,47 +283,53 @@ pub(crate) fn parse_svg_element<'input>(
         append_attribute(parent_id, tag_name, aid, attr.value_storage().clone(), doc);
     }
 
-    let mut insert_attribute = |aid, value: &str| {
-        // Check that attribute already exists.
-        let idx = doc.attrs[attrs_start_idx..]
-            .iter_mut()
-            .position(|a| a.name == aid);
-
-        // Append an attribute as usual.
-        let added = append_attribute(
-            parent_id,
-            tag_name,
-            aid,
-            roxmltree::StringStorage::new_owned(value),
-            doc,
-        );
-
-        // Check that attribute was actually added, because it could be skipped.
-        if added {
-            if let Some(idx) = idx {
-                // Swap the last attribute with an existing one.
-                let last_idx = doc.attrs.len() - 1;
-                doc.attrs.swap(attrs_start_idx + idx, last_idx);
-                // Remove last.
-                doc.attrs.pop();
-            }
-        }
-    };
+    // CSS animations properties in the cascade order.
+    let mut animation_declarations = Vec::new();
 
     // Apply CSS.
     for rule in &style_sheet.rules {
         if rule.selector.matches(&XmlNode(xml_node)) {
             for declaration in &rule.declarations {
                 // TODO: perform XML attribute normalization
-                if let Some(aid) = AId::from_str(declaration.name) {
+                if is_animation_property(declaration.name) {
+                    animation_declarations.push((declaration.name, declaration.value));
+                } else if let Some(aid) = AId::from_str(declaration.name) {
                     // Parse only the presentation attributes.
                     if aid.is_presentation() {
-                        insert_attribute(aid, declaration.value);
+                        insert_attribute(
+                            parent_id,
+                            tag_name,
+                            attrs_start_idx,
+                            aid,
+                            declaration.value,
+                            doc,
+                        );
                     }
                 } else if declaration.name == "marker" {
-                    insert_attribute(AId::MarkerStart, declaration.value);
-                    insert_attribute(AId::MarkerMid, declaration.value);
-                    insert_attribute(AId::MarkerEnd, declaration.value);
+                    insert_attribute(
+                        parent_id,
+                        tag_name,
+                        attrs_start_idx,
+                        AId::MarkerStart,
+                        declaration.value,
+                        doc,
+                    );
+                    insert_attribute(
+                        parent_id,
+                        tag_name,
+                        attrs_start_idx,
+                        AId::MarkerMid,
+                        declaration.value,
+                        doc,
+                    );
+                    insert_attribute(
+                        parent_id,
+                        tag_name,
+                        attrs_start_idx,
+                        AId::MarkerEnd,
+                        declaration.value,
+                        doc,
+                    );
                 }
             }
         }
@@ -292,26 +339,118 @@ pub(crate) fn parse_svg_element<'input>(
     if let Some(value) = xml_node.attribute("style") {
         for declaration in simplecss::DeclarationTokenizer::from(value) {
             // TODO: preform XML attribute normalization
-            if let Some(aid) = AId::from_str(declaration.name) {
+            if is_animation_property(declaration.name) {
+                animation_declarations.push((declaration.name, declaration.value));
+            } else if let Some(aid) = AId::from_str(declaration.name) {
                 // Parse only the presentation attributes.
                 if aid.is_presentation() {
-                    insert_attribute(aid, declaration.value);
+                    insert_attribute(
+                        parent_id,
+                        tag_name,
+                        attrs_start_idx,
+                        aid,
+                        declaration.value,
+                        doc,
+                    );
                 }
             }
         }
     }
 
+    // CSS animations override static values.
+    let animated = doc.animations.resolve_css(&animation_declarations, |aid| {
+        animation_base_value(parent_id, attrs_start_idx, aid, doc)
+    });
+    for (aid, value) in animated {
+        insert_attribute(parent_id, tag_name, attrs_start_idx, aid, &value, doc);
+    }
+
+    // SMIL animations override all other values.
+    let animated = doc.animations.resolve(xml_node, |aid| {
+        animation_base_value(parent_id, attrs_start_idx, aid, doc)
+    });
+    for (aid, value) in animated {
+        insert_attribute(parent_id, tag_name, attrs_start_idx, aid, &value, doc);
+    }
+
     let node_id = doc.append(
         parent_id,
         NodeKind::Element {
             tag_name,
             attributes: ShortRange::new(attrs_start_idx as u32, doc.attrs.len() as u32),
+            range: ShortRange::new(xml_node.range().start as u32, xml_node.range().end as u32),
         },
     );
 
     Ok(node_id)
 }
 
+/// Inserts an attribute, replacing an existing one.
+fn insert_attribute(
+    parent_id: NodeId,
+    tag_name: EId,
+    attrs_start_idx: usize,
+    aid: AId,
+    value: &str,
+    doc: &mut Document,
+) {
+    // Check that attribute already exists.
+    let idx = doc.attrs[attrs_start_idx..]
+        .iter_mut()
+        .position(|a| a.name == aid);
+
+    // Append an attribute as usual.
+    let added = append_attribute(
+        parent_id,
+        tag_name,
+        aid,
+        roxmltree::StringStorage::new_owned(value),
+        doc,
+    );
+
+    // Check that attribute was actually added, because it could be skipped.
+    if added {
+        if let Some(idx) = idx {
+            // Swap the last attribute with an existing one.
+            let last_idx = doc.attrs.len() - 1;
+            doc.attrs.swap(attrs_start_idx + idx, last_idx);
+            // Remove last.
+            doc.attrs.pop();
+        }
+    }
+}
+
+/// Returns a not animated value of an element's attribute.
+fn animation_base_value(
+    parent_id: NodeId,
+    attrs_start_idx: usize,
+    aid: AId,
+    doc: &Document,
+) -> Option<String> {
+    let find = |attrs: &[Attribute]| {
+        attrs
+            .iter()
+            .find(|a| a.name == aid)
+            .map(|a| a.value.to_string())
+    };
+
+    if let Some(value) = find(&doc.attrs[attrs_start_idx..]) {
+        return Some(value);
+    }
+
+    if aid.is_inheritable() {
+        if let Some(value) = doc
+            .get(parent_id)
+            .ancestors()
+            .find_map(|n| find(n.attributes()))
+        {
+            return Some(value);
+        }
+    }
+
+    default_value(aid).map(String::from)
+}
+
 fn append_attribute<'input>(
     parent_id: NodeId,
     tag_name: EId,
@@ -319,12 +458,11 @@ fn append_attribute<'input>(
     value: roxmltree::StringStorage<'input>,
     doc: &mut Document<'input>,
 ) -> bool {
-    match aid {
-        // The `style` attribute will be split into attributes, so we don't need it.
-        AId::Style |
-        // No need to copy a `class` attribute since CSS were already resolved.
-        AId::Class => return false,
-        _ => {}
+    // The `style` attribute will be split into attributes, so we don't need it.
+    // The `class` attribute is kept, even though CSS were already resolved,
+    // because it will be preserved in the tree.
+    if aid == AId::Style {
+        return false;
     }
 
     // Ignore `xlink:href` on `tspan` (which was originally `tref` or `a`),
@@ -384,6 +522,17 @@ fn resolve_inherit(parent_id: NodeId, aid: AId, doc: &mut Document) -> bool {
     }
 
     // Fallback to a default value if possible.
+    let value = match default_value(aid) {
+        Some(v) => v,
+        None => return false,
+    };
+
+    doc.append_attribute(aid, roxmltree::StringStorage::Borrowed(value));
+    true
+}
+
+/// Returns an initial value of a property.
+pub(crate) fn default_value(aid: AId) -> Option<&'static str> {
     let value = match aid {
         AId::ImageRendering | AId::ShapeRendering | AId::TextRendering => "auto",
 
@@ -426,13 +575,13 @@ fn resolve_inherit(parent_id: NodeId, aid: AId, doc: &mut Document) -> bool {
         AId::StrokeMiterlimit => "4",
         AId::StrokeWidth => "1",
         AId::TextAnchor => "start",
+        AId::PointerEvents => "visiblePainted",
         AId::Visibility => "visible",
         AId::WritingMode => "lr-tb",
-        _ => return false,
+        _ => return None,
     };
 
-    doc.append_attribute(aid, roxmltree::StringStorage::Borrowed(value));
-    true
+    Some(value)
 }
 
 fn resolve_href<'a, 'input: 'a>(
@@ -454,6 +603,7 @@ fn parse_svg_use_element<'input>(
     parent_id: NodeId,
     style_sheet: &simplecss::StyleSheet,
     depth: u32,
+    limits: &Limits,
     doc: &mut Document<'input>,
     id_map: &HashMap<&str, roxmltree::Node<'_, 'input>>,
 ) -> Result<(), Error> {
@@ -463,10 +613,7 @@ fn parse_svg_use_element<'input>(
     };
 
     if link == node || link == origin {
-        log::warn!(
-            "Recursive 'use' detected. '{}' will be skipped.",
-            node.attribute((SVG_NS, "id")).unwrap_or_default()
-        );
+        warn_recursive_use(node);
         return Ok(());
     }
 
@@ -507,10 +654,7 @@ fn parse_svg_use_element<'input>(
     }
 
     if is_recursive {
-        log::warn!(
-            "Recursive 'use' detected. '{}' will be skipped.",
-            node.attribute((SVG_NS, "id")).unwrap_or_default()
-        );
+        warn_recursive_use(node);
         return Ok(());
     }
 
@@ -521,11 +665,24 @@ fn parse_svg_use_element<'input>(
         style_sheet,
         true,
         depth + 1,
+        limits,
         doc,
         id_map,
     )
 }
 
+fn warn_recursive_use(node: roxmltree::Node) {
+    let id = node.attribute("id").unwrap_or_default();
+    let message = format!("Recursive 'use' detected. '{}' will be skipped.", id);
+    log::warn!("{}", message);
+
+    let mut diagnostic = Diagnostic::new(DiagnosticCode::RecursiveReference, message);
+    diagnostic.element_id = id.to_string();
+    diagnostic.element_tag = EId::Use.to_str().to_string();
+    diagnostic.offset = Some(node.range().start);
+    usvg_tree::diagnostics::report(diagnostic);
+}
+
 fn resolve_css<'a>(xml: &'a roxmltree::Document<'a>) -> simplecss::StyleSheet<'a> {
     let mut sheet = simplecss::StyleSheet::new();
 
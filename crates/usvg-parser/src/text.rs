@@ -2,8 +2,6 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use std::rc::Rc;
-
 use kurbo::{ParamCurve, ParamCurveArclen};
 use svgtypes::{Length, LengthUnit};
 use usvg_tree::*;
@@ -109,6 +107,7 @@ pub(crate) fn convert(
 
     let text = Text {
         id,
+        classes: text_node.element_classes(),
         transform: Transform::default(),
         rendering_mode,
         positions: pos_list,
@@ -116,6 +115,7 @@ pub(crate) fn convert(
         writing_mode,
         chunks,
         title,
+        origin: converter::convert_origin(text_node, state, cache),
     };
     parent.append_kind(NodeKind::Text(text));
 }
@@ -253,7 +253,8 @@ fn collect_text_chunks_impl(
             }
         }
 
-        let title = child.title()
+        let title = child
+            .title()
             .or_else(|| parent.title())
             .map(ToOwned::to_owned);
         let span = TextSpan {
@@ -268,6 +269,9 @@ fn collect_text_chunks_impl(
             apply_kerning,
             decoration: resolve_decoration(text_node, parent, state, cache),
             visibility: parent.find_attribute(AId::Visibility).unwrap_or_default(),
+            pointer_events: parent
+                .find_attribute(AId::PointerEvents)
+                .unwrap_or_default(),
             dominant_baseline,
             alignment_baseline: parent
                 .find_attribute(AId::AlignmentBaseline)
@@ -347,7 +351,7 @@ fn resolve_text_flow(node: SvgNode, state: &converter::State) -> Option<TextFlow
     let path = if let Some(node_transform) = linked_node.attribute::<Transform>(AId::Transform) {
         let mut path_copy = path.as_ref().clone();
         path_copy = path_copy.transform(node_transform)?;
-        Rc::new(path_copy)
+        Shared::new(path_copy)
     } else {
         path
     };
@@ -362,7 +366,7 @@ fn resolve_text_flow(node: SvgNode, state: &converter::State) -> Option<TextFlow
         node.resolve_length(AId::StartOffset, state, 0.0)
     };
 
-    Some(TextFlow::Path(Rc::new(TextPath { start_offset, path })))
+    Some(TextFlow::Path(Shared::new(TextPath { start_offset, path })))
 }
 
 fn convert_font(node: SvgNode, state: &converter::State) -> Font {
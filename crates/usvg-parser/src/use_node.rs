@@ -2,11 +2,9 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use std::rc::Rc;
-
 use svgtypes::{Length, LengthUnit};
 use usvg_tree::{
-    tiny_skia_path, Group, IsValidLength, Node, NodeExt, NodeKind, NonZeroRect, Path, Size,
+    tiny_skia_path, Group, IsValidLength, Node, NodeExt, NodeKind, NonZeroRect, Path, Shared, Size,
     Transform,
 };
 
@@ -208,7 +206,7 @@ fn clip_element(
     let mut clip_path = usvg_tree::ClipPath::default();
     clip_path.id = cache.gen_clip_path_id();
 
-    let mut path = Path::new(Rc::new(tiny_skia_path::PathBuilder::from_rect(
+    let mut path = Path::new(Shared::new(tiny_skia_path::PathBuilder::from_rect(
         clip_rect.to_rect(),
     )));
     path.fill = Some(usvg_tree::Fill::default());
@@ -224,9 +222,11 @@ fn clip_element(
     let title = node.title().map(ToOwned::to_owned);
     parent.append_kind(NodeKind::Group(Group {
         id,
+        classes: node.element_classes(),
         transform,
-        clip_path: Some(Rc::new(clip_path)),
+        clip_path: Some(Shared::new(clip_path)),
         title,
+        origin: converter::convert_origin(node, state, cache),
         ..Group::default()
     }))
 }
@@ -1,4 +1,5 @@
 use usvg_parser::TreeParsing;
+use usvg_tree::NodeExt;
 
 #[test]
 fn clippath_with_invalid_child() {
@@ -86,3 +87,865 @@ fn invalid_size_1() {
     let result = usvg_tree::Tree::from_str(&svg, &usvg_parser::Options::default());
     assert!(result.is_err());
 }
+
+#[test]
+fn diagnostics() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 1 1'>
+        <rect id='rect1' width='-10' height='10'/>
+        <circle id='circle1' r='5' fill='#zzz'/>
+    </svg>
+    ";
+
+    let diagnostics = usvg_tree::Diagnostics::new();
+    let opt = usvg_parser::Options {
+        diagnostics: Some(diagnostics.clone()),
+        ..usvg_parser::Options::default()
+    };
+    usvg_tree::Tree::from_str(&svg, &opt).unwrap();
+
+    let list = diagnostics.take();
+    assert_eq!(list.len(), 2);
+
+    assert_eq!(list[0].code, usvg_tree::DiagnosticCode::InvalidAttribute);
+    assert_eq!(list[0].element_id, "rect1");
+    assert_eq!(list[0].element_tag, "rect");
+    assert_eq!(list[0].offset, svg.find("<rect"));
+
+    assert_eq!(list[1].code, usvg_tree::DiagnosticCode::InvalidAttribute);
+    assert_eq!(list[1].element_id, "circle1");
+    assert_eq!(list[1].element_tag, "circle");
+    assert_eq!(list[1].offset, svg.find("<circle"));
+}
+
+#[test]
+fn diagnostics_to_json() {
+    let mut diagnostic = usvg_tree::Diagnostic::new(
+        usvg_tree::DiagnosticCode::InvalidLink,
+        "'g' cannot be used to \"fill\" a shape.".to_string(),
+    );
+    diagnostic.element_id = "rect1".to_string();
+    diagnostic.element_tag = "rect".to_string();
+    diagnostic.offset = Some(42);
+
+    assert_eq!(
+        usvg_tree::diagnostics::to_json(&[diagnostic]),
+        "[{\"code\":\"invalid-link\",\"message\":\"'g' cannot be used to \\\"fill\\\" a shape.\",\
+         \"element_id\":\"rect1\",\"element_tag\":\"rect\",\"offset\":42}]"
+    );
+}
+
+#[test]
+fn origins() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 1 1'>
+        <rect id='rect1' width='10' height='10'/>
+        <use id='use1' href='#rect1'/>
+        <switch>
+            <circle r='5'/>
+        </switch>
+        <marker id='marker1'>
+            <rect width='1' height='1'/>
+        </marker>
+        <path d='M 0 0 L 10 10' stroke='black' marker-start='url(#marker1)'/>
+    </svg>
+    ";
+
+    let opt = usvg_parser::Options {
+        keep_origins: true,
+        ..usvg_parser::Options::default()
+    };
+    let tree = usvg_tree::Tree::from_str(&svg, &opt).unwrap();
+
+    let paths: Vec<_> = tree
+        .root
+        .descendants()
+        .filter(|n| matches!(*n.borrow(), usvg_tree::NodeKind::Path(_)))
+        .map(|n| n.borrow().origin().cloned().unwrap())
+        .collect();
+    assert_eq!(paths.len(), 5);
+
+    let rect_start = svg.find("<rect id").unwrap();
+    let rect_end = rect_start + "<rect id='rect1' width='10' height='10'/>".len();
+    assert_eq!(paths[0].element.span, rect_start..rect_end);
+    assert_eq!(paths[0].element.line, 3);
+    assert_eq!(paths[0].element.column, 9);
+    assert_eq!(paths[0].element.tag_name, "rect");
+    assert!(paths[0].instances.is_empty());
+
+    // An element referenced by `use`.
+    assert_eq!(paths[1].element, paths[0].element);
+    assert_eq!(paths[1].instances.len(), 1);
+    assert_eq!(paths[1].instances[0].tag_name, "use");
+    assert_eq!(paths[1].instances[0].span.start, svg.find("<use").unwrap());
+
+    // An element selected by `switch`.
+    assert_eq!(paths[2].element.tag_name, "circle");
+    assert_eq!(paths[2].instances.len(), 1);
+    assert_eq!(paths[2].instances[0].tag_name, "switch");
+
+    // A marker content.
+    assert_eq!(paths[3].element.tag_name, "path");
+    assert!(paths[3].instances.is_empty());
+    assert_eq!(paths[4].element.tag_name, "rect");
+    assert_eq!(paths[4].element.line, 9);
+    assert_eq!(paths[4].instances.len(), 1);
+    assert_eq!(paths[4].instances[0].tag_name, "path");
+}
+
+#[test]
+fn origins_are_disabled_by_default() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 1 1'>
+        <rect width='10' height='10'/>
+    </svg>
+    ";
+
+    let tree = usvg_tree::Tree::from_str(&svg, &usvg_parser::Options::default()).unwrap();
+    let path = tree.root.first_child().unwrap();
+    assert!(path.borrow().origin().is_none());
+}
+
+#[test]
+fn elements_limit() {
+    // Each `use` doubles the number of elements.
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+        <g id='g1'><rect width='10' height='10'/><rect width='10' height='10'/></g>
+        <g id='g2'><use xlink:href='#g1'/><use xlink:href='#g1'/></g>
+        <g id='g3'><use xlink:href='#g2'/><use xlink:href='#g2'/></g>
+        <g id='g4'><use xlink:href='#g3'/><use xlink:href='#g3'/></g>
+    </svg>
+    ";
+
+    let mut opt = usvg_parser::Options::default();
+    opt.limits.max_elements = 50;
+    let result = usvg_tree::Tree::from_str(&svg, &opt);
+    assert!(matches!(
+        result,
+        Err(usvg_parser::Error::ElementsLimitReached)
+    ));
+
+    opt.limits.max_elements = 100;
+    assert!(usvg_tree::Tree::from_str(&svg, &opt).is_ok());
+}
+
+#[test]
+fn nesting_depth_limit() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+        <g><g><g><rect id='rect1' width='10' height='10'/></g></g></g>
+        <use id='use1' xlink:href='#rect1'/>
+        <use id='use2' xlink:href='#use1'/>
+        <use id='use3' xlink:href='#use2'/>
+    </svg>
+    ";
+
+    let mut opt = usvg_parser::Options::default();
+    opt.limits.max_nesting_depth = 3;
+    let result = usvg_tree::Tree::from_str(&svg, &opt);
+    assert!(matches!(
+        result,
+        Err(usvg_parser::Error::NestingDepthLimitReached)
+    ));
+
+    opt.limits.max_nesting_depth = 8;
+    assert!(usvg_tree::Tree::from_str(&svg, &opt).is_ok());
+}
+
+#[test]
+fn image_size_limit() {
+    // Only the PNG header is needed to detect the image size.
+    let mut png = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+    png.extend_from_slice(&13u32.to_be_bytes());
+    png.extend_from_slice(b"IHDR");
+    png.extend_from_slice(&20_000u32.to_be_bytes());
+    png.extend_from_slice(&20_000u32.to_be_bytes());
+    png.extend_from_slice(&[8, 6, 0, 0, 0, 0, 0, 0, 0]);
+
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+        <image xlink:href='image.png' width='10' height='10'/>
+    </svg>
+    ";
+
+    let mut opt = usvg_parser::Options::default();
+    opt.image_href_resolver.resolve_string =
+        Box::new(move |_, _| Some(usvg_tree::ImageKind::PNG(std::sync::Arc::new(png.clone()))));
+    let result = usvg_tree::Tree::from_str(&svg, &opt);
+    assert!(matches!(
+        result,
+        Err(usvg_parser::Error::ImageSizeLimitReached)
+    ));
+
+    opt.limits.max_image_pixels = 20_000 * 20_000;
+    assert!(usvg_tree::Tree::from_str(&svg, &opt).is_ok());
+}
+
+#[test]
+fn gzip_ratio_limit() {
+    use std::io::Write;
+
+    let mut svg = String::from("<svg xmlns='http://www.w3.org/2000/svg'>");
+    for _ in 0..200_000 {
+        svg.push_str("<rect/>");
+    }
+    svg.push_str("</svg>");
+
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::best());
+    encoder.write_all(svg.as_bytes()).unwrap();
+    let data = encoder.finish().unwrap();
+
+    let mut limits = usvg_parser::Limits::default();
+    let result = usvg_parser::decompress_svgz_with_limits(&data, &limits);
+    assert!(matches!(
+        result,
+        Err(usvg_parser::Error::GZipRatioLimitReached)
+    ));
+
+    limits.max_gzip_ratio = 10_000;
+    let result = usvg_parser::decompress_svgz_with_limits(&data, &limits);
+    assert_eq!(result.unwrap(), svg.as_bytes());
+
+    // Not limited.
+    let result = usvg_parser::decompress_svgz(&data);
+    assert_eq!(result.unwrap(), svg.as_bytes());
+}
+
+#[test]
+fn entity_expansion_limit() {
+    // Each entity expands into ten previous ones.
+    let svg = format!(
+        "<!DOCTYPE svg [
+            <!ENTITY a0 '{}'>
+            <!ENTITY a1 '&a0;&a0;&a0;&a0;&a0;&a0;&a0;&a0;&a0;&a0;'>
+            <!ENTITY a2 '&a1;&a1;&a1;&a1;&a1;&a1;&a1;&a1;&a1;&a1;'>
+        ]>
+        <svg xmlns='http://www.w3.org/2000/svg'><text>&a2;&a2;</text></svg>
+        ",
+        "x".repeat(100)
+    );
+
+    let mut opt = usvg_parser::Options::default();
+    opt.limits.max_entity_expansion = 15_000;
+    let result = usvg_tree::Tree::from_str(&svg, &opt);
+    assert!(matches!(
+        result,
+        Err(usvg_parser::Error::EntityExpansionLimitReached)
+    ));
+
+    opt.limits.max_entity_expansion = 30_000;
+    assert!(usvg_tree::Tree::from_str(&svg, &opt).is_ok());
+
+    opt.limits.max_entity_depth = 2;
+    let result = usvg_tree::Tree::from_str(&svg, &opt);
+    assert!(matches!(
+        result,
+        Err(usvg_parser::Error::EntityExpansionLimitReached)
+    ));
+}
+
+#[test]
+fn entity_depth_limit_with_repeated_entity() {
+    // `a2` is expanded at depth 1 first and then at depth 3 via `b2`.
+    let svg = "<!DOCTYPE svg [
+        <!ENTITY a0 'x'>
+        <!ENTITY a1 '&a0;'>
+        <!ENTITY a2 '&a1;'>
+        <!ENTITY b1 '&a2;'>
+        <!ENTITY b2 '&b1;'>
+    ]>
+    <svg xmlns='http://www.w3.org/2000/svg'><text>&a2;&b2;</text></svg>
+    ";
+
+    let mut opt = usvg_parser::Options::default();
+    opt.limits.max_entity_depth = 4;
+    let result = usvg_tree::Tree::from_str(svg, &opt);
+    assert!(matches!(
+        result,
+        Err(usvg_parser::Error::EntityExpansionLimitReached)
+    ));
+
+    opt.limits.max_entity_depth = 5;
+    assert!(usvg_tree::Tree::from_str(svg, &opt).is_ok());
+}
+
+#[test]
+fn entity_expansion_limit_with_doctype_in_comment() {
+    let svg = format!(
+        "<!-- <!DOCTYPE x> -->
+        <!DOCTYPE svg [
+            <!ENTITY a0 '{}'>
+            <!ENTITY a1 '&a0;&a0;&a0;&a0;&a0;&a0;&a0;&a0;&a0;&a0;'>
+        ]>
+        <svg xmlns='http://www.w3.org/2000/svg'><text>&a1;&a1;</text></svg>
+        ",
+        "x".repeat(100)
+    );
+
+    let mut opt = usvg_parser::Options::default();
+    opt.limits.max_entity_expansion = 1500;
+    let result = usvg_tree::Tree::from_str(&svg, &opt);
+    assert!(matches!(
+        result,
+        Err(usvg_parser::Error::EntityExpansionLimitReached)
+    ));
+}
+
+#[test]
+fn entity_expansion_limit_with_entity_in_comment() {
+    let svg = format!(
+        "<!DOCTYPE svg [
+            <!-- <!ENTITY z ' -->
+            <!ENTITY a0 '{}'>
+            <!ENTITY a1 '&a0;&a0;&a0;&a0;&a0;&a0;&a0;&a0;&a0;&a0;'>
+            <!-- ' -->
+        ]>
+        <svg xmlns='http://www.w3.org/2000/svg'><text>&a1;&a1;</text></svg>
+        ",
+        "x".repeat(100)
+    );
+
+    let mut opt = usvg_parser::Options::default();
+    opt.limits.max_entity_expansion = 1500;
+    let result = usvg_tree::Tree::from_str(&svg, &opt);
+    assert!(matches!(
+        result,
+        Err(usvg_parser::Error::EntityExpansionLimitReached)
+    ));
+}
+
+#[test]
+fn dtd_is_disabled() {
+    let svg = "<!DOCTYPE svg [
+        <!ENTITY fill 'green'>
+    ]>
+    <svg xmlns='http://www.w3.org/2000/svg'><rect width='10' height='10' fill='&fill;'/></svg>
+    ";
+
+    let mut opt = usvg_parser::Options::default();
+    assert!(usvg_tree::Tree::from_str(&svg, &opt).is_ok());
+
+    opt.allow_dtd = false;
+    let result = usvg_tree::Tree::from_str(&svg, &opt);
+    assert!(matches!(
+        result,
+        Err(usvg_parser::Error::ParsingFailed(
+            usvg_parser::roxmltree::Error::DtdDetected
+        ))
+    ));
+}
+
+#[test]
+fn dtd_is_disabled_in_nested_svg() {
+    // <!DOCTYPE svg [<!ENTITY w '10'>]><svg ... width='&w;' height='10'/>
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+        <image width='10' height='10' xlink:href='data:image/svg+xml;base64,\
+PCFET0NUWVBFIHN2ZyBbPCFFTlRJVFkgdyAnMTAnPl0+PHN2ZyB4bWxucz0naHR0cDovL3d3dy53My5vcmcvMjAwMC9z\
+dmcnIHdpZHRoPScmdzsnIGhlaWdodD0nMTAnLz4='/>
+    </svg>
+    ";
+
+    let mut opt = usvg_parser::Options::default();
+    assert_eq!(images_count(svg, &opt), 1);
+
+    opt.allow_dtd = false;
+    assert_eq!(images_count(svg, &opt), 0);
+}
+
+fn png_header() -> Vec<u8> {
+    let mut png = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+    png.extend_from_slice(&13u32.to_be_bytes());
+    png.extend_from_slice(b"IHDR");
+    png.extend_from_slice(&1u32.to_be_bytes());
+    png.extend_from_slice(&1u32.to_be_bytes());
+    png.extend_from_slice(&[8, 6, 0, 0, 0, 0, 0, 0, 0]);
+    png
+}
+
+fn images_count(svg: &str, opt: &usvg_parser::Options) -> usize {
+    let tree = usvg_tree::Tree::from_str(svg, opt).unwrap();
+    tree.root
+        .descendants()
+        .filter(|n| matches!(*n.borrow(), usvg_tree::NodeKind::Image(_)))
+        .count()
+}
+
+#[test]
+fn file_access() {
+    let root = std::env::temp_dir().join(format!("usvg-file-access-{}", std::process::id()));
+    let resources_dir = root.join("resources");
+    std::fs::create_dir_all(&resources_dir).unwrap();
+    std::fs::write(resources_dir.join("image.png"), png_header()).unwrap();
+    std::fs::write(resources_dir.join("image.dat"), png_header()).unwrap();
+    std::fs::write(root.join("secret.png"), png_header()).unwrap();
+
+    let svg = |href: &str| {
+        format!(
+            "<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+                <image xlink:href='{}' width='10' height='10'/>
+            </svg>",
+            href
+        )
+    };
+
+    let mut opt = usvg_parser::Options::default();
+    opt.resources_dir = Some(resources_dir.clone());
+    assert_eq!(images_count(&svg("../secret.png"), &opt), 1);
+
+    opt.file_access = usvg_parser::FileAccessPolicy::resources_dir();
+    assert_eq!(images_count(&svg("image.png"), &opt), 1);
+    assert_eq!(images_count(&svg("../secret.png"), &opt), 0);
+    let abs_path = root.join("secret.png");
+    assert_eq!(images_count(&svg(abs_path.to_str().unwrap()), &opt), 0);
+
+    #[cfg(unix)]
+    {
+        let link = resources_dir.join("link.png");
+        let _ = std::fs::remove_file(&link);
+        std::os::unix::fs::symlink(root.join("secret.png"), &link).unwrap();
+        assert_eq!(images_count(&svg("link.png"), &opt), 0);
+    }
+
+    opt.file_access.extensions = vec!["PNG".to_string()];
+    assert_eq!(images_count(&svg("image.png"), &opt), 1);
+    assert_eq!(images_count(&svg("image.dat"), &opt), 0);
+
+    opt.file_access.mime_types = vec!["image/jpeg".to_string()];
+    assert_eq!(images_count(&svg("image.png"), &opt), 0);
+
+    opt.file_access = usvg_parser::FileAccessPolicy::denied();
+    assert_eq!(images_count(&svg("image.png"), &opt), 0);
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn animation() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <rect id='rect1' width='10' height='10' fill='red'>
+            <animate id='anim1' attributeName='x' from='0' to='100' dur='2s' fill='freeze'/>
+            <animate attributeName='fill' values='red;blue' dur='2s'/>
+        </rect>
+        <rect id='rect2' width='10' height='10'>
+            <set attributeName='visibility' to='hidden' begin='1.5s'/>
+            <animateTransform attributeName='transform' type='translate' by='0 50'
+                              begin='anim1.end' dur='1s' fill='freeze'/>
+        </rect>
+        <circle id='circle1' r='5'>
+            <animateMotion path='M 0 0 L 100 0' dur='1s' repeatCount='indefinite'/>
+        </circle>
+    </svg>
+    ";
+
+    let path_at = |time: Option<f32>, id: &str| -> (usvg_tree::Path, usvg_tree::Transform) {
+        let opt = usvg_parser::Options {
+            animation_time: time,
+            ..usvg_parser::Options::default()
+        };
+        let tree = usvg_tree::Tree::from_str(&svg, &opt).unwrap();
+        let node = tree.node_by_id(id).unwrap();
+        let path = match *node.borrow() {
+            usvg_tree::NodeKind::Path(ref path) => path.clone(),
+            _ => unreachable!(),
+        };
+        (path, node.abs_transform())
+    };
+
+    let fill = |path: &usvg_tree::Path| match path.fill.as_ref().unwrap().paint {
+        usvg_tree::Paint::Color(c) => (c.red, c.green, c.blue),
+        _ => unreachable!(),
+    };
+
+    // Animations are ignored by default.
+    let rect1 = path_at(None, "rect1").0;
+    assert_eq!(rect1.data.bounds().x(), 0.0);
+    assert_eq!(fill(&rect1), (255, 0, 0));
+
+    let rect1 = path_at(Some(1.0), "rect1").0;
+    assert_eq!(rect1.data.bounds().x(), 50.0);
+    assert_eq!(fill(&rect1), (128, 0, 128));
+    let (rect2, rect2_ts) = path_at(Some(1.0), "rect2");
+    assert_eq!(rect2.visibility, usvg_tree::Visibility::Visible);
+    assert_eq!(rect2_ts, usvg_tree::Transform::default());
+    let (_, circle1_ts) = path_at(Some(1.25), "circle1");
+    assert_eq!(circle1_ts.tx, 25.0);
+
+    // A frozen value and a removed one.
+    let rect1 = path_at(Some(2.5), "rect1").0;
+    assert_eq!(rect1.data.bounds().x(), 100.0);
+    assert_eq!(fill(&rect1), (255, 0, 0));
+    // Begins when `anim1` ends.
+    let (rect2, rect2_ts) = path_at(Some(2.5), "rect2");
+    assert_eq!(rect2.visibility, usvg_tree::Visibility::Hidden);
+    assert_eq!(rect2_ts.ty, 25.0);
+    let (_, rect2_ts) = path_at(Some(10.0), "rect2");
+    assert_eq!(rect2_ts.ty, 50.0);
+}
+
+#[test]
+fn css_animation() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <style>
+            @keyframes spin { to { transform: rotate(0.5turn) } }
+            @keyframes fade { from { opacity: 0; fill: red } to { fill: blue } }
+            .spin { animation: spin 2s linear infinite; transform-origin: 10px 10px }
+            #rect2 { animation: fade 2s linear 1s forwards }
+        </style>
+        <rect id='rect1' class='spin' width='20' height='20'/>
+        <rect id='rect2' width='10' height='10' style='animation-duration: 4s'/>
+    </svg>
+    ";
+
+    let path_at = |time: f32, id: &str| -> (usvg_tree::Path, usvg_tree::Transform, f32) {
+        let opt = usvg_parser::Options {
+            animation_time: Some(time),
+            ..usvg_parser::Options::default()
+        };
+        let tree = usvg_tree::Tree::from_str(&svg, &opt).unwrap();
+        let node = tree.node_by_id(id).unwrap();
+        let path = match *node.borrow() {
+            usvg_tree::NodeKind::Path(ref path) => path.clone(),
+            _ => unreachable!(),
+        };
+        let opacity = match *node.parent().unwrap().borrow() {
+            usvg_tree::NodeKind::Group(ref g) => g.opacity.get(),
+            _ => unreachable!(),
+        };
+        (path, node.abs_transform(), opacity)
+    };
+
+    let fill = |path: &usvg_tree::Path| match path.fill.as_ref().unwrap().paint {
+        usvg_tree::Paint::Color(c) => (c.red, c.green, c.blue),
+        _ => unreachable!(),
+    };
+
+    // A quarter turn around the origin in the second iteration.
+    let (_, ts, _) = path_at(3.0, "rect1");
+    assert!((ts.ky - 1.0).abs() < 0.001);
+    assert!((ts.tx - 20.0).abs() < 0.001);
+    assert!(ts.ty.abs() < 0.001);
+
+    // Before the delay. The `style` attribute overrides the duration.
+    let (rect2, _, opacity) = path_at(0.5, "rect2");
+    assert_eq!(fill(&rect2), (0, 0, 0));
+    assert_eq!(opacity, 1.0);
+    // Not set opacity is animated to the not animated value.
+    let (rect2, _, opacity) = path_at(3.0, "rect2");
+    assert_eq!(fill(&rect2), (128, 0, 128));
+    assert_eq!(opacity, 0.5);
+    // Frozen at the end.
+    let (rect2, _, opacity) = path_at(10.0, "rect2");
+    assert_eq!(fill(&rect2), (0, 0, 255));
+    assert_eq!(opacity, 1.0);
+}
+
+#[test]
+fn serde_round_trip() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' width='100' height='100'>
+        <linearGradient id='lg1'>
+            <stop offset='0' stop-color='red'/>
+            <stop offset='1' stop-color='blue'/>
+        </linearGradient>
+        <pattern id='patt1' width='10' height='10' patternUnits='userSpaceOnUse'>
+            <rect width='5' height='5' fill='url(#lg1)'/>
+        </pattern>
+        <clipPath id='clip1'>
+            <rect width='50' height='50'/>
+        </clipPath>
+        <clipPath id='clip2' clip-path='url(#clip1)'>
+            <circle cx='50' cy='50' r='40'/>
+        </clipPath>
+        <filter id='filter1'>
+            <feGaussianBlur stdDeviation='2'/>
+        </filter>
+        <g clip-path='url(#clip2)' filter='url(#filter1)'>
+            <rect width='50' height='50' fill='url(#lg1)'/>
+            <rect x='50' width='50' height='50' fill='url(#lg1)' stroke='url(#patt1)'/>
+        </g>
+    </svg>
+    ";
+
+    let tree = usvg_tree::Tree::from_str(&svg, &usvg_parser::Options::default()).unwrap();
+    let json = serde_json::to_string(&tree).unwrap();
+
+    // Shared resources are stored only once.
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(value["resources"].as_array().unwrap().len(), 5);
+
+    let tree2: usvg_tree::Tree = serde_json::from_str(&json).unwrap();
+    assert_eq!(serde_json::to_string(&tree2).unwrap(), json);
+
+    let paints: Vec<_> = tree2
+        .root
+        .descendants()
+        .filter_map(|node| match *node.borrow() {
+            usvg_tree::NodeKind::Path(ref path) => path.fill.as_ref().map(|f| f.paint.clone()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(paints.len(), 2);
+    assert!(paints[0] == paints[1]);
+}
+
+#[test]
+fn tree_editing() {
+    let template = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 100 100'>
+        <linearGradient id='lg1'>
+            <stop offset='0' stop-color='white'/>
+            <stop offset='1' stop-color='black'/>
+        </linearGradient>
+        <rect id='rect1' class='slot wide' width='50' height='50' fill='url(#lg1)'/>
+        <rect id='rect2' class='slot' x='50' width='50' height='50' fill='url(#lg1)'/>
+    </svg>
+    ";
+
+    let data = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 100 100'>
+        <linearGradient id='lg1'>
+            <stop offset='0' stop-color='red'/>
+            <stop offset='1' stop-color='blue'/>
+        </linearGradient>
+        <clipPath id='clip1'>
+            <rect id='rect1' width='10' height='10'/>
+        </clipPath>
+        <g id='g1' clip-path='url(#clip1)'>
+            <rect id='rect1' width='20' height='20' fill='url(#lg1)'/>
+        </g>
+    </svg>
+    ";
+
+    let opt = usvg_parser::Options::default();
+    let mut tree = usvg_tree::Tree::from_str(&template, &opt).unwrap();
+    let data_tree = usvg_tree::Tree::from_str(&data, &opt).unwrap();
+
+    let slots: Vec<_> = tree.nodes_by_class("slot").collect();
+    assert_eq!(slots.len(), 2);
+    assert_eq!(&*slots[0].id(), "rect1");
+    assert_eq!(tree.nodes_by_class("wide").count(), 1);
+
+    // Imported IDs must be unique.
+    let root = tree.root.clone();
+    let g = data_tree.root.first_child().unwrap();
+    tree.insert_node(&root, 1, g.clone()).unwrap();
+    assert_eq!(root.children().nth(1), Some(g.clone()));
+    assert_eq!(&*g.id(), "g1");
+
+    let rect = g.first_child().unwrap();
+    assert_eq!(&*rect.id(), "rect1-2");
+    match *rect.borrow() {
+        usvg_tree::NodeKind::Path(ref path) => match path.fill.as_ref().unwrap().paint {
+            usvg_tree::Paint::LinearGradient(ref lg) => assert_eq!(lg.id, "lg1-1"),
+            _ => unreachable!(),
+        },
+        _ => unreachable!(),
+    }
+
+    match *g.borrow() {
+        usvg_tree::NodeKind::Group(ref group) => {
+            let clip = group.clip_path.as_ref().unwrap();
+            assert_eq!(clip.id, "clip1");
+            assert_eq!(&*clip.root.first_child().unwrap().id(), "rect1-1");
+        }
+        _ => unreachable!(),
+    }
+
+    // Moving doesn't change IDs.
+    tree.move_node(&g, 2).unwrap();
+    assert_eq!(root.last_child(), Some(g.clone()));
+    assert_eq!(&*g.id(), "g1");
+
+    let wrapper = tree
+        .wrap_node(&slots[0], usvg_tree::Group::default())
+        .unwrap();
+    assert_eq!(slots[0].parent(), Some(wrapper.clone()));
+    assert_eq!(root.first_child(), Some(wrapper.clone()));
+
+    let ts = usvg_tree::Transform::from_translate(10.0, 0.0);
+    tree.set_transform(&wrapper, ts).unwrap();
+    assert_eq!(slots[0].abs_transform(), ts);
+
+    let ts = usvg_tree::Transform::from_scale(0.0, 1.0);
+    assert_eq!(
+        tree.set_transform(&wrapper, ts),
+        Err(usvg_tree::EditError::InvalidTransform)
+    );
+
+    // Only the template gradient is replaced.
+    let lg = match *slots[1].borrow() {
+        usvg_tree::NodeKind::Path(ref path) => path.fill.as_ref().unwrap().paint.clone(),
+        _ => unreachable!(),
+    };
+    let red = usvg_tree::Paint::Color(usvg_tree::Color::new_rgb(255, 0, 0));
+    assert_eq!(tree.replace_paint(&lg, red.clone()), 2);
+    assert_eq!(tree.replace_paint(&lg, red), 0);
+
+    assert_eq!(
+        tree.set_fill(&wrapper, None),
+        Err(usvg_tree::EditError::NotPaintable)
+    );
+    tree.set_fill(&slots[1], None).unwrap();
+
+    assert_eq!(tree.remove_node(&root), Err(usvg_tree::EditError::RootNode));
+    assert_eq!(
+        tree.insert_node(&slots[0], 0, g.clone()),
+        Err(usvg_tree::EditError::NotAGroup)
+    );
+    assert_eq!(
+        tree.insert_node(&wrapper, 0, root.clone()),
+        Err(usvg_tree::EditError::RootNode)
+    );
+    tree.remove_node(&g).unwrap();
+    assert!(!tree.contains_node(&g));
+    assert_eq!(root.children().count(), 2);
+}
+
+#[test]
+fn visitor() {
+    struct Collector(Vec<(String, usvg_tree::NodeContext, usvg_tree::Transform)>);
+
+    impl usvg_tree::Visitor for Collector {
+        fn visit_node(&mut self, node: &usvg_tree::Node, ctx: &usvg_tree::VisitContext) -> bool {
+            let id = node.id().to_string();
+            if !id.is_empty() || ctx.context() == usvg_tree::NodeContext::Marker {
+                self.0.push((id, ctx.context(), ctx.transform));
+            }
+
+            true
+        }
+    }
+
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'
+         viewBox='0 0 200 200'>
+        <clipPath id='clip1' transform='translate(5 0)'>
+            <rect id='clip-rect' width='10' height='10'/>
+        </clipPath>
+        <mask id='mask1'>
+            <rect id='mask-rect' width='10' height='10' fill='white'/>
+        </mask>
+        <pattern id='patt1' x='2' y='3' width='10' height='10' patternUnits='userSpaceOnUse'>
+            <rect id='patt-rect' width='5' height='5'/>
+        </pattern>
+        <marker id='marker1'>
+            <circle r='1'/>
+        </marker>
+        <filter id='filter1'>
+            <feImage xlink:href='#image-rect'/>
+        </filter>
+        <g id='g1' transform='translate(10 20)' clip-path='url(#clip1)' mask='url(#mask1)'>
+            <rect id='rect1' width='20' height='20' fill='url(#patt1)'/>
+        </g>
+        <path id='path1' d='M 100 100 L 150 100' stroke='black' marker-start='url(#marker1)'/>
+        <g id='g2' filter='url(#filter1)'>
+            <rect id='image-rect' width='20' height='20'/>
+        </g>
+    </svg>
+    ";
+
+    let tree = usvg_tree::Tree::from_str(&svg, &usvg_parser::Options::default()).unwrap();
+    let mut collector = Collector(Vec::new());
+    tree.visit(&mut collector);
+
+    let find = |id: &str, context| {
+        collector
+            .0
+            .iter()
+            .find(|(id2, context2, _)| id2 == id && *context2 == context)
+            .map(|(_, _, ts)| *ts)
+    };
+
+    use usvg_tree::NodeContext;
+    use usvg_tree::Transform;
+    assert_eq!(
+        find("rect1", NodeContext::Tree),
+        Some(Transform::from_translate(10.0, 20.0))
+    );
+    assert_eq!(
+        find("clip-rect", NodeContext::ClipPath),
+        Some(Transform::from_translate(15.0, 20.0))
+    );
+    assert_eq!(
+        find("mask-rect", NodeContext::Mask),
+        Some(Transform::from_translate(10.0, 20.0))
+    );
+    assert_eq!(
+        find("patt-rect", NodeContext::Pattern),
+        Some(Transform::from_translate(12.0, 23.0))
+    );
+    assert_eq!(
+        find("image-rect", NodeContext::FilterImage),
+        Some(Transform::default())
+    );
+    assert_eq!(
+        find("image-rect", NodeContext::Tree),
+        Some(Transform::default())
+    );
+    assert!(find("", NodeContext::Marker).is_some());
+
+    // Make all resources content red.
+    struct Painter;
+
+    impl usvg_tree::VisitorMut for Painter {
+        fn visit_node_mut(
+            &mut self,
+            kind: &mut usvg_tree::NodeKind,
+            ctx: &usvg_tree::VisitContext,
+        ) -> bool {
+            if let usvg_tree::NodeKind::Path(ref mut path) = kind {
+                if ctx.context() != NodeContext::Tree {
+                    let red = usvg_tree::Color::new_rgb(255, 0, 0);
+                    path.fill = Some(usvg_tree::Fill::from_paint(usvg_tree::Paint::Color(red)));
+                }
+            }
+
+            true
+        }
+    }
+
+    let mut tree = tree;
+    tree.visit_mut(&mut Painter);
+
+    let rect = tree.node_by_id("rect1").unwrap();
+    let patt = match *rect.borrow() {
+        usvg_tree::NodeKind::Path(ref path) => match path.fill.as_ref().unwrap().paint {
+            usvg_tree::Paint::Pattern(ref patt) => patt.clone(),
+            _ => unreachable!(),
+        },
+        _ => unreachable!(),
+    };
+    let patt_rect = patt.root.first_child().unwrap();
+    match *patt_rect.borrow() {
+        usvg_tree::NodeKind::Path(ref path) => match path.fill.as_ref().unwrap().paint {
+            usvg_tree::Paint::Color(c) => assert_eq!(c, usvg_tree::Color::new_rgb(255, 0, 0)),
+            _ => unreachable!(),
+        },
+        _ => unreachable!(),
+    }
+
+    struct MainTree(usize);
+
+    impl usvg_tree::Visitor for MainTree {
+        fn visit_node(&mut self, _: &usvg_tree::Node, ctx: &usvg_tree::VisitContext) -> bool {
+            assert!(matches!(
+                ctx.context(),
+                NodeContext::Tree | NodeContext::Marker
+            ));
+            self.0 += 1;
+            true
+        }
+
+        fn visit_resources(&self) -> bool {
+            false
+        }
+    }
+
+    let mut main_tree = MainTree(0);
+    tree.visit(&mut main_tree);
+    assert_eq!(main_tree.0, tree.root.descendants().count());
+}
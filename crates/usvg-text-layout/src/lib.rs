@@ -62,8 +62,10 @@ impl TextToPath for Text {
         // Create a group will all paths that was created during text-to-path conversion.
         let group = Node::new(NodeKind::Group(Group {
             id: self.id.clone(),
+            classes: self.classes.clone(),
             transform: self.transform,
             title: self.title.clone(),
+            origin: self.origin.clone(),
             ..Group::default()
         }));
 
@@ -71,6 +73,7 @@ impl TextToPath for Text {
         for mut path in new_paths {
             fix_obj_bounding_box(&mut path, bbox);
             path.rendering_mode = rendering_mode;
+            path.origin = self.origin.clone();
             group.append_kind(NodeKind::Path(path));
         }
 
@@ -645,7 +648,11 @@ fn resolve_font(font: &Font, fontdb: &fontdb::Database) -> Option<ResolvedFont>
 
     let id = fontdb.query(&query);
     if id.is_none() {
-        log::warn!("No match for '{}' font-family.", font.families.join(", "));
+        report!(
+            FontNotFound,
+            "No match for '{}' font-family.",
+            font.families.join(", ")
+        );
     }
 
     fontdb.load_font(id?)
@@ -708,16 +715,19 @@ fn convert_span(
 
     let path = Path {
         id: String::new(),
+        classes: Vec::new(),
         transform: Transform::default(),
         visibility: span.visibility,
+        pointer_events: span.pointer_events,
         fill,
         stroke: span.stroke.clone(),
         paint_order: span.paint_order,
         rendering_mode: ShapeRendering::default(),
         vector_effect: VectorEffect::None,
         text_bbox: Some(bboxes.bounds().to_non_zero_rect()?),
-        data: Rc::new(path),
+        data: Shared::new(path),
         title: span.title.clone(),
+        origin: None,
     };
 
     Some(path)
@@ -773,7 +783,7 @@ fn convert_decoration(
         let rect = match NonZeroRect::from_xywh(0.0, -thickness / 2.0, dec_span.width, thickness) {
             Some(v) => v,
             None => {
-                log::warn!("a decoration span has a malformed bbox");
+                report!(TextLayoutFailed, "a decoration span has a malformed bbox");
                 continue;
             }
         };
@@ -792,8 +802,9 @@ fn convert_decoration(
     let mut path_data = builder.finish()?;
     path_data = path_data.transform(transform)?;
 
-    let mut path = Path::new(Rc::new(path_data));
+    let mut path = Path::new(Shared::new(path_data));
     path.visibility = span.visibility;
+    path.pointer_events = span.pointer_events;
     path.fill = decoration.fill.take();
     path.stroke = decoration.stroke.take();
     Some(path)
@@ -836,7 +847,7 @@ fn paint_server_to_user_space_on_use(paint: Paint, bbox: Rect) -> Option<Paint>
         Paint::Color(_) => paint,
         Paint::LinearGradient(ref lg) => {
             let transform = lg.transform.post_concat(ts);
-            Paint::LinearGradient(Rc::new(LinearGradient {
+            Paint::LinearGradient(Shared::new(LinearGradient {
                 id: String::new(),
                 x1: lg.x1,
                 y1: lg.y1,
@@ -852,7 +863,7 @@ fn paint_server_to_user_space_on_use(paint: Paint, bbox: Rect) -> Option<Paint>
         }
         Paint::RadialGradient(ref rg) => {
             let transform = rg.transform.post_concat(ts);
-            Paint::RadialGradient(Rc::new(RadialGradient {
+            Paint::RadialGradient(Shared::new(RadialGradient {
                 id: String::new(),
                 cx: rg.cx,
                 cy: rg.cy,
@@ -869,7 +880,7 @@ fn paint_server_to_user_space_on_use(paint: Paint, bbox: Rect) -> Option<Paint>
         }
         Paint::Pattern(ref patt) => {
             let transform = patt.transform.post_concat(ts);
-            Paint::Pattern(Rc::new(Pattern {
+            Paint::Pattern(Shared::new(Pattern {
                 id: String::new(),
                 units: Units::UserSpaceOnUse,
                 content_units: patt.content_units,
@@ -1062,7 +1073,7 @@ fn outline_chunk(
         // We assume, that shaping with an any font will produce the same amount of glyphs.
         // Otherwise an error.
         if glyphs.len() != tmp_glyphs.len() {
-            log::warn!("Text layouting failed.");
+            report!(TextLayoutFailed, "Text layouting failed.");
             return Vec::new();
         }
 
@@ -1164,7 +1175,8 @@ fn shape_text(
         if glyph.is_missing() {
             let c = glyph.byte_idx.char_from(text);
             // TODO: print a full grapheme
-            log::warn!(
+            report!(
+                FontNotFound,
                 "No fonts with a {}/U+{:X} character were found.",
                 c,
                 c as u32
@@ -1357,7 +1369,12 @@ fn find_font_for_char(
             .find(|f| f.1 == fontdb::Language::English_UnitedStates)
             .unwrap_or(&base_face.families[0]);
 
-        log::warn!("Fallback from {} to {}.", base_family.0, new_family.0);
+        report!(
+            FontNotFound,
+            "Fallback from {} to {}.",
+            base_family.0,
+            new_family.0
+        );
         return fontdb.load_font(face.id);
     }
 
@@ -0,0 +1,278 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+/*!
+Structured diagnostics.
+
+All recoverable issues found during parsing and rendering are sent to the `log` crate.
+In addition to that, they can be collected into a [`Diagnostics`] object.
+
+Diagnostics are reported to the collector that is currently active on the calling thread.
+`usvg::Options::diagnostics` activates a collector during parsing automatically,
+while text conversion and rendering should be wrapped in [`Diagnostics::scope`] manually.
+*/
+
+use std::cell::RefCell;
+use std::sync::{Arc, Mutex};
+
+#[doc(hidden)]
+pub use log;
+
+/// Logs a warning and reports it to the active diagnostics collector.
+///
+/// Use `report!(node, Code, ...)` to attach element's info.
+/// The `node` must have a `diagnostic(code, message)` method.
+///
+/// For internal use by `usvg` and `resvg` crates.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! report {
+    ($code:ident, $fmt:literal $($arg:tt)*) => {{
+        let message = format!($fmt $($arg)*);
+        $crate::diagnostics::log::warn!("{}", message);
+        $crate::diagnostics::report($crate::Diagnostic::new(
+            $crate::DiagnosticCode::$code,
+            message,
+        ));
+    }};
+    ($node:expr, $code:ident, $fmt:literal $($arg:tt)*) => {{
+        let message = format!($fmt $($arg)*);
+        $crate::diagnostics::log::warn!("{}", message);
+        $crate::diagnostics::report($node.diagnostic($crate::DiagnosticCode::$code, message));
+    }};
+}
+
+/// A diagnostic code.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum DiagnosticCode {
+    /// Input data contains invalid characters.
+    InvalidInput,
+    /// An attribute has an invalid value.
+    InvalidAttribute,
+    /// A required attribute is missing.
+    MissingAttribute,
+    /// An element is not allowed in the current context.
+    InvalidElement,
+    /// A link references an element of an invalid type.
+    InvalidLink,
+    /// An element has an invalid size or region.
+    InvalidSize,
+    /// An element references itself, directly or indirectly.
+    RecursiveReference,
+    /// An image cannot be loaded or decoded.
+    ImageLoadingFailed,
+    /// A feature is not supported.
+    Unsupported,
+    /// A font or a glyph cannot be resolved.
+    FontNotFound,
+    /// Text layout failed.
+    TextLayoutFailed,
+    /// A filter cannot be applied.
+    FilterFailed,
+    /// Rendering failed. Usually because of a failed allocation.
+    RenderingFailed,
+}
+
+impl DiagnosticCode {
+    /// Returns a code as a string.
+    ///
+    /// Those are stable and can be used for filtering.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiagnosticCode::InvalidInput => "invalid-input",
+            DiagnosticCode::InvalidAttribute => "invalid-attribute",
+            DiagnosticCode::MissingAttribute => "missing-attribute",
+            DiagnosticCode::InvalidElement => "invalid-element",
+            DiagnosticCode::InvalidLink => "invalid-link",
+            DiagnosticCode::InvalidSize => "invalid-size",
+            DiagnosticCode::RecursiveReference => "recursive-reference",
+            DiagnosticCode::ImageLoadingFailed => "image-loading-failed",
+            DiagnosticCode::Unsupported => "unsupported",
+            DiagnosticCode::FontNotFound => "font-not-found",
+            DiagnosticCode::TextLayoutFailed => "text-layout-failed",
+            DiagnosticCode::FilterFailed => "filter-failed",
+            DiagnosticCode::RenderingFailed => "rendering-failed",
+        }
+    }
+}
+
+impl std::fmt::Display for DiagnosticCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A diagnostic message.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Diagnostic {
+    /// Diagnostic code.
+    pub code: DiagnosticCode,
+
+    /// A human-readable message.
+    pub message: String,
+
+    /// ID of the element that caused the diagnostic.
+    ///
+    /// Can be empty.
+    pub element_id: String,
+
+    /// Tag name of the element that caused the diagnostic.
+    ///
+    /// Can be empty.
+    pub element_tag: String,
+
+    /// A byte offset of the element in the source SVG.
+    ///
+    /// `None` when unknown.
+    pub offset: Option<usize>,
+}
+
+impl Diagnostic {
+    /// Creates a new diagnostic without an element info.
+    pub fn new(code: DiagnosticCode, message: String) -> Self {
+        Diagnostic {
+            code,
+            message,
+            element_id: String::new(),
+            element_tag: String::new(),
+            offset: None,
+        }
+    }
+
+    /// Serializes the diagnostic into a JSON object.
+    pub fn to_json(&self) -> String {
+        let mut s = String::new();
+        s.push_str("{\"code\":");
+        push_json_string(self.code.as_str(), &mut s);
+        s.push_str(",\"message\":");
+        push_json_string(&self.message, &mut s);
+        s.push_str(",\"element_id\":");
+        push_json_string(&self.element_id, &mut s);
+        s.push_str(",\"element_tag\":");
+        push_json_string(&self.element_tag, &mut s);
+        s.push_str(",\"offset\":");
+        match self.offset {
+            Some(offset) => s.push_str(&offset.to_string()),
+            None => s.push_str("null"),
+        }
+        s.push('}');
+        s
+    }
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}: {}", self.code, self.message)
+    }
+}
+
+fn push_json_string(text: &str, s: &mut String) {
+    s.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => s.push_str("\\\""),
+            '\\' => s.push_str("\\\\"),
+            '\n' => s.push_str("\\n"),
+            '\r' => s.push_str("\\r"),
+            '\t' => s.push_str("\\t"),
+            c if (c as u32) < 0x20 => s.push_str(&format!("\\u{:04x}", c as u32)),
+            c => s.push(c),
+        }
+    }
+    s.push('"');
+}
+
+/// Serializes a list of diagnostics into a JSON array.
+pub fn to_json(list: &[Diagnostic]) -> String {
+    let mut s = String::from("[");
+    for (i, d) in list.iter().enumerate() {
+        if i != 0 {
+            s.push(',');
+        }
+        s.push_str(&d.to_json());
+    }
+    s.push(']');
+    s
+}
+
+/// A diagnostics collector.
+///
+/// Cheap to clone. All clones share the same list.
+#[derive(Clone, Default, Debug)]
+pub struct Diagnostics {
+    list: Arc<Mutex<Vec<Diagnostic>>>,
+}
+
+thread_local! {
+    static ACTIVE: RefCell<Vec<Diagnostics>> = const { RefCell::new(Vec::new()) };
+}
+
+impl Diagnostics {
+    /// Creates a new, empty collector.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a diagnostic.
+    pub fn push(&self, diagnostic: Diagnostic) {
+        self.lock().push(diagnostic);
+    }
+
+    /// Returns the number of collected diagnostics.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Checks that there are no collected diagnostics.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Returns a copy of the collected diagnostics.
+    pub fn to_vec(&self) -> Vec<Diagnostic> {
+        self.lock().clone()
+    }
+
+    /// Takes all collected diagnostics, leaving the collector empty.
+    pub fn take(&self) -> Vec<Diagnostic> {
+        std::mem::take(&mut *self.lock())
+    }
+
+    /// Runs `f` with this collector being active on the current thread.
+    ///
+    /// Scopes can be nested. Diagnostics are reported to the innermost one.
+    pub fn scope<T, F: FnOnce() -> T>(&self, f: F) -> T {
+        struct Guard;
+        impl Drop for Guard {
+            fn drop(&mut self) {
+                ACTIVE.with(|active| active.borrow_mut().pop());
+            }
+        }
+
+        ACTIVE.with(|active| active.borrow_mut().push(self.clone()));
+        let _guard = Guard;
+        f()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Diagnostic>> {
+        // A poisoned list is still a valid list.
+        self.list.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Returns the collector that is currently active on the calling thread, if any.
+///
+/// Can be used to activate the same collector on other threads.
+pub fn current() -> Option<Diagnostics> {
+    ACTIVE.with(|active| active.borrow().last().cloned())
+}
+
+/// Reports a diagnostic to the active collector, if any.
+pub fn report(diagnostic: Diagnostic) {
+    ACTIVE.with(|active| {
+        if let Some(diagnostics) = active.borrow().last() {
+            diagnostics.push(diagnostic);
+        }
+    });
+}
@@ -0,0 +1,618 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Tree editing.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::*;
+
+/// A tree editing error.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EditError {
+    /// A node is not a part of the tree.
+    ///
+    /// Nodes inside clip paths, masks, patterns and filters
+    /// are not a part of the tree either.
+    NodeNotInTree,
+
+    /// The root node cannot be moved, removed or wrapped.
+    RootNode,
+
+    /// Only groups can have children.
+    NotAGroup,
+
+    /// A node cannot be inserted into itself or its descendants.
+    RecursiveInsertion,
+
+    /// A child index is out of bounds.
+    IndexOutOfBounds,
+
+    /// Only paths and text can have a fill and a stroke.
+    NotPaintable,
+
+    /// A transform is not finite or not invertible.
+    InvalidTransform,
+}
+
+impl std::fmt::Display for EditError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            EditError::NodeNotInTree => {
+                write!(f, "the node is not a part of the tree")
+            }
+            EditError::RootNode => {
+                write!(f, "the root node cannot be moved, removed or wrapped")
+            }
+            EditError::NotAGroup => {
+                write!(f, "only groups can have children")
+            }
+            EditError::RecursiveInsertion => {
+                write!(f, "a node cannot be inserted into itself")
+            }
+            EditError::IndexOutOfBounds => {
+                write!(f, "a child index is out of bounds")
+            }
+            EditError::NotPaintable => {
+                write!(f, "only paths and text can have a fill and a stroke")
+            }
+            EditError::InvalidTransform => {
+                write!(f, "the transform is not finite or not invertible")
+            }
+        }
+    }
+}
+
+impl std::error::Error for EditError {}
+
+impl Tree {
+    /// Returns renderable nodes with the specified class.
+    pub fn nodes_by_class<'a>(&self, class: &'a str) -> impl Iterator<Item = Node> + 'a {
+        self.root
+            .descendants()
+            .filter(move |node| node.borrow().classes().iter().any(|c| c == class))
+    }
+
+    /// Checks that the node is a part of the tree.
+    ///
+    /// Nodes inside clip paths, masks, patterns and filters are not.
+    pub fn contains_node(&self, node: &Node) -> bool {
+        node.ancestors().last().as_ref() == Some(&self.root)
+    }
+
+    /// Appends a node to a group.
+    ///
+    /// See [`Tree::insert_node`] for details.
+    pub fn append_node(&mut self, parent: &Node, node: Node) -> Result<(), EditError> {
+        let index = parent.children().filter(|child| *child != node).count();
+        self.insert_node(parent, index, node)
+    }
+
+    /// Inserts a node into a group at the specified index.
+    ///
+    /// When the node is already a part of the tree, it will be moved.
+    ///
+    /// Otherwise, it will be detached from its current parent and its subtree
+    /// will be imported. Node and resource IDs that clash with the ones
+    /// already present in the tree will get a numeric suffix, like `rect1-1`.
+    /// Resources already used by the tree will stay shared.
+    pub fn insert_node(
+        &mut self,
+        parent: &Node,
+        index: usize,
+        node: Node,
+    ) -> Result<(), EditError> {
+        if !self.contains_node(parent) {
+            return Err(EditError::NodeNotInTree);
+        }
+
+        if node == self.root {
+            return Err(EditError::RootNode);
+        }
+
+        if !matches!(*parent.borrow(), NodeKind::Group(_)) {
+            return Err(EditError::NotAGroup);
+        }
+
+        if parent.ancestors().any(|n| n == node) {
+            return Err(EditError::RecursiveInsertion);
+        }
+
+        if index > parent.children().filter(|child| *child != node).count() {
+            return Err(EditError::IndexOutOfBounds);
+        }
+
+        let import = !self.contains_node(&node);
+        node.detach();
+        if import {
+            Importer::new(self).import(&node);
+        }
+
+        match parent.children().nth(index) {
+            Some(sibling) => sibling.insert_before(node),
+            None => parent.append(node),
+        }
+
+        Ok(())
+    }
+
+    /// Moves a node to the specified index inside its parent.
+    pub fn move_node(&mut self, node: &Node, index: usize) -> Result<(), EditError> {
+        self.check_non_root_node(node)?;
+        let parent = node.parent().ok_or(EditError::RootNode)?;
+        self.insert_node(&parent, index, node.clone())
+    }
+
+    /// Removes a node from the tree.
+    ///
+    /// Resources used only by the removed subtree are freed automatically.
+    pub fn remove_node(&mut self, node: &Node) -> Result<(), EditError> {
+        self.check_non_root_node(node)?;
+        node.detach();
+        Ok(())
+    }
+
+    /// Wraps a node in a new group.
+    ///
+    /// The group takes node's place and is imported like in [`Tree::insert_node`].
+    /// Returns the group node.
+    pub fn wrap_node(&mut self, node: &Node, group: Group) -> Result<Node, EditError> {
+        self.check_non_root_node(node)?;
+
+        let group_node = Node::new(NodeKind::Group(group));
+        Importer::new(self).import(&group_node);
+
+        node.insert_before(group_node.clone());
+        node.detach();
+        group_node.append(node.clone());
+        Ok(group_node)
+    }
+
+    /// Sets node's fill.
+    ///
+    /// Sets the fill of all spans for text nodes.
+    /// Paint servers are imported like in [`Tree::insert_node`].
+    pub fn set_fill(&mut self, node: &Node, fill: Option<Fill>) -> Result<(), EditError> {
+        self.set_style(node, fill, |path| &mut path.fill, |span| &mut span.fill)
+    }
+
+    /// Sets node's stroke.
+    ///
+    /// Sets the stroke of all spans for text nodes.
+    /// Paint servers are imported like in [`Tree::insert_node`].
+    pub fn set_stroke(&mut self, node: &Node, stroke: Option<Stroke>) -> Result<(), EditError> {
+        self.set_style(
+            node,
+            stroke,
+            |path| &mut path.stroke,
+            |span| &mut span.stroke,
+        )
+    }
+
+    /// Replaces all occurrences of a paint with a new one.
+    ///
+    /// Colors are compared by value and paint servers by reference.
+    /// Affects fills and strokes of all paths, text spans and text decorations,
+    /// including the ones inside clip paths, masks, patterns and filters.
+    ///
+    /// Returns the number of replaced paints.
+    pub fn replace_paint(&mut self, old: &Paint, new: Paint) -> usize {
+        let new = Importer::new(self).import_paint(&new);
+
+        // Collect nodes first, so the new paint content will not be affected.
+        let mut nodes = Vec::new();
+        collect_all_nodes(&self.root, &mut HashSet::new(), &mut nodes);
+
+        let mut count = 0;
+        for node in nodes {
+            for_each_paint(&mut node.borrow_mut(), &mut |paint| {
+                if paint == old {
+                    *paint = new.clone();
+                    count += 1;
+                }
+            });
+        }
+
+        count
+    }
+
+    /// Sets node's transform.
+    pub fn set_transform(&mut self, node: &Node, transform: Transform) -> Result<(), EditError> {
+        if !self.contains_node(node) {
+            return Err(EditError::NodeNotInTree);
+        }
+
+        if !transform.is_valid() {
+            return Err(EditError::InvalidTransform);
+        }
+
+        match *node.borrow_mut() {
+            NodeKind::Group(ref mut e) => e.transform = transform,
+            NodeKind::Path(ref mut e) => e.transform = transform,
+            NodeKind::Image(ref mut e) => e.transform = transform,
+            NodeKind::Text(ref mut e) => e.transform = transform,
+        }
+
+        Ok(())
+    }
+
+    fn check_non_root_node(&self, node: &Node) -> Result<(), EditError> {
+        if !self.contains_node(node) {
+            return Err(EditError::NodeNotInTree);
+        }
+
+        if *node == self.root {
+            return Err(EditError::RootNode);
+        }
+
+        Ok(())
+    }
+
+    fn set_style<T: HasPaint + Clone>(
+        &mut self,
+        node: &Node,
+        mut style: Option<T>,
+        path_style: fn(&mut Path) -> &mut Option<T>,
+        span_style: fn(&mut TextSpan) -> &mut Option<T>,
+    ) -> Result<(), EditError> {
+        if !self.contains_node(node) {
+            return Err(EditError::NodeNotInTree);
+        }
+
+        if !matches!(*node.borrow(), NodeKind::Path(_) | NodeKind::Text(_)) {
+            return Err(EditError::NotPaintable);
+        }
+
+        if let Some(ref mut style) = style {
+            let paint = Importer::new(self).import_paint(style.paint_mut());
+            *style.paint_mut() = paint;
+        }
+
+        match *node.borrow_mut() {
+            NodeKind::Path(ref mut path) => *path_style(path) = style,
+            NodeKind::Text(ref mut text) => {
+                for chunk in &mut text.chunks {
+                    for span in &mut chunk.spans {
+                        *span_style(span) = style.clone();
+                    }
+                }
+            }
+            NodeKind::Group(_) | NodeKind::Image(_) => {}
+        }
+
+        Ok(())
+    }
+}
+
+trait HasPaint {
+    fn paint_mut(&mut self) -> &mut Paint;
+}
+
+impl HasPaint for Fill {
+    fn paint_mut(&mut self) -> &mut Paint {
+        &mut self.paint
+    }
+}
+
+impl HasPaint for Stroke {
+    fn paint_mut(&mut self) -> &mut Paint {
+        &mut self.paint
+    }
+}
+
+fn for_each_paint(kind: &mut NodeKind, f: &mut dyn FnMut(&mut Paint)) {
+    fn push(fill: &mut Option<Fill>, stroke: &mut Option<Stroke>, f: &mut dyn FnMut(&mut Paint)) {
+        if let Some(ref mut fill) = fill {
+            f(&mut fill.paint);
+        }
+
+        if let Some(ref mut stroke) = stroke {
+            f(&mut stroke.paint);
+        }
+    }
+
+    match kind {
+        NodeKind::Path(ref mut path) => push(&mut path.fill, &mut path.stroke, f),
+        NodeKind::Text(ref mut text) => {
+            for chunk in &mut text.chunks {
+                for span in &mut chunk.spans {
+                    push(&mut span.fill, &mut span.stroke, f);
+
+                    let decoration = &mut span.decoration;
+                    let lines = [
+                        &mut decoration.underline,
+                        &mut decoration.overline,
+                        &mut decoration.line_through,
+                    ];
+                    for line in IntoIterator::into_iter(lines).flatten() {
+                        push(&mut line.fill, &mut line.stroke, f);
+                    }
+                }
+            }
+        }
+        NodeKind::Group(_) | NodeKind::Image(_) => {}
+    }
+}
+
+// Collects all nodes, including resources content. Each subroot is visited only once.
+fn collect_all_nodes(root: &Node, visited: &mut HashSet<usize>, nodes: &mut Vec<Node>) {
+    for node in root.descendants() {
+        nodes.push(node.clone());
+        node.subroots(|subroot| {
+            if visited.insert(node_addr(&subroot)) {
+                collect_all_nodes(&subroot, visited, nodes);
+            }
+        });
+    }
+}
+
+// Node data is never moved, so its address is unique while the node is alive.
+fn node_addr(node: &Node) -> usize {
+    &*node.borrow() as *const NodeKind as usize
+}
+
+fn addr<T>(resource: &Shared<T>) -> usize {
+    Shared::as_ptr(resource) as *const () as usize
+}
+
+/// Imports nodes and resources into a tree, keeping IDs unique.
+#[derive(Default)]
+struct Importer {
+    // All node and resource IDs used by the tree.
+    ids: HashSet<String>,
+    // Resources already used by the tree.
+    known: HashSet<usize>,
+    // Imported resources by their original address.
+    paints: HashMap<usize, Paint>,
+    clip_paths: HashMap<usize, Shared<ClipPath>>,
+    masks: HashMap<usize, Shared<Mask>>,
+    filters: HashMap<usize, Shared<filter::Filter>>,
+}
+
+impl Importer {
+    fn new(tree: &Tree) -> Self {
+        let mut importer = Importer::default();
+
+        tree.paint_servers(|paint| match paint {
+            Paint::Color(_) => {}
+            Paint::LinearGradient(ref lg) => importer.register(&lg.id, addr(lg)),
+            Paint::RadialGradient(ref rg) => importer.register(&rg.id, addr(rg)),
+            Paint::Pattern(ref patt) => importer.register(&patt.id, addr(patt)),
+        });
+
+        tree.clip_paths(|clip| {
+            let mut clip = Some(clip);
+            while let Some(c) = clip {
+                importer.register(&c.id, addr(&c));
+                clip = c.clip_path.clone();
+            }
+        });
+
+        tree.masks(|mask| {
+            let mut mask = Some(mask);
+            while let Some(m) = mask {
+                importer.register(&m.id, addr(&m));
+                mask = m.mask.clone();
+            }
+        });
+
+        tree.filters(|filter| importer.register(&filter.id, addr(&filter)));
+
+        let mut nodes = Vec::new();
+        collect_all_nodes(&tree.root, &mut HashSet::new(), &mut nodes);
+        for node in nodes {
+            let id = node.id();
+            if !id.is_empty() {
+                importer.ids.insert(id.to_string());
+            }
+        }
+
+        importer
+    }
+
+    fn register(&mut self, id: &str, addr: usize) {
+        if !id.is_empty() {
+            self.ids.insert(id.to_string());
+        }
+
+        self.known.insert(addr);
+    }
+
+    // Returns a new ID when the current one is already in use.
+    fn unique_id(&mut self, id: &str) -> Option<String> {
+        if id.is_empty() || self.ids.insert(id.to_string()) {
+            return None;
+        }
+
+        let mut n = 1;
+        loop {
+            let new_id = format!("{}-{}", id, n);
+            if self.ids.insert(new_id.clone()) {
+                return Some(new_id);
+            }
+
+            n += 1;
+        }
+    }
+
+    fn import(&mut self, root: &Node) {
+        for node in root.descendants() {
+            // Edit a copy, so we would not hold a lock while importing resources content.
+            let mut kind = node.borrow().clone();
+            self.import_kind(&mut kind);
+            *node.borrow_mut() = kind;
+        }
+    }
+
+    fn import_kind(&mut self, kind: &mut NodeKind) {
+        let id = match kind {
+            NodeKind::Group(ref mut e) => &mut e.id,
+            NodeKind::Path(ref mut e) => &mut e.id,
+            NodeKind::Image(ref mut e) => &mut e.id,
+            NodeKind::Text(ref mut e) => &mut e.id,
+        };
+
+        if let Some(new_id) = self.unique_id(id) {
+            *id = new_id;
+        }
+
+        if let NodeKind::Group(ref mut g) = kind {
+            if let Some(ref mut clip) = g.clip_path {
+                *clip = self.import_clip_path(clip);
+            }
+
+            if let Some(ref mut mask) = g.mask {
+                *mask = self.import_mask(mask);
+            }
+
+            for filter in &mut g.filters {
+                *filter = self.import_filter(filter);
+            }
+        }
+
+        for_each_paint(kind, &mut |paint| *paint = self.import_paint(paint));
+    }
+
+    fn import_paint(&mut self, paint: &Paint) -> Paint {
+        let key = match paint {
+            Paint::Color(_) => return paint.clone(),
+            Paint::LinearGradient(ref lg) => addr(lg),
+            Paint::RadialGradient(ref rg) => addr(rg),
+            Paint::Pattern(ref patt) => addr(patt),
+        };
+
+        if self.known.contains(&key) {
+            return paint.clone();
+        }
+
+        if let Some(imported) = self.paints.get(&key) {
+            return imported.clone();
+        }
+
+        let imported = match paint {
+            Paint::Color(_) => unreachable!(),
+            Paint::LinearGradient(ref lg) => match self.unique_id(&lg.id) {
+                Some(id) => Paint::LinearGradient(Shared::new(LinearGradient {
+                    id,
+                    ..LinearGradient::clone(lg)
+                })),
+                None => paint.clone(),
+            },
+            Paint::RadialGradient(ref rg) => match self.unique_id(&rg.id) {
+                Some(id) => Paint::RadialGradient(Shared::new(RadialGradient {
+                    id,
+                    ..RadialGradient::clone(rg)
+                })),
+                None => paint.clone(),
+            },
+            Paint::Pattern(ref patt) => {
+                self.import(&patt.root);
+                match self.unique_id(&patt.id) {
+                    Some(id) => Paint::Pattern(Shared::new(Pattern {
+                        id,
+                        ..Pattern::clone(patt)
+                    })),
+                    None => paint.clone(),
+                }
+            }
+        };
+
+        self.paints.insert(key, imported.clone());
+        imported
+    }
+
+    fn import_clip_path(&mut self, clip: &Shared<ClipPath>) -> Shared<ClipPath> {
+        let key = addr(clip);
+        if self.known.contains(&key) {
+            return clip.clone();
+        }
+
+        if let Some(imported) = self.clip_paths.get(&key) {
+            return imported.clone();
+        }
+
+        let sub_clip = clip.clip_path.as_ref().map(|c| self.import_clip_path(c));
+        self.import(&clip.root);
+
+        let id = self.unique_id(&clip.id);
+        let imported = if id.is_some() || !same_resource(&sub_clip, &clip.clip_path) {
+            Shared::new(ClipPath {
+                id: id.unwrap_or_else(|| clip.id.clone()),
+                clip_path: sub_clip,
+                ..ClipPath::clone(clip)
+            })
+        } else {
+            clip.clone()
+        };
+
+        self.clip_paths.insert(key, imported.clone());
+        imported
+    }
+
+    fn import_mask(&mut self, mask: &Shared<Mask>) -> Shared<Mask> {
+        let key = addr(mask);
+        if self.known.contains(&key) {
+            return mask.clone();
+        }
+
+        if let Some(imported) = self.masks.get(&key) {
+            return imported.clone();
+        }
+
+        let sub_mask = mask.mask.as_ref().map(|m| self.import_mask(m));
+        self.import(&mask.root);
+
+        let id = self.unique_id(&mask.id);
+        let imported = if id.is_some() || !same_resource(&sub_mask, &mask.mask) {
+            Shared::new(Mask {
+                id: id.unwrap_or_else(|| mask.id.clone()),
+                mask: sub_mask,
+                ..Mask::clone(mask)
+            })
+        } else {
+            mask.clone()
+        };
+
+        self.masks.insert(key, imported.clone());
+        imported
+    }
+
+    fn import_filter(&mut self, filter: &Shared<filter::Filter>) -> Shared<filter::Filter> {
+        let key = addr(filter);
+        if self.known.contains(&key) {
+            return filter.clone();
+        }
+
+        if let Some(imported) = self.filters.get(&key) {
+            return imported.clone();
+        }
+
+        for primitive in &filter.primitives {
+            if let filter::Kind::Image(ref image) = primitive.kind {
+                if let filter::ImageKind::Use(ref node) = image.data {
+                    self.import(node);
+                }
+            }
+        }
+
+        let imported = match self.unique_id(&filter.id) {
+            Some(id) => Shared::new(filter::Filter {
+                id,
+                ..filter::Filter::clone(filter)
+            }),
+            None => filter.clone(),
+        };
+
+        self.filters.insert(key, imported.clone());
+        imported
+    }
+}
+
+fn same_resource<T>(a: &Option<Shared<T>>, b: &Option<Shared<T>>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => Shared::ptr_eq(a, b),
+        (None, None) => true,
+        _ => false,
+    }
+}
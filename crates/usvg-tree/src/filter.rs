@@ -13,6 +13,7 @@ use crate::{BlendMode, Color, ImageRendering, Node, NonZeroF32, NonZeroRect, Opa
 ///
 /// `filter` element in the SVG.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Filter {
     /// Element's ID.
     ///
@@ -33,6 +34,7 @@ pub struct Filter {
     /// Filter region.
     ///
     /// `x`, `y`, `width` and `height` in the SVG.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::non_zero_rect"))]
     pub rect: NonZeroRect,
 
     /// A list of filter primitives.
@@ -41,6 +43,7 @@ pub struct Filter {
 
 /// A filter primitive element.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Primitive {
     /// `x` coordinate of the filter subregion.
     pub x: Option<f32>,
@@ -71,6 +74,7 @@ pub struct Primitive {
 /// A filter kind.
 #[allow(missing_docs)]
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Kind {
     Blend(Blend),
     ColorMatrix(ColorMatrix),
@@ -119,6 +123,7 @@ impl Kind {
 /// Identifies input for a filter primitive.
 #[allow(missing_docs)]
 #[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Input {
     SourceGraphic,
     SourceAlpha,
@@ -128,6 +133,7 @@ pub enum Input {
 /// A color interpolation mode.
 #[allow(missing_docs)]
 #[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ColorInterpolation {
     SRGB,
     LinearRGB,
@@ -143,6 +149,7 @@ impl Default for ColorInterpolation {
 ///
 /// `feBlend` element in the SVG.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Blend {
     /// Identifies input for the given filter primitive.
     ///
@@ -164,6 +171,7 @@ pub struct Blend {
 ///
 /// `feColorMatrix` element in the SVG.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ColorMatrix {
     /// Identifies input for the given filter primitive.
     ///
@@ -178,9 +186,11 @@ pub struct ColorMatrix {
 
 /// A color matrix filter primitive kind.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(missing_docs)]
 pub enum ColorMatrixKind {
     Matrix(Vec<f32>), // Guarantee to have 20 numbers.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::positive"))]
     Saturate(PositiveF32),
     HueRotate(f32),
     LuminanceToAlpha,
@@ -199,6 +209,7 @@ impl Default for ColorMatrixKind {
 ///
 /// `feComponentTransfer` element in the SVG.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ComponentTransfer {
     /// Identifies input for the given filter primitive.
     ///
@@ -222,6 +233,7 @@ pub struct ComponentTransfer {
 ///
 /// <https://www.w3.org/TR/SVG11/filters.html#transferFuncElements>
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TransferFunction {
     /// Keeps a component as is.
     Identity,
@@ -253,6 +265,7 @@ pub enum TransferFunction {
 ///
 /// `feComposite` element in the SVG.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Composite {
     /// Identifies input for the given filter primitive.
     ///
@@ -273,6 +286,7 @@ pub struct Composite {
 /// An images compositing operation.
 #[allow(missing_docs)]
 #[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CompositeOperator {
     Over,
     In,
@@ -286,6 +300,7 @@ pub enum CompositeOperator {
 ///
 /// `feConvolveMatrix` element in the SVG.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ConvolveMatrix {
     /// Identifies input for the given filter primitive.
     ///
@@ -320,6 +335,7 @@ pub struct ConvolveMatrix {
 ///
 /// Used primarily by [`ConvolveMatrix`].
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ConvolveMatrixData {
     /// Returns a matrix's X target.
     ///
@@ -386,6 +402,7 @@ impl ConvolveMatrixData {
 /// An edges processing mode.
 #[allow(missing_docs)]
 #[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum EdgeMode {
     None,
     Duplicate,
@@ -396,6 +413,7 @@ pub enum EdgeMode {
 ///
 /// `feDisplacementMap` element in the SVG.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DisplacementMap {
     /// Identifies input for the given filter primitive.
     ///
@@ -426,6 +444,7 @@ pub struct DisplacementMap {
 /// A color channel.
 #[allow(missing_docs)]
 #[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ColorChannel {
     R,
     G,
@@ -439,6 +458,7 @@ pub enum ColorChannel {
 ///
 /// `feDropShadow` element in the SVG.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DropShadow {
     /// Identifies input for the given filter primitive.
     ///
@@ -454,11 +474,13 @@ pub struct DropShadow {
     /// A standard deviation along the X-axis.
     ///
     /// `stdDeviation` in the SVG.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::positive"))]
     pub std_dev_x: PositiveF32,
 
     /// A standard deviation along the Y-axis.
     ///
     /// `stdDeviation` in the SVG.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::positive"))]
     pub std_dev_y: PositiveF32,
 
     /// A flood color.
@@ -469,6 +491,7 @@ pub struct DropShadow {
     /// A flood opacity.
     ///
     /// `flood-opacity` in the SVG.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::normalized"))]
     pub opacity: Opacity,
 }
 
@@ -476,6 +499,7 @@ pub struct DropShadow {
 ///
 /// `feFlood` element in the SVG.
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Flood {
     /// A flood color.
     ///
@@ -485,6 +509,7 @@ pub struct Flood {
     /// A flood opacity.
     ///
     /// `flood-opacity` in the SVG.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::normalized"))]
     pub opacity: Opacity,
 }
 
@@ -492,6 +517,7 @@ pub struct Flood {
 ///
 /// `feGaussianBlur` element in the SVG.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GaussianBlur {
     /// Identifies input for the given filter primitive.
     ///
@@ -501,11 +527,13 @@ pub struct GaussianBlur {
     /// A standard deviation along the X-axis.
     ///
     /// `stdDeviation` in the SVG.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::positive"))]
     pub std_dev_x: PositiveF32,
 
     /// A standard deviation along the Y-axis.
     ///
     /// `stdDeviation` in the SVG.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::positive"))]
     pub std_dev_y: PositiveF32,
 }
 
@@ -513,8 +541,13 @@ pub struct GaussianBlur {
 ///
 /// `feImage` element in the SVG.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Image {
     /// Value of the `preserveAspectRatio` attribute.
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serialization::AspectRatioDef")
+    )]
     pub aspect: AspectRatio,
 
     /// Rendering method.
@@ -528,6 +561,7 @@ pub struct Image {
 
 /// Kind of the `feImage` data.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ImageKind {
     /// An image data.
     Image(crate::ImageKind),
@@ -536,6 +570,7 @@ pub enum ImageKind {
     ///
     /// Isn't inside a dummy group like clip, mask and pattern because
     /// `feImage` can reference only a single element.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::node"))]
     Use(Node),
 }
 
@@ -543,6 +578,7 @@ pub enum ImageKind {
 ///
 /// `feDiffuseLighting` element in the SVG.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DiffuseLighting {
     /// Identifies input for the given filter primitive.
     ///
@@ -572,6 +608,7 @@ pub struct DiffuseLighting {
 ///
 /// `feSpecularLighting` element in the SVG.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SpecularLighting {
     /// Identifies input for the given filter primitive.
     ///
@@ -607,6 +644,7 @@ pub struct SpecularLighting {
 /// A light source kind.
 #[allow(missing_docs)]
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum LightSource {
     DistantLight(DistantLight),
     PointLight(PointLight),
@@ -617,6 +655,7 @@ pub enum LightSource {
 ///
 /// `feDistantLight` element in the SVG.
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DistantLight {
     /// Direction angle for the light source on the XY plane (clockwise),
     /// in degrees from the x axis.
@@ -634,6 +673,7 @@ pub struct DistantLight {
 ///
 /// `fePointLight` element in the SVG.
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PointLight {
     /// X location for the light source.
     ///
@@ -655,6 +695,7 @@ pub struct PointLight {
 ///
 /// `feSpotLight` element in the SVG.
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SpotLight {
     /// X location for the light source.
     ///
@@ -689,6 +730,7 @@ pub struct SpotLight {
     /// Exponent value controlling the focus for the light source.
     ///
     /// `specularExponent` in the SVG.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::positive"))]
     pub specular_exponent: PositiveF32,
 
     /// A limiting cone which restricts the region where the light is projected.
@@ -701,6 +743,7 @@ pub struct SpotLight {
 ///
 /// `feMerge` element in the SVG.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Merge {
     /// List of input layers that should be merged.
     ///
@@ -712,6 +755,7 @@ pub struct Merge {
 ///
 /// `feMorphology` element in the SVG.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Morphology {
     /// Identifies input for the given filter primitive.
     ///
@@ -728,6 +772,7 @@ pub struct Morphology {
     /// A value of zero disables the effect of the given filter primitive.
     ///
     /// `radius` in the SVG.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::positive"))]
     pub radius_x: PositiveF32,
 
     /// A filter radius along the Y-axis.
@@ -735,12 +780,14 @@ pub struct Morphology {
     /// A value of zero disables the effect of the given filter primitive.
     ///
     /// `radius` in the SVG.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::positive"))]
     pub radius_y: PositiveF32,
 }
 
 /// A morphology operation.
 #[allow(missing_docs)]
 #[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MorphologyOperator {
     Erode,
     Dilate,
@@ -750,6 +797,7 @@ pub enum MorphologyOperator {
 ///
 /// `feOffset` element in the SVG.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Offset {
     /// Identifies input for the given filter primitive.
     ///
@@ -767,6 +815,7 @@ pub struct Offset {
 ///
 /// `feTile` element in the SVG.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Tile {
     /// Identifies input for the given filter primitive.
     ///
@@ -778,15 +827,18 @@ pub struct Tile {
 ///
 /// `feTurbulence` element in the SVG.
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Turbulence {
     /// Identifies the base frequency for the noise function.
     ///
     /// `baseFrequency` in the SVG.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::positive"))]
     pub base_frequency_x: PositiveF32,
 
     /// Identifies the base frequency for the noise function.
     ///
     /// `baseFrequency` in the SVG.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::positive"))]
     pub base_frequency_y: PositiveF32,
 
     /// Identifies the number of octaves for the noise function.
@@ -813,6 +865,7 @@ pub struct Turbulence {
 /// A turbulence kind for the `feTurbulence` filter.
 #[allow(missing_docs)]
 #[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TurbulenceKind {
     FractalNoise,
     Turbulence,
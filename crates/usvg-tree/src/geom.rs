@@ -47,11 +47,17 @@ impl IsValidLength for f64 {
 
 /// View box.
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ViewBox {
     /// Value of the `viewBox` attribute.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::non_zero_rect"))]
     pub rect: NonZeroRect,
 
     /// Value of the `preserveAspectRatio` attribute.
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serialization::AspectRatioDef")
+    )]
     pub aspect: AspectRatio,
 }
 
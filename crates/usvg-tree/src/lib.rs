@@ -18,11 +18,16 @@
 #![allow(clippy::too_many_arguments)]
 #![allow(clippy::derivable_impls)]
 
+pub mod diagnostics;
+mod edit;
 pub mod filter;
 mod geom;
+pub mod node;
+#[cfg(feature = "serde")]
+mod serialization;
 mod text;
+mod visitor;
 
-use std::rc::Rc;
 use std::sync::Arc;
 
 pub use strict_num::{self, ApproxEqUlps, NonZeroPositiveF32, NormalizedF32, PositiveF32};
@@ -30,8 +35,12 @@ pub use svgtypes::{Align, AspectRatio};
 
 pub use tiny_skia_path;
 
+pub use crate::diagnostics::{Diagnostic, DiagnosticCode, Diagnostics};
+pub use crate::edit::EditError;
 pub use crate::geom::*;
+pub use crate::node::{Node, NodeEdge, NodeRef, NodeRefMut};
 pub use crate::text::*;
+pub use crate::visitor::{NodeContext, VisitContext, Visitor, VisitorMut};
 
 /// An alias to `NormalizedF32`.
 pub type Opacity = NormalizedF32;
@@ -63,6 +72,7 @@ impl NonZeroF32 {
 /// An element units.
 #[allow(missing_docs)]
 #[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Units {
     UserSpaceOnUse,
     ObjectBoundingBox,
@@ -75,6 +85,7 @@ pub enum Units {
 /// `visibility` attribute in the SVG.
 #[allow(missing_docs)]
 #[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Visibility {
     Visible,
     Hidden,
@@ -87,10 +98,35 @@ impl Default for Visibility {
     }
 }
 
+/// A pointer-events property.
+///
+/// `pointer-events` attribute in the SVG.
+#[allow(missing_docs)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PointerEvents {
+    VisiblePainted,
+    VisibleFill,
+    VisibleStroke,
+    Visible,
+    Painted,
+    Fill,
+    Stroke,
+    All,
+    None,
+}
+
+impl Default for PointerEvents {
+    fn default() -> Self {
+        Self::VisiblePainted
+    }
+}
+
 /// A shape rendering method.
 ///
 /// `shape-rendering` attribute in the SVG.
 #[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(missing_docs)]
 pub enum ShapeRendering {
     OptimizeSpeed,
@@ -129,7 +165,15 @@ impl std::str::FromStr for ShapeRendering {
     }
 }
 
+/// A vector effect.
+///
+/// `vector-effect` in SVG.
+///
+/// Vector effects on groups are not supported. Instead, a group's `vector-effect`
+/// is applied to each descendant shape and image, which is exact only
+/// for `non-scaling-stroke`.
 #[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(missing_docs)]
 pub enum VectorEffect {
     None,
@@ -165,6 +209,7 @@ impl std::str::FromStr for VectorEffect {
 /// `text-rendering` attribute in the SVG.
 #[allow(missing_docs)]
 #[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TextRendering {
     OptimizeSpeed,
     OptimizeLegibility,
@@ -195,6 +240,7 @@ impl std::str::FromStr for TextRendering {
 /// `image-rendering` attribute in the SVG.
 #[allow(missing_docs)]
 #[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ImageRendering {
     OptimizeQuality,
     OptimizeSpeed,
@@ -223,6 +269,7 @@ impl std::str::FromStr for ImageRendering {
 /// `mix-blend-mode` attribute in the SVG.
 #[allow(missing_docs)]
 #[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BlendMode {
     Normal,
     Multiply,
@@ -253,6 +300,7 @@ impl Default for BlendMode {
 /// `spreadMethod` attribute in the SVG.
 #[allow(missing_docs)]
 #[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SpreadMethod {
     Pad,
     Reflect,
@@ -267,6 +315,7 @@ impl Default for SpreadMethod {
 
 /// A generic gradient.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BaseGradient {
     /// Coordinate system units.
     ///
@@ -276,6 +325,7 @@ pub struct BaseGradient {
     /// Gradient transform.
     ///
     /// `gradientTransform` in SVG.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::transform"))]
     pub transform: Transform,
 
     /// Gradient spreading method.
@@ -292,6 +342,7 @@ pub struct BaseGradient {
 /// `linearGradient` element in SVG.
 #[allow(missing_docs)]
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LinearGradient {
     /// Element's ID.
     ///
@@ -321,6 +372,7 @@ impl std::ops::Deref for LinearGradient {
 /// `radialGradient` element in SVG.
 #[allow(missing_docs)]
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RadialGradient {
     /// Element's ID.
     ///
@@ -330,6 +382,7 @@ pub struct RadialGradient {
 
     pub cx: f32,
     pub cy: f32,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::positive"))]
     pub r: PositiveF32,
     pub fx: f32,
     pub fy: f32,
@@ -353,10 +406,12 @@ pub type StopOffset = NormalizedF32;
 ///
 /// `stop` element in SVG.
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Stop {
     /// Gradient stop offset.
     ///
     /// `offset` in SVG.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::normalized"))]
     pub offset: StopOffset,
 
     /// Gradient stop color.
@@ -367,6 +422,7 @@ pub struct Stop {
     /// Gradient stop opacity.
     ///
     /// `stop-opacity` in SVG.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::normalized"))]
     pub opacity: Opacity,
 }
 
@@ -374,6 +430,7 @@ pub struct Stop {
 ///
 /// `pattern` element in SVG.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Pattern {
     /// Element's ID.
     ///
@@ -395,11 +452,13 @@ pub struct Pattern {
     /// Pattern transform.
     ///
     /// `patternTransform` in SVG.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::transform"))]
     pub transform: Transform,
 
     /// Pattern rectangle.
     ///
     /// `x`, `y`, `width` and `height` in SVG.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::non_zero_rect"))]
     pub rect: NonZeroRect,
 
     /// Pattern viewbox.
@@ -408,6 +467,7 @@ pub struct Pattern {
     /// Pattern children.
     ///
     /// The root node is always `Group`.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::node"))]
     pub root: Node,
 }
 
@@ -465,6 +525,7 @@ impl PartialEq for StrokeMiterlimit {
 /// `stroke-linecap` attribute in the SVG.
 #[allow(missing_docs)]
 #[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum LineCap {
     Butt,
     Round,
@@ -482,6 +543,7 @@ impl Default for LineCap {
 /// `stroke-linejoin` attribute in the SVG.
 #[allow(missing_docs)]
 #[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum LineJoin {
     Miter,
     Round,
@@ -497,12 +559,18 @@ impl Default for LineJoin {
 /// A stroke style.
 #[allow(missing_docs)]
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Stroke {
     pub paint: Paint,
     pub dasharray: Option<Vec<f32>>,
     pub dashoffset: f32,
     pub miterlimit: StrokeMiterlimit,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::normalized"))]
     pub opacity: Opacity,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serialization::non_zero_positive")
+    )]
     pub width: StrokeWidth,
     pub linecap: LineCap,
     pub linejoin: LineJoin,
@@ -530,6 +598,7 @@ impl Default for Stroke {
 /// `fill-rule` attribute in the SVG.
 #[allow(missing_docs)]
 #[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FillRule {
     NonZero,
     EvenOdd,
@@ -544,8 +613,10 @@ impl Default for FillRule {
 /// A fill style.
 #[allow(missing_docs)]
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Fill {
     pub paint: Paint,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::normalized"))]
     pub opacity: Opacity,
     pub rule: FillRule,
 }
@@ -574,6 +645,7 @@ impl Default for Fill {
 
 /// A 8-bit RGB color.
 #[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(missing_docs)]
 pub struct Color {
     pub red: u8,
@@ -606,11 +678,15 @@ impl Color {
 /// `paint` value type in the SVG.
 #[allow(missing_docs)]
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Paint {
     Color(Color),
-    LinearGradient(Rc<LinearGradient>),
-    RadialGradient(Rc<RadialGradient>),
-    Pattern(Rc<Pattern>),
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::resource"))]
+    LinearGradient(Shared<LinearGradient>),
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::resource"))]
+    RadialGradient(Shared<RadialGradient>),
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::resource"))]
+    Pattern(Shared<Pattern>),
 }
 
 impl Paint {
@@ -633,9 +709,13 @@ impl PartialEq for Paint {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (Self::Color(lc), Self::Color(rc)) => lc == rc,
-            (Self::LinearGradient(ref lg1), Self::LinearGradient(ref lg2)) => Rc::ptr_eq(lg1, lg2),
-            (Self::RadialGradient(ref rg1), Self::RadialGradient(ref rg2)) => Rc::ptr_eq(rg1, rg2),
-            (Self::Pattern(ref p1), Self::Pattern(ref p2)) => Rc::ptr_eq(p1, p2),
+            (Self::LinearGradient(ref lg1), Self::LinearGradient(ref lg2)) => {
+                Shared::ptr_eq(lg1, lg2)
+            }
+            (Self::RadialGradient(ref rg1), Self::RadialGradient(ref rg2)) => {
+                Shared::ptr_eq(rg1, rg2)
+            }
+            (Self::Pattern(ref p1), Self::Pattern(ref p2)) => Shared::ptr_eq(p1, p2),
             _ => false,
         }
     }
@@ -645,6 +725,7 @@ impl PartialEq for Paint {
 ///
 /// `clipPath` element in SVG.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClipPath {
     /// Element's ID.
     ///
@@ -660,16 +741,22 @@ pub struct ClipPath {
     /// Clip path transform.
     ///
     /// `transform` in SVG.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::transform"))]
     pub transform: Transform,
 
     /// Additional clip path.
     ///
     /// `clip-path` in SVG.
-    pub clip_path: Option<Rc<Self>>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serialization::resource_option")
+    )]
+    pub clip_path: Option<Shared<Self>>,
 
     /// Clip path children.
     ///
     /// The root node is always `Group`.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::node"))]
     pub root: Node,
 }
 
@@ -687,6 +774,7 @@ impl Default for ClipPath {
 
 /// A mask type.
 #[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MaskType {
     /// Indicates that the luminance values of the mask should be used.
     Luminance,
@@ -704,6 +792,7 @@ impl Default for MaskType {
 ///
 /// `mask` element in SVG.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Mask {
     /// Element's ID.
     ///
@@ -724,6 +813,7 @@ pub struct Mask {
     /// Mask rectangle.
     ///
     /// `x`, `y`, `width` and `height` in SVG.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::non_zero_rect"))]
     pub rect: NonZeroRect,
 
     /// Mask type.
@@ -734,17 +824,23 @@ pub struct Mask {
     /// Additional mask.
     ///
     /// `mask` in SVG.
-    pub mask: Option<Rc<Self>>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serialization::resource_option")
+    )]
+    pub mask: Option<Shared<Self>>,
 
     /// Clip path children.
     ///
     /// The root node is always `Group`.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::node"))]
     pub root: Node,
 }
 
 /// Node's kind.
 #[allow(missing_docs)]
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum NodeKind {
     Group(Group),
     Path(Path),
@@ -763,6 +859,16 @@ impl NodeKind {
         }
     }
 
+    /// Returns node's classes.
+    pub fn classes(&self) -> &[String] {
+        match self {
+            NodeKind::Group(ref e) => &e.classes,
+            NodeKind::Path(ref e) => &e.classes,
+            NodeKind::Image(ref e) => &e.classes,
+            NodeKind::Text(ref e) => &e.classes,
+        }
+    }
+
     /// Returns node's transform.
     pub fn transform(&self) -> Transform {
         match self {
@@ -772,6 +878,49 @@ impl NodeKind {
             NodeKind::Text(ref e) => e.transform,
         }
     }
+
+    /// Returns node's origin in the source SVG.
+    pub fn origin(&self) -> Option<&Origin> {
+        match self {
+            NodeKind::Group(ref e) => e.origin.as_deref(),
+            NodeKind::Path(ref e) => e.origin.as_deref(),
+            NodeKind::Image(ref e) => e.origin.as_deref(),
+            NodeKind::Text(ref e) => e.origin.as_deref(),
+        }
+    }
+}
+
+/// An element location in the source SVG.
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SourceLocation {
+    /// Element's byte range in the source SVG.
+    pub span: std::ops::Range<usize>,
+
+    /// A line of the element's start. Starts from 1.
+    pub line: u32,
+
+    /// A column of the element's start, in characters. Starts from 1.
+    pub column: u32,
+
+    /// Element's tag name in the source SVG.
+    ///
+    /// Like `rect`, even when it was converted into a `Path`.
+    pub tag_name: String,
+}
+
+/// A node origin in the source SVG.
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Origin {
+    /// An element this node was created from.
+    pub element: SourceLocation,
+
+    /// Elements that instantiated the `element`, outermost first.
+    ///
+    /// Contains `use` and `switch` elements, as well as shapes that reference a marker.
+    /// Empty when the `element` was rendered directly.
+    pub instances: Vec<SourceLocation>,
 }
 
 /// A group container.
@@ -781,6 +930,7 @@ impl NodeKind {
 ///
 /// `g` element in SVG.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Group {
     /// Element's ID.
     ///
@@ -789,13 +939,21 @@ pub struct Group {
     /// Can be empty.
     pub id: String,
 
+    /// Element's classes.
+    ///
+    /// Taken from the SVG `class` attribute.
+    /// Can be empty.
+    pub classes: Vec<String>,
+
     /// Element transform.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::transform"))]
     pub transform: Transform,
 
     /// Group opacity.
     ///
     /// After the group is rendered we should combine
     /// it with a parent group using the specified opacity.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::normalized"))]
     pub opacity: Opacity,
 
     /// Group blend mode.
@@ -809,22 +967,43 @@ pub struct Group {
     pub isolate: bool,
 
     /// Element's clip path.
-    pub clip_path: Option<Rc<ClipPath>>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serialization::resource_option")
+    )]
+    pub clip_path: Option<Shared<ClipPath>>,
 
     /// Element's mask.
-    pub mask: Option<Rc<Mask>>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serialization::resource_option")
+    )]
+    pub mask: Option<Shared<Mask>>,
 
     /// Element's filters.
-    pub filters: Vec<Rc<filter::Filter>>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::resource_vec"))]
+    pub filters: Vec<Shared<filter::Filter>>,
+
+    /// Indicates that the group is a `marker` instance.
+    ///
+    /// Markers are converted into regular groups by the parser.
+    pub marker_instance: bool,
 
     /// Element's title.
     pub title: Option<String>,
+
+    /// Element's origin in the source SVG.
+    ///
+    /// Set only when origins tracking was enabled during parsing.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::rc_option"))]
+    pub origin: Option<Shared<Origin>>,
 }
 
 impl Default for Group {
     fn default() -> Self {
         Group {
             id: String::new(),
+            classes: Vec::new(),
             transform: Transform::default(),
             opacity: Opacity::ONE,
             blend_mode: BlendMode::Normal,
@@ -832,7 +1011,9 @@ impl Default for Group {
             clip_path: None,
             mask: None,
             filters: Vec::new(),
+            marker_instance: false,
             title: None,
+            origin: None,
         }
     }
 }
@@ -856,6 +1037,7 @@ impl Group {
 ///
 /// [`paint-order`]: https://www.w3.org/TR/SVG2/painting.html#PaintOrder
 #[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(missing_docs)]
 pub enum PaintOrder {
     FillAndStroke,
@@ -870,6 +1052,7 @@ impl Default for PaintOrder {
 
 /// A path element.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Path {
     /// Element's ID.
     ///
@@ -878,12 +1061,24 @@ pub struct Path {
     /// Can be empty.
     pub id: String,
 
+    /// Element's classes.
+    ///
+    /// Taken from the SVG `class` attribute.
+    /// Can be empty.
+    pub classes: Vec<String>,
+
     /// Element transform.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::transform"))]
     pub transform: Transform,
 
     /// Element visibility.
     pub visibility: Visibility,
 
+    /// Element's pointer events.
+    ///
+    /// `pointer-events` in SVG.
+    pub pointer_events: PointerEvents,
+
     /// Fill style.
     pub fill: Option<Fill>,
 
@@ -904,7 +1099,7 @@ pub struct Path {
     pub rendering_mode: ShapeRendering,
 
     /// Vector effect.
-    /// 
+    ///
     /// `vector-effect` in SVG.
     pub vector_effect: VectorEffect,
 
@@ -918,25 +1113,38 @@ pub struct Path {
     ///
     /// As the name suggests, this property will be set only for paths
     /// that were converted from text.
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serialization::non_zero_rect_option")
+    )]
     pub text_bbox: Option<NonZeroRect>,
 
     /// Segments list.
     ///
     /// All segments are in absolute coordinates.
 
-    pub data: Rc<tiny_skia_path::Path>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::path"))]
+    pub data: Shared<tiny_skia_path::Path>,
 
     /// Title.
     pub title: Option<String>,
+
+    /// Element's origin in the source SVG.
+    ///
+    /// Set only when origins tracking was enabled during parsing.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::rc_option"))]
+    pub origin: Option<Shared<Origin>>,
 }
 
 impl Path {
     /// Creates a new `Path` with default values.
-    pub fn new(data: Rc<tiny_skia_path::Path>) -> Self {
+    pub fn new(data: Shared<tiny_skia_path::Path>) -> Self {
         Path {
             id: String::new(),
+            classes: Vec::new(),
             transform: Transform::default(),
             visibility: Visibility::Visible,
+            pointer_events: PointerEvents::default(),
             fill: None,
             stroke: None,
             paint_order: PaintOrder::default(),
@@ -945,18 +1153,23 @@ impl Path {
             text_bbox: None,
             data,
             title: None,
+            origin: None,
         }
     }
 }
 
 /// An embedded image kind.
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ImageKind {
     /// A reference to raw JPEG data. Should be decoded by the caller.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::bytes"))]
     JPEG(Arc<Vec<u8>>),
     /// A reference to raw PNG data. Should be decoded by the caller.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::bytes"))]
     PNG(Arc<Vec<u8>>),
     /// A reference to raw GIF data. Should be decoded by the caller.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::bytes"))]
     GIF(Arc<Vec<u8>>),
     /// A preprocessed SVG tree. Can be rendered as is.
     SVG(Tree),
@@ -977,6 +1190,7 @@ impl std::fmt::Debug for ImageKind {
 ///
 /// `image` element in SVG.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Image {
     /// Element's ID.
     ///
@@ -985,12 +1199,24 @@ pub struct Image {
     /// Can be empty.
     pub id: String,
 
+    /// Element's classes.
+    ///
+    /// Taken from the SVG `class` attribute.
+    /// Can be empty.
+    pub classes: Vec<String>,
+
     /// Element transform.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::transform"))]
     pub transform: Transform,
 
     /// Element visibility.
     pub visibility: Visibility,
 
+    /// Element's pointer events.
+    ///
+    /// `pointer-events` in SVG.
+    pub pointer_events: PointerEvents,
+
     /// An image rectangle in which it should be fit.
     ///
     /// Combination of the `x`, `y`, `width`, `height` and `preserveAspectRatio`
@@ -1002,19 +1228,35 @@ pub struct Image {
     /// `image-rendering` in SVG.
     pub rendering_mode: ImageRendering,
 
+    /// Vector effect.
+    ///
+    /// `vector-effect` in SVG.
+    pub vector_effect: VectorEffect,
+
     /// Image data.
     pub kind: ImageKind,
 
     /// Title.
     pub title: Option<String>,
+
+    /// Element's origin in the source SVG.
+    ///
+    /// Set only when origins tracking was enabled during parsing.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::rc_option"))]
+    pub origin: Option<Shared<Origin>>,
 }
 
-/// Alias for `rctree::Node<NodeKind>`.
-pub type Node = rctree::Node<NodeKind>;
+/// A shared pointer used for nodes data and resources.
+pub type Shared<T> = Arc<T>;
 
-// TODO: impl a Debug
 /// A nodes tree container.
-#[allow(missing_debug_implementations)]
+///
+/// Can be edited using methods like [`Tree::insert_node`], which keep IDs unique
+/// and shared resources consistent. The tree doesn't cache anything derived from nodes,
+/// but a renderer-specific tree, like `resvg::Tree`, must be recreated after editing.
+///
+/// When the `serde` feature is enabled, shared resources, like gradients, patterns,
+/// clip paths, masks and filters, are serialized once and referenced by an index.
 #[derive(Clone)]
 pub struct Tree {
     /// Image size.
@@ -1066,25 +1308,62 @@ impl Tree {
     /// Calls a closure for each [`ClipPath`] in the tree.
     ///
     /// Doesn't guarantee to have unique clip paths. A caller must deduplicate them manually.
-    pub fn clip_paths<F: FnMut(Rc<ClipPath>)>(&self, mut f: F) {
+    pub fn clip_paths<F: FnMut(Shared<ClipPath>)>(&self, mut f: F) {
         loop_over_clip_paths(&self.root, &mut f)
     }
 
     /// Calls a closure for each [`Mask`] in the tree.
     ///
     /// Doesn't guarantee to have unique masks. A caller must deduplicate them manually.
-    pub fn masks<F: FnMut(Rc<Mask>)>(&self, mut f: F) {
+    pub fn masks<F: FnMut(Shared<Mask>)>(&self, mut f: F) {
         loop_over_masks(&self.root, &mut f)
     }
 
     /// Calls a closure for each [`Filter`](filter::Filter) in the tree.
     ///
     /// Doesn't guarantee to have unique filters. A caller must deduplicate them manually.
-    pub fn filters<F: FnMut(Rc<filter::Filter>)>(&self, mut f: F) {
+    pub fn filters<F: FnMut(Shared<filter::Filter>)>(&self, mut f: F) {
         loop_over_filters(&self.root, &mut f)
     }
 }
 
+impl std::fmt::Debug for Tree {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Tree")
+            .field("size", &self.size)
+            .field("view_box", &self.view_box)
+            .field("root", &DebugNode(self.root.clone()))
+            .finish()
+    }
+}
+
+// Nodes print only their own data, so we have to print children manually.
+struct DebugNode(Node);
+
+impl std::fmt::Debug for DebugNode {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let kind = self.0.borrow();
+        if !self.0.has_children() {
+            return std::fmt::Debug::fmt(&*kind, f);
+        }
+
+        f.debug_struct("Node")
+            .field("kind", &*kind)
+            .field("children", &DebugChildren(self.0.clone()))
+            .finish()
+    }
+}
+
+struct DebugChildren(Node);
+
+impl std::fmt::Debug for DebugChildren {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.0.children().map(DebugNode))
+            .finish()
+    }
+}
+
 fn has_text_nodes(root: &Node) -> bool {
     for node in root.descendants() {
         if let NodeKind::Text(_) = *node.borrow() {
@@ -1145,7 +1424,7 @@ fn loop_over_paint_servers(root: &Node, f: &mut dyn FnMut(&Paint)) {
     }
 }
 
-fn loop_over_clip_paths(root: &Node, f: &mut dyn FnMut(Rc<ClipPath>)) {
+fn loop_over_clip_paths(root: &Node, f: &mut dyn FnMut(Shared<ClipPath>)) {
     for node in root.descendants() {
         if let NodeKind::Group(ref g) = *node.borrow() {
             if let Some(ref clip) = g.clip_path {
@@ -1161,7 +1440,7 @@ fn loop_over_clip_paths(root: &Node, f: &mut dyn FnMut(Rc<ClipPath>)) {
     }
 }
 
-fn loop_over_masks(root: &Node, f: &mut dyn FnMut(Rc<Mask>)) {
+fn loop_over_masks(root: &Node, f: &mut dyn FnMut(Shared<Mask>)) {
     for node in root.descendants() {
         if let NodeKind::Group(ref g) = *node.borrow() {
             if let Some(ref mask) = g.mask {
@@ -1177,7 +1456,7 @@ fn loop_over_masks(root: &Node, f: &mut dyn FnMut(Rc<Mask>)) {
     }
 }
 
-fn loop_over_filters(root: &Node, f: &mut dyn FnMut(Rc<filter::Filter>)) {
+fn loop_over_filters(root: &Node, f: &mut dyn FnMut(Shared<filter::Filter>)) {
     for node in root.descendants() {
         if let NodeKind::Group(ref g) = *node.borrow() {
             for filter in &g.filters {
@@ -1256,13 +1535,39 @@ fn node_subroots(node: &Node, f: &mut dyn FnMut(Node)) {
     }
 }
 
+/// A borrowed node ID.
+///
+/// Keeps the node borrowed while alive.
+pub struct NodeIdRef<'a>(NodeRef<'a>);
+
+impl std::ops::Deref for NodeIdRef<'_> {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &str {
+        self.0.id()
+    }
+}
+
+impl std::fmt::Debug for NodeIdRef<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        std::fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl std::fmt::Display for NodeIdRef<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        std::fmt::Display::fmt(&**self, f)
+    }
+}
+
 /// Additional `Node` methods.
 pub trait NodeExt {
     /// Returns node's ID.
     ///
     /// If a current node doesn't support ID - an empty string
     /// will be returned.
-    fn id(&self) -> std::cell::Ref<str>;
+    fn id(&self) -> NodeIdRef<'_>;
 
     /// Returns node's transform.
     ///
@@ -1314,8 +1619,8 @@ pub trait NodeExt {
 
 impl NodeExt for Node {
     #[inline]
-    fn id(&self) -> std::cell::Ref<str> {
-        std::cell::Ref::map(self.borrow(), |v| v.id())
+    fn id(&self) -> NodeIdRef<'_> {
+        NodeIdRef(self.borrow())
     }
 
     #[inline]
@@ -0,0 +1,701 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! A thread-safe nodes tree.
+//!
+//! Has the same API as `rctree`, but uses `Arc` and locks instead of `Rc` and `RefCell`,
+//! therefore a tree can be shared between threads.
+//!
+//! Each node holds strong references to its next sibling and first child,
+//! but only weak references to its parent, previous sibling and last child.
+//! Node data and node links are guarded by separate locks and tree manipulation methods
+//! never hold more than one lock at a time.
+//! Which means that tree editing is not atomic and should not be done concurrently.
+//!
+//! Node data borrows from different threads block until a conflicting borrow is released.
+//! Like with `RefCell`, conflicting borrows from the same thread panic,
+//! because waiting for them would never end.
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::{
+    Arc, Condvar, Mutex, MutexGuard, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard, Weak,
+};
+use std::thread::{self, ThreadId};
+
+use crate::NodeKind;
+
+type Link = Arc<NodeData>;
+type WeakLink = Weak<NodeData>;
+
+struct NodeData {
+    links: Mutex<Links>,
+    borrows: Mutex<Borrows>,
+    released: Condvar,
+    kind: RwLock<NodeKind>,
+}
+
+#[derive(Default)]
+struct Links {
+    parent: Option<WeakLink>,
+    first_child: Option<Link>,
+    last_child: Option<WeakLink>,
+    previous_sibling: Option<WeakLink>,
+    next_sibling: Option<Link>,
+}
+
+/// Threads that currently borrow node data.
+///
+/// Node data is accessed only after a borrow is registered here,
+/// therefore the `kind` lock itself is never contended.
+#[derive(Default)]
+struct Borrows {
+    readers: Vec<ThreadId>,
+    writer: Option<ThreadId>,
+}
+
+impl NodeData {
+    #[inline]
+    fn links(&self) -> MutexGuard<'_, Links> {
+        self.links.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    #[inline]
+    fn borrows(&self) -> MutexGuard<'_, Borrows> {
+        self.borrows.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn wait<'a>(&self, borrows: MutexGuard<'a, Borrows>) -> MutexGuard<'a, Borrows> {
+        self.released
+            .wait(borrows)
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Drop for NodeData {
+    fn drop(&mut self) {
+        // Collect all descendant nodes and detach them to prevent the stack overflow.
+        let links = self.links.get_mut().unwrap_or_else(PoisonError::into_inner);
+        let mut stack = Vec::new();
+        if let Some(first_child) = links.first_child.clone() {
+            for child in Node(first_child).following_siblings() {
+                stack.extend(child.descendants());
+            }
+        }
+
+        for node in stack {
+            node.detach();
+        }
+    }
+}
+
+/// A shared reference to node data. Returned by [`Node::borrow`].
+pub struct NodeRef<'a> {
+    // Must be released before the borrow.
+    kind: RwLockReadGuard<'a, NodeKind>,
+    _borrow: Borrow<'a>,
+}
+
+impl Deref for NodeRef<'_> {
+    type Target = NodeKind;
+
+    #[inline]
+    fn deref(&self) -> &NodeKind {
+        &self.kind
+    }
+}
+
+impl fmt::Debug for NodeRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/// A unique/mutable reference to node data. Returned by [`Node::borrow_mut`].
+pub struct NodeRefMut<'a> {
+    // Must be released before the borrow.
+    kind: RwLockWriteGuard<'a, NodeKind>,
+    _borrow: Borrow<'a>,
+}
+
+impl Deref for NodeRefMut<'_> {
+    type Target = NodeKind;
+
+    #[inline]
+    fn deref(&self) -> &NodeKind {
+        &self.kind
+    }
+}
+
+impl DerefMut for NodeRefMut<'_> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut NodeKind {
+        &mut self.kind
+    }
+}
+
+impl fmt::Debug for NodeRefMut<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/// Unregisters a node data borrow of the current thread.
+///
+/// Guards are not `Send`, so a borrow is always released by the thread that made it.
+struct Borrow<'a>(&'a NodeData);
+
+impl Drop for Borrow<'_> {
+    fn drop(&mut self) {
+        let current = thread::current().id();
+        let mut borrows = self.0.borrows();
+        if borrows.writer == Some(current) {
+            borrows.writer = None;
+        } else if let Some(idx) = borrows.readers.iter().position(|id| *id == current) {
+            borrows.readers.swap_remove(idx);
+        }
+
+        drop(borrows);
+        self.0.released.notify_all();
+    }
+}
+
+/// A tree node.
+///
+/// **Note:** Cloning a `Node` only increments a reference count. It does not copy the data.
+pub struct Node(Link);
+
+impl Clone for Node {
+    #[inline]
+    fn clone(&self) -> Self {
+        Node(Arc::clone(&self.0))
+    }
+}
+
+impl PartialEq for Node {
+    #[inline]
+    fn eq(&self, other: &Node) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl fmt::Debug for Node {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&*self.borrow(), f)
+    }
+}
+
+impl Node {
+    /// Creates a new node from its associated data.
+    pub fn new(kind: NodeKind) -> Node {
+        Node(Arc::new(NodeData {
+            links: Mutex::new(Links::default()),
+            borrows: Mutex::new(Borrows::default()),
+            released: Condvar::new(),
+            kind: RwLock::new(kind),
+        }))
+    }
+
+    /// Returns a parent node, unless this node is the root of the tree.
+    pub fn parent(&self) -> Option<Node> {
+        self.0.links().parent.as_ref()?.upgrade().map(Node)
+    }
+
+    /// Returns a first child of this node, unless it has no child.
+    pub fn first_child(&self) -> Option<Node> {
+        self.0.links().first_child.clone().map(Node)
+    }
+
+    /// Returns a last child of this node, unless it has no child.
+    pub fn last_child(&self) -> Option<Node> {
+        self.0.links().last_child.as_ref()?.upgrade().map(Node)
+    }
+
+    /// Returns the previous sibling of this node, unless it is a first child.
+    pub fn previous_sibling(&self) -> Option<Node> {
+        self.0
+            .links()
+            .previous_sibling
+            .as_ref()?
+            .upgrade()
+            .map(Node)
+    }
+
+    /// Returns the next sibling of this node, unless it is a last child.
+    pub fn next_sibling(&self) -> Option<Node> {
+        self.0.links().next_sibling.clone().map(Node)
+    }
+
+    /// Returns a shared reference to this node's data.
+    ///
+    /// Blocks while the node is mutably borrowed by another thread.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is currently mutably borrowed by the current thread.
+    pub fn borrow(&self) -> NodeRef<'_> {
+        let current = thread::current().id();
+        let mut borrows = self.0.borrows();
+        while let Some(writer) = borrows.writer {
+            if writer == current {
+                drop(borrows);
+                panic!("the node is already mutably borrowed");
+            }
+
+            borrows = self.0.wait(borrows);
+        }
+
+        borrows.readers.push(current);
+        drop(borrows);
+
+        NodeRef {
+            kind: self.0.kind.read().unwrap_or_else(PoisonError::into_inner),
+            _borrow: Borrow(&self.0),
+        }
+    }
+
+    /// Returns a unique/mutable reference to this node's data.
+    ///
+    /// Blocks while the node is borrowed by another thread.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is currently borrowed by the current thread.
+    pub fn borrow_mut(&self) -> NodeRefMut<'_> {
+        let current = thread::current().id();
+        let mut borrows = self.0.borrows();
+        while borrows.writer.is_some() || !borrows.readers.is_empty() {
+            if borrows.writer == Some(current) || borrows.readers.contains(&current) {
+                drop(borrows);
+                panic!("the node is already borrowed");
+            }
+
+            borrows = self.0.wait(borrows);
+        }
+
+        borrows.writer = Some(current);
+        drop(borrows);
+
+        NodeRefMut {
+            kind: self.0.kind.write().unwrap_or_else(PoisonError::into_inner),
+            _borrow: Borrow(&self.0),
+        }
+    }
+
+    /// Returns an iterator of nodes to this node and its ancestors.
+    ///
+    /// Includes the current node.
+    pub fn ancestors(&self) -> Ancestors {
+        Ancestors(Some(self.clone()))
+    }
+
+    /// Returns an iterator of nodes to this node and the siblings before it.
+    ///
+    /// Includes the current node.
+    pub fn preceding_siblings(&self) -> PrecedingSiblings {
+        PrecedingSiblings(Some(self.clone()))
+    }
+
+    /// Returns an iterator of nodes to this node and the siblings after it.
+    ///
+    /// Includes the current node.
+    pub fn following_siblings(&self) -> FollowingSiblings {
+        FollowingSiblings(Some(self.clone()))
+    }
+
+    /// Returns an iterator of nodes to this node's children.
+    pub fn children(&self) -> Children {
+        Children {
+            next: self.first_child(),
+            next_back: self.last_child(),
+        }
+    }
+
+    /// Returns `true` if this node has children nodes.
+    pub fn has_children(&self) -> bool {
+        self.0.links().first_child.is_some()
+    }
+
+    /// Returns an iterator of nodes to this node and its descendants, in tree order.
+    ///
+    /// Includes the current node.
+    pub fn descendants(&self) -> Descendants {
+        Descendants(self.traverse())
+    }
+
+    /// Returns an iterator of nodes to this node and its descendants, in tree order.
+    pub fn traverse(&self) -> Traverse {
+        Traverse {
+            root: self.clone(),
+            next: Some(NodeEdge::Start(self.clone())),
+            next_back: Some(NodeEdge::End(self.clone())),
+        }
+    }
+
+    /// Detaches a node from its parent and siblings. Children are not affected.
+    pub fn detach(&self) {
+        let (parent, previous_sibling_weak, next_sibling) = {
+            let mut links = self.0.links();
+            (
+                links.parent.take().and_then(|weak| weak.upgrade()),
+                links.previous_sibling.take(),
+                links.next_sibling.take(),
+            )
+        };
+
+        let previous_sibling = previous_sibling_weak
+            .as_ref()
+            .and_then(|weak| weak.upgrade());
+
+        if let Some(ref next_sibling) = next_sibling {
+            next_sibling.links().previous_sibling = previous_sibling_weak;
+        } else if let Some(ref parent) = parent {
+            parent.links().last_child = previous_sibling_weak;
+        }
+
+        if let Some(previous_sibling) = previous_sibling {
+            previous_sibling.links().next_sibling = next_sibling;
+        } else if let Some(ref parent) = parent {
+            parent.links().first_child = next_sibling;
+        }
+    }
+
+    /// Appends a new child to this node, after existing children.
+    pub fn append(&self, new_child: Node) {
+        assert!(*self != new_child, "a node cannot be appended to itself");
+
+        new_child.detach();
+
+        let last_child = self
+            .0
+            .links()
+            .last_child
+            .replace(Arc::downgrade(&new_child.0))
+            .and_then(|weak| weak.upgrade());
+
+        {
+            let mut links = new_child.0.links();
+            links.parent = Some(Arc::downgrade(&self.0));
+            links.previous_sibling = last_child.as_ref().map(Arc::downgrade);
+        }
+
+        match last_child {
+            Some(last_child) => last_child.links().next_sibling = Some(new_child.0),
+            None => self.0.links().first_child = Some(new_child.0),
+        }
+    }
+
+    /// Prepends a new child to this node, before existing children.
+    pub fn prepend(&self, new_child: Node) {
+        assert!(*self != new_child, "a node cannot be prepended to itself");
+
+        new_child.detach();
+
+        let first_child = self.0.links().first_child.take();
+
+        new_child.0.links().parent = Some(Arc::downgrade(&self.0));
+        match first_child {
+            Some(first_child) => {
+                first_child.links().previous_sibling = Some(Arc::downgrade(&new_child.0));
+                new_child.0.links().next_sibling = Some(first_child);
+            }
+            None => {
+                self.0.links().last_child = Some(Arc::downgrade(&new_child.0));
+            }
+        }
+
+        self.0.links().first_child = Some(new_child.0);
+    }
+
+    /// Inserts a new sibling after this node.
+    pub fn insert_after(&self, new_sibling: Node) {
+        assert!(
+            *self != new_sibling,
+            "a node cannot be inserted after itself"
+        );
+
+        new_sibling.detach();
+
+        let (parent, next_sibling) = {
+            let mut links = self.0.links();
+            (links.parent.clone(), links.next_sibling.take())
+        };
+
+        {
+            let mut links = new_sibling.0.links();
+            links.parent = parent.clone();
+            links.previous_sibling = Some(Arc::downgrade(&self.0));
+        }
+
+        match next_sibling {
+            Some(next_sibling) => {
+                next_sibling.links().previous_sibling = Some(Arc::downgrade(&new_sibling.0));
+                new_sibling.0.links().next_sibling = Some(next_sibling);
+            }
+            None => {
+                if let Some(parent) = parent.and_then(|weak| weak.upgrade()) {
+                    parent.links().last_child = Some(Arc::downgrade(&new_sibling.0));
+                }
+            }
+        }
+
+        self.0.links().next_sibling = Some(new_sibling.0);
+    }
+
+    /// Inserts a new sibling before this node.
+    pub fn insert_before(&self, new_sibling: Node) {
+        assert!(
+            *self != new_sibling,
+            "a node cannot be inserted before itself"
+        );
+
+        new_sibling.detach();
+
+        let (parent, previous_sibling_weak) = {
+            let mut links = self.0.links();
+            let previous_sibling = links
+                .previous_sibling
+                .replace(Arc::downgrade(&new_sibling.0));
+            (links.parent.clone(), previous_sibling)
+        };
+
+        let previous_sibling = previous_sibling_weak
+            .as_ref()
+            .and_then(|weak| weak.upgrade());
+
+        {
+            let mut links = new_sibling.0.links();
+            links.parent = parent.clone();
+            links.next_sibling = Some(self.0.clone());
+            if previous_sibling.is_some() {
+                links.previous_sibling = previous_sibling_weak;
+            }
+        }
+
+        match previous_sibling {
+            Some(previous_sibling) => {
+                previous_sibling.links().next_sibling = Some(new_sibling.0);
+            }
+            None => {
+                if let Some(parent) = parent.and_then(|weak| weak.upgrade()) {
+                    parent.links().first_child = Some(new_sibling.0);
+                }
+            }
+        }
+    }
+
+    /// Returns a copy of a current node without children.
+    pub fn make_copy(&self) -> Node {
+        Node::new(self.borrow().clone())
+    }
+
+    /// Returns a copy of a current node with children.
+    pub fn make_deep_copy(&self) -> Node {
+        let root = self.make_copy();
+        Node::_make_deep_copy(&root, self);
+        root
+    }
+
+    fn _make_deep_copy(parent: &Node, node: &Node) {
+        for child in node.children() {
+            let new_node = child.make_copy();
+            parent.append(new_node.clone());
+
+            if child.has_children() {
+                Node::_make_deep_copy(&new_node, &child);
+            }
+        }
+    }
+}
+
+macro_rules! impl_node_iterator {
+    ($name: ident, $next: expr) => {
+        impl Iterator for $name {
+            type Item = Node;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                let node = self.0.take()?;
+                self.0 = $next(&node);
+                Some(node)
+            }
+        }
+    };
+}
+
+/// An iterator of nodes to the ancestors a given node.
+#[derive(Clone, Debug)]
+pub struct Ancestors(Option<Node>);
+impl_node_iterator!(Ancestors, |node: &Node| node.parent());
+
+/// An iterator of nodes to the siblings before a given node.
+#[derive(Clone, Debug)]
+pub struct PrecedingSiblings(Option<Node>);
+impl_node_iterator!(PrecedingSiblings, |node: &Node| node.previous_sibling());
+
+/// An iterator of nodes to the siblings after a given node.
+#[derive(Clone, Debug)]
+pub struct FollowingSiblings(Option<Node>);
+impl_node_iterator!(FollowingSiblings, |node: &Node| node.next_sibling());
+
+/// A double ended iterator of nodes to the children of a given node.
+#[derive(Clone, Debug)]
+pub struct Children {
+    next: Option<Node>,
+    next_back: Option<Node>,
+}
+
+impl Children {
+    // true if self.next_back's next sibling is self.next
+    fn finished(&self) -> bool {
+        match self.next_back {
+            Some(ref next_back) => next_back.next_sibling() == self.next,
+            _ => true,
+        }
+    }
+}
+
+impl Iterator for Children {
+    type Item = Node;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished() {
+            return None;
+        }
+
+        let node = self.next.take()?;
+        self.next = node.next_sibling();
+        Some(node)
+    }
+}
+
+impl DoubleEndedIterator for Children {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.finished() {
+            return None;
+        }
+
+        let node = self.next_back.take()?;
+        self.next_back = node.previous_sibling();
+        Some(node)
+    }
+}
+
+/// An iterator of nodes to a given node and its descendants, in tree order.
+#[derive(Clone, Debug)]
+pub struct Descendants(Traverse);
+
+impl Iterator for Descendants {
+    type Item = Node;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.0.next()? {
+                NodeEdge::Start(node) => return Some(node),
+                NodeEdge::End(_) => {}
+            }
+        }
+    }
+}
+
+/// A node type during traverse.
+#[derive(Clone, PartialEq, Debug)]
+pub enum NodeEdge {
+    /// Indicates that start of a node that has children.
+    ///
+    /// Yielded by `Traverse::next` before the node's descendants.
+    Start(Node),
+
+    /// Indicates that end of a node that has children.
+    ///
+    /// Yielded by `Traverse::next` after the node's descendants.
+    End(Node),
+}
+
+impl NodeEdge {
+    fn next_item(&self, root: &Node) -> Option<NodeEdge> {
+        match *self {
+            NodeEdge::Start(ref node) => match node.first_child() {
+                Some(first_child) => Some(NodeEdge::Start(first_child)),
+                None => Some(NodeEdge::End(node.clone())),
+            },
+            NodeEdge::End(ref node) => {
+                if *node == *root {
+                    None
+                } else {
+                    match node.next_sibling() {
+                        Some(next_sibling) => Some(NodeEdge::Start(next_sibling)),
+                        // `node.parent()` here can only be `None`
+                        // if the tree has been modified during iteration.
+                        None => node.parent().map(NodeEdge::End),
+                    }
+                }
+            }
+        }
+    }
+
+    fn previous_item(&self, root: &Node) -> Option<NodeEdge> {
+        match *self {
+            NodeEdge::End(ref node) => match node.last_child() {
+                Some(last_child) => Some(NodeEdge::End(last_child)),
+                None => Some(NodeEdge::Start(node.clone())),
+            },
+            NodeEdge::Start(ref node) => {
+                if *node == *root {
+                    None
+                } else {
+                    match node.previous_sibling() {
+                        Some(previous_sibling) => Some(NodeEdge::End(previous_sibling)),
+                        // `node.parent()` here can only be `None`
+                        // if the tree has been modified during iteration.
+                        None => node.parent().map(NodeEdge::Start),
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A double ended iterator of nodes to a given node and its descendants, in tree order.
+#[derive(Clone, Debug)]
+pub struct Traverse {
+    root: Node,
+    next: Option<NodeEdge>,
+    next_back: Option<NodeEdge>,
+}
+
+impl Traverse {
+    // true if self.next_back's next item is self.next
+    fn finished(&self) -> bool {
+        match self.next_back {
+            Some(ref next_back) => next_back.next_item(&self.root) == self.next,
+            _ => true,
+        }
+    }
+}
+
+impl Iterator for Traverse {
+    type Item = NodeEdge;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished() {
+            return None;
+        }
+
+        let item = self.next.take()?;
+        self.next = item.next_item(&self.root);
+        Some(item)
+    }
+}
+
+impl DoubleEndedIterator for Traverse {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.finished() {
+            return None;
+        }
+
+        let item = self.next_back.take()?;
+        self.next_back = item.previous_item(&self.root);
+        Some(item)
+    }
+}
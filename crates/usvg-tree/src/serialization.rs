@@ -0,0 +1,739 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! `serde` support.
+//!
+//! Shared resources, like paint servers, clip paths, masks, filters and text paths,
+//! are stored once per tree, in a `resources` list, and referenced by their index in it.
+//! The list is ordered in a way that a resource can reference only the preceding ones.
+//! Indices are used instead of IDs, because resource IDs are not guaranteed
+//! to be unique or non-empty, like in a tree that was built manually.
+//!
+//! Foreign types are serialized using the most compact representation:
+//! transforms as `[sx, ky, kx, sy, tx, ty]`, rects as `[x, y, width, height]`
+//! and paths as a list of segments.
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
+
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
+use serde::ser::{self, Serializer};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    filter, ClipPath, Fill, LinearGradient, Mask, Node, NodeKind, NonZeroF32, Paint, Pattern,
+    RadialGradient, Shared, Size, Stroke, StrokeMiterlimit, TextFlow, TextPath, Tree, ViewBox,
+};
+
+thread_local! {
+    // Resource pointer to index maps. One per tree that is being serialized.
+    static SERIALIZE_CONTEXT: RefCell<Vec<HashMap<usize, usize>>> = const { RefCell::new(Vec::new()) };
+    // Already deserialized resources. One list per tree that is being deserialized.
+    static DESERIALIZE_CONTEXT: RefCell<Vec<Vec<SharedResource>>> = const { RefCell::new(Vec::new()) };
+}
+
+struct SerializeContextGuard;
+
+impl SerializeContextGuard {
+    fn push(ids: HashMap<usize, usize>) -> Self {
+        SERIALIZE_CONTEXT.with(|ctx| ctx.borrow_mut().push(ids));
+        SerializeContextGuard
+    }
+}
+
+impl Drop for SerializeContextGuard {
+    fn drop(&mut self) {
+        SERIALIZE_CONTEXT.with(|ctx| ctx.borrow_mut().pop());
+    }
+}
+
+struct DeserializeContextGuard;
+
+impl DeserializeContextGuard {
+    fn push() -> Self {
+        DESERIALIZE_CONTEXT.with(|ctx| ctx.borrow_mut().push(Vec::new()));
+        DeserializeContextGuard
+    }
+}
+
+impl Drop for DeserializeContextGuard {
+    fn drop(&mut self) {
+        DESERIALIZE_CONTEXT.with(|ctx| ctx.borrow_mut().pop());
+    }
+}
+
+pub(crate) trait SharedItem: Sized {
+    fn unwrap(resource: &SharedResource) -> Option<Shared<Self>>;
+}
+
+macro_rules! resources {
+    ($($variant:ident($ty:ty)),+ $(,)?) => {
+        #[derive(Deserialize)]
+        #[serde(rename = "Resource")]
+        enum Resource {
+            $($variant($ty)),+
+        }
+
+        #[derive(Serialize)]
+        #[serde(rename = "Resource")]
+        enum ResourceRef<'a> {
+            $($variant(&'a $ty)),+
+        }
+
+        #[derive(Clone)]
+        pub(crate) enum SharedResource {
+            $($variant(Shared<$ty>)),+
+        }
+
+        impl SharedResource {
+            fn id(&self) -> usize {
+                match self {
+                    $(SharedResource::$variant(ref r) => pointer_id(r)),+
+                }
+            }
+
+            fn to_ref(&self) -> ResourceRef<'_> {
+                match self {
+                    $(SharedResource::$variant(ref r) => ResourceRef::$variant(r)),+
+                }
+            }
+        }
+
+        impl From<Resource> for SharedResource {
+            fn from(resource: Resource) -> Self {
+                match resource {
+                    $(Resource::$variant(r) => SharedResource::$variant(Shared::new(r))),+
+                }
+            }
+        }
+
+        $(
+            impl SharedItem for $ty {
+                fn unwrap(resource: &SharedResource) -> Option<Shared<Self>> {
+                    match resource {
+                        SharedResource::$variant(ref r) => Some(r.clone()),
+                        _ => None,
+                    }
+                }
+            }
+        )+
+    };
+}
+
+resources!(
+    ClipPath(ClipPath),
+    Mask(Mask),
+    Filter(filter::Filter),
+    LinearGradient(LinearGradient),
+    RadialGradient(RadialGradient),
+    Pattern(Pattern),
+    TextPath(TextPath),
+);
+
+fn pointer_id<T>(rc: &Shared<T>) -> usize {
+    Shared::as_ptr(rc) as *const () as usize
+}
+
+/// Collects all resources referenced by the tree in the dependency order.
+#[derive(Default)]
+struct ResourcesCollector {
+    seen: HashSet<usize>,
+    list: Vec<SharedResource>,
+}
+
+impl ResourcesCollector {
+    fn collect_node(&mut self, root: &Node) {
+        for node in root.descendants() {
+            match *node.borrow() {
+                NodeKind::Group(ref group) => {
+                    if let Some(ref clip_path) = group.clip_path {
+                        self.add(SharedResource::ClipPath(clip_path.clone()));
+                    }
+
+                    if let Some(ref mask) = group.mask {
+                        self.add(SharedResource::Mask(mask.clone()));
+                    }
+
+                    for filter in &group.filters {
+                        self.add(SharedResource::Filter(filter.clone()));
+                    }
+                }
+                NodeKind::Path(ref path) => self.collect_style(&path.fill, &path.stroke),
+                NodeKind::Text(ref text) => {
+                    for chunk in &text.chunks {
+                        if let TextFlow::Path(ref path) = chunk.text_flow {
+                            self.add(SharedResource::TextPath(path.clone()));
+                        }
+
+                        for span in &chunk.spans {
+                            self.collect_style(&span.fill, &span.stroke);
+
+                            let decoration = &span.decoration;
+                            for style in [
+                                &decoration.underline,
+                                &decoration.overline,
+                                &decoration.line_through,
+                            ]
+                            .iter()
+                            .filter_map(|s| s.as_ref())
+                            {
+                                self.collect_style(&style.fill, &style.stroke);
+                            }
+                        }
+                    }
+                }
+                // Nested SVG trees have their own resources.
+                NodeKind::Image(_) => {}
+            }
+        }
+    }
+
+    fn collect_style(&mut self, fill: &Option<Fill>, stroke: &Option<Stroke>) {
+        if let Some(ref fill) = fill {
+            self.collect_paint(&fill.paint);
+        }
+
+        if let Some(ref stroke) = stroke {
+            self.collect_paint(&stroke.paint);
+        }
+    }
+
+    fn collect_paint(&mut self, paint: &Paint) {
+        match paint {
+            Paint::Color(_) => {}
+            Paint::LinearGradient(ref lg) => self.add(SharedResource::LinearGradient(lg.clone())),
+            Paint::RadialGradient(ref rg) => self.add(SharedResource::RadialGradient(rg.clone())),
+            Paint::Pattern(ref patt) => self.add(SharedResource::Pattern(patt.clone())),
+        }
+    }
+
+    fn add(&mut self, resource: SharedResource) {
+        if !self.seen.insert(resource.id()) {
+            return;
+        }
+
+        // Dependencies must be stored first.
+        match resource {
+            SharedResource::ClipPath(ref clip_path) => {
+                if let Some(ref clip_path) = clip_path.clip_path {
+                    self.add(SharedResource::ClipPath(clip_path.clone()));
+                }
+
+                self.collect_node(&clip_path.root);
+            }
+            SharedResource::Mask(ref mask) => {
+                if let Some(ref mask) = mask.mask {
+                    self.add(SharedResource::Mask(mask.clone()));
+                }
+
+                self.collect_node(&mask.root);
+            }
+            SharedResource::Pattern(ref patt) => {
+                self.collect_node(&patt.root);
+            }
+            SharedResource::Filter(ref filter) => {
+                for primitive in &filter.primitives {
+                    if let filter::Kind::Image(ref fe) = primitive.kind {
+                        if let filter::ImageKind::Use(ref node) = fe.data {
+                            self.collect_node(node);
+                        }
+                    }
+                }
+            }
+            SharedResource::LinearGradient(_)
+            | SharedResource::RadialGradient(_)
+            | SharedResource::TextPath(_) => {}
+        }
+
+        self.list.push(resource);
+    }
+}
+
+struct Resources(Vec<SharedResource>);
+
+impl Serialize for Resources {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter().map(|r| r.to_ref()))
+    }
+}
+
+impl<'de> Deserialize<'de> for Resources {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ResourcesVisitor;
+
+        impl<'de> Visitor<'de> for ResourcesVisitor {
+            type Value = Resources;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a list of resources")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                // Each resource must be available to the following ones right away.
+                while let Some(resource) = seq.next_element::<Resource>()? {
+                    let resource = SharedResource::from(resource);
+                    DESERIALIZE_CONTEXT.with(|ctx| {
+                        if let Some(list) = ctx.borrow_mut().last_mut() {
+                            list.push(resource);
+                        }
+                    });
+                }
+
+                Ok(Resources(Vec::new()))
+            }
+        }
+
+        deserializer.deserialize_seq(ResourcesVisitor)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Tree")]
+struct TreeData {
+    #[serde(with = "size")]
+    size: Size,
+    view_box: ViewBox,
+    resources: Resources,
+    #[serde(with = "node")]
+    root: Node,
+}
+
+impl Serialize for Tree {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut collector = ResourcesCollector::default();
+        collector.collect_node(&self.root);
+
+        let ids = collector
+            .list
+            .iter()
+            .enumerate()
+            .map(|(idx, r)| (r.id(), idx))
+            .collect();
+        let _guard = SerializeContextGuard::push(ids);
+
+        TreeData {
+            size: self.size,
+            view_box: self.view_box,
+            resources: Resources(collector.list),
+            root: self.root.clone(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Tree {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let _guard = DeserializeContextGuard::push();
+        let data = TreeData::deserialize(deserializer)?;
+
+        if !matches!(*data.root.borrow(), NodeKind::Group(_)) {
+            return Err(de::Error::custom("the root node must be a group"));
+        }
+
+        Ok(Tree {
+            size: data.size,
+            view_box: data.view_box,
+            root: data.root,
+        })
+    }
+}
+
+fn resource_id<T, E: ser::Error>(rc: &Shared<T>) -> Result<usize, E> {
+    let ptr = pointer_id(rc);
+    SERIALIZE_CONTEXT
+        .with(|ctx| ctx.borrow().last().and_then(|ids| ids.get(&ptr).copied()))
+        .ok_or_else(|| E::custom("shared resources can be serialized only as a part of a tree"))
+}
+
+fn resource_by_id<T: SharedItem, E: de::Error>(id: usize) -> Result<Shared<T>, E> {
+    let resource = DESERIALIZE_CONTEXT
+        .with(|ctx| ctx.borrow().last().and_then(|list| list.get(id).cloned()))
+        .ok_or_else(|| E::custom(format_args!("unknown resource {}", id)))?;
+    T::unwrap(&resource).ok_or_else(|| E::custom(format_args!("resource {} has a wrong type", id)))
+}
+
+/// `Shared<T>` resource as an index.
+pub(crate) mod resource {
+    use super::*;
+
+    pub fn serialize<S: Serializer, T>(
+        value: &Shared<T>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        resource_id(value)?.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, T: SharedItem>(
+        deserializer: D,
+    ) -> Result<Shared<T>, D::Error> {
+        resource_by_id(usize::deserialize(deserializer)?)
+    }
+}
+
+/// `Option<Shared<T>>` resource as an optional index.
+pub(crate) mod resource_option {
+    use super::*;
+
+    pub fn serialize<S: Serializer, T>(
+        value: &Option<Shared<T>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let id = match value {
+            Some(ref rc) => Some(resource_id(rc)?),
+            None => None,
+        };
+        id.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, T: SharedItem>(
+        deserializer: D,
+    ) -> Result<Option<Shared<T>>, D::Error> {
+        match Option::<usize>::deserialize(deserializer)? {
+            Some(id) => resource_by_id(id).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// `Vec<Shared<T>>` resources as a list of indices.
+pub(crate) mod resource_vec {
+    use super::*;
+
+    pub fn serialize<S: Serializer, T>(
+        value: &[Shared<T>],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let ids = value
+            .iter()
+            .map(resource_id)
+            .collect::<Result<Vec<usize>, S::Error>>()?;
+        ids.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, T: SharedItem>(
+        deserializer: D,
+    ) -> Result<Vec<Shared<T>>, D::Error> {
+        Vec::<usize>::deserialize(deserializer)?
+            .into_iter()
+            .map(resource_by_id)
+            .collect()
+    }
+}
+
+/// Non-shared `Option<Shared<T>>`, serialized inline.
+pub(crate) mod rc_option {
+    use super::*;
+
+    pub fn serialize<S: Serializer, T: Serialize>(
+        value: &Option<Shared<T>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        value.as_deref().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, T: Deserialize<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Shared<T>>, D::Error> {
+        Ok(Option::<T>::deserialize(deserializer)?.map(Shared::new))
+    }
+}
+
+/// A node with all its descendants.
+pub(crate) mod node {
+    use super::*;
+
+    // Nodes iterators yield owned nodes, so there is no point in borrowing.
+    struct NodeRef(Node);
+
+    impl Serialize for NodeRef {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeStruct;
+
+            let mut s = serializer.serialize_struct("Node", 2)?;
+            s.serialize_field("kind", &*self.0.borrow())?;
+            s.serialize_field("children", &ChildrenRef(self.0.clone()))?;
+            s.end()
+        }
+    }
+
+    struct ChildrenRef(Node);
+
+    impl Serialize for ChildrenRef {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_seq(self.0.children().map(NodeRef))
+        }
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename = "Node")]
+    struct NodeData {
+        kind: NodeKind,
+        children: Vec<NodeData>,
+    }
+
+    impl NodeData {
+        fn into_node(self) -> Node {
+            let node = Node::new(self.kind);
+            for child in self.children {
+                node.append(child.into_node());
+            }
+            node
+        }
+    }
+
+    pub fn serialize<S: Serializer>(value: &Node, serializer: S) -> Result<S::Ok, S::Error> {
+        NodeRef(value.clone()).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Node, D::Error> {
+        NodeData::deserialize(deserializer).map(NodeData::into_node)
+    }
+}
+
+/// `Transform` as `[sx, ky, kx, sy, tx, ty]`.
+pub(crate) mod transform {
+    use super::*;
+    use crate::Transform;
+
+    pub fn serialize<S: Serializer>(ts: &Transform, serializer: S) -> Result<S::Ok, S::Error> {
+        [ts.sx, ts.ky, ts.kx, ts.sy, ts.tx, ts.ty].serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Transform, D::Error> {
+        let [sx, ky, kx, sy, tx, ty] = <[f32; 6]>::deserialize(deserializer)?;
+        Ok(Transform::from_row(sx, ky, kx, sy, tx, ty))
+    }
+}
+
+/// `NonZeroRect` as `[x, y, width, height]`.
+pub(crate) mod non_zero_rect {
+    use super::*;
+    use crate::NonZeroRect;
+
+    pub fn serialize<S: Serializer>(rect: &NonZeroRect, serializer: S) -> Result<S::Ok, S::Error> {
+        [rect.x(), rect.y(), rect.width(), rect.height()].serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<NonZeroRect, D::Error> {
+        let [x, y, w, h] = <[f32; 4]>::deserialize(deserializer)?;
+        NonZeroRect::from_xywh(x, y, w, h).ok_or_else(|| de::Error::custom("invalid rect"))
+    }
+}
+
+/// `Option<NonZeroRect>` as an optional `[x, y, width, height]`.
+pub(crate) mod non_zero_rect_option {
+    use super::*;
+    use crate::NonZeroRect;
+
+    pub fn serialize<S: Serializer>(
+        rect: &Option<NonZeroRect>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        rect.map(|r| [r.x(), r.y(), r.width(), r.height()])
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<NonZeroRect>, D::Error> {
+        match Option::<[f32; 4]>::deserialize(deserializer)? {
+            Some([x, y, w, h]) => NonZeroRect::from_xywh(x, y, w, h)
+                .map(Some)
+                .ok_or_else(|| de::Error::custom("invalid rect")),
+            None => Ok(None),
+        }
+    }
+}
+
+/// `Size` as `[width, height]`.
+pub(crate) mod size {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(size: &Size, serializer: S) -> Result<S::Ok, S::Error> {
+        [size.width(), size.height()].serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Size, D::Error> {
+        let [w, h] = <[f32; 2]>::deserialize(deserializer)?;
+        Size::from_wh(w, h).ok_or_else(|| de::Error::custom("invalid size"))
+    }
+}
+
+/// `tiny_skia_path::Path` as a list of segments.
+pub(crate) mod path {
+    use super::*;
+    use tiny_skia_path::{PathBuilder, PathSegment};
+
+    #[derive(Serialize, Deserialize)]
+    enum Segment {
+        M(f32, f32),
+        L(f32, f32),
+        Q(f32, f32, f32, f32),
+        C(f32, f32, f32, f32, f32, f32),
+        Z,
+    }
+
+    pub fn serialize<S: Serializer>(
+        path: &Shared<tiny_skia_path::Path>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(path.segments().map(|seg| match seg {
+            PathSegment::MoveTo(p) => Segment::M(p.x, p.y),
+            PathSegment::LineTo(p) => Segment::L(p.x, p.y),
+            PathSegment::QuadTo(p1, p) => Segment::Q(p1.x, p1.y, p.x, p.y),
+            PathSegment::CubicTo(p1, p2, p) => Segment::C(p1.x, p1.y, p2.x, p2.y, p.x, p.y),
+            PathSegment::Close => Segment::Z,
+        }))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Shared<tiny_skia_path::Path>, D::Error> {
+        let mut builder = PathBuilder::new();
+        for seg in Vec::<Segment>::deserialize(deserializer)? {
+            match seg {
+                Segment::M(x, y) => builder.move_to(x, y),
+                Segment::L(x, y) => builder.line_to(x, y),
+                Segment::Q(x1, y1, x, y) => builder.quad_to(x1, y1, x, y),
+                Segment::C(x1, y1, x2, y2, x, y) => builder.cubic_to(x1, y1, x2, y2, x, y),
+                Segment::Z => builder.close(),
+            }
+        }
+
+        builder
+            .finish()
+            .map(Shared::new)
+            .ok_or_else(|| de::Error::custom("invalid path"))
+    }
+}
+
+/// Raw image data.
+pub(crate) mod bytes {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(data: &Arc<Vec<u8>>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(data)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Arc<Vec<u8>>, D::Error> {
+        struct BytesVisitor;
+
+        impl<'de> Visitor<'de> for BytesVisitor {
+            type Value = Vec<u8>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a byte array")
+            }
+
+            fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+                Ok(v.to_vec())
+            }
+
+            fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+                Ok(v)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut data = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+                while let Some(b) = seq.next_element()? {
+                    data.push(b);
+                }
+                Ok(data)
+            }
+        }
+
+        deserializer
+            .deserialize_byte_buf(BytesVisitor)
+            .map(Arc::new)
+    }
+}
+
+macro_rules! checked_f32 {
+    ($module:ident, $ty:ty, $name:expr) => {
+        /// A checked `f32` as is.
+        pub(crate) mod $module {
+            use super::*;
+
+            pub fn serialize<S: Serializer>(n: &$ty, serializer: S) -> Result<S::Ok, S::Error> {
+                n.get().serialize(serializer)
+            }
+
+            pub fn deserialize<'de, D: Deserializer<'de>>(
+                deserializer: D,
+            ) -> Result<$ty, D::Error> {
+                let n = f32::deserialize(deserializer)?;
+                <$ty>::new(n).ok_or_else(|| de::Error::custom(concat!("invalid ", $name)))
+            }
+        }
+    };
+}
+
+checked_f32!(normalized, strict_num::NormalizedF32, "normalized number");
+checked_f32!(positive, strict_num::PositiveF32, "positive number");
+checked_f32!(
+    non_zero_positive,
+    strict_num::NonZeroPositiveF32,
+    "non-zero positive number"
+);
+
+impl Serialize for NonZeroF32 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.get().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for NonZeroF32 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let n = f32::deserialize(deserializer)?;
+        NonZeroF32::new(n).ok_or_else(|| de::Error::custom("invalid non-zero number"))
+    }
+}
+
+impl Serialize for StrokeMiterlimit {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.get().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for StrokeMiterlimit {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let n = f32::deserialize(deserializer)?;
+        if n.is_finite() && n >= 1.0 {
+            Ok(StrokeMiterlimit::new(n))
+        } else {
+            Err(de::Error::custom("invalid stroke miterlimit"))
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "svgtypes::Align")]
+enum AlignDef {
+    None,
+    XMinYMin,
+    XMidYMin,
+    XMaxYMin,
+    XMinYMid,
+    XMidYMid,
+    XMaxYMid,
+    XMinYMax,
+    XMidYMax,
+    XMaxYMax,
+}
+
+/// `AspectRatio` as is.
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "svgtypes::AspectRatio")]
+pub(crate) struct AspectRatioDef {
+    defer: bool,
+    #[serde(with = "AlignDef")]
+    align: svgtypes::Align,
+    slice: bool,
+}
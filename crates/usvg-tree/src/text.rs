@@ -2,15 +2,16 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use std::rc::Rc;
-
 use strict_num::NonZeroPositiveF32;
 
-use crate::{Fill, PaintOrder, Stroke, TextRendering, Transform, Visibility};
+use crate::{
+    Fill, Origin, PaintOrder, PointerEvents, Shared, Stroke, TextRendering, Transform, Visibility,
+};
 
 /// A font stretch property.
 #[allow(missing_docs)]
 #[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FontStretch {
     UltraCondensed,
     ExtraCondensed,
@@ -32,6 +33,7 @@ impl Default for FontStretch {
 
 /// A font style property.
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FontStyle {
     /// A face that is neither italic not obliqued.
     Normal,
@@ -50,6 +52,7 @@ impl Default for FontStyle {
 
 /// Text font properties.
 #[derive(Clone, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Font {
     /// A list of family names.
     ///
@@ -66,6 +69,7 @@ pub struct Font {
 /// A dominant baseline property.
 #[allow(missing_docs)]
 #[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DominantBaseline {
     Auto,
     UseScript,
@@ -90,6 +94,7 @@ impl Default for DominantBaseline {
 /// An alignment baseline property.
 #[allow(missing_docs)]
 #[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AlignmentBaseline {
     Auto,
     Baseline,
@@ -114,6 +119,7 @@ impl Default for AlignmentBaseline {
 /// A baseline shift property.
 #[allow(missing_docs)]
 #[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BaselineShift {
     Baseline,
     Subscript,
@@ -131,6 +137,7 @@ impl Default for BaselineShift {
 /// A length adjust property.
 #[allow(missing_docs)]
 #[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum LengthAdjust {
     Spacing,
     SpacingAndGlyphs,
@@ -149,6 +156,7 @@ impl Default for LengthAdjust {
 ///
 /// Also, in SVG you can specify text decoration stroking.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TextDecorationStyle {
     /// A fill style.
     pub fill: Option<Fill>,
@@ -158,6 +166,7 @@ pub struct TextDecorationStyle {
 
 /// A text span decoration.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TextDecoration {
     /// An optional underline and its style.
     pub underline: Option<TextDecorationStyle>,
@@ -171,6 +180,7 @@ pub struct TextDecoration {
 ///
 /// Spans do not overlap inside a text chunk.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TextSpan {
     /// A span start in bytes.
     ///
@@ -189,6 +199,10 @@ pub struct TextSpan {
     /// A font.
     pub font: Font,
     /// A font size.
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serialization::non_zero_positive")
+    )]
     pub font_size: NonZeroPositiveF32,
     /// Indicates that small caps should be used.
     ///
@@ -210,6 +224,8 @@ pub struct TextSpan {
     pub baseline_shift: Vec<BaselineShift>,
     /// A visibility property.
     pub visibility: Visibility,
+    /// A pointer-events property.
+    pub pointer_events: PointerEvents,
     /// A letter spacing property.
     pub letter_spacing: f32,
     /// A word spacing property.
@@ -225,6 +241,7 @@ pub struct TextSpan {
 /// A text chunk anchor property.
 #[allow(missing_docs)]
 #[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TextAnchor {
     Start,
     Middle,
@@ -239,6 +256,7 @@ impl Default for TextAnchor {
 
 /// A path used by text-on-path.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TextPath {
     /// A text offset in SVG coordinates.
     ///
@@ -246,24 +264,28 @@ pub struct TextPath {
     pub start_offset: f32,
 
     /// A path.
-    pub path: Rc<tiny_skia_path::Path>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::path"))]
+    pub path: Shared<tiny_skia_path::Path>,
 }
 
 /// A text chunk flow property.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TextFlow {
     /// A linear layout.
     ///
     /// Includes left-to-right, right-to-left and top-to-bottom.
     Linear,
     /// A text-on-path layout.
-    Path(Rc<TextPath>),
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::resource"))]
+    Path(Shared<TextPath>),
 }
 
 /// A text chunk.
 ///
 /// Text alignment and BIDI reordering can only be done inside a text chunk.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TextChunk {
     /// An absolute X axis offset.
     pub x: Option<f32>,
@@ -283,6 +305,7 @@ pub struct TextChunk {
 ///
 /// _Character_ is a Unicode codepoint.
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CharacterPosition {
     /// An absolute X axis position.
     pub x: Option<f32>,
@@ -297,6 +320,7 @@ pub struct CharacterPosition {
 /// A writing mode.
 #[allow(missing_docs)]
 #[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum WritingMode {
     LeftToRight,
     TopToBottom,
@@ -306,6 +330,7 @@ pub enum WritingMode {
 ///
 /// `text` element in SVG.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Text {
     /// Element's ID.
     ///
@@ -314,7 +339,14 @@ pub struct Text {
     /// Can be empty.
     pub id: String,
 
+    /// Element's classes.
+    ///
+    /// Taken from the SVG `class` attribute.
+    /// Can be empty.
+    pub classes: Vec<String>,
+
     /// Element transform.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::transform"))]
     pub transform: Transform,
 
     /// Rendering mode.
@@ -340,4 +372,10 @@ pub struct Text {
 
     /// Title.
     pub title: Option<String>,
+
+    /// Element's origin in the source SVG.
+    ///
+    /// Set only when origins tracking was enabled during parsing.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::rc_option"))]
+    pub origin: Option<Shared<Origin>>,
 }
@@ -0,0 +1,259 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Tree traversal.
+
+use crate::*;
+
+/// A context a node is located in.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum NodeContext {
+    /// The main tree.
+    Tree,
+    /// A clip path content.
+    ClipPath,
+    /// A mask content.
+    Mask,
+    /// A pattern content.
+    Pattern,
+    /// A group generated from a `marker` element and its content.
+    Marker,
+    /// A node referenced by an `feImage` filter primitive.
+    FilterImage,
+}
+
+/// A node visiting context.
+#[derive(Clone, Copy, Debug)]
+pub struct VisitContext<'a> {
+    /// Node's absolute transform.
+    ///
+    /// Includes node's own transform and transforms of the resources it is located in.
+    /// `objectBoundingBox` units are not taken into account, since they depend
+    /// on the bounding box of a referencing element.
+    pub transform: Transform,
+
+    /// Contexts a node is located in, outermost first.
+    ///
+    /// The first one is always [`NodeContext::Tree`].
+    /// Nested resources produce multiple entries, like a pattern inside a mask.
+    pub contexts: &'a [NodeContext],
+}
+
+impl VisitContext<'_> {
+    /// Returns the innermost context.
+    pub fn context(&self) -> NodeContext {
+        self.contexts.last().copied().unwrap_or(NodeContext::Tree)
+    }
+
+    /// Checks that a node is located in the specified context, directly or not.
+    pub fn is_inside(&self, context: NodeContext) -> bool {
+        self.contexts.contains(&context)
+    }
+}
+
+/// A tree visitor.
+///
+/// See [`Tree::visit`].
+pub trait Visitor {
+    /// Visits a node.
+    ///
+    /// Returns `false` to skip node's children and resources.
+    fn visit_node(&mut self, node: &Node, ctx: &VisitContext) -> bool;
+
+    /// Checks that the content of referenced resources should be visited as well.
+    ///
+    /// Those are clip paths, masks, patterns and `feImage` nodes.
+    #[inline]
+    fn visit_resources(&self) -> bool {
+        true
+    }
+}
+
+/// A mutable tree visitor.
+///
+/// See [`Tree::visit_mut`].
+pub trait VisitorMut {
+    /// Visits a node.
+    ///
+    /// The node is locked during the call, so a visitor must not access it
+    /// or its ancestors via [`Node`] methods.
+    ///
+    /// Returns `false` to skip node's children and resources.
+    fn visit_node_mut(&mut self, kind: &mut NodeKind, ctx: &VisitContext) -> bool;
+
+    /// Checks that the content of referenced resources should be visited as well.
+    ///
+    /// Those are clip paths, masks, patterns and `feImage` nodes.
+    #[inline]
+    fn visit_resources(&self) -> bool {
+        true
+    }
+}
+
+impl Tree {
+    /// Visits all nodes in the tree in the rendering order.
+    ///
+    /// The content of resources referenced by a node is visited right after the node itself,
+    /// before its children. Resources shared by multiple nodes are visited once per reference,
+    /// since each reference has a different transform.
+    pub fn visit<V: Visitor>(&self, visitor: &mut V) {
+        let resources = visitor.visit_resources();
+        let mut f = |node: &Node, ctx: &VisitContext| visitor.visit_node(node, ctx);
+        let mut walker = Walker {
+            f: &mut f,
+            resources,
+            contexts: vec![NodeContext::Tree],
+        };
+        walker.walk(&self.root, Transform::default());
+    }
+
+    /// Visits all nodes in the tree in the rendering order, allowing their modification.
+    ///
+    /// Changes made to a node, like a new transform or a new clip path,
+    /// are taken into account when visiting its children and resources.
+    ///
+    /// See [`Tree::visit`] for details.
+    pub fn visit_mut<V: VisitorMut>(&mut self, visitor: &mut V) {
+        let resources = visitor.visit_resources();
+        let mut f = |node: &Node, ctx: &VisitContext| {
+            let mut kind = node.borrow_mut();
+            visitor.visit_node_mut(&mut kind, ctx)
+        };
+        let mut walker = Walker {
+            f: &mut f,
+            resources,
+            contexts: vec![NodeContext::Tree],
+        };
+        walker.walk(&self.root, Transform::default());
+    }
+}
+
+struct Walker<'a> {
+    f: &'a mut dyn FnMut(&Node, &VisitContext) -> bool,
+    resources: bool,
+    contexts: Vec<NodeContext>,
+}
+
+impl Walker<'_> {
+    fn walk(&mut self, node: &Node, parent_ts: Transform) {
+        let is_marker = matches!(*node.borrow(), NodeKind::Group(ref g) if g.marker_instance);
+        if is_marker {
+            self.contexts.push(NodeContext::Marker);
+        }
+
+        let ctx = VisitContext {
+            transform: parent_ts.pre_concat(node.transform()),
+            contexts: &self.contexts,
+        };
+
+        if (self.f)(node, &ctx) {
+            // The node could have been changed by a visitor.
+            let ts = parent_ts.pre_concat(node.transform());
+
+            if self.resources {
+                for (root, root_ts, context) in node_resources(node, ts) {
+                    self.contexts.push(context);
+                    self.walk(&root, root_ts);
+                    self.contexts.pop();
+                }
+            }
+
+            for child in node.children() {
+                self.walk(&child, ts);
+            }
+        }
+
+        if is_marker {
+            self.contexts.pop();
+        }
+    }
+}
+
+// Collects resources subroots with their base transforms.
+fn node_resources(node: &Node, ts: Transform) -> Vec<(Node, Transform, NodeContext)> {
+    let mut list = Vec::new();
+    let mut patterns = Vec::new();
+    match *node.borrow() {
+        NodeKind::Group(ref g) => {
+            let mut clip = g.clip_path.as_ref();
+            while let Some(c) = clip {
+                let clip_ts = ts.pre_concat(c.transform);
+                list.push((c.root.clone(), clip_ts, NodeContext::ClipPath));
+                clip = c.clip_path.as_ref();
+            }
+
+            let mut mask = g.mask.as_ref();
+            while let Some(m) = mask {
+                list.push((m.root.clone(), ts, NodeContext::Mask));
+                mask = m.mask.as_ref();
+            }
+
+            for filter in &g.filters {
+                for primitive in &filter.primitives {
+                    if let filter::Kind::Image(ref image) = primitive.kind {
+                        if let filter::ImageKind::Use(ref use_node) = image.data {
+                            list.push((use_node.clone(), ts, NodeContext::FilterImage));
+                        }
+                    }
+                }
+            }
+        }
+        NodeKind::Path(ref path) => {
+            push_pattern(path.fill.as_ref().map(|f| &f.paint), &mut patterns);
+            push_pattern(path.stroke.as_ref().map(|s| &s.paint), &mut patterns);
+        }
+        NodeKind::Image(_) => {}
+        NodeKind::Text(ref text) => {
+            for chunk in &text.chunks {
+                for span in &chunk.spans {
+                    push_pattern(span.fill.as_ref().map(|f| &f.paint), &mut patterns);
+                    push_pattern(span.stroke.as_ref().map(|s| &s.paint), &mut patterns);
+
+                    let decoration = &span.decoration;
+                    let lines = [
+                        &decoration.underline,
+                        &decoration.overline,
+                        &decoration.line_through,
+                    ];
+                    for line in IntoIterator::into_iter(lines).flatten() {
+                        push_pattern(line.fill.as_ref().map(|f| &f.paint), &mut patterns);
+                        push_pattern(line.stroke.as_ref().map(|s| &s.paint), &mut patterns);
+                    }
+                }
+            }
+        }
+    }
+
+    for patt in patterns {
+        list.push((
+            patt.root.clone(),
+            pattern_transform(&patt, ts),
+            NodeContext::Pattern,
+        ));
+    }
+
+    list
+}
+
+// Each pattern is visited once per node, even when used by both fill and stroke.
+fn push_pattern(paint: Option<&Paint>, patterns: &mut Vec<Shared<Pattern>>) {
+    if let Some(Paint::Pattern(ref patt)) = paint {
+        if !patterns.iter().any(|p| Shared::ptr_eq(p, patt)) {
+            patterns.push(patt.clone());
+        }
+    }
+}
+
+fn pattern_transform(patt: &Pattern, ts: Transform) -> Transform {
+    let mut ts = ts
+        .pre_concat(patt.transform)
+        .pre_translate(patt.rect.x(), patt.rect.y());
+
+    if let Some(vbox) = patt.view_box {
+        let vbox_ts = utils::view_box_to_transform(vbox.rect, vbox.aspect, patt.rect.size());
+        ts = ts.pre_concat(vbox_ts);
+    }
+
+    ts
+}
@@ -52,6 +52,7 @@ and can focus just on the rendering part.
 #![warn(missing_debug_implementations)]
 #![warn(missing_copy_implementations)]
 
+mod optimizer;
 mod writer;
 
 pub use usvg_parser::*;
@@ -59,6 +60,7 @@ pub use usvg_parser::*;
 pub use usvg_text_layout::*;
 pub use usvg_tree::*;
 
+pub use optimizer::OptimizeOptions;
 pub use writer::XmlOptions;
 
 /// A trait to write `usvg::Tree` back to SVG.
@@ -72,3 +74,17 @@ impl TreeWriting for usvg_tree::Tree {
         writer::convert(self, opt)
     }
 }
+
+/// A trait to optimize `usvg::Tree`.
+pub trait TreeOptimizing {
+    /// Applies optimization passes to `usvg::Tree`.
+    ///
+    /// Text must be converted into paths beforehand, otherwise text elements are left as is.
+    fn optimize(&mut self, opt: &OptimizeOptions);
+}
+
+impl TreeOptimizing for usvg_tree::Tree {
+    fn optimize(&mut self, opt: &OptimizeOptions) {
+        optimizer::optimize(self, opt)
+    }
+}
@@ -8,7 +8,7 @@ use std::path::PathBuf;
 use std::process;
 
 use pico_args::Arguments;
-use usvg::TreeWriting;
+use usvg::{TreeOptimizing, TreeWriting};
 use usvg_parser::TreeParsing;
 use usvg_text_layout::TreeTextToPath;
 
@@ -50,6 +50,9 @@ OPTIONS:
                                     contains the SVG file, but can be set to any.
                                     [default: input file directory
                                     or none when reading from stdin]
+  --file-access MODE                Sets which files can be loaded by images
+                                    [default: all] [possible values: all, resources-dir, none]
+  --time SECONDS                    Resolves animations at the specified time
 
   --preserve-text                   Disables conversion of text to paths
   --font-family FAMILY              Sets the default font family that will be
@@ -103,8 +106,20 @@ OPTIONS:
   --transforms-precision NUM        Set the transform values numeric precision.
                                     Smaller precision can lead to a malformed output in some cases
                                     [values: 2..8 (inclusive)] [default: 8]
+  --optimize LEVEL                  Optimizes the tree before writing
+                                    0 - none
+                                    1 - removes invisible elements and duplicated resources
+                                    2 - also bakes transforms, ungroups and merges paths
+                                    3 - also simplifies paths. Changes the rendering result
+                                    [values: 0..3 (inclusive)] [default: 0]
   --quiet                           Disables warnings
+  --json-diagnostics                Prints warnings to stderr as a JSON array
   --unforgiving                     Don't try to proceed in the face of parse errors
+  --no-dtd                          Rejects documents with a DTD
+  --max-entity-expansion BYTES      Sets the maximum size of the text produced by
+                                    DTD entities expansion [default: 10485760]
+  --max-entity-depth NUM            Sets the maximum DTD entities nesting depth
+                                    [default: 10]
 
 ARGS:
   <in-svg>                          Input file
@@ -118,7 +133,9 @@ struct Args {
     shape_rendering: usvg_tree::ShapeRendering,
     text_rendering: usvg_tree::TextRendering,
     image_rendering: usvg_tree::ImageRendering,
+    file_access: usvg_parser::FileAccess,
     resources_dir: Option<PathBuf>,
+    time: Option<f32>,
 
     preserve_text: bool,
     font_family: Option<String>,
@@ -140,9 +157,14 @@ struct Args {
     attrs_indent: xmlwriter::Indent,
     coordinates_precision: Option<u8>,
     transforms_precision: Option<u8>,
+    optimize: u8,
 
     quiet: bool,
+    json_diagnostics: bool,
     forgiving: bool,
+    no_dtd: bool,
+    max_entity_expansion: Option<u64>,
+    max_entity_depth: Option<u32>,
 
     input: String,
     output: String,
@@ -178,6 +200,10 @@ fn collect_args() -> Result<Args, pico_args::Error> {
         resources_dir: input
             .opt_value_from_str("--resources-dir")
             .unwrap_or_default(),
+        file_access: input
+            .opt_value_from_fn("--file-access", parse_file_access)?
+            .unwrap_or(usvg_parser::FileAccess::Unrestricted),
+        time: input.opt_value_from_fn("--time", parse_time)?,
 
         preserve_text: input.contains("--preserve-text"),
         font_family: input.opt_value_from_str("--font-family")?,
@@ -210,9 +236,16 @@ fn collect_args() -> Result<Args, pico_args::Error> {
         coordinates_precision: input
             .opt_value_from_fn("--coordinates-precision", parse_precision)?,
         transforms_precision: input.opt_value_from_fn("--transforms-precision", parse_precision)?,
+        optimize: input
+            .opt_value_from_fn("--optimize", parse_optimize)?
+            .unwrap_or(0),
 
         quiet: input.contains("--quiet"),
+        json_diagnostics: input.contains("--json-diagnostics"),
         forgiving: !input.contains("--unforgiving"),
+        no_dtd: input.contains("--no-dtd"),
+        max_entity_expansion: input.opt_value_from_str("--max-entity-expansion")?,
+        max_entity_depth: input.opt_value_from_str("--max-entity-depth")?,
 
         input: input.free_from_str()?,
         output: input.free_from_str()?,
@@ -239,6 +272,25 @@ fn parse_font_size(s: &str) -> Result<u32, String> {
     }
 }
 
+fn parse_file_access(s: &str) -> Result<usvg_parser::FileAccess, String> {
+    match s {
+        "all" => Ok(usvg_parser::FileAccess::Unrestricted),
+        "resources-dir" => Ok(usvg_parser::FileAccess::ResourcesDir),
+        "none" => Ok(usvg_parser::FileAccess::Denied),
+        _ => Err("invalid file access mode".to_string()),
+    }
+}
+
+fn parse_time(s: &str) -> Result<f32, String> {
+    let n: f32 = s.parse().map_err(|_| "invalid time")?;
+
+    if n.is_finite() && n >= 0.0 {
+        Ok(n)
+    } else {
+        Err("time cannot be negative".to_string())
+    }
+}
+
 fn parse_languages(s: &str) -> Result<Vec<String>, String> {
     let mut langs = Vec::new();
     for lang in s.split(',') {
@@ -287,6 +339,16 @@ fn parse_precision(s: &str) -> Result<u8, String> {
     }
 }
 
+fn parse_optimize(s: &str) -> Result<u8, String> {
+    let n: u8 = s.parse().map_err(|_| "invalid optimization LEVEL value")?;
+
+    if n <= 3 {
+        Ok(n)
+    } else {
+        Err("optimization LEVEL cannot be larger than 3".to_string())
+    }
+}
+
 #[derive(Clone, PartialEq, Debug)]
 enum InputFrom<'a> {
     Stdin,
@@ -308,13 +370,23 @@ fn main() {
         }
     };
 
-    if !args.quiet {
+    let json_diagnostics = args.json_diagnostics && !args.quiet;
+    if !args.quiet && !json_diagnostics {
         if let Ok(()) = log::set_logger(&LOGGER) {
             log::set_max_level(log::LevelFilter::Warn);
         }
     }
 
-    if let Err(e) = process(args) {
+    let result = if json_diagnostics {
+        let diagnostics = usvg_tree::Diagnostics::new();
+        let result = diagnostics.scope(|| process(args));
+        eprintln!("{}", usvg_tree::diagnostics::to_json(&diagnostics.take()));
+        result
+    } else {
+        process(args)
+    };
+
+    if let Err(e) = result {
         eprintln!("Error: {}.", e.to_string());
         std::process::exit(1);
     }
@@ -405,6 +477,14 @@ fn process(args: Args) -> Result<(), String> {
         }
     };
 
+    let mut limits = usvg_parser::Limits::default();
+    if let Some(n) = args.max_entity_expansion {
+        limits.max_entity_expansion = n;
+    }
+    if let Some(n) = args.max_entity_depth {
+        limits.max_entity_depth = n;
+    }
+
     let re_opt = usvg_parser::Options {
         resources_dir,
         dpi: args.dpi as f32,
@@ -419,12 +499,22 @@ fn process(args: Args) -> Result<(), String> {
         text_rendering: args.text_rendering,
         image_rendering: args.image_rendering,
         forgiving: args.forgiving,
+        allow_dtd: !args.no_dtd,
         default_size: usvg_tree::Size::from_wh(
             args.default_width as f32,
             args.default_height as f32,
         )
         .unwrap(),
         image_href_resolver: usvg_parser::ImageHrefResolver::default(),
+        file_access: usvg_parser::FileAccessPolicy {
+            access: args.file_access,
+            ..usvg_parser::FileAccessPolicy::default()
+        },
+        // Diagnostics are collected by the caller.
+        diagnostics: None,
+        keep_origins: false,
+        limits,
+        animation_time: args.time,
     };
 
     let input_svg = match in_svg {
@@ -437,6 +527,10 @@ fn process(args: Args) -> Result<(), String> {
         tree.convert_text(&fontdb);
     }
 
+    if args.optimize > 0 {
+        tree.optimize(&usvg::OptimizeOptions::from_level(args.optimize));
+    }
+
     let xml_opt = usvg::XmlOptions {
         id_prefix: args.id_prefix,
         coordinates_precision: args.coordinates_precision.unwrap_or(8),
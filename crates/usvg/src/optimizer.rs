@@ -0,0 +1,1246 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::HashSet;
+
+use usvg_tree::tiny_skia_path::{PathBuilder, PathSegment, PathVerb, Point};
+use usvg_tree::*;
+
+/// Tree optimization options.
+///
+/// All passes, except path simplification, preserve the rendering result.
+/// Pointer events are not preserved, since hidden and transparent elements are removed.
+#[derive(Clone, Copy, Debug)]
+pub struct OptimizeOptions {
+    /// Removes hidden elements, fully transparent groups and paints,
+    /// empty groups and groups that are clipped out completely.
+    ///
+    /// Default: true
+    pub remove_invisible: bool,
+
+    /// Merges identical gradients, patterns and filters.
+    ///
+    /// Default: true
+    pub deduplicate_resources: bool,
+
+    /// Moves group transforms down to children and applies path transforms to path data,
+    /// when this doesn't affect strokes and paint servers.
+    ///
+    /// Default: true
+    pub bake_transforms: bool,
+
+    /// Replaces clip paths consisting of a single rectangle with an intersection
+    /// of the clipped paths and the rectangle.
+    ///
+    /// Default: true
+    pub clip_rects_to_paths: bool,
+
+    /// Removes groups that don't affect rendering.
+    ///
+    /// Default: true
+    pub ungroup: bool,
+
+    /// Merges adjacent paths with the same style that don't overlap.
+    ///
+    /// Default: true
+    pub merge_paths: bool,
+
+    /// Simplifies path data within the specified tolerance.
+    ///
+    /// The tolerance is set in the root element units.
+    /// Unlike other passes, changes the rendering result.
+    ///
+    /// Default: None
+    pub simplify_paths: Option<f32>,
+}
+
+impl OptimizeOptions {
+    /// Creates options for the specified optimization level.
+    ///
+    /// - 0 - no optimizations
+    /// - 1 - removes invisible elements and duplicated resources
+    /// - 2 - also bakes transforms, converts clip rectangles, ungroups and merges paths
+    /// - 3 - also simplifies paths with a 0.1 tolerance
+    pub fn from_level(level: u8) -> Self {
+        let level1 = level >= 1;
+        let level2 = level >= 2;
+        OptimizeOptions {
+            remove_invisible: level1,
+            deduplicate_resources: level1,
+            bake_transforms: level2,
+            clip_rects_to_paths: level2,
+            ungroup: level2,
+            merge_paths: level2,
+            simplify_paths: if level >= 3 { Some(0.1) } else { None },
+        }
+    }
+}
+
+impl Default for OptimizeOptions {
+    fn default() -> Self {
+        Self::from_level(2)
+    }
+}
+
+pub(crate) fn optimize(tree: &mut Tree, opt: &OptimizeOptions) {
+    if opt.remove_invisible {
+        remove_invisible(&tree.root, false);
+    }
+
+    if opt.deduplicate_resources {
+        deduplicate_resources(tree);
+    }
+
+    if opt.bake_transforms {
+        bake_transforms(&tree.root, false);
+    }
+
+    if opt.clip_rects_to_paths {
+        clip_rects_to_paths(&tree.root, false);
+    }
+
+    if opt.ungroup {
+        ungroup(&tree.root);
+    }
+
+    if opt.merge_paths {
+        merge_paths(&tree.root);
+    }
+
+    if let Some(tolerance) = opt.simplify_paths {
+        simplify_paths(tree, tolerance);
+    }
+
+    // Clipping can produce new empty groups.
+    if opt.remove_invisible {
+        remove_invisible(&tree.root, false);
+    }
+}
+
+// Checks that group rendering depends on its content bounding box.
+fn group_uses_bbox(g: &Group) -> bool {
+    let mut clip = g.clip_path.as_ref();
+    while let Some(c) = clip {
+        if c.units == Units::ObjectBoundingBox {
+            return true;
+        }
+        clip = c.clip_path.as_ref();
+    }
+
+    let mut mask = g.mask.as_ref();
+    while let Some(m) = mask {
+        if m.units == Units::ObjectBoundingBox || m.content_units == Units::ObjectBoundingBox {
+            return true;
+        }
+        mask = m.mask.as_ref();
+    }
+
+    g.filters.iter().any(|f| {
+        f.units == Units::ObjectBoundingBox || f.primitive_units == Units::ObjectBoundingBox
+    })
+}
+
+// Checks that children bounding boxes affect the rendering,
+// when a node or its ancestors depend on it.
+fn is_bbox_dependent(node: &Node, parent_dependent: bool) -> bool {
+    parent_dependent || matches!(*node.borrow(), NodeKind::Group(ref g) if group_uses_bbox(g))
+}
+
+// Hidden elements still contribute to bounding boxes,
+// therefore they are kept when a bounding box is in use.
+fn remove_invisible(parent: &Node, dependent: bool) {
+    let mut curr = parent.first_child();
+    while let Some(node) = curr {
+        curr = node.next_sibling();
+
+        if !dependent && is_invisible(&node) {
+            node.detach();
+            continue;
+        }
+
+        remove_invisible(&node, is_bbox_dependent(&node, dependent));
+
+        let is_empty = matches!(*node.borrow(), NodeKind::Group(ref g) if g.filters.is_empty());
+        if is_empty && !node.has_children() {
+            node.detach();
+        }
+    }
+}
+
+fn is_invisible(node: &Node) -> bool {
+    if let NodeKind::Path(ref mut path) = *node.borrow_mut() {
+        if path.fill.as_ref().map(|f| f.opacity) == Some(Opacity::ZERO) {
+            path.fill = None;
+        }
+
+        if path.stroke.as_ref().map(|s| s.opacity) == Some(Opacity::ZERO) {
+            path.stroke = None;
+        }
+
+        return path.visibility != Visibility::Visible
+            || (path.fill.is_none() && path.stroke.is_none());
+    }
+
+    match *node.borrow() {
+        NodeKind::Group(ref g) => {
+            if g.opacity == Opacity::ZERO {
+                return true;
+            }
+
+            // An empty clip path or mask hides everything.
+            let mut clip = g.clip_path.as_ref();
+            while let Some(c) = clip {
+                if !c.root.has_children() {
+                    return true;
+                }
+                clip = c.clip_path.as_ref();
+            }
+
+            let mut mask = g.mask.as_ref();
+            while let Some(m) = mask {
+                if !m.root.has_children() {
+                    return true;
+                }
+                mask = m.mask.as_ref();
+            }
+
+            // Filters can render outside the content.
+            if !g.filters.is_empty() {
+                return false;
+            }
+
+            if let Some(ref clip) = g.clip_path {
+                if clip.units == Units::UserSpaceOnUse {
+                    let clip_ts = clip.transform.pre_concat(clip.root.transform());
+                    let clip_bbox = children_bbox(&clip.root, clip_ts);
+                    let content_bbox = children_bbox(node, Transform::default());
+                    if let (Some(clip_bbox), Some(content_bbox)) = (clip_bbox, content_bbox) {
+                        match (clip_bbox.to_rect(), content_bbox.to_rect()) {
+                            (Some(r1), Some(r2)) => return r1.intersect(&r2).is_none(),
+                            (None, _) => return true,
+                            _ => {}
+                        }
+                    }
+                }
+            }
+
+            false
+        }
+        NodeKind::Image(ref image) => image.visibility != Visibility::Visible,
+        NodeKind::Path(_) | NodeKind::Text(_) => false,
+    }
+}
+
+// Calculates a conservative bounding box of the children, including strokes.
+//
+// Returns `None` when it cannot be calculated, like for text or filters.
+fn children_bbox(parent: &Node, ts: Transform) -> Option<BBox> {
+    let mut bbox = BBox::default();
+    for child in parent.children() {
+        let child_bbox = match *child.borrow() {
+            NodeKind::Group(ref g) => {
+                if !g.filters.is_empty() {
+                    return None;
+                }
+
+                let child_bbox = children_bbox(&child, ts.pre_concat(g.transform))?;
+                if child_bbox.is_default() {
+                    continue;
+                }
+
+                child_bbox
+            }
+            NodeKind::Path(ref path) => {
+                if path.vector_effect != VectorEffect::None {
+                    return None;
+                }
+
+                BBox::from(path_bbox(path)?).transform(ts.pre_concat(path.transform))?
+            }
+            NodeKind::Image(ref image) => {
+                let rect = image.view_box.rect;
+                BBox::from(rect.transform(ts.pre_concat(image.transform))?)
+            }
+            NodeKind::Text(_) => return None,
+        };
+
+        bbox = bbox.expand(child_bbox);
+    }
+
+    Some(bbox)
+}
+
+// Returns path bounds expanded by the stroke, in path coordinates.
+fn path_bbox(path: &Path) -> Option<Rect> {
+    let rect = path.data.bounds();
+    let d = match path.stroke {
+        // A miter join can extend up to `miterlimit * width / 2` from the outline.
+        Some(ref stroke) => stroke.width.get() * stroke.miterlimit.get().max(1.0),
+        None => return Some(rect),
+    };
+
+    Rect::from_ltrb(
+        rect.left() - d,
+        rect.top() - d,
+        rect.right() + d,
+        rect.bottom() + d,
+    )
+}
+
+fn deduplicate_resources(tree: &mut Tree) {
+    // Gradients go first, so patterns referencing them could be compared by pointers.
+    let mut linear = Vec::new();
+    let mut radial = Vec::new();
+    tree.paint_servers(|paint| match paint {
+        Paint::LinearGradient(ref lg) => push_unique(&mut linear, lg),
+        Paint::RadialGradient(ref rg) => push_unique(&mut radial, rg),
+        _ => {}
+    });
+
+    let mut replacer = Replacer {
+        linear: find_duplicates(&linear, |a, b| {
+            a.x1 == b.x1
+                && a.y1 == b.y1
+                && a.x2 == b.x2
+                && a.y2 == b.y2
+                && base_eq(&a.base, &b.base)
+        }),
+        radial: find_duplicates(&radial, |a, b| {
+            a.cx == b.cx
+                && a.cy == b.cy
+                && a.r == b.r
+                && a.fx == b.fx
+                && a.fy == b.fy
+                && base_eq(&a.base, &b.base)
+        }),
+        ..Replacer::default()
+    };
+    replacer.run(tree);
+
+    let mut patterns = Vec::new();
+    tree.paint_servers(|paint| {
+        if let Paint::Pattern(ref patt) = paint {
+            push_unique(&mut patterns, patt);
+        }
+    });
+
+    let mut replacer = Replacer {
+        patterns: find_duplicates(&patterns, pattern_eq),
+        ..Replacer::default()
+    };
+    replacer.run(tree);
+
+    let mut filters = Vec::new();
+    tree.filters(|filter| push_unique(&mut filters, &filter));
+
+    let mut replacer = Replacer {
+        filters: find_duplicates(&filters, filter_eq),
+        ..Replacer::default()
+    };
+    replacer.run(tree);
+}
+
+fn push_unique<T>(list: &mut Vec<Shared<T>>, item: &Shared<T>) {
+    if !list.iter().any(|i| Shared::ptr_eq(i, item)) {
+        list.push(item.clone());
+    }
+}
+
+// Returns pairs of a duplicate and its first occurrence.
+fn find_duplicates<T>(
+    list: &[Shared<T>],
+    eq: impl Fn(&T, &T) -> bool,
+) -> Vec<(Shared<T>, Shared<T>)> {
+    let mut pairs = Vec::new();
+    for (i, item) in list.iter().enumerate() {
+        let original = list[..i]
+            .iter()
+            .filter(|prev| !pairs.iter().any(|(dup, _)| Shared::ptr_eq(dup, prev)))
+            .find(|prev| eq(prev, item));
+
+        if let Some(original) = original {
+            pairs.push((item.clone(), original.clone()));
+        }
+    }
+
+    pairs
+}
+
+fn base_eq(a: &BaseGradient, b: &BaseGradient) -> bool {
+    a.units == b.units
+        && a.transform == b.transform
+        && a.spread_method == b.spread_method
+        && a.stops.len() == b.stops.len()
+        && a.stops.iter().zip(&b.stops).all(|(s1, s2)| {
+            s1.offset == s2.offset && s1.color == s2.color && s1.opacity == s2.opacity
+        })
+}
+
+fn pattern_eq(a: &Pattern, b: &Pattern) -> bool {
+    let view_box_eq = match (a.view_box, b.view_box) {
+        (Some(vb1), Some(vb2)) => vb1.rect == vb2.rect && vb1.aspect == vb2.aspect,
+        (None, None) => true,
+        _ => false,
+    };
+
+    a.units == b.units
+        && a.content_units == b.content_units
+        && a.transform == b.transform
+        && a.rect == b.rect
+        && view_box_eq
+        && node_eq(&a.root, &b.root)
+}
+
+// Compares nodes structurally. Resources are compared by pointers.
+//
+// Images and text are never equal.
+fn node_eq(a: &Node, b: &Node) -> bool {
+    let kind_eq = match (&*a.borrow(), &*b.borrow()) {
+        (NodeKind::Group(ref g1), NodeKind::Group(ref g2)) => {
+            g1.transform == g2.transform
+                && g1.opacity == g2.opacity
+                && g1.blend_mode == g2.blend_mode
+                && g1.isolate == g2.isolate
+                && option_ptr_eq(&g1.clip_path, &g2.clip_path)
+                && option_ptr_eq(&g1.mask, &g2.mask)
+                && g1.filters.len() == g2.filters.len()
+                && g1
+                    .filters
+                    .iter()
+                    .zip(&g2.filters)
+                    .all(|(f1, f2)| Shared::ptr_eq(f1, f2))
+        }
+        (NodeKind::Path(ref p1), NodeKind::Path(ref p2)) => {
+            p1.transform == p2.transform
+                && p1.text_bbox == p2.text_bbox
+                && p1.data == p2.data
+                && style_eq(p1, p2)
+        }
+        _ => false,
+    };
+
+    kind_eq
+        && a.children().count() == b.children().count()
+        && a.children()
+            .zip(b.children())
+            .all(|(c1, c2)| node_eq(&c1, &c2))
+}
+
+fn option_ptr_eq<T>(a: &Option<Shared<T>>, b: &Option<Shared<T>>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => Shared::ptr_eq(a, b),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+fn filter_eq(a: &filter::Filter, b: &filter::Filter) -> bool {
+    // `feImage` content is not a part of the debug output.
+    let has_image = |f: &filter::Filter| {
+        f.primitives
+            .iter()
+            .any(|p| matches!(p.kind, filter::Kind::Image(_)))
+    };
+
+    if has_image(a) || has_image(b) {
+        return false;
+    }
+
+    // Filter primitives don't implement `PartialEq`,
+    // so we compare their debug representation instead.
+    let to_string = |f: &filter::Filter| {
+        let f = filter::Filter {
+            id: String::new(),
+            ..f.clone()
+        };
+        format!("{:?}", f)
+    };
+
+    to_string(a) == to_string(b)
+}
+
+#[derive(Default)]
+struct Replacer {
+    linear: Vec<(Shared<LinearGradient>, Shared<LinearGradient>)>,
+    radial: Vec<(Shared<RadialGradient>, Shared<RadialGradient>)>,
+    patterns: Vec<(Shared<Pattern>, Shared<Pattern>)>,
+    filters: Vec<(Shared<filter::Filter>, Shared<filter::Filter>)>,
+}
+
+impl Replacer {
+    fn run(&mut self, tree: &mut Tree) {
+        let is_empty = self.linear.is_empty()
+            && self.radial.is_empty()
+            && self.patterns.is_empty()
+            && self.filters.is_empty();
+
+        if !is_empty {
+            tree.visit_mut(self);
+        }
+    }
+
+    fn replace_paint(&self, paint: &mut Paint) {
+        match paint {
+            Paint::Color(_) => {}
+            Paint::LinearGradient(ref mut lg) => replace(&self.linear, lg),
+            Paint::RadialGradient(ref mut rg) => replace(&self.radial, rg),
+            Paint::Pattern(ref mut patt) => replace(&self.patterns, patt),
+        }
+    }
+
+    fn replace_style(&self, fill: &mut Option<Fill>, stroke: &mut Option<Stroke>) {
+        if let Some(ref mut fill) = fill {
+            self.replace_paint(&mut fill.paint);
+        }
+
+        if let Some(ref mut stroke) = stroke {
+            self.replace_paint(&mut stroke.paint);
+        }
+    }
+}
+
+impl VisitorMut for Replacer {
+    fn visit_node_mut(&mut self, kind: &mut NodeKind, _: &VisitContext) -> bool {
+        match kind {
+            NodeKind::Group(ref mut g) => {
+                for filter in &mut g.filters {
+                    replace(&self.filters, filter);
+                }
+            }
+            NodeKind::Path(ref mut path) => {
+                self.replace_style(&mut path.fill, &mut path.stroke);
+            }
+            NodeKind::Image(_) => {}
+            NodeKind::Text(ref mut text) => {
+                for chunk in &mut text.chunks {
+                    for span in &mut chunk.spans {
+                        self.replace_style(&mut span.fill, &mut span.stroke);
+
+                        let decoration = &mut span.decoration;
+                        let lines = [
+                            &mut decoration.underline,
+                            &mut decoration.overline,
+                            &mut decoration.line_through,
+                        ];
+                        for line in IntoIterator::into_iter(lines).flatten() {
+                            self.replace_style(&mut line.fill, &mut line.stroke);
+                        }
+                    }
+                }
+            }
+        }
+
+        true
+    }
+}
+
+fn replace<T>(pairs: &[(Shared<T>, Shared<T>)], item: &mut Shared<T>) {
+    if let Some((_, original)) = pairs.iter().find(|(dup, _)| Shared::ptr_eq(dup, item)) {
+        *item = original.clone();
+    }
+}
+
+// Transforms other than scale and translate change bounding boxes,
+// therefore they are kept when a bounding box is in use.
+fn bake_transforms(parent: &Node, dependent: bool) {
+    for node in parent.children() {
+        // Must be checked before the node is borrowed.
+        let has_children_effects = node.children().any(|c| has_vector_effects(&c));
+        let push_down = match *node.borrow_mut() {
+            NodeKind::Group(ref mut g) => {
+                // Clip paths, masks and filters are defined in the group coordinates.
+                let can_push = !g.transform.is_identity()
+                    && (!dependent || g.transform.is_scale_translate())
+                    && g.clip_path.is_none()
+                    && g.mask.is_none()
+                    && g.filters.is_empty()
+                    && !has_children_effects;
+
+                if can_push {
+                    Some(std::mem::take(&mut g.transform))
+                } else {
+                    None
+                }
+            }
+            NodeKind::Path(ref mut path) => {
+                bake_path_transform(path, !dependent);
+                None
+            }
+            NodeKind::Image(_) | NodeKind::Text(_) => None,
+        };
+
+        if let Some(ts) = push_down {
+            for child in node.children() {
+                let mut kind = child.borrow_mut();
+                let child_ts = transform_mut(&mut kind);
+                *child_ts = ts.pre_concat(*child_ts);
+            }
+        }
+
+        bake_transforms(&node, is_bbox_dependent(&node, dependent));
+    }
+}
+
+// Vector effects depend on the element transform.
+fn has_vector_effects(node: &Node) -> bool {
+    node.descendants().any(|n| match *n.borrow() {
+        NodeKind::Path(ref path) => path.vector_effect != VectorEffect::None,
+        NodeKind::Image(ref image) => image.vector_effect != VectorEffect::None,
+        NodeKind::Group(_) | NodeKind::Text(_) => false,
+    })
+}
+
+fn transform_mut(kind: &mut NodeKind) -> &mut Transform {
+    match kind {
+        NodeKind::Group(ref mut g) => &mut g.transform,
+        NodeKind::Path(ref mut path) => &mut path.transform,
+        NodeKind::Image(ref mut image) => &mut image.transform,
+        NodeKind::Text(ref mut text) => &mut text.transform,
+    }
+}
+
+// `can_rotate` indicates that the path bounding box can change,
+// since no ancestor depends on it.
+fn bake_path_transform(path: &mut Path, can_rotate: bool) {
+    let ts = path.transform;
+    if ts.is_identity() || path.vector_effect != VectorEffect::None {
+        return;
+    }
+
+    // A bounding box is preserved only by a positive scale and translate.
+    let keeps_bbox = ts.is_scale_translate() && ts.sx > 0.0 && ts.sy > 0.0;
+    if !keeps_bbox && !can_rotate {
+        return;
+    }
+
+    let paints = [
+        path.fill.as_ref().map(|f| &f.paint),
+        path.stroke.as_ref().map(|s| &s.paint),
+    ];
+    let paints_ok = IntoIterator::into_iter(paints)
+        .flatten()
+        .all(|paint| match paint.units() {
+            None => true,
+            Some(Units::ObjectBoundingBox) => keeps_bbox,
+            Some(Units::UserSpaceOnUse) => false,
+        });
+
+    if !paints_ok {
+        return;
+    }
+
+    // Strokes can be scaled only uniformly. And since stroking in a rotated
+    // coordinate system produces a slightly different anti-aliasing, rotations are kept.
+    let scale = if path.stroke.is_some() {
+        let scale = ts.sx.abs();
+        if !ts.is_scale_translate() || !scale.approx_eq_ulps(&ts.sy.abs(), 4) {
+            return;
+        }
+
+        scale
+    } else {
+        1.0
+    };
+
+    let data = match path.data.as_ref().clone().transform(ts) {
+        Some(data) => data,
+        None => return,
+    };
+
+    if let Some(ref mut stroke) = path.stroke {
+        stroke.width = match StrokeWidth::new(stroke.width.get() * scale) {
+            Some(width) => width,
+            None => return,
+        };
+
+        if let Some(ref mut list) = stroke.dasharray {
+            list.iter_mut().for_each(|n| *n *= scale);
+        }
+
+        stroke.dashoffset *= scale;
+    }
+
+    if let Some(bbox) = path.text_bbox {
+        path.text_bbox = bbox.transform(ts);
+    }
+
+    path.data = Shared::new(data);
+    path.transform = Transform::default();
+}
+
+// Clipping changes the group bounding box,
+// therefore it is kept when a bounding box is in use.
+fn clip_rects_to_paths(parent: &Node, dependent: bool) {
+    for node in parent.children() {
+        clip_rects_to_paths(&node, is_bbox_dependent(&node, dependent));
+
+        let rect = match *node.borrow() {
+            NodeKind::Group(ref g) if !dependent && !group_uses_bbox(g) => {
+                g.clip_path.as_ref().and_then(|c| clip_rect(c))
+            }
+            _ => None,
+        };
+
+        let rect = match rect {
+            Some(rect) => rect,
+            None => continue,
+        };
+
+        let mut clipped = Vec::new();
+        for child in node.children() {
+            let data = match *child.borrow() {
+                NodeKind::Path(ref path) if can_clip_path(path) => clip_path_data(&path.data, rect),
+                _ => None,
+            };
+
+            match data {
+                Some(data) => clipped.push((child, data)),
+                None => break,
+            }
+        }
+
+        if clipped.len() != node.children().count() {
+            continue;
+        }
+
+        for (child, data) in clipped {
+            match data {
+                Some(data) => {
+                    if let NodeKind::Path(ref mut path) = *child.borrow_mut() {
+                        path.data = Shared::new(data);
+                    }
+                }
+                None => child.detach(),
+            }
+        }
+
+        if let NodeKind::Group(ref mut g) = *node.borrow_mut() {
+            g.clip_path = None;
+        }
+    }
+}
+
+// Returns a clip path rectangle in the clipped group coordinates.
+fn clip_rect(clip: &ClipPath) -> Option<Rect> {
+    if clip.units != Units::UserSpaceOnUse || clip.clip_path.is_some() {
+        return None;
+    }
+
+    let mut children = clip.root.children();
+    let child = children.next()?;
+    if children.next().is_some() {
+        return None;
+    }
+
+    let ts = clip.transform.pre_concat(clip.root.transform());
+    let rect = match *child.borrow() {
+        NodeKind::Path(ref path) => {
+            let ts = ts.pre_concat(path.transform);
+            let is_valid = path.visibility == Visibility::Visible
+                && path.fill.is_some()
+                && path.vector_effect == VectorEffect::None
+                && !ts.has_skew();
+
+            if !is_valid {
+                return None;
+            }
+
+            path_rect(&path.data)?.transform(ts)
+        }
+        _ => None,
+    };
+
+    rect
+}
+
+// Checks that a path is an axis-aligned rectangle and returns its bounds.
+fn path_rect(data: &tiny_skia_path::Path) -> Option<Rect> {
+    use PathVerb::{Close, Line, Move};
+
+    let points = data.points();
+    let is_closed = match data.verbs() {
+        [Move, Line, Line, Line] | [Move, Line, Line, Line, Close] => true,
+        [Move, Line, Line, Line, Line] | [Move, Line, Line, Line, Line, Close] => {
+            points[4] == points[0]
+        }
+        _ => false,
+    };
+
+    if !is_closed {
+        return None;
+    }
+
+    let p = &points[..4];
+    let horizontal_first =
+        p[0].y == p[1].y && p[1].x == p[2].x && p[2].y == p[3].y && p[3].x == p[0].x;
+    let vertical_first =
+        p[0].x == p[1].x && p[1].y == p[2].y && p[2].x == p[3].x && p[3].y == p[0].y;
+
+    if horizontal_first || vertical_first {
+        Some(data.bounds())
+    } else {
+        None
+    }
+}
+
+fn can_clip_path(path: &Path) -> bool {
+    let paint_ok = match path.fill {
+        Some(ref fill) => fill.paint.units() != Some(Units::ObjectBoundingBox),
+        None => false,
+    };
+
+    paint_ok
+        && path.stroke.is_none()
+        && path.transform.is_identity()
+        && path.vector_effect == VectorEffect::None
+        && path.text_bbox.is_none()
+}
+
+// Intersects path data with a rectangle.
+//
+// Returns `None` when the path contains curves crossing the rectangle
+// and `Some(None)` when the path is completely outside of it.
+fn clip_path_data(data: &tiny_skia_path::Path, rect: Rect) -> Option<Option<tiny_skia_path::Path>> {
+    let mut builder = PathBuilder::new();
+    for subpath in split_subpaths(data) {
+        let bounds = match points_bounds(subpath.iter().flat_map(segment_points)) {
+            Some(bounds) => bounds,
+            None => continue,
+        };
+
+        let is_inside = bounds.left() >= rect.left()
+            && bounds.top() >= rect.top()
+            && bounds.right() <= rect.right()
+            && bounds.bottom() <= rect.bottom();
+
+        if is_inside {
+            push_segments(&mut builder, &subpath);
+            continue;
+        }
+
+        if bounds.intersect(&rect).is_none() {
+            continue;
+        }
+
+        // Only polygons can be clipped.
+        let mut polygon = Vec::new();
+        for segment in &subpath {
+            match *segment {
+                PathSegment::MoveTo(p) | PathSegment::LineTo(p) => polygon.push(p),
+                PathSegment::Close => {}
+                PathSegment::QuadTo(..) | PathSegment::CubicTo(..) => return None,
+            }
+        }
+
+        let polygon = clip_polygon(polygon, rect);
+        if polygon.len() > 2 {
+            builder.move_to(polygon[0].x, polygon[0].y);
+            for p in &polygon[1..] {
+                builder.line_to(p.x, p.y);
+            }
+            builder.close();
+        }
+    }
+
+    Some(builder.finish())
+}
+
+fn split_subpaths(data: &tiny_skia_path::Path) -> Vec<Vec<PathSegment>> {
+    let mut subpaths: Vec<Vec<PathSegment>> = Vec::new();
+    for segment in data.segments() {
+        match (segment, subpaths.last_mut()) {
+            (PathSegment::MoveTo(_), _) | (_, None) => subpaths.push(vec![segment]),
+            (_, Some(subpath)) => subpath.push(segment),
+        }
+    }
+
+    subpaths
+}
+
+fn segment_points(segment: &PathSegment) -> Vec<Point> {
+    match *segment {
+        PathSegment::MoveTo(p) | PathSegment::LineTo(p) => vec![p],
+        PathSegment::QuadTo(p1, p) => vec![p1, p],
+        PathSegment::CubicTo(p1, p2, p) => vec![p1, p2, p],
+        PathSegment::Close => Vec::new(),
+    }
+}
+
+fn points_bounds(points: impl Iterator<Item = Point>) -> Option<Rect> {
+    let mut bbox = BBox::default();
+    for p in points {
+        bbox = bbox.expand(Rect::from_xywh(p.x, p.y, 0.0, 0.0)?);
+    }
+
+    bbox.to_rect()
+}
+
+fn push_segments(builder: &mut PathBuilder, segments: &[PathSegment]) {
+    for segment in segments {
+        match *segment {
+            PathSegment::MoveTo(p) => builder.move_to(p.x, p.y),
+            PathSegment::LineTo(p) => builder.line_to(p.x, p.y),
+            PathSegment::QuadTo(p1, p) => builder.quad_to(p1.x, p1.y, p.x, p.y),
+            PathSegment::CubicTo(p1, p2, p) => builder.cubic_to(p1.x, p1.y, p2.x, p2.y, p.x, p.y),
+            PathSegment::Close => builder.close(),
+        }
+    }
+}
+
+// Sutherland–Hodgman polygon clipping.
+fn clip_polygon(mut polygon: Vec<Point>, rect: Rect) -> Vec<Point> {
+    // Left, right, top and bottom edges. Each is a coordinate axis, a bound and a side.
+    let edges = [
+        (true, rect.left(), false),
+        (true, rect.right(), true),
+        (false, rect.top(), false),
+        (false, rect.bottom(), true),
+    ];
+
+    for &(is_x, bound, is_max) in &edges {
+        let coord = |p: Point| if is_x { p.x } else { p.y };
+        let is_inside = |p: Point| {
+            if is_max {
+                coord(p) <= bound
+            } else {
+                coord(p) >= bound
+            }
+        };
+        let intersection = |a: Point, b: Point| {
+            let t = (bound - coord(a)) / (coord(b) - coord(a));
+            if is_x {
+                Point::from_xy(bound, a.y + t * (b.y - a.y))
+            } else {
+                Point::from_xy(a.x + t * (b.x - a.x), bound)
+            }
+        };
+
+        let input = std::mem::take(&mut polygon);
+        let mut prev = match input.last() {
+            Some(p) => *p,
+            None => break,
+        };
+
+        for &curr in &input {
+            if is_inside(curr) {
+                if !is_inside(prev) {
+                    polygon.push(intersection(prev, curr));
+                }
+                polygon.push(curr);
+            } else if is_inside(prev) {
+                polygon.push(intersection(prev, curr));
+            }
+
+            prev = curr;
+        }
+    }
+
+    polygon
+}
+
+fn ungroup(parent: &Node) {
+    let mut curr = parent.first_child();
+    while let Some(node) = curr {
+        curr = node.next_sibling();
+
+        ungroup(&node);
+
+        let is_redundant = match *node.borrow() {
+            NodeKind::Group(ref g) => {
+                !g.should_isolate()
+                    && g.transform.is_identity()
+                    && g.id.is_empty()
+                    && g.classes.is_empty()
+                    && g.title.is_none()
+            }
+            _ => false,
+        };
+
+        if is_redundant {
+            while let Some(child) = node.first_child() {
+                child.detach();
+                node.insert_before(child);
+            }
+
+            node.detach();
+        }
+    }
+}
+
+fn merge_paths(parent: &Node) {
+    let mut curr = parent.first_child();
+    while let Some(node) = curr {
+        merge_paths(&node);
+
+        while let Some(next) = node.next_sibling() {
+            if !merge_path_pair(&node, &next) {
+                break;
+            }
+
+            next.detach();
+        }
+
+        curr = node.next_sibling();
+    }
+}
+
+// Appends the second path data to the first one.
+fn merge_path_pair(node1: &Node, node2: &Node) -> bool {
+    let mut kind1 = node1.borrow_mut();
+    let kind2 = node2.borrow();
+    let (path1, path2) = match (&mut *kind1, &*kind2) {
+        (NodeKind::Path(ref mut p1), NodeKind::Path(ref p2)) => (p1, p2),
+        _ => return false,
+    };
+
+    if !is_mergeable(path1) || !is_mergeable(path2) || !style_eq(path1, path2) {
+        return false;
+    }
+
+    // Overlapping paths cannot be merged, since overlapping areas
+    // would be painted once instead of twice.
+    match (path_bbox(path1), path_bbox(path2)) {
+        (Some(r1), Some(r2)) if r1.intersect(&r2).is_none() => {}
+        _ => return false,
+    }
+
+    let mut builder = PathBuilder::new();
+    push_segments(&mut builder, &path1.data.segments().collect::<Vec<_>>());
+    push_segments(&mut builder, &path2.data.segments().collect::<Vec<_>>());
+    match builder.finish() {
+        Some(data) => {
+            path1.data = Shared::new(data);
+            true
+        }
+        None => false,
+    }
+}
+
+fn is_mergeable(path: &Path) -> bool {
+    let paints = [
+        path.fill.as_ref().map(|f| &f.paint),
+        path.stroke.as_ref().map(|s| &s.paint),
+    ];
+    let paints_ok = IntoIterator::into_iter(paints)
+        .flatten()
+        .all(|paint| paint.units() != Some(Units::ObjectBoundingBox));
+
+    paints_ok
+        && path.id.is_empty()
+        && path.classes.is_empty()
+        && path.title.is_none()
+        && path.text_bbox.is_none()
+        && path.vector_effect == VectorEffect::None
+        && !matches!(path.stroke, Some(ref s) if s.dasharray.is_some())
+}
+
+fn style_eq(path1: &Path, path2: &Path) -> bool {
+    let fill_eq = match (&path1.fill, &path2.fill) {
+        (Some(f1), Some(f2)) => {
+            f1.paint == f2.paint && f1.opacity == f2.opacity && f1.rule == f2.rule
+        }
+        (None, None) => true,
+        _ => false,
+    };
+
+    let stroke_eq = match (&path1.stroke, &path2.stroke) {
+        (Some(s1), Some(s2)) => {
+            s1.paint == s2.paint
+                && s1.dasharray == s2.dasharray
+                && s1.dashoffset == s2.dashoffset
+                && s1.miterlimit == s2.miterlimit
+                && s1.opacity == s2.opacity
+                && s1.width == s2.width
+                && s1.linecap == s2.linecap
+                && s1.linejoin == s2.linejoin
+        }
+        (None, None) => true,
+        _ => false,
+    };
+
+    fill_eq
+        && stroke_eq
+        && path1.transform == path2.transform
+        && path1.visibility == path2.visibility
+        && path1.pointer_events == path2.pointer_events
+        && path1.paint_order == path2.paint_order
+        && path1.rendering_mode == path2.rendering_mode
+        && path1.vector_effect == path2.vector_effect
+}
+
+fn simplify_paths(tree: &mut Tree, tolerance: f32) {
+    // The tolerance of content in `objectBoundingBox` units is unknown,
+    // since it depends on the bounding box of a referencing element.
+    let mut done = HashSet::new();
+    let mut skip = |root: &Node| {
+        for node in root.descendants() {
+            if let NodeKind::Path(ref path) = *node.borrow() {
+                done.insert(data_addr(&path.data));
+            }
+        }
+    };
+
+    tree.clip_paths(|clip| {
+        if clip.units == Units::ObjectBoundingBox {
+            skip(&clip.root);
+        }
+    });
+    tree.masks(|mask| {
+        if mask.content_units == Units::ObjectBoundingBox {
+            skip(&mask.root);
+        }
+    });
+    tree.paint_servers(|paint| {
+        if let Paint::Pattern(ref patt) = paint {
+            if patt.units == Units::ObjectBoundingBox
+                || patt.content_units == Units::ObjectBoundingBox
+            {
+                skip(&patt.root);
+            }
+        }
+    });
+
+    tree.visit_mut(&mut Simplifier { tolerance, done });
+}
+
+struct Simplifier {
+    tolerance: f32,
+    // Resources are visited once per reference, so we have to remember
+    // already simplified data to avoid simplifying it multiple times.
+    done: HashSet<usize>,
+}
+
+impl VisitorMut for Simplifier {
+    fn visit_node_mut(&mut self, kind: &mut NodeKind, ctx: &VisitContext) -> bool {
+        if let NodeKind::Path(ref mut path) = kind {
+            if self.done.contains(&data_addr(&path.data)) {
+                return true;
+            }
+
+            let ts = ctx.transform;
+            let scale = (ts.sx * ts.sy - ts.kx * ts.ky).abs().sqrt();
+            let tolerance = self.tolerance / scale;
+            if tolerance.is_finite() {
+                if let Some(data) = simplify_path_data(&path.data, tolerance) {
+                    path.data = Shared::new(data);
+                }
+            }
+
+            self.done.insert(data_addr(&path.data));
+        }
+
+        true
+    }
+}
+
+fn data_addr(data: &Shared<tiny_skia_path::Path>) -> usize {
+    Shared::as_ptr(data) as usize
+}
+
+fn simplify_path_data(data: &tiny_skia_path::Path, tolerance: f32) -> Option<tiny_skia_path::Path> {
+    let mut builder = PathBuilder::new();
+    // A run of line segments, starting with the current point.
+    let mut polyline: Vec<Point> = Vec::new();
+    let flush = |builder: &mut PathBuilder, polyline: &mut Vec<Point>| {
+        if polyline.len() > 1 {
+            let mut points = Vec::new();
+            simplify_polyline(polyline, tolerance, &mut points);
+            for p in points {
+                builder.line_to(p.x, p.y);
+            }
+        }
+        polyline.clear();
+    };
+
+    let mut prev = Point::zero();
+    for segment in data.segments() {
+        match segment {
+            PathSegment::MoveTo(p) => {
+                flush(&mut builder, &mut polyline);
+                builder.move_to(p.x, p.y);
+                polyline.push(p);
+                prev = p;
+            }
+            PathSegment::LineTo(p) => {
+                if polyline.is_empty() {
+                    polyline.push(prev);
+                }
+                polyline.push(p);
+                prev = p;
+            }
+            PathSegment::QuadTo(p1, p) => {
+                if segment_distance(p1, prev, p) <= tolerance {
+                    if polyline.is_empty() {
+                        polyline.push(prev);
+                    }
+                    polyline.push(p);
+                } else {
+                    flush(&mut builder, &mut polyline);
+                    builder.quad_to(p1.x, p1.y, p.x, p.y);
+                }
+                prev = p;
+            }
+            PathSegment::CubicTo(p1, p2, p) => {
+                let is_flat = segment_distance(p1, prev, p) <= tolerance
+                    && segment_distance(p2, prev, p) <= tolerance;
+                if is_flat {
+                    if polyline.is_empty() {
+                        polyline.push(prev);
+                    }
+                    polyline.push(p);
+                } else {
+                    flush(&mut builder, &mut polyline);
+                    builder.cubic_to(p1.x, p1.y, p2.x, p2.y, p.x, p.y);
+                }
+                prev = p;
+            }
+            PathSegment::Close => {
+                flush(&mut builder, &mut polyline);
+                builder.close();
+            }
+        }
+    }
+
+    flush(&mut builder, &mut polyline);
+    builder.finish()
+}
+
+// Ramer–Douglas–Peucker simplification.
+//
+// Pushes all the resulting points, except the first one.
+fn simplify_polyline(points: &[Point], tolerance: f32, out: &mut Vec<Point>) {
+    let first = points[0];
+    let last = points[points.len() - 1];
+
+    let mut max_dist = 0.0;
+    let mut max_idx = 0;
+    for (i, p) in points.iter().enumerate().take(points.len() - 1).skip(1) {
+        let dist = segment_distance(*p, first, last);
+        if dist > max_dist {
+            max_dist = dist;
+            max_idx = i;
+        }
+    }
+
+    if max_dist > tolerance {
+        simplify_polyline(&points[..=max_idx], tolerance, out);
+        simplify_polyline(&points[max_idx..], tolerance, out);
+    } else {
+        out.push(last);
+    }
+}
+
+// Returns a distance from a point to a line segment.
+fn segment_distance(p: Point, a: Point, b: Point) -> f32 {
+    let ab = b - a;
+    let len2 = ab.x * ab.x + ab.y * ab.y;
+    let t = if len2 > 0.0 {
+        (((p.x - a.x) * ab.x + (p.y - a.y) * ab.y) / len2).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+
+    let d = Point::from_xy(a.x + t * ab.x - p.x, a.y + t * ab.y - p.y);
+    (d.x * d.x + d.y * d.y).sqrt()
+}
@@ -4,7 +4,6 @@
 
 use std::fmt::Display;
 use std::io::Write;
-use std::rc::Rc;
 
 use crate::TreeWriting;
 use usvg_parser::{AId, EId};
@@ -81,7 +80,7 @@ pub(crate) fn convert(tree: &Tree, opt: &XmlOptions) -> String {
 fn conv_filters(tree: &Tree, opt: &XmlOptions, xml: &mut XmlWriter) {
     let mut filters = Vec::new();
     tree.filters(|filter| {
-        if !filters.iter().any(|other| Rc::ptr_eq(&filter, other)) {
+        if !filters.iter().any(|other| Shared::ptr_eq(&filter, other)) {
             filters.push(filter);
         }
     });
@@ -503,7 +502,7 @@ fn conv_defs(tree: &Tree, opt: &XmlOptions, xml: &mut XmlWriter) {
 
     let mut clip_paths = Vec::new();
     tree.clip_paths(|clip| {
-        if !clip_paths.iter().any(|other| Rc::ptr_eq(&clip, other)) {
+        if !clip_paths.iter().any(|other| Shared::ptr_eq(&clip, other)) {
             clip_paths.push(clip);
         }
     });
@@ -524,7 +523,7 @@ fn conv_defs(tree: &Tree, opt: &XmlOptions, xml: &mut XmlWriter) {
 
     let mut masks = Vec::new();
     tree.masks(|mask| {
-        if !masks.iter().any(|other| Rc::ptr_eq(&mask, other)) {
+        if !masks.iter().any(|other| Shared::ptr_eq(&mask, other)) {
             masks.push(mask);
         }
     });
@@ -556,7 +555,7 @@ fn conv_title(title: Option<&str>, xml: &mut XmlWriter) {
     let Some(title) = title else {
         return;
     };
-    
+
     xml.start_svg_element(EId::Title);
     write_text(title, xml);
     xml.end_element();
@@ -578,6 +577,7 @@ fn conv_element(node: &Node, is_clip_path: bool, opt: &XmlOptions, xml: &mut Xml
             if !img.id.is_empty() {
                 xml.write_id_attribute(&img.id, opt);
             }
+            xml.write_classes(&img.classes);
 
             xml.write_rect_attrs(img.view_box.rect);
             if !img.view_box.aspect.is_default() {
@@ -585,6 +585,8 @@ fn conv_element(node: &Node, is_clip_path: bool, opt: &XmlOptions, xml: &mut Xml
             }
 
             xml.write_visibility(img.visibility);
+            xml.write_pointer_events(img.pointer_events);
+            xml.write_vector_effect(img.vector_effect);
 
             match img.rendering_mode {
                 ImageRendering::OptimizeQuality => {}
@@ -621,6 +623,7 @@ fn conv_element(node: &Node, is_clip_path: bool, opt: &XmlOptions, xml: &mut Xml
             if !g.id.is_empty() {
                 xml.write_id_attribute(&g.id, opt);
             };
+            xml.write_classes(&g.classes);
 
             if let Some(ref clip) = g.clip_path {
                 xml.write_func_iri(AId::ClipPath, &clip.id, opt);
@@ -678,7 +681,6 @@ fn conv_element(node: &Node, is_clip_path: bool, opt: &XmlOptions, xml: &mut Xml
             conv_title(g.title.as_deref(), xml);
             conv_elements(node, false, opt, xml);
 
-
             xml.end_element();
         }
         NodeKind::Text(ref text) => {
@@ -692,19 +694,19 @@ fn conv_element(node: &Node, is_clip_path: bool, opt: &XmlOptions, xml: &mut Xml
             if !text.id.is_empty() {
                 xml.write_id_attribute(&text.id, opt);
             }
+            xml.write_classes(&text.classes);
 
             xml.write_transform(AId::Transform, text.transform, opt);
             if text.writing_mode == WritingMode::TopToBottom {
                 xml.write_svg_attribute(AId::WritingMode, "vertical-rl");
             }
 
-
             conv_title(text.title.as_deref(), xml);
 
             // TODO: text flow (linear or path), per-character positions and rotation angles
 
             // TODO: special case if there is only one chunk? Or maybe it is better to always
-            // wrap text in a <tspan> element.  
+            // wrap text in a <tspan> element.
             for chunk in &text.chunks {
                 // Write the first span with the chunk's X and Y (if any). Subsequent spans will be
                 // laid out relative to the first.
@@ -712,10 +714,28 @@ fn conv_element(node: &Node, is_clip_path: bool, opt: &XmlOptions, xml: &mut Xml
                     continue;
                 };
 
-                conv_tspan(&chunk.text, chunk.x, chunk.y, chunk.anchor, first_span, is_clip_path, opt, xml);
+                conv_tspan(
+                    &chunk.text,
+                    chunk.x,
+                    chunk.y,
+                    chunk.anchor,
+                    first_span,
+                    is_clip_path,
+                    opt,
+                    xml,
+                );
 
                 for span in chunk.spans.iter().skip(1) {
-                    conv_tspan(&chunk.text, None, None, chunk.anchor, span, is_clip_path, opt, xml);
+                    conv_tspan(
+                        &chunk.text,
+                        None,
+                        None,
+                        chunk.anchor,
+                        span,
+                        is_clip_path,
+                        opt,
+                        xml,
+                    );
                 }
             }
 
@@ -724,11 +744,20 @@ fn conv_element(node: &Node, is_clip_path: bool, opt: &XmlOptions, xml: &mut Xml
     }
 }
 
-fn conv_tspan(text: &str, x: Option<f32>, y: Option<f32>, anchor: TextAnchor, span: &TextSpan, is_clip_path: bool, opt: &XmlOptions, xml: &mut XmlWriter) {
+fn conv_tspan(
+    text: &str,
+    x: Option<f32>,
+    y: Option<f32>,
+    anchor: TextAnchor,
+    span: &TextSpan,
+    is_clip_path: bool,
+    opt: &XmlOptions,
+    xml: &mut XmlWriter,
+) {
     // `text` contains the text for the entire chunk, not just this span:
     let text_bytes = &text.as_bytes()[span.start..span.end];
     let text = std::str::from_utf8(text_bytes).unwrap();
-    
+
     xml.start_svg_element(EId::Tspan);
 
     if let Some(x) = x {
@@ -743,6 +772,7 @@ fn conv_tspan(text: &str, x: Option<f32>, y: Option<f32>, anchor: TextAnchor, sp
     write_stroke(&span.stroke, opt, xml);
 
     xml.write_visibility(span.visibility);
+    xml.write_pointer_events(span.pointer_events);
 
     if span.paint_order == PaintOrder::StrokeAndFill {
         xml.write_svg_attribute(AId::PaintOrder, "stroke");
@@ -777,7 +807,10 @@ fn conv_tspan(text: &str, x: Option<f32>, y: Option<f32>, anchor: TextAnchor, sp
     let decorations = [
         span.decoration.underline.is_some().then_some("underline"),
         span.decoration.overline.is_some().then_some("overline"),
-        span.decoration.line_through.is_some().then_some("line-through"),
+        span.decoration
+            .line_through
+            .is_some()
+            .then_some("line-through"),
     ];
     if decorations.iter().any(|decoration| decoration.is_some()) {
         xml.write_attribute_raw(AId::TextDecoration.to_str(), |buf| {
@@ -872,12 +905,9 @@ fn conv_tspan(text: &str, x: Option<f32>, y: Option<f32>, anchor: TextAnchor, sp
     xml.end_element();
 }
 
-fn write_text(string: &str, xml: &mut XmlWriter)
-{
+fn write_text(string: &str, xml: &mut XmlWriter) {
     // TODO: this is quite inefficient.
-    let string = string
-        .replace("&", "&amp;")
-        .replace(">", "&gt;");
+    let string = string.replace("&", "&amp;").replace(">", "&gt;");
     xml.write_text(&string);
 }
 
@@ -885,12 +915,15 @@ trait XmlWriterExt {
     fn start_svg_element(&mut self, id: EId);
     fn write_svg_attribute<V: Display + ?Sized>(&mut self, id: AId, value: &V);
     fn write_id_attribute(&mut self, value: &str, opt: &XmlOptions);
+    fn write_classes(&mut self, classes: &[String]);
     fn write_color(&mut self, id: AId, color: Color);
     fn write_viewbox(&mut self, view_box: &ViewBox);
     fn write_aspect(&mut self, aspect: AspectRatio);
     fn write_units(&mut self, id: AId, units: Units, def: Units);
     fn write_transform(&mut self, id: AId, units: Transform, opt: &XmlOptions);
     fn write_visibility(&mut self, value: Visibility);
+    fn write_pointer_events(&mut self, value: PointerEvents);
+    fn write_vector_effect(&mut self, value: VectorEffect);
     fn write_func_iri(&mut self, aid: AId, id: &str, opt: &XmlOptions);
     fn write_rect_attrs(&mut self, r: NonZeroRect);
     fn write_numbers(&mut self, aid: AId, list: &[f32]);
@@ -921,6 +954,12 @@ impl XmlWriterExt for XmlWriter {
         }
     }
 
+    fn write_classes(&mut self, classes: &[String]) {
+        if !classes.is_empty() {
+            self.write_svg_attribute(AId::Class, &classes.join(" "));
+        }
+    }
+
     #[inline(never)]
     fn write_color(&mut self, id: AId, c: Color) {
         static CHARS: &[u8] = b"0123456789abcdef";
@@ -1022,6 +1061,34 @@ impl XmlWriterExt for XmlWriter {
         }
     }
 
+    fn write_pointer_events(&mut self, value: PointerEvents) {
+        let value = match value {
+            PointerEvents::VisiblePainted => return,
+            PointerEvents::VisibleFill => "visibleFill",
+            PointerEvents::VisibleStroke => "visibleStroke",
+            PointerEvents::Visible => "visible",
+            PointerEvents::Painted => "painted",
+            PointerEvents::Fill => "fill",
+            PointerEvents::Stroke => "stroke",
+            PointerEvents::All => "all",
+            PointerEvents::None => "none",
+        };
+
+        self.write_attribute(AId::PointerEvents.to_str(), value);
+    }
+
+    fn write_vector_effect(&mut self, value: VectorEffect) {
+        let value = match value {
+            VectorEffect::None => return,
+            VectorEffect::NonScalingStroke => "non-scaling-stroke",
+            VectorEffect::NonScalingSize => "non-scaling-size",
+            VectorEffect::NonRotation => "non-rotation",
+            VectorEffect::FixedPosition => "fixed-position",
+        };
+
+        self.write_svg_attribute(AId::VectorEffect, value);
+    }
+
     fn write_func_iri(&mut self, aid: AId, id: &str, opt: &XmlOptions) {
         let prefix = opt.id_prefix.as_deref().unwrap_or_default();
         self.write_attribute_fmt(aid.to_str(), format_args!("url(#{}{})", prefix, id));
@@ -1173,11 +1240,13 @@ fn write_path(
     if !path.id.is_empty() {
         xml.write_id_attribute(&path.id, opt);
     }
+    xml.write_classes(&path.classes);
 
     write_fill(&path.fill, is_clip_path, opt, xml);
     write_stroke(&path.stroke, opt, xml);
 
     xml.write_visibility(path.visibility);
+    xml.write_pointer_events(path.pointer_events);
 
     if path.paint_order == PaintOrder::StrokeAndFill {
         xml.write_svg_attribute(AId::PaintOrder, "stroke");
@@ -1191,13 +1260,7 @@ fn write_path(
         ShapeRendering::GeometricPrecision => {}
     }
 
-    match path.vector_effect {
-        VectorEffect::None => {},
-        VectorEffect::NonScalingStroke => xml.write_svg_attribute(AId::VectorEffect, "non-scaling-stroke"),
-        VectorEffect::NonScalingSize => xml.write_svg_attribute(AId::VectorEffect, "non-scaling-size"),
-        VectorEffect::NonRotation => xml.write_svg_attribute(AId::VectorEffect, "non-rotation"),
-        VectorEffect::FixedPosition => xml.write_svg_attribute(AId::VectorEffect, "fixed-position"),
-    }
+    xml.write_vector_effect(path.vector_effect);
 
     if let Some(id) = clip_path {
         xml.write_func_iri(AId::ClipPath, id, opt);
@@ -0,0 +1,319 @@
+use usvg::{NodeExt, NodeKind, OptimizeOptions, TreeOptimizing, TreeParsing};
+
+fn parse(svg: &str) -> usvg::Tree {
+    usvg::Tree::from_str(svg, &usvg::Options::default()).unwrap()
+}
+
+fn optimize(svg: &str, opt: OptimizeOptions) -> usvg::Tree {
+    let mut tree = parse(svg);
+    tree.optimize(&opt);
+    tree
+}
+
+fn path(tree: &usvg::Tree, id: &str) -> usvg::Path {
+    match *tree.node_by_id(id).unwrap().borrow() {
+        NodeKind::Path(ref path) => path.clone(),
+        _ => panic!("'{}' is not a path", id),
+    }
+}
+
+fn paths(tree: &usvg::Tree) -> Vec<usvg::Path> {
+    tree.root
+        .descendants()
+        .filter_map(|node| match *node.borrow() {
+            NodeKind::Path(ref path) => Some(path.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn ids(tree: &usvg::Tree) -> Vec<String> {
+    tree.root
+        .descendants()
+        .map(|node| node.id().to_string())
+        .filter(|id| !id.is_empty())
+        .collect()
+}
+
+fn bounds(path: &usvg::Path) -> (f32, f32, f32, f32) {
+    let rect = path.data.bounds();
+    (rect.left(), rect.top(), rect.right(), rect.bottom())
+}
+
+#[test]
+fn remove_invisible() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <clipPath id='clip-far'>
+            <rect x='150' y='150' width='40' height='40'/>
+        </clipPath>
+        <clipPath id='clip-bbox' clipPathUnits='objectBoundingBox'>
+            <rect width='0.5' height='0.5'/>
+        </clipPath>
+        <rect id='visible' width='10' height='10'/>
+        <rect id='hidden' width='10' height='10' visibility='hidden'/>
+        <rect id='transparent-fill' width='10' height='10' fill-opacity='0'/>
+        <g id='transparent-group' opacity='0'>
+            <rect width='10' height='10'/>
+        </g>
+        <g id='clipped-out' clip-path='url(#clip-far)'>
+            <rect width='100' height='100'/>
+        </g>
+        <g id='bbox-dependent' clip-path='url(#clip-bbox)'>
+            <rect width='10' height='10'/>
+            <rect id='hidden-in-bbox' x='100' width='10' height='10' visibility='hidden'/>
+        </g>
+    </svg>
+    ";
+
+    let opt = OptimizeOptions {
+        remove_invisible: true,
+        ..OptimizeOptions::from_level(0)
+    };
+
+    assert_eq!(
+        ids(&parse(svg)),
+        [
+            "visible",
+            "hidden",
+            "transparent-fill",
+            "transparent-group",
+            "clipped-out",
+            "bbox-dependent",
+            "hidden-in-bbox",
+        ]
+    );
+
+    // Hidden elements still contribute to a bounding box, which is used by a clip path.
+    assert_eq!(
+        ids(&optimize(svg, opt)),
+        ["visible", "bbox-dependent", "hidden-in-bbox"]
+    );
+}
+
+#[test]
+fn deduplicate_resources() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <linearGradient id='lg1'>
+            <stop offset='0' stop-color='green'/>
+            <stop offset='1' stop-color='blue'/>
+        </linearGradient>
+        <linearGradient id='lg2'>
+            <stop offset='0' stop-color='green'/>
+            <stop offset='1' stop-color='blue'/>
+        </linearGradient>
+        <linearGradient id='lg3'>
+            <stop offset='0' stop-color='green'/>
+            <stop offset='1' stop-color='red'/>
+        </linearGradient>
+        <filter id='blur1'>
+            <feGaussianBlur stdDeviation='2'/>
+        </filter>
+        <filter id='blur2'>
+            <feGaussianBlur stdDeviation='2'/>
+        </filter>
+        <rect id='rect1' width='10' height='10' fill='url(#lg1)'/>
+        <rect id='rect2' width='10' height='10' fill='url(#lg2)'/>
+        <rect id='rect3' width='10' height='10' fill='url(#lg3)'/>
+        <g id='group1' filter='url(#blur1)'>
+            <rect width='10' height='10'/>
+        </g>
+        <g id='group2' filter='url(#blur2)'>
+            <rect width='10' height='10'/>
+        </g>
+    </svg>
+    ";
+
+    let tree = optimize(
+        svg,
+        OptimizeOptions {
+            deduplicate_resources: true,
+            ..OptimizeOptions::from_level(0)
+        },
+    );
+
+    let fill = |id: &str| path(&tree, id).fill.unwrap().paint;
+    assert!(fill("rect1") == fill("rect2"));
+    assert!(fill("rect1") != fill("rect3"));
+
+    let filter = |id: &str| match *tree.node_by_id(id).unwrap().borrow() {
+        NodeKind::Group(ref g) => g.filters[0].clone(),
+        _ => unreachable!(),
+    };
+    assert!(usvg::Shared::ptr_eq(&filter("group1"), &filter("group2")));
+}
+
+#[test]
+fn bake_transforms() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <clipPath id='clip-bbox' clipPathUnits='objectBoundingBox'>
+            <rect width='0.5' height='0.5'/>
+        </clipPath>
+        <g id='group' transform='translate(10 20)'>
+            <rect id='rect' width='10' height='10' transform='scale(2)'/>
+        </g>
+        <rect id='stroked' width='10' height='10' stroke='black' transform='scale(2 1)'/>
+        <g id='bbox-dependent' clip-path='url(#clip-bbox)'>
+            <rect id='rotated' width='10' height='10' transform='rotate(45)'/>
+        </g>
+    </svg>
+    ";
+
+    let tree = optimize(
+        svg,
+        OptimizeOptions {
+            bake_transforms: true,
+            ..OptimizeOptions::from_level(0)
+        },
+    );
+
+    // Element transforms are stored in anonymous parent groups.
+    let parent = tree.node_by_id("rect").unwrap().parent().unwrap();
+    assert!(parent.transform().is_identity());
+    assert!(tree.node_by_id("group").unwrap().transform().is_identity());
+    assert_eq!(bounds(&path(&tree, "rect")), (10.0, 20.0, 30.0, 40.0));
+
+    // Strokes cannot be scaled non-uniformly.
+    assert_eq!(bounds(&path(&tree, "stroked")), (0.0, 0.0, 10.0, 10.0));
+
+    // Rotations change a bounding box, which is used by a clip path.
+    assert_eq!(bounds(&path(&tree, "rotated")), (0.0, 0.0, 10.0, 10.0));
+}
+
+#[test]
+fn clip_rects_to_paths() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <clipPath id='clip'>
+            <rect x='20' y='20' width='60' height='60'/>
+        </clipPath>
+        <g id='polygons' clip-path='url(#clip)'>
+            <polygon id='triangle' points='0,0 100,0 0,100'/>
+            <rect id='inside' x='30' y='30' width='10' height='10'/>
+            <rect x='150' y='150' width='10' height='10'/>
+        </g>
+        <g id='curves' clip-path='url(#clip)'>
+            <circle cx='20' cy='20' r='20'/>
+        </g>
+    </svg>
+    ";
+
+    let tree = optimize(
+        svg,
+        OptimizeOptions {
+            clip_rects_to_paths: true,
+            ..OptimizeOptions::from_level(0)
+        },
+    );
+
+    let clip_path = |id: &str| match *tree.node_by_id(id).unwrap().borrow() {
+        NodeKind::Group(ref g) => g.clip_path.clone(),
+        _ => unreachable!(),
+    };
+
+    // Polygons are clipped and paths outside the rectangle are removed.
+    assert!(clip_path("polygons").is_none());
+    assert_eq!(bounds(&path(&tree, "triangle")), (20.0, 20.0, 80.0, 80.0));
+    assert_eq!(bounds(&path(&tree, "inside")), (30.0, 30.0, 40.0, 40.0));
+    assert_eq!(tree.node_by_id("polygons").unwrap().children().count(), 2);
+
+    // Curves crossing the rectangle cannot be clipped.
+    assert!(clip_path("curves").is_some());
+}
+
+#[test]
+fn ungroup() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <g transform='translate(10 10)'>
+            <rect id='rect1' width='10' height='10'/>
+        </g>
+        <g id='named' transform='translate(10 10)'>
+            <rect id='rect2' width='10' height='10'/>
+        </g>
+    </svg>
+    ";
+
+    let count_groups = |tree: &usvg::Tree| {
+        tree.root
+            .descendants()
+            .filter(|node| matches!(*node.borrow(), NodeKind::Group(_)))
+            .count()
+    };
+
+    let opt = OptimizeOptions {
+        ungroup: true,
+        ..OptimizeOptions::from_level(0)
+    };
+
+    // Groups with a transform are not redundant.
+    assert_eq!(count_groups(&optimize(svg, opt)), 3);
+
+    // Unless the transform was baked. Named groups are kept.
+    let tree = optimize(
+        svg,
+        OptimizeOptions {
+            bake_transforms: true,
+            ..opt
+        },
+    );
+    assert_eq!(count_groups(&tree), 2);
+    assert!(tree.node_by_id("rect1").unwrap().parent() == Some(tree.root.clone()));
+    assert!(tree.node_by_id("rect2").unwrap().parent() == tree.node_by_id("named"));
+}
+
+#[test]
+fn merge_paths() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <rect width='10' height='10'/>
+        <rect x='20' width='10' height='10'/>
+        <rect x='25' width='10' height='10'/>
+        <rect x='40' width='10' height='10' fill='green'/>
+    </svg>
+    ";
+
+    let opt = OptimizeOptions {
+        merge_paths: true,
+        ..OptimizeOptions::from_level(0)
+    };
+
+    assert_eq!(paths(&parse(svg)).len(), 4);
+
+    // Overlapping paths and paths with a different style are not merged.
+    let paths = paths(&optimize(svg, opt));
+    assert_eq!(paths.len(), 3);
+    assert_eq!(bounds(&paths[0]), (0.0, 0.0, 30.0, 10.0));
+}
+
+#[test]
+fn simplify_paths() {
+    let svg = "
+    <svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 200 200'>
+        <polyline points='0,0 10,0.01 20,0 30,0.02 40,0 50,10' fill='none' stroke='black'/>
+    </svg>
+    ";
+
+    let original = paths(&parse(svg)).remove(0);
+    assert_eq!(original.data.points().len(), 6);
+
+    // Points within the tolerance are removed and the end points are preserved.
+    let tree = optimize(
+        svg,
+        OptimizeOptions {
+            simplify_paths: Some(0.1),
+            ..OptimizeOptions::from_level(0)
+        },
+    );
+    let simplified = paths(&tree).remove(0);
+    assert_eq!(
+        simplified.data.points(),
+        [
+            usvg::tiny_skia_path::Point::from_xy(0.0, 0.0),
+            usvg::tiny_skia_path::Point::from_xy(40.0, 0.0),
+            usvg::tiny_skia_path::Point::from_xy(50.0, 10.0),
+        ]
+    );
+}